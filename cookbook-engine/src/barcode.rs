@@ -0,0 +1,302 @@
+//! Product barcodes (EAN/UPC) and the Open Food Facts importer.
+//!
+//! Codes are stored in one canonical form so a scan matches however the code was written:
+//! spaces and dashes are dropped, UPC-A codes gain the leading zero that makes them EAN-13,
+//! and GTIN-14 codes with a leading zero lose it. EAN-8 codes stay as they are.
+//!
+//! The importer reads a products export from Open Food Facts (the tab-separated dump, or a
+//! comma-separated export with the same column names) a line at a time, so the full dump
+//! never has to fit in memory. Products are matched to ingredients by their most specific
+//! category first and then by words in their name; the suggestions are for a person to
+//! review, nothing is mapped until they are applied.
+use crate::ingredient_parser::singular_forms;
+use crate::types::{CookbookError, Ingredient, Package};
+use crate::units;
+use std::collections::HashSet;
+use std::io::BufRead;
+
+/// Confidence of a match on a product category, e.g. "en:tomatoes"
+const CATEGORY_CONFIDENCE: f64 = 0.9;
+/// Confidence of a match on words in the product name
+const NAME_CONFIDENCE: f64 = 0.6;
+
+/// The canonical form of an EAN-8, UPC-A, EAN-13 or GTIN-14 code, or None if it isn't one
+/// (wrong length, not digits, or a bad check digit)
+pub fn normalize(code: &str) -> Option<String> {
+    let digits: String = code
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect();
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let canonical = match digits.len() {
+        8 | 13 => digits,
+        12 => format!("0{}", digits),
+        14 => match digits.strip_prefix('0') {
+            Some(ean) => ean.to_string(),
+            None => digits,
+        },
+        _ => return None,
+    };
+    has_valid_check_digit(&canonical).then_some(canonical)
+}
+
+/// GTIN check digit: weights 3 and 1 alternate leftwards from the digit before it
+fn has_valid_check_digit(digits: &str) -> bool {
+    let values: Vec<u32> = digits.chars().filter_map(|c| c.to_digit(10)).collect();
+    let Some((check, body)) = values.split_last() else {
+        return false;
+    };
+    let sum: u32 = body
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, d)| if i % 2 == 0 { d * 3 } else { *d })
+        .sum();
+    (10 - sum % 10) % 10 == *check
+}
+
+/// Finds the ingredient and package a barcode belongs to
+pub fn lookup<'a>(
+    ingredients: impl IntoIterator<Item = &'a Ingredient>,
+    code: &str,
+) -> Option<(&'a Ingredient, &'a Package)> {
+    let code = normalize(code)?;
+    ingredients.into_iter().find_map(|ingredient| {
+        ingredient
+            .packages
+            .iter()
+            .find(|p| p.barcode == code)
+            .map(|p| (ingredient, p))
+    })
+}
+
+/// Reads a package size like "500 g", "1,5 l" or "6 x 125 g" as an amount and unit.
+/// None for sizes without a known unit, e.g. "1 pack"
+pub fn parse_package_size(text: &str) -> Option<(f64, String)> {
+    let text = text.trim().to_lowercase().replace(',', ".");
+    let (count, size) = match text.split_once('x') {
+        Some((count, size)) => (count.trim().parse::<f64>().ok()?, size.trim()),
+        None => (1.0, text.as_str()),
+    };
+    let number_end = size
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(size.len());
+    let amount: f64 = size[..number_end].parse().ok()?;
+    let unit = size[number_end..].split_whitespace().next()?;
+    units::dimension(unit)?;
+    Some((count * amount, units::normalize(unit)))
+}
+
+/// A suggested mapping from a product barcode to an ingredient
+#[derive(Debug, Clone, PartialEq)]
+pub struct BarcodeSuggestion {
+    /// The product's name in the export
+    pub product: String,
+    pub ingredient: String,
+    /// The barcode and package size to record on the ingredient
+    pub package: Package,
+    /// 0–1; category matches score higher than name matches
+    pub confidence: f64,
+    /// What matched, e.g. `category "tomatoes"`
+    pub reason: String,
+}
+
+/// Columns the importer uses, by position in the header
+struct Columns {
+    code: usize,
+    product_name: Option<usize>,
+    generic_name: Option<usize>,
+    quantity: Option<usize>,
+    categories: Option<usize>,
+}
+
+impl Columns {
+    fn from_header(fields: &[String]) -> Option<Self> {
+        let find = |name: &str| fields.iter().position(|f| f.trim() == name);
+        Some(Columns {
+            code: find("code")?,
+            product_name: find("product_name"),
+            generic_name: find("generic_name"),
+            quantity: find("quantity"),
+            categories: find("categories_tags").or_else(|| find("categories")),
+        })
+    }
+}
+
+/// Suggests ingredient mappings for the products in an Open Food Facts export.
+///
+/// Products whose barcode is in `known`, that have an invalid barcode, or that match no
+/// ingredient are skipped; a barcode listed twice is only suggested once. Suggestions are
+/// sorted by ingredient, most confident first.
+pub fn suggest_mappings<'a>(
+    reader: impl BufRead,
+    ingredients: impl IntoIterator<Item = &'a Ingredient>,
+    known: &HashSet<String>,
+) -> Result<Vec<BarcodeSuggestion>, CookbookError> {
+    let names: Vec<(String, Vec<String>)> = ingredients
+        .into_iter()
+        .map(|i| (i.name.clone(), names_of(i)))
+        .collect();
+    let mut lines = reader.lines();
+    let Some(header) = lines.next() else {
+        return Ok(Vec::new());
+    };
+    let header = header.map_err(|e| CookbookError::ReadError(e.to_string()))?;
+    let delimiter = if header.contains('\t') { '\t' } else { ',' };
+    let columns = Columns::from_header(&split_record(&header, delimiter)).ok_or_else(|| {
+        CookbookError::ParseError("suggest_mappings: the export has no \"code\" column".into())
+    })?;
+
+    let mut seen = HashSet::new();
+    let mut suggestions = Vec::new();
+    for line in lines {
+        let line = line.map_err(|e| CookbookError::ReadError(e.to_string()))?;
+        let fields = split_record(&line, delimiter);
+        let field =
+            |index: Option<usize>| index.and_then(|i| fields.get(i)).map_or("", |f| f.trim());
+        let Some(code) = normalize(field(Some(columns.code))) else {
+            continue;
+        };
+        if known.contains(&code) || seen.contains(&code) {
+            continue;
+        }
+        let product = match field(columns.product_name) {
+            "" => field(columns.generic_name),
+            name => name,
+        };
+        let matched = match_categories(field(columns.categories), &names).or_else(|| {
+            let text = format!("{} {}", product, field(columns.generic_name));
+            match_name(&text, &names)
+        });
+        let Some((ingredient, confidence, reason)) = matched else {
+            continue;
+        };
+        let size = parse_package_size(field(columns.quantity));
+        seen.insert(code.clone());
+        suggestions.push(BarcodeSuggestion {
+            product: product.to_string(),
+            ingredient,
+            package: Package {
+                barcode: code,
+                quantity: size.as_ref().map(|(amount, _)| *amount),
+                unit: size.map(|(_, unit)| unit).unwrap_or_default(),
+            },
+            confidence,
+            reason,
+        });
+    }
+    suggestions.sort_by(|a, b| {
+        a.ingredient
+            .cmp(&b.ingredient)
+            .then(b.confidence.total_cmp(&a.confidence))
+    });
+    Ok(suggestions)
+}
+
+/// Splits a line of the export into fields. Comma-separated lines may quote fields, with
+/// `""` for a quote inside one; the tab-separated dump doesn't quote
+fn split_record(line: &str, delimiter: char) -> Vec<String> {
+    if delimiter == '\t' {
+        return line.split('\t').map(str::to_string).collect();
+    }
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// An ingredient's name, slug and translated forms as lowercase words, longest first
+fn names_of(ingredient: &Ingredient) -> Vec<String> {
+    let mut names = vec![
+        ingredient.name.to_lowercase(),
+        ingredient.slug.replace('_', " ").to_lowercase(),
+    ];
+    if let Some(translations) = &ingredient.translations {
+        for forms in translations.values() {
+            names.push(forms.one.to_lowercase());
+            names.push(forms.other.to_lowercase());
+        }
+    }
+    names.retain(|n| !n.trim().is_empty());
+    names.sort_by_key(|n| std::cmp::Reverse(n.len()));
+    names.dedup();
+    names
+}
+
+/// Matches the most specific category tag ("en:canned-tomatoes" → "canned tomatoes") that
+/// names an ingredient
+fn match_categories(
+    categories: &str,
+    names: &[(String, Vec<String>)],
+) -> Option<(String, f64, String)> {
+    categories.split(',').rev().find_map(|tag| {
+        let tag = tag.rsplit(':').next().unwrap_or(tag).replace('-', " ");
+        let tag = tag.trim().to_lowercase();
+        let forms: Vec<String> = std::iter::once(tag.clone())
+            .chain(singular_forms(&tag))
+            .collect();
+        longest_match(names, |name| forms.iter().any(|f| f == name)).map(|ingredient| {
+            let reason = format!("category \"{}\"", tag);
+            (ingredient, CATEGORY_CONFIDENCE, reason)
+        })
+    })
+}
+
+/// Matches an ingredient named by whole words in the product's name
+fn match_name(text: &str, names: &[(String, Vec<String>)]) -> Option<(String, f64, String)> {
+    let lower = text.to_lowercase();
+    let words: Vec<&str> = lower
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    longest_match(names, |name| {
+        let name_words: Vec<&str> = name.split_whitespace().collect();
+        !name_words.is_empty()
+            && words.windows(name_words.len()).any(|window| {
+                window
+                    .iter()
+                    .zip(&name_words)
+                    .all(|(word, name_word)| word_matches(word, name_word))
+            })
+    })
+    .map(|ingredient| {
+        let reason = format!("name \"{}\"", text.trim());
+        (ingredient, NAME_CONFIDENCE, reason)
+    })
+}
+
+/// The ingredient with the longest name `matches` accepts, so "tomato paste" wins over "tomato"
+fn longest_match(
+    names: &[(String, Vec<String>)],
+    matches: impl Fn(&str) -> bool,
+) -> Option<String> {
+    names
+        .iter()
+        .filter_map(|(ingredient, forms)| {
+            forms
+                .iter()
+                .find(|name| matches(name))
+                .map(|name| (ingredient, name.len()))
+        })
+        .max_by_key(|(_, len)| *len)
+        .map(|(ingredient, _)| ingredient.clone())
+}
+
+fn word_matches(word: &str, name_word: &str) -> bool {
+    word == name_word || singular_forms(word).iter().any(|f| f == name_word)
+}
@@ -0,0 +1,93 @@
+//! Suggests barcode mappings for ingredients from a downloaded Open Food Facts export.
+//!
+//! Usage: cookbook-barcodes <data_dir> <products.csv> [--apply] [--min-confidence=<0-1>]
+//!
+//! Lists the suggested mappings first. They are only recorded on the ingredients when
+//! `--apply` is given, and then only those at or above `--min-confidence` (default 0).
+use cookbook_engine::DataManager;
+use std::path::Path;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let apply = args.iter().any(|a| a == "--apply");
+    let min_confidence = match args
+        .iter()
+        .find_map(|a| a.strip_prefix("--min-confidence="))
+    {
+        None => 0.0,
+        Some(value) => match value.parse::<f64>() {
+            Ok(value) => value,
+            Err(_) => {
+                eprintln!("Error: --min-confidence takes a number from 0 to 1");
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+    let paths: Vec<&String> = args.iter().filter(|a| !a.starts_with("--")).collect();
+    let [data_dir, export] = paths.as_slice() else {
+        eprintln!(
+            "Usage: cookbook-barcodes <data_dir> <products.csv> [--apply] [--min-confidence=<0-1>]"
+        );
+        return ExitCode::FAILURE;
+    };
+
+    let mut dm = match DataManager::new(data_dir) {
+        Ok(dm) => dm,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let suggestions = match dm.suggest_barcode_mappings(Path::new(export)) {
+        Ok(suggestions) => suggestions,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let selected: Vec<_> = suggestions
+        .iter()
+        .filter(|s| s.confidence >= min_confidence)
+        .collect();
+    for suggestion in &selected {
+        let size = match suggestion.package.quantity {
+            Some(quantity) => format!("{} {}", quantity, suggestion.package.unit),
+            None => "size unknown".to_string(),
+        };
+        println!(
+            "{} -> {} ({}, {}): {}, {:.0}%",
+            suggestion.package.barcode,
+            suggestion.ingredient,
+            suggestion.product,
+            size,
+            suggestion.reason,
+            suggestion.confidence * 100.0
+        );
+    }
+    println!("{} suggested mappings", selected.len());
+
+    if selected.is_empty() {
+        return ExitCode::SUCCESS;
+    }
+    if !apply {
+        println!("Dry run only. Re-run with --apply to record these mappings.");
+        return ExitCode::SUCCESS;
+    }
+
+    let mut failed = 0;
+    for suggestion in selected {
+        if let Err(e) =
+            dm.add_ingredient_package(&suggestion.ingredient, suggestion.package.clone())
+        {
+            println!("Failed {}: {}", suggestion.package.barcode, e);
+            failed += 1;
+        }
+    }
+    println!("Recorded the mappings, {} failed", failed);
+    if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
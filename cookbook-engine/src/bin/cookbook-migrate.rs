@@ -0,0 +1,83 @@
+//! Upgrades every data file in a data directory to the current schema version.
+//!
+//! Usage: cookbook-migrate <data_dir> [--apply]
+//!
+//! Always starts with a dry run that lists what would change. Files are only rewritten
+//! when `--apply` is given.
+use cookbook_engine::migration::{self, MigrationReport};
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let apply = args.iter().any(|a| a == "--apply");
+    let Some(data_dir) = args.iter().find(|a| !a.starts_with("--")) else {
+        eprintln!("Usage: cookbook-migrate <data_dir> [--apply]");
+        return ExitCode::FAILURE;
+    };
+
+    // Dry run first, so the plan is always shown before anything is written
+    let plan = match migration::migrate_data_dir(data_dir, true) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    print_report(&plan);
+
+    if plan.migrated.is_empty() {
+        println!("Nothing to migrate.");
+        return ExitCode::SUCCESS;
+    }
+    if !apply {
+        println!("Dry run only. Re-run with --apply to rewrite these files.");
+        return ExitCode::SUCCESS;
+    }
+
+    match migration::migrate_data_dir(data_dir, false) {
+        Ok(report) => {
+            print_report(&report);
+            if report.failed.is_empty() {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::FAILURE
+            }
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn print_report(report: &MigrationReport) {
+    let verb = if report.dry_run {
+        "Would migrate"
+    } else {
+        "Migrated"
+    };
+    for file in &report.migrated {
+        println!(
+            "{} {} (v{} -> v{}): {}",
+            verb,
+            file.path.display(),
+            file.from_version,
+            file.to_version,
+            file.steps.join(", ")
+        );
+    }
+    for (path, error) in &report.failed {
+        println!("Failed {}: {}", path.display(), error);
+    }
+    println!(
+        "{} {}, {} up to date, {} failed",
+        report.migrated.len(),
+        if report.dry_run {
+            "to migrate"
+        } else {
+            "migrated"
+        },
+        report.up_to_date,
+        report.failed.len()
+    );
+}
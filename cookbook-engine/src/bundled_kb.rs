@@ -0,0 +1,31 @@
+//! Knowledge base articles shipped with the engine.
+//!
+//! The articles in `src/kb/` are compiled into the library so every frontend (GTK, the
+//! Android bridge, installed builds) has them without a source checkout. Entries in
+//! `{data_dir}/kb` take precedence; these are only used as a fallback.
+
+/// Bundled article files as `(file name, Markdown content)`
+pub const BUNDLED_KB_ENTRIES: &[(&str, &str)] = &[
+    ("basil.md", include_str!("kb/basil.md")),
+    ("chicken.md", include_str!("kb/chicken.md")),
+    ("chocolate.md", include_str!("kb/chocolate.md")),
+    ("egg.md", include_str!("kb/egg.md")),
+    ("flour.md", include_str!("kb/flour.md")),
+    ("garlic.md", include_str!("kb/garlic.md")),
+    ("olive_oil.md", include_str!("kb/olive_oil.md")),
+    ("onion.md", include_str!("kb/onion.md")),
+    ("potato.md", include_str!("kb/potato.md")),
+    ("rice.md", include_str!("kb/rice.md")),
+    ("tomato.md", include_str!("kb/tomato.md")),
+];
+
+/// Bundled images referenced by the articles, as `(file name, bytes)`
+pub const BUNDLED_KB_IMAGES: &[(&str, &[u8])] = &[("potato.jpg", include_bytes!("kb/potato.jpg"))];
+
+/// Returns the bytes of a bundled image by file name
+pub fn bundled_image(name: &str) -> Option<&'static [u8]> {
+    BUNDLED_KB_IMAGES
+        .iter()
+        .find(|(file_name, _)| *file_name == name)
+        .map(|(_, bytes)| *bytes)
+}
@@ -0,0 +1,178 @@
+//! Cost estimates from the prices recorded on ingredients.
+//!
+//! Each line is costed at the ingredient's latest price, converted to the line's unit with
+//! `units::convert_ingredient`. Lines that can't be costed — no price, no amount, or units
+//! that don't convert (a price per kg for an amount in cups, without a density) — are left
+//! out of the total and reported, so a total is only exact when `CostEstimate::is_complete`
+//! says so. Prices older than `STALE_AFTER_DAYS` are still used but flagged.
+use crate::shopping::ShoppingList;
+use crate::types::{Ingredient, Price, Recipe};
+use crate::units;
+use chrono::NaiveDate;
+
+/// Prices older than this many days are flagged as stale
+pub const STALE_AFTER_DAYS: i64 = 180;
+
+/// Why a line's cost is missing or uncertain
+#[derive(Debug, Clone, PartialEq)]
+pub enum PriceIssue {
+    /// No price recorded for the ingredient
+    Missing,
+    /// The line has no amount to cost, e.g. "salt to taste"
+    NoAmount,
+    /// The price is per a unit the line's unit doesn't convert to, even with the
+    /// ingredient's density or unit weight
+    Unconvertible { price_unit: String },
+    /// The price is older than `STALE_AFTER_DAYS`; the cost is still counted
+    Stale { date: NaiveDate },
+}
+
+/// The cost of one ingredient line
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineCost {
+    pub ingredient: String,
+    /// None if the line couldn't be costed
+    pub cost: Option<f64>,
+    /// The price used, if there was one
+    pub price: Option<Price>,
+    pub issue: Option<PriceIssue>,
+}
+
+/// The cost of a set of ingredient lines
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CostEstimate {
+    /// Sum of the lines that could be costed
+    pub total: f64,
+    pub lines: Vec<LineCost>,
+}
+
+impl CostEstimate {
+    /// True if every line was costed (stale prices count)
+    pub fn is_complete(&self) -> bool {
+        self.lines.iter().all(|l| l.cost.is_some())
+    }
+
+    /// Ingredients without a usable price, amount or conversion
+    pub fn uncosted(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter(|l| l.cost.is_none())
+            .map(|l| l.ingredient.as_str())
+            .collect()
+    }
+
+    /// Ingredients costed at a stale price
+    pub fn stale(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter(|l| matches!(l.issue, Some(PriceIssue::Stale { .. })))
+            .map(|l| l.ingredient.as_str())
+            .collect()
+    }
+}
+
+/// The cost of a recipe at its own servings
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecipeCost {
+    pub estimate: CostEstimate,
+    pub servings: Option<u32>,
+}
+
+impl RecipeCost {
+    /// The total divided by the recipe's servings, or None if it gives none
+    pub fn per_serving(&self) -> Option<f64> {
+        self.servings
+            .filter(|s| *s > 0)
+            .map(|s| self.estimate.total / s as f64)
+    }
+}
+
+/// Costs `amount` of `unit` of an ingredient at its latest price
+pub fn line_cost(
+    name: &str,
+    ingredient: Option<&Ingredient>,
+    amount: Option<f64>,
+    unit: &str,
+    today: NaiveDate,
+) -> LineCost {
+    let price = ingredient.and_then(|i| i.latest_price()).cloned();
+    let (cost, issue) = match (&price, amount) {
+        (None, _) => (None, Some(PriceIssue::Missing)),
+        (Some(_), None) => (None, Some(PriceIssue::NoAmount)),
+        (Some(price), Some(amount)) => {
+            match units::convert_ingredient(amount, unit, &price.unit, ingredient) {
+                Err(_) => (
+                    None,
+                    Some(PriceIssue::Unconvertible {
+                        price_unit: price.unit.clone(),
+                    }),
+                ),
+                Ok(in_price_unit) => {
+                    let stale = (today - price.date).num_days() > STALE_AFTER_DAYS;
+                    (
+                        Some(in_price_unit * price.price),
+                        stale.then_some(PriceIssue::Stale { date: price.date }),
+                    )
+                }
+            }
+        }
+    };
+    LineCost {
+        ingredient: name.to_string(),
+        cost,
+        price,
+        issue,
+    }
+}
+
+fn estimate(lines: Vec<LineCost>) -> CostEstimate {
+    CostEstimate {
+        total: lines.iter().filter_map(|l| l.cost).sum(),
+        lines,
+    }
+}
+
+/// Costs a recipe's required ingredients. `ingredient` looks an ingredient up by name
+pub fn recipe_cost<'a>(
+    recipe: &Recipe,
+    ingredient: impl Fn(&str) -> Option<&'a Ingredient>,
+    today: NaiveDate,
+) -> RecipeCost {
+    let lines = recipe
+        .required_ingredients()
+        .map(|line| {
+            line_cost(
+                &line.ingredient,
+                ingredient(&line.ingredient),
+                line.quantity.as_ref().and_then(|q| q.value()),
+                line.quantity_type.as_deref().unwrap_or_default(),
+                today,
+            )
+        })
+        .collect();
+    RecipeCost {
+        estimate: estimate(lines),
+        servings: recipe.servings,
+    }
+}
+
+/// Costs a shopping list at the amounts it gives
+pub fn shopping_cost<'a>(
+    list: &ShoppingList,
+    ingredient: impl Fn(&str) -> Option<&'a Ingredient>,
+) -> CostEstimate {
+    let lines = list
+        .items
+        .iter()
+        .map(|item| {
+            line_cost(
+                &item.ingredient,
+                ingredient(&item.ingredient),
+                item.quantity,
+                &item.quantity_type,
+                list.today,
+            )
+        })
+        .collect();
+    estimate(lines)
+}
@@ -1,8 +1,24 @@
+use crate::barcode::{self, BarcodeSuggestion};
+use crate::bundled_kb;
+use crate::cost::{self, CostEstimate, RecipeCost};
+use crate::duplicates::{self, DuplicateSuggestion, IngredientMerge};
+use crate::forecast::{self, PantryForecast};
+use crate::ingredient_parser::{self, ParsedIngredient};
+use crate::links::{self, BrokenLink, LinkSource, LinkTarget};
+use crate::markdown::{self, MarkupFormat};
+use crate::merge::{self, PantryMergeResult};
+use crate::planner::{self, MealPlanSuggestion, PlanRequest};
+use crate::quantity::Quantity;
+use crate::shopping::{self, ShoppingList, StockCheck};
+use crate::slug;
+use crate::steps::Step;
 use crate::types::*;
-use std::collections::HashMap;
-use std::path::{Path, PathBuf};
-use std::fs;
+use crate::units::{self, UnitSystem};
+use chrono::NaiveDate;
 use log::{debug, error, info, warn};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
 
 // Main struct for managing the cookbook data
 // It contains methods for loading, updating, and retrieving ingredients, recipes, and pantry items
@@ -13,7 +29,11 @@ pub struct DataManager {
     ingredients: HashMap<String, Ingredient>,
     recipes: Vec<Recipe>,
     pantry: Option<Pantry>,
+    meal_plan: MealPlan,
+    pantry_journal: PantryJournal,
     kb_entries: HashMap<String, KnowledgeBaseEntry>,
+    kb_entry_files: HashMap<String, PathBuf>,
+    bundled_kb_slugs: HashSet<String>,
 }
 
 // Implementing methods for DataManager
@@ -33,7 +53,12 @@ pub struct DataManager {
 /// - `{data_dir}/ingredients/*.yaml` - YAML files for each ingredient
 /// - `{data_dir}/recipes/*.md` - Markdown files for each recipe
 /// - `{data_dir}/pantry.yaml` - YAML file containing pantry inventory
-/// - `{data_dir}/kb/*.md` - Markdown files for knowledge base entries
+/// - `{data_dir}/mealplan.yaml` - YAML file with the recipes planned per date and meal
+///   (optional; created when the first meal is planned)
+/// - `{data_dir}/pantry_journal.yaml` - YAML file recording every change to a pantry
+///   quantity (optional; created by the first change)
+/// - `{data_dir}/kb/*.md` - Markdown files for knowledge base entries (optional; the
+///   entries bundled with the engine are used for any slug not found here)
 ///
 /// # Main Functionality
 ///
@@ -85,7 +110,11 @@ impl DataManager {
             ingredients: HashMap::new(),
             recipes: Vec::new(),
             pantry: None,
+            meal_plan: MealPlan::default(),
+            pantry_journal: PantryJournal::default(),
             kb_entries: HashMap::new(),
+            kb_entry_files: HashMap::new(),
+            bundled_kb_slugs: HashSet::new(),
         };
 
         info!("Loading data...");
@@ -107,6 +136,8 @@ impl DataManager {
         self.load_ingredients()?;
         self.load_recipes()?;
         self.load_pantry()?;
+        self.load_meal_plan()?;
+        self.load_pantry_journal()?;
         self.load_kb_entries()?;
 
         Ok(()) // Return Ok if all data loading is successful
@@ -140,7 +171,14 @@ impl DataManager {
             // Check if the entry is a file and has a .yaml extension
             // If it is, load the ingredient from the file
             if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("yaml") {
-                let ingredient = Ingredient::from_file(&path)?;
+                let mut ingredient = Ingredient::from_file(&path)?;
+                // The file name is authoritative, so hand-made or renamed files keep working
+                if let Some(stem) = slug::slug_from_path(&path) {
+                    if ingredient.slug != stem {
+                        debug!("Ingredient '{}' is stored as {:?}", ingredient.name, path);
+                        ingredient.slug = stem;
+                    }
+                }
                 self.ingredients.insert(ingredient.name.clone(), ingredient);
             }
         }
@@ -181,7 +219,8 @@ impl DataManager {
                 // Check if the entry is a file and has a .md extension
                 match Recipe::from_file(&path) {
                     // Load the recipe from the file
-                    Ok(recipe) => {
+                    Ok(mut recipe) => {
+                        recipe.slug = slug::slug_from_path(&path).unwrap_or_default();
                         // Use lowercase title as key to avoid case-sensitive duplicates
                         let title_key = recipe.title.to_lowercase();
                         recipes_map.insert(title_key, recipe);
@@ -243,43 +282,107 @@ impl DataManager {
         Ok(()) // Return Ok if the pantry is loaded successfully
     }
 
+    /// Loads the meal plan from mealplan.yaml, or starts an empty one if there is none
+    /// A file that fails to load (e.g. a half-synced copy) is logged and an empty plan is used,
+    /// so the rest of the data can still be opened
+    fn load_meal_plan(&mut self) -> Result<(), CookbookError> {
+        let path = self.data_dir.join("mealplan.yaml");
+        self.meal_plan = if path.exists() {
+            match MealPlan::from_file(&path) {
+                Ok(meal_plan) => {
+                    info!("Loaded meal plan with {} entries", meal_plan.entries.len());
+                    meal_plan
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to load meal plan {:?}, starting an empty one: {}",
+                        path, e
+                    );
+                    MealPlan::default()
+                }
+            }
+        } else {
+            MealPlan::default()
+        };
+        Ok(())
+    }
+
+    /// Loads the pantry journal from pantry_journal.yaml, or starts an empty one if there is none
+    /// The journal only feeds forecasts, so a file that fails to load is logged and replaced
+    /// by an empty journal rather than stopping the rest of the data from opening
+    fn load_pantry_journal(&mut self) -> Result<(), CookbookError> {
+        let path = self.data_dir.join("pantry_journal.yaml");
+        self.pantry_journal = if path.exists() {
+            match PantryJournal::from_file(&path) {
+                Ok(journal) => {
+                    info!(
+                        "Loaded pantry journal with {} changes",
+                        journal.changes.len()
+                    );
+                    journal
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to load pantry journal {:?}, starting an empty one: {}",
+                        path, e
+                    );
+                    PantryJournal::default()
+                }
+            }
+        } else {
+            PantryJournal::default()
+        };
+        Ok(())
+    }
+
     /// Loads knowledge base entries from the kb directory
-    /// Returns an error if the directory does not exist or if any entry file fails to load
+    /// Returns an error if the directory cannot be read
     /// The kb directory should contain Markdown files for each knowledge base entry
     /// Each entry file should include the frontmatter with the slug, title, image, and content
     /// The slug should be a unique identifier for the entry
-    /// The title should be the title of the entry
-    /// The image should be the path to the image file (if any)
-    /// The content should be the main content of the entry
-    /// The knowledge base entries should be stored in the kb_entries HashMap
-    /// The keys of the HashMap should be the slugs of the entries
-    /// The values of the HashMap should be the KnowledgeBaseEntry struct
-    /// The KnowledgeBaseEntry struct should include the slug, title, image, and content fields
+    /// The knowledge base entries should be stored in the kb_entries HashMap, keyed by slug
+    /// The file each entry came from is kept in kb_entry_files, as it may not match the slug
+    /// Entries bundled with the engine fill in any slug the data directory does not provide
     fn load_kb_entries(&mut self) -> Result<(), CookbookError> {
-        // Path to the canonical KB directory in the engine crate
-        let kb_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/kb");
-        if !kb_dir.exists() {
-            // KB directory doesn't exist - this is OK for applications that don't need KB functionality
-            // (like the pantryman app which only manages pantry/ingredients)
-            warn!("Knowledge Base directory not found: {:?} - continuing without KB entries", kb_dir);
-            return Ok(());
+        let kb_dir = self.data_dir.join("kb");
+        if kb_dir.exists() {
+            // Read the contents of the kb directory
+            let entries =
+                fs::read_dir(&kb_dir).map_err(|e| CookbookError::ListDirError(e.to_string()))?;
+
+            // Iterate through each entry in the directory
+            for entry in entries {
+                let entry = entry.map_err(|e| CookbookError::ListDirError(e.to_string()))?;
+                let path = entry.path();
+                if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("md") {
+                    match KnowledgeBaseEntry::from_file(&path) {
+                        Ok(kb_entry) => {
+                            self.kb_entry_files
+                                .insert(kb_entry.slug.clone(), path.clone());
+                            self.kb_entries.insert(kb_entry.slug.clone(), kb_entry);
+                        }
+                        Err(e) => warn!("Failed to load KB entry {:?}: {}", path, e),
+                    }
+                }
+            }
+        } else {
+            // The kb directory is optional - the bundled entries are still available
+            debug!(
+                "No KB directory at {:?}, using bundled entries only",
+                kb_dir
+            );
         }
 
-        // Read the contents of the kb directory
-        let entries =
-            std::fs::read_dir(&kb_dir).map_err(|e| CookbookError::ListDirError(e.to_string()))?;
-
-        // Iterate through each entry in the directory
-        for entry in entries {
-            let entry = entry.map_err(|e| CookbookError::ListDirError(e.to_string()))?;
-            let path = entry.path();
-            if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("md") {
-                match KnowledgeBaseEntry::from_file(&path) {
-                    Ok(kb_entry) => {
+        // Fall back to the bundled entries for anything the user hasn't written themselves
+        for (file_name, content) in bundled_kb::BUNDLED_KB_ENTRIES {
+            match KnowledgeBaseEntry::from_markdown(content) {
+                Ok(kb_entry) => {
+                    if !self.kb_entries.contains_key(&kb_entry.slug) {
+                        self.bundled_kb_slugs.insert(kb_entry.slug.clone());
                         self.kb_entries.insert(kb_entry.slug.clone(), kb_entry);
                     }
-                    Err(e) => warn!("Failed to load KB entry {:?}: {}", path, e),
                 }
+                Err(e) => warn!("Failed to load bundled KB entry {}: {}", file_name, e),
             }
         }
         Ok(())
@@ -384,7 +487,7 @@ impl DataManager {
             .values()
             .filter(|ingredient| {
                 // Check if the ingredient has a KB reference that matches the slug
-                ingredient.kb.as_ref().map_or(false, |slug| slug == kb_slug)
+                ingredient.kb.as_ref().is_some_and(|slug| slug == kb_slug)
             })
             .collect()
     }
@@ -472,7 +575,7 @@ impl DataManager {
         }
 
         // Sort ingredients within each category
-        for (_category, ingredients) in &mut result {
+        for ingredients in result.values_mut() {
             ingredients.sort_by(|a, b| a.name.cmp(&b.name));
         }
 
@@ -496,7 +599,7 @@ impl DataManager {
         }
 
         // Sort ingredients within each category
-        for (_category, items) in &mut result {
+        for items in result.values_mut() {
             items.sort_by(|a, b| a.0.name.cmp(&b.0.name));
         }
 
@@ -520,7 +623,7 @@ impl DataManager {
                 // Search in instructions
                 recipe.instructions.to_lowercase().contains(&query_lower) ||
                 // Search in tags
-                recipe.tags.as_ref().map_or(false, |tags|
+                recipe.tags.as_ref().is_some_and(|tags|
                     tags.iter().any(|tag| tag.to_lowercase().contains(&query_lower))
                 )
             })
@@ -573,6 +676,122 @@ impl DataManager {
         None
     }
 
+    /// Resolves the target of a `[[wiki link]]`
+    /// KB articles are tried first (by slug, then title), then ingredients (by name, slug or
+    /// English translation), then recipes (by title). Matching ignores case, and spaces
+    /// match underscores
+    pub fn resolve_wiki_link(&self, target: &str) -> Option<LinkTarget> {
+        let key = links::link_key(target);
+        if key.is_empty() {
+            return None;
+        }
+
+        let kb = self
+            .kb_entries
+            .values()
+            .find(|e| links::link_key(&e.slug) == key)
+            .or_else(|| {
+                self.kb_entries
+                    .values()
+                    .find(|e| links::link_key(&e.title) == key)
+            });
+        if let Some(entry) = kb {
+            return Some(LinkTarget::Kb(entry.slug.clone()));
+        }
+
+        let ingredient = self
+            .ingredients
+            .values()
+            .find(|i| links::link_key(&i.name) == key || links::link_key(&i.slug) == key)
+            .or_else(|| self.find_ingredient_by_name_or_translation(target.trim(), "en"));
+        if let Some(ingredient) = ingredient {
+            return Some(LinkTarget::Ingredient(ingredient.name.clone()));
+        }
+
+        self.recipes
+            .iter()
+            .find(|r| links::link_key(&r.title) == key)
+            .map(|r| LinkTarget::Recipe(r.title.clone()))
+    }
+
+    /// Renders Markdown (recipe instructions, KB content) to a single markup string,
+    /// resolving `[[wiki links]]` against the loaded data
+    pub fn render_markdown(&self, markdown_text: &str, format: MarkupFormat) -> String {
+        markdown::render_to_string(markdown_text, format, &|target| {
+            self.resolve_wiki_link(target)
+        })
+    }
+
+    /// Returns every link that points at something that doesn't exist
+    /// This covers `[[wiki links]]` in recipe instructions and KB articles, and the kb field
+    /// of ingredients
+    pub fn find_broken_links(&self) -> Vec<BrokenLink> {
+        let mut broken: Vec<BrokenLink> = self
+            .link_sources()
+            .into_iter()
+            .flat_map(|(source, text)| {
+                links::parse_wiki_links(text)
+                    .into_iter()
+                    .filter(|link| self.resolve_wiki_link(&link.target).is_none())
+                    .map(move |link| BrokenLink {
+                        source: source.clone(),
+                        target: link.target,
+                    })
+            })
+            .collect();
+
+        for ingredient in self.ingredients.values() {
+            if let Some(slug) = &ingredient.kb {
+                if !self.kb_entries.contains_key(slug) {
+                    broken.push(BrokenLink {
+                        source: LinkSource::Ingredient(ingredient.name.clone()),
+                        target: slug.clone(),
+                    });
+                }
+            }
+        }
+
+        broken.sort_by(|a, b| (&a.source, &a.target).cmp(&(&b.source, &b.target)));
+        broken
+    }
+
+    /// Returns the recipes and KB articles whose text links to `target`, sorted
+    /// Ingredients that link to a KB article through their kb field are not included; see
+    /// get_ingredients_with_kb_reference for those
+    pub fn get_backlinks(&self, target: &LinkTarget) -> Vec<LinkSource> {
+        let mut sources: Vec<LinkSource> = self
+            .link_sources()
+            .into_iter()
+            .filter(|(source, text)| {
+                let is_self = match (source, target) {
+                    (LinkSource::Recipe(a), LinkTarget::Recipe(b)) => a == b,
+                    (LinkSource::Kb(a), LinkTarget::Kb(b)) => a == b,
+                    _ => false,
+                };
+                !is_self
+                    && links::parse_wiki_links(text)
+                        .iter()
+                        .any(|link| self.resolve_wiki_link(&link.target).as_ref() == Some(target))
+            })
+            .map(|(source, _)| source)
+            .collect();
+        sources.sort();
+        sources
+    }
+
+    /// Returns every piece of text that can contain wiki links, with where it came from
+    fn link_sources(&self) -> Vec<(LinkSource, &str)> {
+        let recipes = self
+            .recipes
+            .iter()
+            .map(|r| (LinkSource::Recipe(r.title.clone()), r.instructions.as_str()));
+        let kb = self
+            .kb_entries
+            .values()
+            .map(|e| (LinkSource::Kb(e.slug.clone()), e.content.as_str()));
+        recipes.chain(kb).collect()
+    }
+
     /// Updates an ingredient in the pantry with new quantity and quantity_type values
     pub fn update_pantry_item(
         &mut self,
@@ -582,7 +801,7 @@ impl DataManager {
     ) -> Result<bool, CookbookError> {
         // Make sure we have a pantry loaded
         if self.pantry.is_none() {
-            return Err(CookbookError::UpdateError(format!("No pantry loaded")));
+            return Err(CookbookError::UpdateError("No pantry loaded".to_string()));
         }
         let pantry = self
             .pantry
@@ -604,6 +823,11 @@ impl DataManager {
             .items
             .iter()
             .position(|item| item.ingredient == ingredient_name);
+        let before = pantry_item_index.map(|index| {
+            let item = &pantry.items[index];
+            (item.quantity, item.quantity_type.clone())
+        });
+        let unit = quantity_type.clone().unwrap_or_default();
 
         if let Some(index) = pantry_item_index {
             // Update the existing pantry item
@@ -617,6 +841,7 @@ impl DataManager {
                 quantity,
                 quantity_type: quantity_type.unwrap_or_default(),
                 last_updated: today,
+                expires: None,
             };
 
             pantry.items.push(new_item);
@@ -625,16 +850,291 @@ impl DataManager {
         // Save the updated pantry to file
         let pantry_path = self.data_dir.join("pantry.yaml");
         pantry.to_file(pantry_path)?;
+        self.record_pantry_change(ingredient_name, before, quantity, &unit);
+
+        Ok(true)
+    }
 
+    /// Sets or clears the use-by date of an item in the pantry
+    /// Returns false if the ingredient is not in the pantry
+    pub fn set_pantry_expiry(
+        &mut self,
+        ingredient_name: &str,
+        expires: Option<NaiveDate>,
+    ) -> Result<bool, CookbookError> {
+        let Some(pantry) = self.pantry.as_mut() else {
+            return Err(CookbookError::UpdateError("No pantry loaded".to_string()));
+        };
+        let Some(item) = pantry
+            .items
+            .iter_mut()
+            .find(|item| item.ingredient == ingredient_name)
+        else {
+            return Ok(false);
+        };
+        if item.expires != expires {
+            item.expires = expires;
+            pantry.to_file(self.data_dir.join("pantry.yaml"))?;
+        }
         Ok(true)
     }
 
+    /// Returns the recorded changes to an ingredient's pantry quantity, oldest first
+    pub fn get_pantry_changes(&self, ingredient_name: &str) -> Vec<&PantryChange> {
+        self.pantry_journal
+            .changes
+            .iter()
+            .filter(|c| c.ingredient == ingredient_name)
+            .collect()
+    }
+
+    /// Forecasts when a pantry item runs out, from its recorded use and the meals planned
+    /// from `today` on. Returns None if it isn't in the pantry or has no quantity
+    pub fn forecast_pantry_item(
+        &self,
+        ingredient_name: &str,
+        today: NaiveDate,
+    ) -> Option<PantryForecast> {
+        let item = self.get_pantry_item(ingredient_name)?;
+        let changes = self.get_pantry_changes(ingredient_name);
+        let ingredient = self.get_ingredient(ingredient_name);
+        forecast::forecast(
+            item,
+            ingredient,
+            &changes,
+            &self.planned_recipes(today),
+            today,
+        )
+    }
+
+    /// What to buy before `next_shop`: ingredients that meals planned until then need but the
+    /// pantry doesn't have, and pantry items forecast to run out before then
+    pub fn shopping_list(&self, today: NaiveDate, next_shop: NaiveDate) -> ShoppingList {
+        let pantry = self.pantry.as_ref().map_or(&[][..], |p| &p.items[..]);
+        shopping::shopping_list(
+            pantry,
+            &self.pantry_journal.changes,
+            &self.planned_recipes(today),
+            |name| self.get_ingredient(name),
+            today,
+            next_shop,
+        )
+    }
+
+    /// Checks whether the pantry holds enough of each of a recipe's required ingredients, at
+    /// `servings` or the recipe's own servings. Returns None if there is no such recipe
+    pub fn check_recipe_stock(
+        &self,
+        title: &str,
+        servings: Option<u32>,
+    ) -> Option<Vec<StockCheck>> {
+        let recipe = self.get_recipe(title)?;
+        let factor = match (servings, recipe.servings) {
+            (Some(wanted), Some(base)) if base > 0 => wanted as f64 / base as f64,
+            _ => 1.0,
+        };
+        let pantry = self.pantry.as_ref().map_or(&[][..], |p| &p.items[..]);
+        Some(shopping::check_stock(recipe, factor, pantry, |name| {
+            self.get_ingredient(name)
+        }))
+    }
+
+    /// The meals planned from `from` on, with their recipes
+    fn planned_recipes(&self, from: NaiveDate) -> Vec<(&MealPlanEntry, &Recipe)> {
+        self.meal_plan
+            .entries
+            .iter()
+            .filter(|e| e.date >= from)
+            .filter_map(|e| self.get_recipe(&e.recipe).map(|r| (e, r)))
+            .collect()
+    }
+
+    /// Appends a change to pantry_journal.yaml, unless the quantity stayed the same.
+    /// `before` is the item's quantity and unit before the change, None if it is new.
+    /// The pantry itself is already saved, so a failure to write the journal is only logged
+    fn record_pantry_change(
+        &mut self,
+        ingredient_name: &str,
+        before: Option<(Option<f64>, String)>,
+        quantity: Option<f64>,
+        quantity_type: &str,
+    ) {
+        let previous = match before {
+            Some((previous, unit)) if units::same_unit(&unit, quantity_type) => {
+                if previous == quantity {
+                    return;
+                }
+                previous
+            }
+            // A change of unit records the old quantity in the new unit, when it converts
+            Some((Some(previous), unit)) => units::convert_ingredient(
+                previous,
+                &unit,
+                quantity_type,
+                self.ingredients.get(ingredient_name),
+            )
+            .ok(),
+            _ => None,
+        };
+        self.pantry_journal.changes.push(PantryChange {
+            date: chrono::Local::now().date_naive(),
+            ingredient: ingredient_name.to_string(),
+            previous,
+            quantity,
+            quantity_type: quantity_type.to_string(),
+        });
+        if let Err(e) = self.save_pantry_journal() {
+            warn!(
+                "Failed to record pantry change of '{}': {}",
+                ingredient_name, e
+            );
+        }
+    }
+
+    /// Points the journal's changes to an ingredient at its new name
+    fn rename_in_pantry_journal(&mut self, from: &str, to: &str) {
+        let mut renamed = false;
+        for change in &mut self.pantry_journal.changes {
+            if change.ingredient == from {
+                change.ingredient = to.to_string();
+                renamed = true;
+            }
+        }
+        if renamed {
+            if let Err(e) = self.save_pantry_journal() {
+                warn!("Failed to rename '{}' in the pantry journal: {}", from, e);
+            }
+        }
+    }
+
+    fn save_pantry_journal(&self) -> Result<(), CookbookError> {
+        self.pantry_journal
+            .to_file(self.data_dir.join("pantry_journal.yaml"))
+    }
+
+    /// Lists cloud-provider conflict copies of pantry.yaml in the data directory
+    /// (e.g. "pantry (conflicted copy 2026-01-02).yaml"), sorted by file name
+    pub fn find_pantry_conflict_copies(&self) -> Result<Vec<PathBuf>, CookbookError> {
+        merge::find_pantry_conflict_copies(&self.data_dir)
+    }
+
+    /// Merges a diverged copy of the pantry into the loaded one and saves the result
+    /// The base parameter is the last version both sides agreed on, if known
+    /// Conflicting items keep their local version and are returned for the caller to resolve
+    pub fn merge_pantry(
+        &mut self,
+        base: Option<&Pantry>,
+        remote: &Pantry,
+    ) -> Result<PantryMergeResult, CookbookError> {
+        let empty = Pantry {
+            version: 1,
+            items: Vec::new(),
+        };
+        let local = self.pantry.as_ref().unwrap_or(&empty);
+        let result = merge::merge_pantries(base.unwrap_or(&empty), local, remote);
+        let before = local.items.clone();
+
+        let pantry_path = self.data_dir.join("pantry.yaml");
+        result.merged.to_file(pantry_path)?;
+        self.pantry = Some(result.merged.clone());
+
+        // Journal what the merge changed: new or changed items, then removed ones
+        let previous = |name: &str| {
+            before
+                .iter()
+                .find(|item| item.ingredient == name)
+                .map(|item| (item.quantity, item.quantity_type.clone()))
+        };
+        for item in &result.merged.items {
+            let was = previous(&item.ingredient);
+            self.record_pantry_change(&item.ingredient, was, item.quantity, &item.quantity_type);
+        }
+        for item in &before {
+            if !result
+                .merged
+                .items
+                .iter()
+                .any(|m| m.ingredient == item.ingredient)
+            {
+                let was = Some((item.quantity, item.quantity_type.clone()));
+                self.record_pantry_change(&item.ingredient, was, Some(0.0), &item.quantity_type);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Merges a conflict copy of pantry.yaml into the loaded pantry
+    /// The copy has no known common ancestor, so nothing is treated as deleted
+    /// The copy is removed when the merge was clean and kept otherwise, so no data is lost
+    pub fn merge_pantry_conflict_copy(
+        &mut self,
+        path: &Path,
+    ) -> Result<PantryMergeResult, CookbookError> {
+        // Only ever touch conflict copies that live in our own data directory
+        let file_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        if path.parent() != Some(self.data_dir.as_path())
+            || !merge::is_pantry_conflict_copy(file_name)
+        {
+            return Err(CookbookError::UpdateError(format!(
+                "merge_pantry_conflict_copy: {:?} is not a pantry conflict copy in the data directory",
+                path
+            )));
+        }
+
+        let remote = Pantry::from_file(path)?;
+        let result = self.merge_pantry(None, &remote)?;
+
+        if result.is_clean() {
+            info!("Merged conflict copy {:?} cleanly, removing it", path);
+            fs::remove_file(path).map_err(|e| {
+                CookbookError::WriteError(format!("Failed to remove conflict copy: {}", e))
+            })?;
+        } else {
+            warn!(
+                "Conflict copy {:?} left in place: {} unresolved items",
+                path,
+                result.conflicts.len()
+            );
+        }
+
+        Ok(result)
+    }
+
     /// Updates an ingredient's properties (name, category, kb, tags)
+    /// Renaming also updates the pantry and every recipe that uses the ingredient
     pub fn update_ingredient(
         &mut self,
         original_name: &str,
         new_ingredient: Ingredient,
     ) -> Result<bool, CookbookError> {
+        self.rename_ingredient(original_name, new_ingredient)
+            .map(|_| true)
+    }
+
+    /// Returns the titles of the recipes a rename of this ingredient would rewrite, sorted
+    /// This is a dry run of rename_ingredient; nothing is changed
+    pub fn preview_ingredient_rename(&self, original_name: &str) -> Vec<String> {
+        let mut titles: Vec<String> = self
+            .recipes
+            .iter()
+            .filter(|r| r.ingredients.iter().any(|i| i.ingredient == original_name))
+            .map(|r| r.title.clone())
+            .collect();
+        titles.sort();
+        titles
+    }
+
+    /// Updates an ingredient like update_ingredient, returning the titles of the recipes
+    /// that were rewritten because the name changed (empty if it didn't)
+    pub fn rename_ingredient(
+        &mut self,
+        original_name: &str,
+        new_ingredient: Ingredient,
+    ) -> Result<Vec<String>, CookbookError> {
         // Check if the original ingredient exists
         if !self.ingredients.contains_key(original_name) {
             return Err(CookbookError::UpdateError(format!(
@@ -643,9 +1143,11 @@ impl DataManager {
             )));
         }
 
-        // Check if the new name conflicts with an existing ingredient (if name is changing)
+        // Check if the new name conflicts with another ingredient, ignoring case
         if original_name != new_ingredient.name
-            && self.ingredients.contains_key(&new_ingredient.name)
+            && self.ingredients.keys().any(|name| {
+                name != original_name && slug::slugs_collide(name, &new_ingredient.name)
+            })
         {
             return Err(CookbookError::UpdateError(format!(
                 "update_ingredient: Cannot rename: ingredient '{}' already exists",
@@ -653,13 +1155,28 @@ impl DataManager {
             )));
         }
 
-        let ingredients_dir = self.data_dir.join("ingredients");
-        let old_path = ingredients_dir.join(format!("{}.yaml", original_name.replace(" ", "_")));
-        let new_path =
-            ingredients_dir.join(format!("{}.yaml", new_ingredient.name.replace(" ", "_")));
+        // The file keeps its slug unless a different one is asked for
+        let mut new_ingredient = new_ingredient;
+        let old_slug = self.ingredients[original_name].slug.clone();
+        if new_ingredient.slug.is_empty() || new_ingredient.slug == old_slug {
+            new_ingredient.slug = old_slug.clone();
+        } else {
+            let others = self
+                .ingredients
+                .iter()
+                .filter(|(name, _)| name.as_str() != original_name)
+                .map(|(_, i)| i.slug.as_str());
+            new_ingredient.slug = assign_slug(&new_ingredient.slug, &new_ingredient.name, others)?;
+        }
+        let old_path = self.ingredient_path(&old_slug)?;
+        let new_path = self.ingredient_path(&new_ingredient.slug)?;
 
         // Handle name changes
+        let mut changed_recipes = Vec::new();
         if original_name != new_ingredient.name {
+            // Update recipe references first, so a failure leaves the ingredient untouched
+            changed_recipes = self.rename_in_recipes(original_name, &new_ingredient.name)?;
+
             // Update any pantry reference
             if let Some(pantry) = self.pantry.as_mut() {
                 for item in &mut pantry.items {
@@ -672,6 +1189,7 @@ impl DataManager {
                 let pantry_path = self.data_dir.join("pantry.yaml");
                 pantry.to_file(pantry_path)?;
             }
+            self.rename_in_pantry_journal(original_name, &new_ingredient.name);
 
             // Remove the old ingredient from our HashMap
             self.ingredients.remove(original_name);
@@ -682,16 +1200,92 @@ impl DataManager {
             .insert(new_ingredient.name.clone(), new_ingredient.clone());
 
         // Write the ingredient to file
-        new_ingredient.to_file(&new_path)?;
+        new_ingredient.to_file_preserving(&new_path, &old_path)?;
 
-        // If the name changed, remove the old file
-        if original_name != new_ingredient.name && old_path.exists() {
+        // If the slug changed, remove the old file (unless only its case changed, in which case
+        // a case-insensitive filesystem has already overwritten it)
+        if !slug::slugs_collide(&old_slug, &new_ingredient.slug) && old_path.exists() {
             fs::remove_file(old_path).map_err(|e| {
                 CookbookError::WriteError(format!("Failed to remove old ingredient file: {}", e))
             })?;
         }
 
-        Ok(true)
+        Ok(changed_recipes)
+    }
+
+    /// Points every recipe that uses `old_name` at `new_name` and rewrites their files
+    fn rename_in_recipes(
+        &mut self,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<Vec<String>, CookbookError> {
+        let titles = self.rewrite_recipes(|recipe| {
+            let mut changed = false;
+            for ingredient in &mut recipe.ingredients {
+                if ingredient.ingredient == old_name {
+                    ingredient.ingredient = new_name.to_string();
+                    changed = true;
+                }
+            }
+            changed
+        })?;
+        if !titles.is_empty() {
+            info!(
+                "Renamed ingredient '{}' to '{}' in {} recipes",
+                old_name,
+                new_name,
+                titles.len()
+            );
+        }
+        Ok(titles)
+    }
+
+    /// Applies `edit` to every recipe and rewrites the files of those it changed
+    /// (`edit` returns true if it changed the recipe), returning their titles, sorted.
+    /// All files are written to temporary copies first and only moved into place once every
+    /// copy succeeded, so a failed write doesn't leave half the recipes changed
+    fn rewrite_recipes(
+        &mut self,
+        edit: impl Fn(&mut Recipe) -> bool,
+    ) -> Result<Vec<String>, CookbookError> {
+        let recipes_dir = self.data_dir.join("recipes");
+        let mut staged = Vec::new();
+        for (index, recipe) in self.recipes.iter().enumerate() {
+            let mut updated = recipe.clone();
+            if !edit(&mut updated) {
+                continue;
+            }
+            let written = slug::slug_path(&recipes_dir, &recipe.slug, "md").and_then(|path| {
+                let tmp_path = path.with_extension("md.tmp");
+                match updated.to_file_preserving(&tmp_path, &path) {
+                    Ok(()) => Ok((path, tmp_path)),
+                    Err(e) => {
+                        let _ = fs::remove_file(&tmp_path);
+                        Err(e)
+                    }
+                }
+            });
+            match written {
+                Ok((path, tmp_path)) => staged.push((index, path, tmp_path, updated)),
+                Err(e) => {
+                    for (_, _, tmp, _) in &staged {
+                        let _ = fs::remove_file(tmp);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        let mut titles = Vec::new();
+        for (index, path, tmp_path, updated) in staged {
+            fs::rename(&tmp_path, &path).map_err(|e| {
+                CookbookError::WriteError(format!("Failed to replace recipe file: {}", e))
+            })?;
+            titles.push(updated.title.clone());
+            self.recipes[index] = updated;
+        }
+        titles.sort();
+        Ok(titles)
     }
 
     /// Updates a recipe's properties including title, ingredients, prep time, downtime, servings, tags, and instructions
@@ -708,9 +1302,11 @@ impl DataManager {
             )));
         }
 
-        // Check if the new title conflicts with an existing recipe (if title is changing)
+        // Check if the new title conflicts with another recipe, ignoring case
         if original_title != new_recipe.title
-            && self.recipes.iter().any(|r| r.title == new_recipe.title)
+            && self.recipes.iter().any(|r| {
+                r.title != original_title && slug::slugs_collide(&r.title, &new_recipe.title)
+            })
         {
             return Err(CookbookError::UpdateError(format!(
                 "update_recipe: Cannot rename: recipe '{}' already exists",
@@ -718,9 +1314,26 @@ impl DataManager {
             )));
         }
 
-        let recipes_dir = self.data_dir.join("recipes");
-        let old_path = recipes_dir.join(format!("{}.md", original_title.replace(" ", "_")));
-        let new_path = recipes_dir.join(format!("{}.md", new_recipe.title.replace(" ", "_")));
+        // The file keeps its slug unless a different one is asked for
+        let mut new_recipe = new_recipe;
+        let old_slug = self
+            .recipes
+            .iter()
+            .find(|r| r.title == original_title)
+            .map(|r| r.slug.clone())
+            .unwrap_or_default();
+        if new_recipe.slug.is_empty() || new_recipe.slug == old_slug {
+            new_recipe.slug = old_slug.clone();
+        } else {
+            let others = self
+                .recipes
+                .iter()
+                .filter(|r| r.title != original_title)
+                .map(|r| r.slug.as_str());
+            new_recipe.slug = assign_slug(&new_recipe.slug, &new_recipe.title, others)?;
+        }
+        let old_path = self.recipe_path(&old_slug)?;
+        let new_path = self.recipe_path(&new_recipe.slug)?;
 
         // Update recipe in the recipes vector
         // First remove the old recipe
@@ -730,10 +1343,24 @@ impl DataManager {
         self.recipes.push(new_recipe.clone());
 
         // Write the recipe to file
-        new_recipe.to_file(&new_path)?;
+        new_recipe.to_file_preserving(&new_path, &old_path)?;
+
+        // Planned meals follow the recipe to its new title
+        if original_title != new_recipe.title {
+            let mut renamed = false;
+            for entry in &mut self.meal_plan.entries {
+                if entry.recipe == original_title {
+                    entry.recipe = new_recipe.title.clone();
+                    renamed = true;
+                }
+            }
+            if renamed {
+                self.save_meal_plan()?;
+            }
+        }
 
-        // If the title changed, remove the old file
-        if original_title != new_recipe.title && old_path.exists() {
+        // If the slug changed, remove the old file (unless only its case changed)
+        if !slug::slugs_collide(&old_slug, &new_recipe.slug) && old_path.exists() {
             fs::remove_file(old_path).map_err(|e| {
                 CookbookError::WriteError(format!("Failed to remove old recipe file: {}", e))
             })?;
@@ -745,13 +1372,22 @@ impl DataManager {
     /// Removes an ingredient from the pantry by name
     pub fn remove_from_pantry(&mut self, ingredient_name: &str) -> Result<bool, CookbookError> {
         if let Some(pantry) = self.pantry.as_mut() {
-            let original_len = pantry.items.len();
-            pantry
-                .items
-                .retain(|item| item.ingredient != ingredient_name);
+            let (removed, kept): (Vec<PantryItem>, Vec<PantryItem>) =
+                std::mem::take(&mut pantry.items)
+                    .into_iter()
+                    .partition(|item| item.ingredient == ingredient_name);
+            pantry.items = kept;
             let pantry_path = self.data_dir.join("pantry.yaml");
             pantry.to_file(pantry_path)?;
-            Ok(pantry.items.len() < original_len)
+            for item in &removed {
+                self.record_pantry_change(
+                    ingredient_name,
+                    Some((item.quantity, item.quantity_type.clone())),
+                    Some(0.0),
+                    &item.quantity_type,
+                );
+            }
+            Ok(!removed.is_empty())
         } else {
             Err(CookbookError::UpdateError("No pantry loaded".to_string()))
         }
@@ -771,16 +1407,12 @@ impl DataManager {
 
         if remove_from_pantry {
             // Remove from pantry if present
-            if let Some(pantry) = self.pantry.as_mut() {
-                pantry
-                    .items
-                    .retain(|item| item.ingredient != new_ingredient.name);
-                let pantry_path = self.data_dir.join("pantry.yaml");
-                pantry.to_file(pantry_path)?;
+            if self.pantry.is_some() {
+                self.remove_from_pantry(&new_ingredient.name)?;
             }
         } else {
             // Add or update pantry item
-            let qt = quantity_type.unwrap_or_else(|| "".to_string());
+            let qt = quantity_type.unwrap_or_default();
             self.update_pantry_item(&new_ingredient.name, quantity, Some(qt))?;
         }
 
@@ -831,15 +1463,14 @@ impl DataManager {
     }
 
     /// Checks if all ingredients for a specific recipe are in the pantry
-    /// Returns true if all ingredients are available, false otherwise
+    /// Returns true if all required (non-optional) ingredients are available, false otherwise
     /// The recipe_title parameter is the title of the recipe to check
     pub fn are_all_ingredients_in_pantry(&self, recipe_title: &str) -> bool {
         // First, get the recipe
         if let Some(recipe) = self.get_recipe(recipe_title) {
-            // Check if all ingredients are in pantry
+            // Check if all required ingredients are in pantry
             recipe
-                .ingredients
-                .iter()
+                .required_ingredients()
                 .all(|ingredient| self.is_in_pantry(&ingredient.ingredient))
         } else {
             false // Recipe not found
@@ -859,11 +1490,13 @@ impl DataManager {
         categories
     }
 
-    /// Returns the path to a KB image in the engine's canonical KB directory.
-    pub fn get_kb_image_path(&self, image_name: &str) -> Option<std::path::PathBuf> {
-        // The canonical KB directory is at cookbook-engine/src/kb/
-        let kb_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/kb");
-        let image_path = kb_dir.join(image_name);
+    /// Returns the path to a KB image in the data directory's kb folder, if it exists
+    /// Images bundled with the engine have no path; use get_kb_image_data for those
+    pub fn get_kb_image_path(&self, image_name: &str) -> Option<PathBuf> {
+        if slug::validate_slug(image_name).is_err() {
+            return None;
+        }
+        let image_path = self.data_dir.join("kb").join(image_name);
         if image_path.exists() {
             Some(image_path)
         } else {
@@ -871,84 +1504,736 @@ impl DataManager {
         }
     }
 
-    /// Creates a new recipe and writes it to the recipes directory
-    pub fn create_recipe(&mut self, recipe: Recipe) -> Result<(), CookbookError> {
-        if self.recipes.iter().any(|r| r.title == recipe.title) {
+    /// Returns the bytes of a KB image, from the data directory or the bundled images
+    pub fn get_kb_image_data(&self, image_name: &str) -> Option<Vec<u8>> {
+        if let Some(path) = self.get_kb_image_path(image_name) {
+            return fs::read(path).ok();
+        }
+        bundled_kb::bundled_image(image_name).map(|bytes| bytes.to_vec())
+    }
+
+    /// Returns true if a KB entry comes from the bundled set rather than the data directory
+    pub fn is_kb_entry_bundled(&self, slug: &str) -> bool {
+        self.bundled_kb_slugs.contains(slug)
+    }
+
+    /// Creates a new KB entry and writes it to the kb directory
+    pub fn create_kb_entry(&mut self, entry: KnowledgeBaseEntry) -> Result<(), CookbookError> {
+        slug::validate_slug(&entry.slug)?;
+        if self
+            .kb_entries
+            .keys()
+            .any(|s| slug::slugs_collide(s, &entry.slug))
+        {
             return Err(CookbookError::UpdateError(format!(
-                "create_recipe: Recipe '{}' already exists",
-                recipe.title
+                "create_kb_entry: KB entry '{}' already exists",
+                entry.slug
             )));
         }
-        let recipes_dir = self.data_dir.join("recipes");
-        let path = recipes_dir.join(format!("{}.md", recipe.title.replace(' ', "_")));
-        recipe.to_file(&path)?;
-        self.recipes.push(recipe);
+        let path = self.kb_entry_path(&entry.slug)?;
+        entry.to_file(&path)?;
+        self.kb_entry_files.insert(entry.slug.clone(), path);
+        self.kb_entries.insert(entry.slug.clone(), entry);
         Ok(())
     }
 
-    /// Deletes a recipe by title and removes its file
-    pub fn delete_recipe(&mut self, title: &str) -> Result<bool, CookbookError> {
-        let original_len = self.recipes.len();
-        self.recipes.retain(|r| r.title != title);
-        if self.recipes.len() == original_len {
+    /// Updates a KB entry's properties including slug, title, image, and content
+    /// Updating a bundled entry saves an edited copy to the kb directory, which then takes precedence
+    /// Renaming the slug also updates the kb reference of every ingredient that links to it
+    pub fn update_kb_entry(
+        &mut self,
+        original_slug: &str,
+        new_entry: KnowledgeBaseEntry,
+    ) -> Result<bool, CookbookError> {
+        // Check if the original entry exists
+        if !self.kb_entries.contains_key(original_slug) {
             return Err(CookbookError::UpdateError(format!(
-                "delete_recipe: Recipe '{}' does not exist",
-                title
+                "update_kb_entry: KB entry '{}' does not exist",
+                original_slug
             )));
         }
-        let recipes_dir = self.data_dir.join("recipes");
-        let path = recipes_dir.join(format!("{}.md", title.replace(' ', "_")));
-        if path.exists() {
-            fs::remove_file(&path).map_err(|e| {
-                CookbookError::WriteError(format!("Failed to remove recipe file: {}", e))
-            })?;
+        slug::validate_slug(&new_entry.slug)?;
+
+        // Check if the new slug conflicts with an existing entry (if slug is changing)
+        let renamed = original_slug != new_entry.slug;
+        if renamed
+            && self
+                .kb_entries
+                .keys()
+                .any(|s| s != original_slug && slug::slugs_collide(s, &new_entry.slug))
+        {
+            return Err(CookbookError::UpdateError(format!(
+                "update_kb_entry: Cannot rename: KB entry '{}' already exists",
+                new_entry.slug
+            )));
+        }
+
+        // Write the entry to file, keeping hand-added frontmatter from the one it replaces
+        let path = self.kb_entry_path(&new_entry.slug)?;
+        let original_path = self.kb_entry_files.remove(original_slug);
+        new_entry.to_file_preserving(&path, original_path.as_ref().unwrap_or(&path))?;
+
+        // The entry is now saved as <slug>.md. Remove the file it was read from if that had
+        // another name, unless only the case differs: a case-insensitive filesystem has
+        // already overwritten it
+        if let Some(original_path) = original_path {
+            let same_name =
+                slug::slugs_collide(&original_path.to_string_lossy(), &path.to_string_lossy());
+            if !same_name && original_path.exists() {
+                fs::remove_file(&original_path).map_err(|e| {
+                    CookbookError::WriteError(format!("Failed to remove old KB entry file: {}", e))
+                })?;
+            }
+        }
+
+        if renamed {
+            self.remove_kb_entry_file(original_slug)?;
+
+            // Update any ingredient reference
+            let linked: Vec<Ingredient> = self
+                .get_ingredients_with_kb_reference(original_slug)
+                .into_iter()
+                .cloned()
+                .collect();
+            for mut ingredient in linked {
+                ingredient.kb = Some(new_entry.slug.clone());
+                let name = ingredient.name.clone();
+                self.update_ingredient(&name, ingredient)?;
+            }
         }
+
+        self.bundled_kb_slugs.remove(&new_entry.slug);
+        self.kb_entry_files.insert(new_entry.slug.clone(), path);
+        self.kb_entries.insert(new_entry.slug.clone(), new_entry);
         Ok(true)
     }
 
-    /// Creates a new ingredient and writes it to the ingredients directory
-    pub fn create_ingredient(&mut self, ingredient: Ingredient) -> Result<(), CookbookError> {
-        if self.ingredients.contains_key(&ingredient.name) {
+    /// Deletes a KB entry by slug and removes its file
+    /// Deleting an edited copy of a bundled entry restores the bundled version
+    /// Bundled entries themselves cannot be deleted
+    /// Ingredients that linked to a deleted entry have their link cleared
+    pub fn delete_kb_entry(&mut self, slug: &str) -> Result<bool, CookbookError> {
+        if !self.kb_entries.contains_key(slug) {
             return Err(CookbookError::UpdateError(format!(
-                "create_ingredient: Ingredient '{}' already exists",
-                ingredient.name
+                "delete_kb_entry: KB entry '{}' does not exist",
+                slug
             )));
         }
-        let ingredients_dir = self.data_dir.join("ingredients");
-        let path = ingredients_dir.join(format!("{}.yaml", ingredient.name.replace(" ", "_")));
-        ingredient.to_file(&path)?;
-        self.ingredients.insert(ingredient.name.clone(), ingredient);
+        if self.is_kb_entry_bundled(slug) {
+            return Err(CookbookError::UpdateError(format!(
+                "delete_kb_entry: KB entry '{}' is bundled with the app and cannot be deleted",
+                slug
+            )));
+        }
+        self.remove_kb_entry_file(slug)?;
+
+        // A restored bundled entry keeps its links
+        if !self.kb_entries.contains_key(slug) {
+            let linked: Vec<Ingredient> = self
+                .get_ingredients_with_kb_reference(slug)
+                .into_iter()
+                .cloned()
+                .collect();
+            for mut ingredient in linked {
+                ingredient.kb = None;
+                let name = ingredient.name.clone();
+                self.update_ingredient(&name, ingredient)?;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Links exactly the given ingredients to a KB entry by setting their kb field
+    /// Ingredients that linked to the entry but are not in the list have their link cleared
+    /// Returns the names of the ingredients whose files were rewritten, sorted
+    pub fn set_kb_links(
+        &mut self,
+        slug: &str,
+        ingredient_names: &[String],
+    ) -> Result<Vec<String>, CookbookError> {
+        if !self.kb_entries.contains_key(slug) {
+            return Err(CookbookError::UpdateError(format!(
+                "set_kb_links: KB entry '{}' does not exist",
+                slug
+            )));
+        }
+        if let Some(missing) = ingredient_names
+            .iter()
+            .find(|name| !self.ingredients.contains_key(name.as_str()))
+        {
+            return Err(CookbookError::UpdateError(format!(
+                "set_kb_links: Ingredient '{}' does not exist",
+                missing
+            )));
+        }
+
+        let mut changed: Vec<Ingredient> = Vec::new();
+        for ingredient in self.ingredients.values() {
+            let linked = ingredient.kb.as_deref() == Some(slug);
+            let wanted = ingredient_names.contains(&ingredient.name);
+            if linked != wanted {
+                let mut updated = ingredient.clone();
+                updated.kb = if wanted { Some(slug.to_string()) } else { None };
+                changed.push(updated);
+            }
+        }
+
+        let mut names = Vec::new();
+        for ingredient in changed {
+            let name = ingredient.name.clone();
+            self.update_ingredient(&name, ingredient)?;
+            names.push(name);
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// Records a price paid for an ingredient, keeping its price history in date order
+    pub fn add_ingredient_price(&mut self, name: &str, price: Price) -> Result<(), CookbookError> {
+        if !price.price.is_finite() || price.price < 0.0 {
+            return Err(CookbookError::UpdateError(format!(
+                "add_ingredient_price: {} is not a price",
+                price.price
+            )));
+        }
+        let Some(ingredient) = self.ingredients.get(name) else {
+            return Err(CookbookError::UpdateError(format!(
+                "add_ingredient_price: Ingredient '{}' does not exist",
+                name
+            )));
+        };
+        let mut updated = ingredient.clone();
+        let index = updated.prices.partition_point(|p| p.date <= price.date);
+        updated.prices.insert(index, price);
+        self.update_ingredient(name, updated)?;
         Ok(())
     }
 
-    /// Deletes an ingredient by name, and removes it from the pantry if present
-    pub fn delete_ingredient(&mut self, ingredient_name: &str) -> Result<bool, CookbookError> {
-        // Remove from ingredients map
-        if self.ingredients.remove(ingredient_name).is_none() {
+    /// Removes a recorded price; returns false if the ingredient has no such price
+    pub fn remove_ingredient_price(
+        &mut self,
+        name: &str,
+        price: &Price,
+    ) -> Result<bool, CookbookError> {
+        let Some(ingredient) = self.ingredients.get(name) else {
+            return Err(CookbookError::UpdateError(format!(
+                "remove_ingredient_price: Ingredient '{}' does not exist",
+                name
+            )));
+        };
+        let Some(index) = ingredient.prices.iter().position(|p| p == price) else {
+            return Ok(false);
+        };
+        let mut updated = ingredient.clone();
+        updated.prices.remove(index);
+        self.update_ingredient(name, updated)?;
+        Ok(true)
+    }
+
+    /// Estimates what a recipe costs at its own servings, from the latest ingredient prices
+    pub fn recipe_cost(&self, title: &str, today: NaiveDate) -> Option<RecipeCost> {
+        let recipe = self.get_recipe(title)?;
+        Some(cost::recipe_cost(
+            recipe,
+            |name| self.get_ingredient(name),
+            today,
+        ))
+    }
+
+    /// Returns the recipes that cost at most `max_per_serving` per serving, sorted by title.
+    /// Recipes whose cost can't be worked out in full, or that give no servings, are left out
+    pub fn recipes_within_cost(&self, max_per_serving: f64, today: NaiveDate) -> Vec<&Recipe> {
+        let mut recipes: Vec<&Recipe> = self
+            .recipes
+            .iter()
+            .filter(|recipe| {
+                let cost = cost::recipe_cost(recipe, |name| self.get_ingredient(name), today);
+                cost.estimate.is_complete()
+                    && cost.per_serving().is_some_and(|c| c <= max_per_serving)
+            })
+            .collect();
+        recipes.sort_by(|a, b| a.title.cmp(&b.title));
+        recipes
+    }
+
+    /// Estimates what a shopping list costs, from the latest ingredient prices
+    pub fn shopping_list_cost(&self, list: &ShoppingList) -> CostEstimate {
+        cost::shopping_cost(list, |name| self.get_ingredient(name))
+    }
+
+    /// Finds the ingredient and package a scanned EAN/UPC code belongs to
+    pub fn lookup_barcode(&self, code: &str) -> Option<(&Ingredient, &Package)> {
+        barcode::lookup(self.ingredients.values(), code)
+    }
+
+    /// Records a product barcode on an ingredient, replacing the package size if the
+    /// ingredient already has the code. Fails if the code is invalid or belongs to another
+    /// ingredient
+    pub fn add_ingredient_package(
+        &mut self,
+        name: &str,
+        package: Package,
+    ) -> Result<(), CookbookError> {
+        let Some(code) = barcode::normalize(&package.barcode) else {
+            return Err(CookbookError::UpdateError(format!(
+                "add_ingredient_package: '{}' is not an EAN or UPC code",
+                package.barcode
+            )));
+        };
+        if let Some((owner, _)) = self.lookup_barcode(&code).filter(|(i, _)| i.name != name) {
+            return Err(CookbookError::UpdateError(format!(
+                "add_ingredient_package: {} is already mapped to '{}'",
+                code, owner.name
+            )));
+        }
+        let Some(ingredient) = self.ingredients.get(name) else {
+            return Err(CookbookError::UpdateError(format!(
+                "add_ingredient_package: Ingredient '{}' does not exist",
+                name
+            )));
+        };
+        let mut updated = ingredient.clone();
+        let package = Package {
+            barcode: code,
+            ..package
+        };
+        match updated
+            .packages
+            .iter_mut()
+            .find(|p| p.barcode == package.barcode)
+        {
+            Some(existing) => *existing = package,
+            None => updated.packages.push(package),
+        }
+        self.update_ingredient(name, updated)?;
+        Ok(())
+    }
+
+    /// Removes a barcode from an ingredient; returns false if the ingredient doesn't have it
+    pub fn remove_ingredient_package(
+        &mut self,
+        name: &str,
+        code: &str,
+    ) -> Result<bool, CookbookError> {
+        let Some(ingredient) = self.ingredients.get(name) else {
             return Err(CookbookError::UpdateError(format!(
-                "Ingredient '{}' does not exist",
+                "remove_ingredient_package: Ingredient '{}' does not exist",
+                name
+            )));
+        };
+        let code = barcode::normalize(code).unwrap_or_else(|| code.to_string());
+        let Some(index) = ingredient.packages.iter().position(|p| p.barcode == code) else {
+            return Ok(false);
+        };
+        let mut updated = ingredient.clone();
+        updated.packages.remove(index);
+        self.update_ingredient(name, updated)?;
+        Ok(true)
+    }
+
+    /// Adds one package of a scanned product to the pantry. The package size is added to the
+    /// stock when the units convert, and becomes the stock when there was none. Returns None
+    /// if no ingredient has the code
+    pub fn add_to_pantry_by_barcode(
+        &mut self,
+        code: &str,
+    ) -> Result<Option<PantryItem>, CookbookError> {
+        let Some((ingredient, package)) = self.lookup_barcode(code) else {
+            return Ok(None);
+        };
+        let name = ingredient.name.clone();
+        let package = package.clone();
+        let current = self.get_pantry_item(&name);
+        let (quantity, unit) = match (current, package.quantity) {
+            (Some(item), None) => (item.quantity, item.quantity_type.clone()),
+            (Some(item), Some(amount)) if item.quantity.is_some() => {
+                let unit = &item.quantity_type;
+                let added =
+                    units::convert_ingredient(amount, &package.unit, unit, Some(ingredient))
+                        .map_err(|missing| {
+                            CookbookError::UpdateError(format!(
+                                "add_to_pantry_by_barcode: {} {} doesn't convert to {} without {}",
+                                amount, package.unit, unit, missing
+                            ))
+                        })?;
+                (item.quantity.map(|q| q + added), unit.clone())
+            }
+            (_, amount) => (amount, package.unit),
+        };
+        let unit = Some(unit).filter(|u| !u.is_empty());
+        self.update_pantry_item(&name, quantity, unit)?;
+        Ok(self.get_pantry_item(&name).cloned())
+    }
+
+    /// Suggests barcode mappings from an Open Food Facts products export, skipping barcodes
+    /// already mapped. See the `barcode` module
+    pub fn suggest_barcode_mappings(
+        &self,
+        export: &Path,
+    ) -> Result<Vec<BarcodeSuggestion>, CookbookError> {
+        let file = fs::File::open(export)
+            .map_err(|e| CookbookError::ReadError(format!("{}: {}", export.display(), e)))?;
+        let known: HashSet<String> = self
+            .ingredients
+            .values()
+            .flat_map(|i| i.packages.iter().map(|p| p.barcode.clone()))
+            .collect();
+        barcode::suggest_mappings(
+            std::io::BufReader::new(file),
+            self.ingredients.values(),
+            &known,
+        )
+    }
+
+    /// Returns the file path for a recipe, named after its slug
+    fn recipe_path(&self, slug: &str) -> Result<PathBuf, CookbookError> {
+        slug::slug_path(&self.data_dir.join("recipes"), slug, "md")
+    }
+
+    /// Returns the file path for an ingredient, named after its slug
+    fn ingredient_path(&self, slug: &str) -> Result<PathBuf, CookbookError> {
+        slug::slug_path(&self.data_dir.join("ingredients"), slug, "yaml")
+    }
+
+    /// Returns the file path for a KB entry, creating the kb directory if needed
+    fn kb_entry_path(&self, slug: &str) -> Result<PathBuf, CookbookError> {
+        let kb_dir = self.data_dir.join("kb");
+        fs::create_dir_all(&kb_dir).map_err(|e| {
+            CookbookError::WriteError(format!("Failed to create KB directory: {}", e))
+        })?;
+        slug::slug_path(&kb_dir, slug, "md")
+    }
+
+    /// Removes the file a KB entry was read from and its in-memory copy, falling back to the
+    /// bundled entry
+    fn remove_kb_entry_file(&mut self, slug: &str) -> Result<(), CookbookError> {
+        if let Some(path) = self.kb_entry_files.remove(slug).filter(|p| p.exists()) {
+            fs::remove_file(&path).map_err(|e| {
+                CookbookError::WriteError(format!("Failed to remove KB entry file: {}", e))
+            })?;
+        }
+        self.kb_entries.remove(slug);
+
+        let bundled = bundled_kb::BUNDLED_KB_ENTRIES
+            .iter()
+            .filter_map(|(_, content)| KnowledgeBaseEntry::from_markdown(content).ok())
+            .find(|entry| entry.slug == slug);
+        if let Some(entry) = bundled {
+            self.bundled_kb_slugs.insert(slug.to_string());
+            self.kb_entries.insert(slug.to_string(), entry);
+        }
+        Ok(())
+    }
+
+    /// Creates a new recipe and writes it to the recipes directory
+    /// Titles differing only in case count as the same recipe. The file is named after
+    /// `recipe.slug`, or a slug derived from the title if it is empty, made unique
+    pub fn create_recipe(&mut self, recipe: Recipe) -> Result<(), CookbookError> {
+        if self
+            .recipes
+            .iter()
+            .any(|r| slug::slugs_collide(&r.title, &recipe.title))
+        {
+            return Err(CookbookError::UpdateError(format!(
+                "create_recipe: Recipe '{}' already exists",
+                recipe.title
+            )));
+        }
+        let mut recipe = recipe;
+        let taken = self.recipes.iter().map(|r| r.slug.as_str());
+        recipe.slug = assign_slug(&recipe.slug, &recipe.title, taken)?;
+        let path = self.recipe_path(&recipe.slug)?;
+        recipe.to_file(&path)?;
+        self.recipes.push(recipe);
+        Ok(())
+    }
+
+    /// Deletes a recipe by title and removes its file
+    pub fn delete_recipe(&mut self, title: &str) -> Result<bool, CookbookError> {
+        let Some(index) = self.recipes.iter().position(|r| r.title == title) else {
+            return Err(CookbookError::UpdateError(format!(
+                "delete_recipe: Recipe '{}' does not exist",
+                title
+            )));
+        };
+        let removed = self.recipes.remove(index);
+        let path = self.recipe_path(&removed.slug)?;
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| {
+                CookbookError::WriteError(format!("Failed to remove recipe file: {}", e))
+            })?;
+        }
+        Ok(true)
+    }
+
+    /// Creates a new ingredient and writes it to the ingredients directory
+    /// Names differing only in case count as the same ingredient. The file is named after
+    /// `ingredient.slug`, or a slug derived from the name if it is empty, made unique
+    pub fn create_ingredient(&mut self, ingredient: Ingredient) -> Result<(), CookbookError> {
+        if self
+            .ingredients
+            .keys()
+            .any(|name| slug::slugs_collide(name, &ingredient.name))
+        {
+            return Err(CookbookError::UpdateError(format!(
+                "create_ingredient: Ingredient '{}' already exists",
+                ingredient.name
+            )));
+        }
+        let mut ingredient = ingredient;
+        let taken = self.ingredients.values().map(|i| i.slug.as_str());
+        ingredient.slug = assign_slug(&ingredient.slug, &ingredient.name, taken)?;
+        let path = self.ingredient_path(&ingredient.slug)?;
+        ingredient.to_file(&path)?;
+        self.ingredients.insert(ingredient.name.clone(), ingredient);
+        Ok(())
+    }
+
+    /// Returns the recipes and KB articles that refer to an ingredient, which deleting it
+    /// would leave dangling
+    pub fn ingredient_dependents(&self, ingredient_name: &str) -> IngredientDependents {
+        let kb_links = self
+            .get_backlinks(&LinkTarget::Ingredient(ingredient_name.to_string()))
+            .into_iter()
+            .filter_map(|source| match source {
+                LinkSource::Kb(slug) => Some(slug),
+                _ => None,
+            })
+            .collect();
+        IngredientDependents {
+            recipes: self.preview_ingredient_rename(ingredient_name),
+            kb_links,
+        }
+    }
+
+    /// Deletes an ingredient, its file and its pantry entry, returning what referred to it.
+    /// `strategy` decides what happens to recipes that still use it: `Block` refuses with
+    /// `CookbookError::InUseError`, `Cascade` strips it from them, and `Replace` points them
+    /// at another ingredient. KB articles linking to it are reported but left as they are
+    pub fn delete_ingredient(
+        &mut self,
+        ingredient_name: &str,
+        strategy: DeleteStrategy,
+    ) -> Result<IngredientDependents, CookbookError> {
+        if !self.ingredients.contains_key(ingredient_name) {
+            return Err(CookbookError::UpdateError(format!(
+                "delete_ingredient: Ingredient '{}' does not exist",
                 ingredient_name
             )));
         }
+        let dependents = self.ingredient_dependents(ingredient_name);
+
+        // Deal with the recipes before anything is removed, so a refusal or failed write
+        // leaves the ingredient intact
+        if !dependents.recipes.is_empty() {
+            match &strategy {
+                DeleteStrategy::Block => {
+                    return Err(CookbookError::InUseError(format!(
+                        "'{}' is used by {}",
+                        ingredient_name,
+                        dependents.recipes.join(", ")
+                    )));
+                }
+                DeleteStrategy::Cascade => {
+                    self.rewrite_recipes(|recipe| {
+                        let before = recipe.ingredients.len();
+                        recipe
+                            .ingredients
+                            .retain(|i| i.ingredient != ingredient_name);
+                        recipe.ingredients.len() != before
+                    })?;
+                    info!(
+                        "Removed ingredient '{}' from {} recipes",
+                        ingredient_name,
+                        dependents.recipes.len()
+                    );
+                }
+                DeleteStrategy::Replace(replacement) => {
+                    if replacement == ingredient_name || !self.ingredients.contains_key(replacement)
+                    {
+                        return Err(CookbookError::UpdateError(format!(
+                            "delete_ingredient: Replacement '{}' is not another existing ingredient",
+                            replacement
+                        )));
+                    }
+                    self.rewrite_recipes(|recipe| {
+                        replace_recipe_ingredient(recipe, ingredient_name, replacement)
+                    })?;
+                    info!(
+                        "Replaced ingredient '{}' with '{}' in {} recipes",
+                        ingredient_name,
+                        replacement,
+                        dependents.recipes.len()
+                    );
+                }
+            }
+        }
+
+        // Remove from ingredients map
+        let removed = self.ingredients.remove(ingredient_name);
         // Remove ingredient file
-        let ingredients_dir = self.data_dir.join("ingredients");
-        let path = ingredients_dir.join(format!("{}.yaml", ingredient_name.replace(" ", "_")));
+        let path = self.ingredient_path(&removed.map(|i| i.slug).unwrap_or_default())?;
         if path.exists() {
             std::fs::remove_file(&path).map_err(|e| {
                 CookbookError::WriteError(format!("Failed to remove ingredient file: {}", e))
             })?;
         }
         // Remove from pantry if present
+        if self.pantry.is_some() {
+            self.remove_from_pantry(ingredient_name)?;
+        }
+        Ok(dependents)
+    }
+
+    /// Suggests pairs of ingredients that are probably duplicates, most likely first,
+    /// based on slug similarity and overlapping translations
+    pub fn suggest_duplicate_ingredients(&self) -> Vec<DuplicateSuggestion> {
+        duplicates::find_duplicates(self.ingredients.values())
+    }
+
+    /// Folds the ingredient `merge` into `keep` and deletes `merge`.
+    /// `keep` gains the translations, tags, KB link, recorded prices, barcodes, density and
+    /// unit weight it lacks, recipes using `merge` are rewritten to use `keep`, and pantry
+    /// quantities are added together when their units agree (otherwise `keep`'s stock wins
+    /// and a warning is returned)
+    pub fn merge_ingredients(
+        &mut self,
+        keep: &str,
+        merge: &str,
+    ) -> Result<IngredientMerge, CookbookError> {
+        let (Some(winner), Some(loser)) = (self.ingredients.get(keep), self.ingredients.get(merge))
+        else {
+            return Err(CookbookError::UpdateError(format!(
+                "merge_ingredients: '{}' and '{}' must both exist",
+                keep, merge
+            )));
+        };
+        if keep == merge {
+            return Err(CookbookError::UpdateError(format!(
+                "merge_ingredients: Cannot merge '{}' into itself",
+                keep
+            )));
+        }
+
+        // Combine the ingredient definitions, keeping the winner's values on conflict
+        let mut combined = winner.clone();
+        if let Some(theirs) = &loser.translations {
+            let ours = combined.translations.get_or_insert_with(HashMap::new);
+            for (lang, forms) in theirs {
+                ours.entry(lang.clone()).or_insert_with(|| forms.clone());
+            }
+        }
+        if let Some(theirs) = &loser.tags {
+            let ours = combined.tags.get_or_insert_with(Vec::new);
+            for tag in theirs {
+                if !ours.contains(tag) {
+                    ours.push(tag.clone());
+                }
+            }
+        }
+        if combined.kb.is_none() {
+            combined.kb = loser.kb.clone();
+        }
+        combined.density = combined.density.or(loser.density);
+        combined.unit_weight = combined.unit_weight.or(loser.unit_weight);
+        for price in &loser.prices {
+            let recorded = combined
+                .prices
+                .iter()
+                .any(|p| p.date == price.date && p.store == price.store && p.unit == price.unit);
+            if !recorded {
+                let index = combined.prices.partition_point(|p| p.date <= price.date);
+                combined.prices.insert(index, price.clone());
+            }
+        }
+        for package in &loser.packages {
+            if !combined
+                .packages
+                .iter()
+                .any(|p| p.barcode == package.barcode)
+            {
+                combined.packages.push(package.clone());
+            }
+        }
+        let loser_slug = loser.slug.clone();
+
+        let mut result = IngredientMerge::default();
+        for slug in self.ingredient_dependents(merge).kb_links {
+            result
+                .warnings
+                .push(format!("KB article '{}' still links to '{}'", slug, merge));
+        }
+
+        // Recipes first, so a failed write leaves both ingredients in place
+        result.recipes =
+            self.rewrite_recipes(|recipe| replace_recipe_ingredient(recipe, merge, keep))?;
+
+        combined.to_file(self.ingredient_path(&combined.slug)?)?;
+        self.ingredients.insert(keep.to_string(), combined);
+
+        // Fold the loser's pantry entry into the winner's. Moved stock isn't a change; a
+        // larger total or dropped stock is journaled
+        let mut changes = Vec::new();
         if let Some(pantry) = self.pantry.as_mut() {
-            pantry
-                .items
-                .retain(|item| item.ingredient != ingredient_name);
-            let pantry_path = self.data_dir.join("pantry.yaml");
-            pantry.to_file(pantry_path)?;
-            Ok(true)
-        } else {
-            Ok(true)
+            if let Some(index) = pantry.items.iter().position(|i| i.ingredient == merge) {
+                let mut theirs = pantry.items.remove(index);
+                let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+                match pantry.items.iter_mut().find(|i| i.ingredient == keep) {
+                    Some(ours) => match duplicates::sum_quantities(
+                        ours.quantity,
+                        &ours.quantity_type,
+                        theirs.quantity,
+                        &theirs.quantity_type,
+                    ) {
+                        Some((quantity, unit)) => {
+                            let before = (ours.quantity, ours.quantity_type.clone());
+                            ours.quantity = quantity;
+                            ours.quantity_type = unit.clone();
+                            ours.last_updated = today;
+                            changes.push((keep, Some(before), quantity, unit));
+                        }
+                        None => {
+                            result.warnings.push(format!(
+                                "Pantry stock of '{}' ({} {}) was dropped: its unit differs from '{}'",
+                                merge,
+                                theirs.quantity.map(|q| q.to_string()).unwrap_or_default(),
+                                theirs.quantity_type,
+                                keep
+                            ));
+                            let before = (theirs.quantity, theirs.quantity_type.clone());
+                            changes.push((merge, Some(before), Some(0.0), theirs.quantity_type));
+                        }
+                    },
+                    None => {
+                        theirs.ingredient = keep.to_string();
+                        theirs.last_updated = today;
+                        pantry.items.push(theirs);
+                    }
+                }
+                let pantry_path = self.data_dir.join("pantry.yaml");
+                pantry.to_file(pantry_path)?;
+            }
+        }
+        for (name, before, quantity, unit) in changes {
+            self.record_pantry_change(name, before, quantity, &unit);
         }
+        self.rename_in_pantry_journal(merge, keep);
+
+        // Finally remove the loser
+        self.ingredients.remove(merge);
+        let path = self.ingredient_path(&loser_slug)?;
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| {
+                CookbookError::WriteError(format!("Failed to remove ingredient file: {}", e))
+            })?;
+        }
+        info!(
+            "Merged ingredient '{}' into '{}' ({} recipes rewritten)",
+            merge,
+            keep,
+            result.recipes.len()
+        );
+        Ok(result)
     }
 
     /// Search for an ingredient by any translation (singular or plural) in the given language, or by slug
@@ -989,28 +2274,84 @@ impl DataManager {
         None
     }
 
-    /// Returns the display name for an ingredient in the given language and quantity (for pluralization)
+    /// Parses a free-text ingredient line such as "2 1/2 cups diced tomatoes", matching the
+    /// name against ingredient names, slugs and translations in `lang`
+    pub fn parse_ingredient_line(&self, line: &str, lang: &str) -> ParsedIngredient {
+        ingredient_parser::parse_ingredient_line(line, |candidate| {
+            self.find_ingredient_by_name_or_translation(candidate, lang)
+                .map(|i| i.name.clone())
+        })
+    }
+
+    /// Suggests ingredients for autocomplete as (ingredient name, text that matched) pairs.
+    /// Matches the name, slug and translations in `lang`, ignoring case: an exact match
+    /// (via find_ingredient_by_name_or_translation) comes first, then names starting with
+    /// `query`, then names containing it, alphabetically within each group
+    pub fn ingredient_completions(
+        &self,
+        query: &str,
+        lang: &str,
+        limit: usize,
+    ) -> Vec<(String, String)> {
+        let query = query.trim();
+        let query_lower = query.to_lowercase();
+        if query_lower.is_empty() {
+            return Vec::new();
+        }
+        let exact = self
+            .find_ingredient_by_name_or_translation(query, lang)
+            .map(|i| i.name.clone());
+
+        let mut matches: Vec<(u8, String, String)> = self
+            .ingredients
+            .values()
+            .filter_map(|ingredient| {
+                let mut candidates = vec![ingredient.name.as_str(), ingredient.slug.as_str()];
+                if let Some(forms) = ingredient.translations.as_ref().and_then(|t| t.get(lang)) {
+                    candidates.push(forms.one.as_str());
+                    candidates.push(forms.other.as_str());
+                }
+                let rank_of = |text: &str| {
+                    let text = text.to_lowercase();
+                    if exact.as_deref() == Some(ingredient.name.as_str()) && text == query_lower {
+                        Some(0)
+                    } else if text.starts_with(&query_lower) {
+                        Some(1)
+                    } else if text.contains(&query_lower) {
+                        Some(2)
+                    } else {
+                        None
+                    }
+                };
+                candidates
+                    .into_iter()
+                    .filter_map(|text| rank_of(text).map(|rank| (rank, text)))
+                    .min_by_key(|(rank, _)| *rank)
+                    .map(|(rank, text)| (rank, ingredient.name.clone(), text.to_string()))
+            })
+            .collect();
+        matches.sort();
+        matches
+            .into_iter()
+            .take(limit)
+            .map(|(_, name, matched)| (name, matched))
+            .collect()
+    }
+
+    /// Returns the display name for an ingredient in the given language, in the plural form
+    /// `quantity` calls for ("1 tomato", "1/2 tomato", "2-3 tomatoes")
     pub fn ingredient_display_name(
         ingredient: &Ingredient,
         lang: &str,
-        quantity: Option<f64>,
+        quantity: Option<&Quantity>,
     ) -> String {
         if let Some(translations) = &ingredient.translations {
-            if let Some(forms) = translations.get(lang) {
-                let n = quantity.unwrap_or(1.0);
-                if (n - 1.0).abs() < f64::EPSILON {
-                    return forms.one.clone();
-                } else {
-                    return forms.other.clone();
-                }
-            }
             // fallback to English
-            if let Some(forms) = translations.get("en") {
-                let n = quantity.unwrap_or(1.0);
-                if (n - 1.0).abs() < f64::EPSILON {
-                    return forms.one.clone();
-                } else {
+            if let Some(forms) = translations.get(lang).or_else(|| translations.get("en")) {
+                if quantity.is_some_and(Quantity::is_plural) {
                     return forms.other.clone();
+                } else {
+                    return forms.one.clone();
                 }
             }
         }
@@ -1029,20 +2370,341 @@ impl DataManager {
         lang: &str,
     ) -> String {
         if let Some(ingredient) = self.get_ingredient(&recipe_ingredient.ingredient) {
-            let qty = recipe_ingredient.quantity;
+            let qty = recipe_ingredient.quantity.as_ref();
             Self::ingredient_display_name(ingredient, lang, qty)
         } else {
             // fallback to whatever is in the recipe
             recipe_ingredient.ingredient.clone()
         }
     }
+
+    /// Formats a recipe ingredient for display: "2 1/2 cup tomatoes, diced", "3 eggs",
+    /// "salt to taste", "1 tbsp butter (optional)"
+    pub fn format_recipe_ingredient(
+        &self,
+        recipe_ingredient: &RecipeIngredient,
+        lang: &str,
+    ) -> String {
+        self.format_recipe_ingredient_in(recipe_ingredient, lang, UnitSystem::Original)
+    }
+
+    /// Formats a recipe ingredient like `format_recipe_ingredient`, with its amount shown in
+    /// `system`: "1 ½ cups milk" for 354.9 ml in imperial
+    pub fn format_recipe_ingredient_in(
+        &self,
+        recipe_ingredient: &RecipeIngredient,
+        lang: &str,
+        system: UnitSystem,
+    ) -> String {
+        let name = self.recipe_ingredient_display_name(recipe_ingredient, lang);
+        let unit = recipe_ingredient
+            .quantity_type
+            .as_deref()
+            .filter(|u| !u.is_empty());
+        let mut line = match (&recipe_ingredient.quantity, unit) {
+            (Some(Quantity::Qualitative(text)), _) => format!("{} {}", name, text),
+            (Some(q), Some(u)) => format!("{} {}", units::format_quantity(q, u, system), name),
+            (Some(q), None) => format!("{} {}", q, name),
+            (None, Some(u)) => format!("{} {}", u, name),
+            (None, None) => name,
+        };
+        if let Some(preparation) = recipe_ingredient.preparation.as_deref() {
+            line.push_str(", ");
+            line.push_str(preparation);
+        }
+        if recipe_ingredient.optional {
+            line.push_str(" (optional)");
+        }
+        line
+    }
+
+    /// The recipe's ingredients that `step` mentions, in recipe order. An ingredient counts
+    /// when its name, slug or a translation in `lang` or English appears as a word in the
+    /// step, also with a plural "s" or "es" ("chili flakes" mentions `chili flake`)
+    pub fn step_ingredients<'r>(
+        &self,
+        recipe: &'r Recipe,
+        step: &Step,
+        lang: &str,
+    ) -> Vec<&'r RecipeIngredient> {
+        let text = step.text.to_lowercase();
+        recipe
+            .ingredients
+            .iter()
+            .filter(|ri| {
+                let mut forms = vec![ri.ingredient.clone()];
+                if let Some(ingredient) = self.get_ingredient(&ri.ingredient) {
+                    forms.push(ingredient.slug.replace('_', " "));
+                    for l in [lang, "en"] {
+                        if let Some(t) = ingredient.translations.as_ref().and_then(|t| t.get(l)) {
+                            forms.push(t.one.clone());
+                            forms.push(t.other.clone());
+                        }
+                    }
+                }
+                forms
+                    .iter()
+                    .map(|form| form.trim().to_lowercase())
+                    .any(|form| !form.is_empty() && mentions_word(&text, &form))
+            })
+            .collect()
+    }
+
+    /// Returns the planned meals from `from` to `to` (both included), by date and meal.
+    /// Entries keep their recipe title even if the recipe has since been deleted
+    pub fn get_meal_plan(&self, from: NaiveDate, to: NaiveDate) -> Vec<&MealPlanEntry> {
+        let mut entries: Vec<&MealPlanEntry> = self
+            .meal_plan
+            .entries
+            .iter()
+            .filter(|e| e.date >= from && e.date <= to)
+            .collect();
+        entries.sort_by_key(|e| (e.date, e.meal));
+        entries
+    }
+
+    /// Plans a recipe for a meal and saves mealplan.yaml
+    /// Fails if the recipe does not exist or is already planned for that meal
+    pub fn add_meal_plan_entry(&mut self, entry: MealPlanEntry) -> Result<(), CookbookError> {
+        if self.get_recipe(&entry.recipe).is_none() {
+            return Err(CookbookError::UpdateError(format!(
+                "add_meal_plan_entry: Recipe '{}' does not exist",
+                entry.recipe
+            )));
+        }
+        self.check_meal_plan_slot_free(&entry, "add_meal_plan_entry")?;
+        self.meal_plan.entries.push(entry);
+        self.save_meal_plan()
+    }
+
+    /// Removes a planned meal, matched by date, meal and recipe
+    /// Returns false if it was not in the plan
+    pub fn remove_meal_plan_entry(&mut self, entry: &MealPlanEntry) -> Result<bool, CookbookError> {
+        let before = self.meal_plan.entries.len();
+        self.meal_plan.entries.retain(|e| !e.same_slot(entry));
+        if self.meal_plan.entries.len() == before {
+            return Ok(false);
+        }
+        self.save_meal_plan()?;
+        Ok(true)
+    }
+
+    /// Changes the servings of a planned meal; None cooks the recipe's own servings
+    pub fn set_meal_plan_servings(
+        &mut self,
+        entry: &MealPlanEntry,
+        servings: Option<u32>,
+    ) -> Result<(), CookbookError> {
+        let index = self.meal_plan_index(entry, "set_meal_plan_servings")?;
+        self.meal_plan.entries[index].servings = servings;
+        self.save_meal_plan()
+    }
+
+    /// Moves a planned meal to another date and meal, keeping its servings
+    /// Returns the entry as it is now stored
+    pub fn move_meal_plan_entry(
+        &mut self,
+        entry: &MealPlanEntry,
+        date: NaiveDate,
+        meal: Meal,
+    ) -> Result<MealPlanEntry, CookbookError> {
+        let index = self.meal_plan_index(entry, "move_meal_plan_entry")?;
+        let moved = MealPlanEntry {
+            date,
+            meal,
+            ..self.meal_plan.entries[index].clone()
+        };
+        if !moved.same_slot(entry) {
+            self.check_meal_plan_slot_free(&moved, "move_meal_plan_entry")?;
+        }
+        self.meal_plan.entries[index] = moved.clone();
+        self.save_meal_plan()?;
+        Ok(moved)
+    }
+
+    /// Copies a planned meal to another date and meal, keeping its servings
+    /// Returns the new entry
+    pub fn copy_meal_plan_entry(
+        &mut self,
+        entry: &MealPlanEntry,
+        date: NaiveDate,
+        meal: Meal,
+    ) -> Result<MealPlanEntry, CookbookError> {
+        let index = self.meal_plan_index(entry, "copy_meal_plan_entry")?;
+        let copy = MealPlanEntry {
+            date,
+            meal,
+            ..self.meal_plan.entries[index].clone()
+        };
+        self.check_meal_plan_slot_free(&copy, "copy_meal_plan_entry")?;
+        self.meal_plan.entries.push(copy.clone());
+        self.save_meal_plan()?;
+        Ok(copy)
+    }
+
+    /// Copies every meal planned from `from` to `to` (both included) so the range starts at
+    /// `target`, e.g. last week's plan into this week. Meals already planned in the target
+    /// range are kept, and copies that would repeat them are skipped
+    /// Returns the number of meals copied
+    pub fn copy_meal_plan_range(
+        &mut self,
+        from: NaiveDate,
+        to: NaiveDate,
+        target: NaiveDate,
+    ) -> Result<usize, CookbookError> {
+        let offset = target - from;
+        let copies: Vec<MealPlanEntry> = self
+            .get_meal_plan(from, to)
+            .into_iter()
+            .map(|e| MealPlanEntry {
+                date: e.date + offset,
+                ..e.clone()
+            })
+            .filter(|copy| !self.meal_plan.entries.iter().any(|e| e.same_slot(copy)))
+            .collect();
+        let count = copies.len();
+        if count > 0 {
+            self.meal_plan.entries.extend(copies);
+            self.save_meal_plan()?;
+        }
+        Ok(count)
+    }
+
+    /// Suggests recipes for the meals of `request` that aren't planned yet, using the pantry
+    /// and the meal plan so far. Nothing is saved; add the suggestions that are wanted with
+    /// `add_meal_plan_entry`
+    pub fn suggest_meal_plan(&self, request: &PlanRequest) -> MealPlanSuggestion {
+        let recipes: Vec<&Recipe> = self.recipes.iter().collect();
+        let pantry = self.pantry.as_ref().map_or(&[][..], |p| &p.items[..]);
+        planner::suggest_meal_plan(&recipes, pantry, &self.meal_plan.entries, request)
+    }
+
+    fn meal_plan_index(&self, entry: &MealPlanEntry, caller: &str) -> Result<usize, CookbookError> {
+        self.meal_plan
+            .entries
+            .iter()
+            .position(|e| e.same_slot(entry))
+            .ok_or_else(|| {
+                CookbookError::UpdateError(format!(
+                    "{}: '{}' is not planned for {} on {}",
+                    caller,
+                    entry.recipe,
+                    entry.meal.label(),
+                    entry.date
+                ))
+            })
+    }
+
+    fn check_meal_plan_slot_free(
+        &self,
+        entry: &MealPlanEntry,
+        caller: &str,
+    ) -> Result<(), CookbookError> {
+        if self.meal_plan.entries.iter().any(|e| e.same_slot(entry)) {
+            return Err(CookbookError::UpdateError(format!(
+                "{}: '{}' is already planned for {} on {}",
+                caller,
+                entry.recipe,
+                entry.meal.label(),
+                entry.date
+            )));
+        }
+        Ok(())
+    }
+
+    /// Writes mealplan.yaml, sorted by date and meal
+    fn save_meal_plan(&mut self) -> Result<(), CookbookError> {
+        self.meal_plan.entries.sort_by_key(|e| (e.date, e.meal));
+        self.meal_plan.to_file(self.data_dir.join("mealplan.yaml"))
+    }
+}
+
+/// Picks the slug for a new or re-slugged recipe or ingredient: `requested`, or one derived
+/// from `name` if that is empty, with a numeric suffix if it collides with any of `taken`
+fn assign_slug<'a>(
+    requested: &str,
+    name: &str,
+    taken: impl IntoIterator<Item = &'a str>,
+) -> Result<String, CookbookError> {
+    let base = if requested.trim().is_empty() {
+        slug::slugify(name)
+    } else {
+        requested.to_string()
+    };
+    if base.is_empty() {
+        return Err(CookbookError::InvalidNameError(format!(
+            "'{}' needs at least one letter or digit",
+            name
+        )));
+    }
+    slug::validate_slug(&base)?;
+    Ok(slug::unique_slug(&base, taken))
+}
+
+/// Points a recipe's uses of `old_name` at `new_name`. If the recipe already lists
+/// `new_name` in a compatible unit, the quantities are added together instead of listing it
+/// twice. Returns true if the recipe changed
+fn replace_recipe_ingredient(recipe: &mut Recipe, old_name: &str, new_name: &str) -> bool {
+    if !recipe.ingredients.iter().any(|i| i.ingredient == old_name) {
+        return false;
+    }
+    let mut replaced: Vec<RecipeIngredient> = Vec::with_capacity(recipe.ingredients.len());
+    for mut item in recipe.ingredients.drain(..) {
+        if item.ingredient == old_name {
+            item.ingredient = new_name.to_string();
+        } else if item.ingredient != new_name {
+            replaced.push(item);
+            continue;
+        }
+        let unit = item.quantity_type.clone().unwrap_or_default();
+        let total = replaced.iter_mut().find_map(|existing| {
+            // Lines in different sections or with different notes stay separate
+            let same_line = existing.group == item.group
+                && existing.optional == item.optional
+                && existing.preparation == item.preparation;
+            if existing.ingredient != new_name || !same_line {
+                return None;
+            }
+            let existing_unit = existing.quantity_type.as_deref().unwrap_or("");
+            duplicates::sum_recipe_quantities(
+                existing.quantity.as_ref(),
+                existing_unit,
+                item.quantity.as_ref(),
+                &unit,
+            )
+            .map(|total| (existing, total))
+        });
+        match total {
+            Some((existing, (quantity, unit))) => {
+                existing.quantity = quantity;
+                existing.quantity_type = (!unit.is_empty()).then_some(unit);
+            }
+            None => replaced.push(item),
+        }
+    }
+    recipe.ingredients = replaced;
+    true
 }
 
 impl Recipe {
-    /// Checks if all ingredients for this recipe are in stock (in the pantry)
+    /// Checks if all ingredients for this recipe are in stock (in the pantry); optional
+    /// ingredients don't count
     pub fn all_ingredients_in_stock(&self, data_manager: &DataManager) -> bool {
-        self.ingredients
-            .iter()
+        self.required_ingredients()
             .all(|ingredient| data_manager.is_in_pantry(&ingredient.ingredient))
     }
 }
+
+/// True if `word` appears in `text` on word boundaries, optionally followed by "s" or "es"
+fn mentions_word(text: &str, word: &str) -> bool {
+    text.match_indices(word).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = &text[start + word.len()..];
+        let after = after
+            .strip_prefix("es")
+            .or_else(|| after.strip_prefix('s'))
+            .filter(|rest| !rest.starts_with(char::is_alphanumeric))
+            .unwrap_or(after);
+        !before.is_some_and(char::is_alphanumeric) && !after.starts_with(char::is_alphanumeric)
+    })
+}
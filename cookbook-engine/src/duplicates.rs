@@ -0,0 +1,196 @@
+//! Spotting ingredients that are probably the same thing under two names.
+//!
+//! Data directories collect pairs like `tomato` and `tomatoes`, or an English name next to a
+//! Danish one. This module compares every pair of ingredients by slug similarity and by
+//! overlapping translations and suggests the likely duplicates; `DataManager::merge_ingredients`
+//! folds one into the other.
+use crate::quantity::Quantity;
+use crate::types::*;
+use crate::units;
+use std::collections::HashSet;
+
+/// Slugs at least this similar (0.0–1.0) are suggested as duplicates
+const SIMILARITY_THRESHOLD: f64 = 0.85;
+
+/// Two ingredients that look like duplicates
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateSuggestion {
+    /// Ingredient names, in alphabetical order
+    pub first: String,
+    pub second: String,
+    /// How confident the suggestion is, from 0.0 to 1.0
+    pub score: f64,
+    /// Human-readable reasons, e.g. "Shared translation 'tomato'"
+    pub reasons: Vec<String>,
+}
+
+/// Suggests likely duplicate pairs among `ingredients`, most likely first
+pub fn find_duplicates<'a>(
+    ingredients: impl IntoIterator<Item = &'a Ingredient>,
+) -> Vec<DuplicateSuggestion> {
+    let mut ingredients: Vec<&Ingredient> = ingredients.into_iter().collect();
+    ingredients.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut suggestions = Vec::new();
+    for (i, first) in ingredients.iter().enumerate() {
+        for second in &ingredients[i + 1..] {
+            let mut score: f64 = 0.0;
+            let mut reasons = Vec::new();
+
+            let similarity = slug_similarity(&first.slug, &second.slug);
+            if similarity >= SIMILARITY_THRESHOLD {
+                score = similarity;
+                reasons.push(format!("Similar names ({:.0}%)", similarity * 100.0));
+            }
+
+            let mut shared: Vec<String> = names_of(first)
+                .intersection(&names_of(second))
+                .cloned()
+                .collect();
+            shared.sort();
+            if !shared.is_empty() {
+                score = score.max(0.9);
+                for name in shared {
+                    reasons.push(format!("Shared translation '{}'", name));
+                }
+            }
+
+            if !reasons.is_empty() {
+                suggestions.push(DuplicateSuggestion {
+                    first: first.name.clone(),
+                    second: second.name.clone(),
+                    score,
+                    reasons,
+                });
+            }
+        }
+    }
+    suggestions.sort_by(|a, b| b.score.total_cmp(&a.score));
+    suggestions
+}
+
+/// Similarity of two slugs from 0.0 to 1.0, ignoring case, separators and plural endings
+pub fn slug_similarity(a: &str, b: &str) -> f64 {
+    let a = normalize_slug(a);
+    let b = normalize_slug(b);
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 0.0;
+    }
+    1.0 - edit_distance(&a, &b) as f64 / longest as f64
+}
+
+/// Lowercases a slug, drops separators and strips an English plural ending
+fn normalize_slug(slug: &str) -> String {
+    let plain: String = slug
+        .to_lowercase()
+        .chars()
+        .filter(|c| !matches!(c, '_' | '-' | ' '))
+        .collect();
+    if plain.chars().count() <= 3 || plain.ends_with("ss") {
+        return plain;
+    }
+    // "tomatoes" -> "tomato", "eggs" -> "egg"
+    if let Some(stem) = plain.strip_suffix("oes") {
+        format!("{}o", stem)
+    } else if let Some(stem) = plain.strip_suffix('s') {
+        stem.to_string()
+    } else {
+        plain
+    }
+}
+
+/// The name, slug and every translated form of an ingredient, lowercased
+fn names_of(ingredient: &Ingredient) -> HashSet<String> {
+    let mut names = HashSet::new();
+    names.insert(ingredient.name.to_lowercase());
+    names.insert(ingredient.slug.to_lowercase());
+    if let Some(translations) = &ingredient.translations {
+        for forms in translations.values() {
+            names.insert(forms.one.to_lowercase());
+            names.insert(forms.other.to_lowercase());
+        }
+    }
+    names.remove("");
+    names
+}
+
+/// Levenshtein distance between two strings, counted in characters
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// What `DataManager::merge_ingredients` changed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IngredientMerge {
+    /// Titles of the recipes that were rewritten to use the kept ingredient, sorted
+    pub recipes: Vec<String>,
+    /// Things the merge could not reconcile, e.g. pantry quantities in different units
+    pub warnings: Vec<String>,
+}
+
+/// Adds two quantities of the same ingredient if their units agree or convert (500 g and
+/// 1 kg), returning the total in the first one's unit. A side with neither an amount nor a
+/// unit takes the other's unit; otherwise a missing unit counts pieces, so 3 and 500 g
+/// don't add up
+pub fn sum_quantities(
+    a: Option<f64>,
+    a_unit: &str,
+    b: Option<f64>,
+    b_unit: &str,
+) -> Option<(Option<f64>, String)> {
+    let (unit, factor) = common_unit(a.is_some(), a_unit, b.is_some(), b_unit)?;
+    let total = match (a, b) {
+        (Some(a), Some(b)) => Some(a + b * factor),
+        (a, b) => a.or(b.map(|b| b * factor)),
+    };
+    Some((total, unit))
+}
+
+/// Like `sum_quantities`, for recipe quantities; fails if the quantities can't be added
+/// (e.g. "2" and "to taste")
+pub fn sum_recipe_quantities(
+    a: Option<&Quantity>,
+    a_unit: &str,
+    b: Option<&Quantity>,
+    b_unit: &str,
+) -> Option<(Option<Quantity>, String)> {
+    let (unit, factor) = common_unit(a.is_some(), a_unit, b.is_some(), b_unit)?;
+    let b = b.map(|b| {
+        if factor == 1.0 {
+            b.clone()
+        } else {
+            b.scale(factor)
+        }
+    });
+    let total = match (a, b) {
+        (Some(a), Some(b)) => Some(a.checked_add(&b)?),
+        (a, b) => a.cloned().or(b),
+    };
+    Some((total, unit))
+}
+
+/// The unit to total two quantities in and the factor converting the second one into it,
+/// or None if their units don't match
+fn common_unit(
+    a_has_amount: bool,
+    a_unit: &str,
+    b_has_amount: bool,
+    b_unit: &str,
+) -> Option<(String, f64)> {
+    match (a_unit.trim(), b_unit.trim()) {
+        ("", unit) if !a_has_amount => Some((unit.to_string(), 1.0)),
+        (unit, "") if !b_has_amount => Some((unit.to_string(), 1.0)),
+        (x, y) => units::convert(1.0, y, x).map(|factor| (x.to_string(), factor)),
+    }
+}
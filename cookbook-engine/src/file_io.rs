@@ -1,23 +1,44 @@
+use crate::migration::{self, DataFileKind};
 use crate::types::*;
+use crate::yaml_merge;
+use log::info;
+use serde_yaml::Value;
 use std::fs;
 use std::path::Path;
-use log::info;
+
+/// Ingredient keys left out of the YAML when empty
+const INGREDIENT_OPTIONAL_KEYS: &[&str] = &["prices", "packages", "density", "unit_weight"];
 
 // Implementing method for Ingredient
 impl Ingredient {
-    // Reads an ingredient from a YAML file
+    // Reads an ingredient from a YAML file, upgrading older schema versions in memory
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, CookbookError> {
         let content =
             fs::read_to_string(&path).map_err(|e| CookbookError::ReadError(e.to_string()))?; // Read the file content
-        serde_yaml::from_str(&content).map_err(|e| CookbookError::ParseError(e.to_string()))
+        let mut mapping = migration::parse_mapping(&content)?;
+        migration::upgrade(DataFileKind::Ingredient, &mut mapping)?;
+        serde_yaml::from_value(Value::Mapping(mapping))
+            .map_err(|e| CookbookError::ParseError(e.to_string()))
         // Parse the YAML content
     }
 
-    // Writes an ingredient to a YAML file
+    // Writes an ingredient to a YAML file, stamped with the current schema version,
+    // keeping unknown keys and comments already in the file
     pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), CookbookError> {
-        let yaml = serde_yaml::to_string(self).map_err(|e| {
+        self.to_file_preserving(&path, &path)
+    }
+
+    /// Writes an ingredient to `path`, keeping the unknown keys, key order and comments of
+    /// the YAML in `original` (usually the same file; nothing is kept if it doesn't exist)
+    pub fn to_file_preserving<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        path: P,
+        original: Q,
+    ) -> Result<(), CookbookError> {
+        let yaml = to_versioned_yaml(self, DataFileKind::Ingredient).map_err(|e| {
             CookbookError::ParseError(format!("Failed to serialize ingredient: {}", e))
         })?; // Serialize the ingredient to YAML
+        let yaml = merge_with_existing(original.as_ref(), &yaml, false, INGREDIENT_OPTIONAL_KEYS);
 
         fs::write(&path, yaml).map_err(|e| {
             CookbookError::WriteError(format!("Failed to write ingredient file: {}", e))
@@ -29,11 +50,14 @@ impl Ingredient {
 
 // Implementing method for Pantry
 impl Pantry {
-    // Reads a pantry from a YAML file
+    // Reads a pantry from a YAML file, upgrading older schema versions in memory
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, CookbookError> {
         let content =
             fs::read_to_string(&path).map_err(|e| CookbookError::ReadError(e.to_string()))?; // Read the file content
-        serde_yaml::from_str(&content).map_err(|e| CookbookError::ParseError(e.to_string()))
+        let mut mapping = migration::parse_mapping(&content)?;
+        migration::upgrade(DataFileKind::Pantry, &mut mapping)?;
+        serde_yaml::from_value(Value::Mapping(mapping))
+            .map_err(|e| CookbookError::ParseError(e.to_string()))
         // Parse the YAML content
     }
 
@@ -50,36 +74,94 @@ impl Pantry {
     }
 }
 
+// Implementing method for MealPlan
+impl MealPlan {
+    /// Reads a meal plan from a YAML file, upgrading older schema versions in memory
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, CookbookError> {
+        let content =
+            fs::read_to_string(&path).map_err(|e| CookbookError::ReadError(e.to_string()))?;
+        let mut mapping = migration::parse_mapping(&content)?;
+        migration::upgrade(DataFileKind::MealPlan, &mut mapping)?;
+        serde_yaml::from_value(Value::Mapping(mapping))
+            .map_err(|e| CookbookError::ParseError(e.to_string()))
+    }
+
+    /// Writes a meal plan to a YAML file, stamped with the current schema version
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), CookbookError> {
+        let yaml = to_versioned_yaml(self, DataFileKind::MealPlan).map_err(|e| {
+            CookbookError::ParseError(format!("Failed to serialize meal plan: {}", e))
+        })?;
+        fs::write(&path, yaml).map_err(|e| {
+            CookbookError::WriteError(format!("Failed to write meal plan file: {}", e))
+        })?;
+        info!("Successfully wrote to {}", path.as_ref().display());
+        Ok(())
+    }
+}
+
+// Implementing method for PantryJournal
+impl PantryJournal {
+    /// Reads the pantry journal from a YAML file, upgrading older schema versions in memory
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, CookbookError> {
+        let content =
+            fs::read_to_string(&path).map_err(|e| CookbookError::ReadError(e.to_string()))?;
+        let mut mapping = migration::parse_mapping(&content)?;
+        migration::upgrade(DataFileKind::PantryJournal, &mut mapping)?;
+        serde_yaml::from_value(Value::Mapping(mapping))
+            .map_err(|e| CookbookError::ParseError(e.to_string()))
+    }
+
+    /// Writes the pantry journal to a YAML file, stamped with the current schema version
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), CookbookError> {
+        let yaml = to_versioned_yaml(self, DataFileKind::PantryJournal).map_err(|e| {
+            CookbookError::ParseError(format!("Failed to serialize pantry journal: {}", e))
+        })?;
+        fs::write(&path, yaml).map_err(|e| {
+            CookbookError::WriteError(format!("Failed to write pantry journal file: {}", e))
+        })?;
+        info!("Successfully wrote to {}", path.as_ref().display());
+        Ok(())
+    }
+}
+
 // Implementing method for Recipe
 impl Recipe {
-    /// Reads a recipe from a Markdown file
+    /// Reads a recipe from a Markdown file, upgrading older schema versions in memory
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, CookbookError> {
         let content =
             fs::read_to_string(&path).map_err(|e| CookbookError::ReadError(e.to_string()))?; // Read the file content
 
         // Split frontmatter from content by finding the two "---" delimiters
-        let parts: Vec<&str> = content.splitn(3, "---").collect();
-
-        // Check if we have at least three parts: before, frontmatter, and after
-        if parts.len() < 3 {
-            return Err(CookbookError::MarkdownError(
-                "Invalid markdown format: missing frontmatter delimiters".to_string(),
-            ));
-        }
+        let (front_matter, body) = migration::split_front_matter(&content)?;
 
-        // Parse the YAML frontmatter (parts[1] is between the first and second ---)
-        let mut recipe: Recipe = serde_yaml::from_str(parts[1].trim()).map_err(|e| {
+        // Parse and upgrade the YAML frontmatter
+        let mut mapping = migration::parse_mapping(front_matter.trim()).map_err(|e| {
+            CookbookError::ParseError(format!("Failed to parse YAML frontmatter: {}", e))
+        })?;
+        migration::upgrade(DataFileKind::Recipe, &mut mapping)?;
+        let mut recipe: Recipe = serde_yaml::from_value(Value::Mapping(mapping)).map_err(|e| {
             CookbookError::ParseError(format!("Failed to parse YAML frontmatter: {}", e))
         })?;
 
-        // Store the instructions (parts[2] is after the second ---)
-        recipe.instructions = parts[2].trim().to_string();
+        // Store the instructions (everything after the second ---)
+        recipe.instructions = body.trim().to_string();
 
         Ok(recipe) // Return the parsed recipe
     }
 
-    /// Writes a recipe to a Markdown file with YAML frontmatter
+    /// Writes a recipe to a Markdown file with YAML frontmatter, keeping unknown keys and
+    /// comments already in the file's frontmatter
     pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), CookbookError> {
+        self.to_file_preserving(&path, &path)
+    }
+
+    /// Writes a recipe to `path`, keeping the unknown keys, key order and comments of the
+    /// frontmatter in `original` (usually the same file; nothing is kept if it doesn't exist)
+    pub fn to_file_preserving<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        path: P,
+        original: Q,
+    ) -> Result<(), CookbookError> {
         // Create a copy of the recipe without instructions for YAML serialization
         // This ensures we don't include the instructions in the YAML frontmatter
         let recipe_for_yaml = Recipe {
@@ -91,11 +173,13 @@ impl Recipe {
             tags: self.tags.clone(),
             image: self.image.clone(),
             instructions: String::new(), // Empty string since it's excluded via #[serde(skip)]
+            slug: String::new(),         // Comes from the file name, also excluded
         };
 
         // Serialize to YAML
-        let yaml = serde_yaml::to_string(&recipe_for_yaml)
+        let yaml = to_versioned_yaml(&recipe_for_yaml, DataFileKind::Recipe)
             .map_err(|e| CookbookError::ParseError(format!("Failed to serialize recipe: {}", e)))?;
+        let yaml = merge_with_existing(original.as_ref(), &yaml, true, &[]);
 
         // Build the full content with frontmatter delimiters and instructions
         let content = format!("---\n{}---\n{}", yaml, self.instructions);
@@ -111,30 +195,95 @@ impl Recipe {
 
 // Implementing method for KnowledgeBaseEntry
 impl KnowledgeBaseEntry {
-    /// Reads a knowledge base entry from a Markdown file
+    /// Reads a knowledge base entry from a Markdown file, upgrading older schema versions in memory
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, CookbookError> {
         let content =
             fs::read_to_string(&path).map_err(|e| CookbookError::ReadError(e.to_string()))?; // Read the file content
+        Self::from_markdown(&content)
+    }
 
+    /// Parses a knowledge base entry from Markdown text with YAML frontmatter
+    pub fn from_markdown(content: &str) -> Result<Self, CookbookError> {
         // Split frontmatter from content by finding the two "---" delimiters
-        let parts: Vec<&str> = content.splitn(3, "---").collect();
+        let (front_matter, body) = migration::split_front_matter(content)?;
 
-        // Check if we have at least three parts: before, frontmatter, and after
-        if parts.len() < 3 {
-            return Err(CookbookError::MarkdownError(
-                "Invalid markdown format: missing frontmatter delimiters".to_string(),
-            ));
-        }
-
-        // Parse the YAML frontmatter (parts[1] is between the first and second ---)
-        let mut kb_entry: KnowledgeBaseEntry =
-            serde_yaml::from_str(parts[1].trim()).map_err(|e| {
+        // Parse and upgrade the YAML frontmatter
+        let mut mapping = migration::parse_mapping(front_matter.trim()).map_err(|e| {
+            CookbookError::ParseError(format!("Failed to parse YAML frontmatter: {}", e))
+        })?;
+        migration::upgrade(DataFileKind::KnowledgeBase, &mut mapping)?;
+        let mut kb_entry: KnowledgeBaseEntry = serde_yaml::from_value(Value::Mapping(mapping))
+            .map_err(|e| {
                 CookbookError::ParseError(format!("Failed to parse YAML frontmatter: {}", e))
             })?;
 
-        // Store the content (parts[2] is after the second ---)
-        kb_entry.content = parts[2].trim().to_string();
+        // Store the content (everything after the second ---)
+        kb_entry.content = body.trim().to_string();
 
         Ok(kb_entry) // Return the parsed knowledge base entry
     }
+
+    /// Writes a knowledge base entry to a Markdown file with YAML frontmatter, keeping
+    /// unknown keys and comments already in the file's frontmatter
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), CookbookError> {
+        self.to_file_preserving(&path, &path)
+    }
+
+    /// Writes a knowledge base entry to `path`, keeping the unknown keys, key order and
+    /// comments of the frontmatter in `original` (usually the same file)
+    pub fn to_file_preserving<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        path: P,
+        original: Q,
+    ) -> Result<(), CookbookError> {
+        // The content is excluded from the frontmatter via #[serde(skip)]
+        let yaml = to_versioned_yaml(self, DataFileKind::KnowledgeBase).map_err(|e| {
+            CookbookError::ParseError(format!("Failed to serialize KB entry: {}", e))
+        })?;
+        let yaml = merge_with_existing(original.as_ref(), &yaml, true, &[]);
+
+        // Build the full content with frontmatter delimiters and the article body
+        let content = format!("---\n{}---\n\n{}\n", yaml, self.content.trim());
+
+        fs::write(&path, content).map_err(|e| {
+            CookbookError::WriteError(format!("Failed to write KB entry file: {}", e))
+        })?;
+        info!("Successfully wrote to {}", path.as_ref().display());
+        Ok(())
+    }
+}
+
+/// Merges freshly serialized YAML into the YAML of the file at `original` (its frontmatter
+/// for Markdown files), so hand-added keys and comments survive; returns `yaml` unchanged if
+/// there is no such file. `optional_keys` are the type's keys that are left out when empty
+fn merge_with_existing(
+    original: &Path,
+    yaml: &str,
+    markdown: bool,
+    optional_keys: &[&str],
+) -> String {
+    let Ok(content) = fs::read_to_string(original) else {
+        return yaml.to_string();
+    };
+    let existing = if markdown {
+        match migration::split_front_matter(&content) {
+            Ok((front_matter, _)) => front_matter.trim_start_matches(['\r', '\n']),
+            Err(_) => return yaml.to_string(),
+        }
+    } else {
+        content.as_str()
+    };
+    yaml_merge::merge_yaml_clearing(existing, yaml, optional_keys)
+}
+
+/// Serializes a value to YAML with the current schema version as its first key
+fn to_versioned_yaml<T: serde::Serialize>(
+    value: &T,
+    kind: DataFileKind,
+) -> Result<String, serde_yaml::Error> {
+    let mut value = serde_yaml::to_value(value)?;
+    if let Value::Mapping(mapping) = &mut value {
+        migration::stamp_version(kind, mapping);
+    }
+    serde_yaml::to_string(&value)
 }
@@ -0,0 +1,213 @@
+//! Consumption rates and run-out dates for pantry items.
+//!
+//! Every change to a pantry quantity is recorded in `pantry_journal.yaml`. Decreases count
+//! as use and increases as restocking, so an item's rate is what was used divided by the
+//! days since its first recorded change. A forecast runs the current stock down at that
+//! rate, takes off what the meals planned from today on need, and reports the day the
+//! stock runs out.
+//!
+//! The rate is everyday use, which includes the meals cooked so far; planned meals come on
+//! top of it. Changes and recipe quantities are converted to the pantry item's unit with
+//! `units::convert_ingredient`, so cups of flour count against a stock in kg when the
+//! ingredient has a density. Planned meals whose amount can't be converted, or that give
+//! no amount at all, are listed in `PantryForecast::unmeasured` instead.
+use crate::types::{Ingredient, MealPlanEntry, PantryChange, PantryItem, Recipe};
+use crate::units::{self, MissingConversion};
+use chrono::{Duration, NaiveDate};
+
+/// Fewer days of history than this give no rate: a single busy weekend says little
+pub const MIN_OBSERVED_DAYS: i64 = 7;
+
+/// How fast an ingredient is used, from the pantry journal
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConsumptionRate {
+    /// Average use per day, in `quantity_type`
+    pub per_day: f64,
+    pub quantity_type: String,
+    /// Total use recorded in the journal
+    pub used: f64,
+    /// Days from the first recorded change to today
+    pub days: i64,
+}
+
+/// A planned meal's share of a pantry item
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedUse {
+    pub date: NaiveDate,
+    pub recipe: String,
+    /// In the pantry item's unit, scaled to the planned servings
+    pub quantity: f64,
+}
+
+/// A planned meal whose use of a pantry item couldn't be counted
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnmeasuredUse {
+    pub recipe: String,
+    /// What converting the recipe's amount lacked, or None if it gives no amount
+    pub missing: Option<MissingConversion>,
+}
+
+/// When a pantry item is expected to run out
+#[derive(Debug, Clone, PartialEq)]
+pub struct PantryForecast {
+    pub ingredient: String,
+    /// In stock today
+    pub quantity: f64,
+    pub quantity_type: String,
+    /// None if the journal has too little history or records no use
+    pub rate: Option<ConsumptionRate>,
+    /// Planned meals from today on, by date
+    pub planned: Vec<PlannedUse>,
+    /// Planned meals using the ingredient whose amount could not be counted
+    pub unmeasured: Vec<UnmeasuredUse>,
+    /// The first day without enough left, or None if neither the rate nor the plan use it up
+    pub runs_out: Option<NaiveDate>,
+}
+
+impl PantryForecast {
+    /// The expected use from today up to (not including) `date`
+    pub fn use_before(&self, today: NaiveDate, date: NaiveDate) -> f64 {
+        let days = (date - today).num_days().max(0) as f64;
+        let everyday = self.rate.as_ref().map_or(0.0, |r| r.per_day * days);
+        let planned: f64 = self
+            .planned
+            .iter()
+            .filter(|p| p.date < date)
+            .map(|p| p.quantity)
+            .sum();
+        everyday + planned
+    }
+
+    /// How much more is needed to last until `date`, or 0 if the stock is enough
+    pub fn shortfall(&self, today: NaiveDate, date: NaiveDate) -> f64 {
+        (self.use_before(today, date) - self.quantity).max(0.0)
+    }
+}
+
+/// Estimates the daily use of an ingredient from its journal entries, in `quantity_type`.
+/// Changes in units that don't convert to it are left out
+pub fn consumption_rate(
+    changes: &[&PantryChange],
+    quantity_type: &str,
+    ingredient: Option<&Ingredient>,
+    today: NaiveDate,
+) -> Option<ConsumptionRate> {
+    let changes: Vec<(NaiveDate, f64)> = changes
+        .iter()
+        .filter_map(|c| {
+            units::convert_ingredient(c.used(), &c.quantity_type, quantity_type, ingredient)
+                .ok()
+                .map(|used| (c.date, used))
+        })
+        .collect();
+    let first = changes.iter().map(|(date, _)| *date).min()?;
+    let days = (today - first).num_days();
+    let used: f64 = changes.iter().map(|(_, used)| used).sum();
+    if days < MIN_OBSERVED_DAYS || used <= 0.0 {
+        return None;
+    }
+    Some(ConsumptionRate {
+        per_day: used / days as f64,
+        quantity_type: quantity_type.to_string(),
+        used,
+        days,
+    })
+}
+
+/// Forecasts when `item` runs out, from its journal entries and the planned meals with
+/// their recipes. `ingredient` gives the density and unit weight for converting amounts.
+/// Returns None if the item has no quantity to count down
+pub fn forecast(
+    item: &PantryItem,
+    ingredient: Option<&Ingredient>,
+    changes: &[&PantryChange],
+    plan: &[(&MealPlanEntry, &Recipe)],
+    today: NaiveDate,
+) -> Option<PantryForecast> {
+    let quantity = item.quantity?;
+    let rate = consumption_rate(changes, &item.quantity_type, ingredient, today);
+
+    let mut planned = Vec::new();
+    let mut unmeasured = Vec::new();
+    for (entry, recipe) in plan.iter().filter(|(e, _)| e.date >= today) {
+        let factor = match (entry.servings, recipe.servings) {
+            (Some(wanted), Some(base)) if base > 0 => wanted as f64 / base as f64,
+            _ => 1.0,
+        };
+        for line in recipe
+            .required_ingredients()
+            .filter(|i| i.ingredient == item.ingredient)
+        {
+            let Some(amount) = line.quantity.as_ref().and_then(|q| q.value()) else {
+                unmeasured.push(UnmeasuredUse {
+                    recipe: recipe.title.clone(),
+                    missing: None,
+                });
+                continue;
+            };
+            let unit = line.quantity_type.as_deref().unwrap_or_default();
+            match units::convert_ingredient(amount * factor, unit, &item.quantity_type, ingredient)
+            {
+                Ok(quantity) => planned.push(PlannedUse {
+                    date: entry.date,
+                    recipe: recipe.title.clone(),
+                    quantity,
+                }),
+                Err(missing) => unmeasured.push(UnmeasuredUse {
+                    recipe: recipe.title.clone(),
+                    missing: Some(missing),
+                }),
+            }
+        }
+    }
+    planned.sort_by_key(|p| p.date);
+
+    let per_day = rate.as_ref().map_or(0.0, |r| r.per_day);
+    let runs_out = run_out_date(quantity, per_day, &planned, today);
+    Some(PantryForecast {
+        ingredient: item.ingredient.clone(),
+        quantity,
+        quantity_type: item.quantity_type.clone(),
+        rate,
+        planned,
+        unmeasured,
+        runs_out,
+    })
+}
+
+/// Counts `quantity` down by `per_day` and each planned use in turn
+fn run_out_date(
+    quantity: f64,
+    per_day: f64,
+    planned: &[PlannedUse],
+    today: NaiveDate,
+) -> Option<NaiveDate> {
+    if quantity <= 0.0 {
+        return Some(today);
+    }
+    // What is left on day `date`, after that day's meals
+    let mut left = quantity;
+    let mut date = today;
+    for use_ in planned {
+        let days = (use_.date - date).num_days();
+        if per_day > 0.0 && left - per_day * days as f64 <= 0.0 {
+            break;
+        }
+        left -= per_day * days as f64 + use_.quantity;
+        date = use_.date;
+        if left < 0.0 {
+            // Not enough for that day's meal
+            return Some(date);
+        }
+        if left == 0.0 {
+            // That day's meal takes the last of it
+            return Some(date + Duration::days(1));
+        }
+    }
+    if per_day > 0.0 {
+        let days = (left / per_day).floor() as i64;
+        Some(date + Duration::days(days))
+    } else {
+        None
+    }
+}
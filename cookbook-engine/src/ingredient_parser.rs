@@ -0,0 +1,448 @@
+//! Reading free-text ingredient lines such as "2 1/2 cups diced tomatoes".
+//!
+//! Lines pasted from a website or typed by hand are split into a quantity, a unit, an
+//! ingredient name and preparation notes. The name is matched against known ingredients by a
+//! caller-supplied resolver, trying singular forms and dropping leading words like "diced" or
+//! "large" until something matches. Every result carries a confidence score and the reasons it
+//! was lowered, so callers can flag lines that need a second look.
+use crate::quantity::{self, Amount, Quantity};
+use crate::types::RecipeIngredient;
+
+/// Results below this confidence should be checked by a person
+pub const CONFIDENCE_THRESHOLD: f64 = 0.75;
+
+/// Canonical unit names and the spellings that map to them
+const UNITS: &[(&str, &[&str])] = &[
+    ("g", &["g", "gr", "gram", "grams", "gramme", "grammes"]),
+    ("kg", &["kg", "kilo", "kilos", "kilogram", "kilograms"]),
+    ("mg", &["mg", "milligram", "milligrams"]),
+    (
+        "ml",
+        &[
+            "ml",
+            "milliliter",
+            "milliliters",
+            "millilitre",
+            "millilitres",
+        ],
+    ),
+    (
+        "cl",
+        &[
+            "cl",
+            "centiliter",
+            "centiliters",
+            "centilitre",
+            "centilitres",
+        ],
+    ),
+    (
+        "dl",
+        &["dl", "deciliter", "deciliters", "decilitre", "decilitres"],
+    ),
+    ("l", &["l", "liter", "liters", "litre", "litres"]),
+    ("tsp", &["tsp", "tsps", "teaspoon", "teaspoons"]),
+    (
+        "tbsp",
+        &["tbsp", "tbsps", "tbs", "tablespoon", "tablespoons"],
+    ),
+    ("cup", &["cup", "cups"]),
+    ("oz", &["oz", "ounce", "ounces"]),
+    ("lb", &["lb", "lbs", "pound", "pounds"]),
+    ("pinch", &["pinch", "pinches"]),
+    ("dash", &["dash", "dashes"]),
+    ("clove", &["clove", "cloves"]),
+    ("can", &["can", "cans", "tin", "tins"]),
+    ("slice", &["slice", "slices"]),
+    ("piece", &["piece", "pieces", "pc", "pcs"]),
+    ("bunch", &["bunch", "bunches"]),
+    ("handful", &["handful", "handfuls"]),
+    ("sprig", &["sprig", "sprigs"]),
+    ("stick", &["stick", "sticks"]),
+    ("package", &["package", "packages", "pack", "packs", "pkg"]),
+];
+
+/// Words read as numbers when they start a line ("a pinch of salt", "two eggs");
+/// "half" is handled on its own
+pub(crate) const NUMBER_WORDS: &[(&str, u32)] = &[
+    ("a", 1),
+    ("an", 1),
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+    ("ten", 10),
+    ("eleven", 11),
+    ("twelve", 12),
+    ("dozen", 12),
+];
+
+/// Words between two numbers that make a range ("2 to 3")
+const RANGE_SEPARATORS: &[&str] = &["-", "–", "—", "to", "or"];
+
+/// Descriptive words that may precede a name; dropping them doesn't lower the confidence
+const PREPARATION_WORDS: &[&str] = &[
+    "chopped",
+    "diced",
+    "minced",
+    "sliced",
+    "grated",
+    "shredded",
+    "crushed",
+    "cubed",
+    "halved",
+    "quartered",
+    "peeled",
+    "mashed",
+    "melted",
+    "softened",
+    "beaten",
+    "toasted",
+    "cooked",
+    "drained",
+    "rinsed",
+    "fresh",
+    "freshly",
+    "finely",
+    "roughly",
+    "coarsely",
+    "thinly",
+];
+
+/// Phrases at the end of a line that stand in for an amount ("salt to taste")
+const QUALITATIVE_AMOUNTS: &[&str] = &["to taste", "as needed", "as required"];
+
+/// Phrases at the end of a line that are notes rather than part of the name
+const TRAILING_NOTES: &[&str] = &["for garnish", "for serving", "optional"];
+
+/// One ingredient line, as far as the parser could make sense of it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedIngredient {
+    /// The recipe ingredient read from the line, with notes such as "diced" or "for garnish"
+    /// as its preparation
+    pub ingredient: RecipeIngredient,
+    /// Whether the name matched a known ingredient
+    pub known: bool,
+    /// How sure the parser is, from 0.0 to 1.0
+    pub confidence: f64,
+    /// Why the confidence was lowered, e.g. "'saffron' is not a known ingredient"
+    pub warnings: Vec<String>,
+}
+
+impl ParsedIngredient {
+    /// True if the line should be checked by a person
+    pub fn is_uncertain(&self) -> bool {
+        self.confidence < CONFIDENCE_THRESHOLD
+    }
+}
+
+/// Parses one ingredient line.
+///
+/// `resolve` maps a candidate name (e.g. "tomatoes") to the name of a known ingredient, or
+/// None; `DataManager::parse_ingredient_line` resolves against the loaded ingredients.
+pub fn parse_ingredient_line(
+    line: &str,
+    resolve: impl Fn(&str) -> Option<String>,
+) -> ParsedIngredient {
+    let mut notes: Vec<String> = Vec::new();
+    let mut warnings: Vec<String> = Vec::new();
+    let mut confidence: f64 = 1.0;
+
+    let line = line
+        .trim()
+        .trim_start_matches(['-', '*', '•', '·'])
+        .replace('⁄', "/");
+    let main = take_parenthesized(&line, &mut notes);
+    let (main, after_comma) = split_at_comma(&main);
+    let (main, qualifier) = strip_qualifier(main.trim());
+    let after_comma = after_comma.map(|rest| {
+        let (rest, trailing) = strip_qualifier(rest.trim());
+        (rest.to_string(), trailing)
+    });
+
+    // ── Quantity and unit ────────────────────────────────────────────────────
+    let spaced = space_attached_units(main);
+    let tokens: Vec<&str> = spaced.split_whitespace().collect();
+    let mut position = 0;
+    let mut amount = parse_amount(&tokens);
+    if let Some((_, used)) = amount {
+        position = used;
+    }
+    let followed_by_of = |i: usize| {
+        tokens
+            .get(i + 1)
+            .is_some_and(|t| t.eq_ignore_ascii_case("of"))
+    };
+    let unit_position = position;
+    let mut unit = None;
+    if let Some(found) = tokens.get(position).and_then(|t| canonical_unit(t)) {
+        // Without a number, only "pinch of ..." style units count, so "cloves" stays a name
+        if amount.is_some() || followed_by_of(position) {
+            unit = Some(found.to_string());
+            position += 1;
+        }
+    }
+    if tokens
+        .get(position)
+        .is_some_and(|t| t.eq_ignore_ascii_case("of"))
+    {
+        position += 1;
+    }
+    let mut name_words: Vec<&str> = tokens[position..].to_vec();
+    // "3 cloves" is three of something called cloves, not a unit without an ingredient
+    if name_words.is_empty() && unit.is_some() {
+        name_words = vec![tokens[unit_position]];
+        unit = None;
+    }
+
+    // ── Name ─────────────────────────────────────────────────────────────────
+    let mut name = String::new();
+    let mut known = false;
+    if name_words.is_empty() {
+        confidence = 0.0;
+        warnings.push("No ingredient name".to_string());
+    } else {
+        let (resolved, dropped) = resolve_name(&name_words, &resolve);
+        let descriptive: Vec<&str> = name_words[..dropped]
+            .iter()
+            .copied()
+            .filter(|w| !PREPARATION_WORDS.contains(&w.to_lowercase().as_str()))
+            .collect();
+        match resolved {
+            Some(found) => {
+                name = found;
+                known = true;
+                if !descriptive.is_empty() {
+                    confidence -= 0.1 * descriptive.len() as f64;
+                    warnings.push(format!("Read '{}' as a note", descriptive.join(" ")));
+                }
+                if dropped > 0 {
+                    notes.insert(0, name_words[..dropped].join(" "));
+                }
+            }
+            None => {
+                // Keep everything but leading preparation words as the name
+                let skip = name_words
+                    .iter()
+                    .take(name_words.len() - 1)
+                    .take_while(|w| PREPARATION_WORDS.contains(&w.to_lowercase().as_str()))
+                    .count();
+                if skip > 0 {
+                    notes.insert(0, name_words[..skip].join(" "));
+                }
+                name = name_words[skip..].join(" ");
+                confidence -= 0.4;
+                warnings.push(format!("'{}' is not a known ingredient", name));
+            }
+        }
+        if name.chars().any(|c| c.is_ascii_digit()) {
+            confidence -= 0.3;
+            warnings.push(format!("Could not read the quantity in '{}'", name));
+        }
+    }
+
+    if let Some((rest, trailing)) = after_comma {
+        if !rest.is_empty() {
+            notes.push(rest);
+        }
+        notes.extend(trailing);
+    }
+    notes.extend(qualifier);
+    // "salt to taste": the phrase is the quantity
+    if amount.is_none() && unit.is_none() {
+        if let Some(index) = notes.iter().position(|n| is_qualitative_amount(n)) {
+            amount = Some((Quantity::Qualitative(notes.remove(index)), 0));
+        } else {
+            confidence -= 0.1;
+            warnings.push("No quantity".to_string());
+        }
+    }
+
+    // "(optional)" or ", optional" marks the ingredient rather than describing it
+    let before = notes.len();
+    notes.retain(|n| !n.eq_ignore_ascii_case("optional"));
+    let optional = notes.len() < before;
+
+    ParsedIngredient {
+        ingredient: RecipeIngredient {
+            ingredient: name,
+            quantity: amount.map(|(quantity, _)| quantity),
+            quantity_type: unit,
+            group: None,
+            optional,
+            preparation: (!notes.is_empty()).then(|| notes.join(", ")),
+        },
+        known,
+        confidence: confidence.clamp(0.0, 1.0),
+        warnings,
+    }
+}
+
+/// Returns the canonical spelling of a unit, e.g. "Tablespoons" → "tbsp"
+pub fn canonical_unit(word: &str) -> Option<&'static str> {
+    let word = word.trim_end_matches('.').to_lowercase();
+    UNITS
+        .iter()
+        .find(|(_, spellings)| spellings.contains(&word.as_str()))
+        .map(|(canonical, _)| *canonical)
+}
+
+fn is_qualitative_amount(note: &str) -> bool {
+    QUALITATIVE_AMOUNTS.contains(&note.to_lowercase().as_str())
+}
+
+/// Reads a quantity at the start of `tokens`: a number, a mixed number ("2 1/2"), a number
+/// word or a range ("2-3", "2 to 3"). Returns the quantity and the number of tokens used
+fn parse_amount(tokens: &[&str]) -> Option<(Quantity, usize)> {
+    let first = *tokens.first()?;
+    if let Some((low, high)) = first.split_once(['-', '–', '—']) {
+        let (min, max) = (Amount::parse(low)?, Amount::parse(high)?);
+        return Some((Quantity::Range { min, max }, 1));
+    }
+    let (min, used) = parse_mixed(tokens)?;
+    let separator = tokens.get(used).map(|t| t.to_lowercase());
+    if separator.is_some_and(|s| RANGE_SEPARATORS.contains(&s.as_str())) {
+        if let Some((max, more)) = parse_mixed(&tokens[used + 1..]) {
+            if max.value() > min.value() {
+                return Some((Quantity::Range { min, max }, used + 1 + more));
+            }
+        }
+    }
+    Some((Quantity::Amount(min), used))
+}
+
+/// Reads a number or number word, plus a following fraction for mixed numbers
+fn parse_mixed(tokens: &[&str]) -> Option<(Amount, usize)> {
+    let first = *tokens.first()?;
+    let lower = first.to_lowercase();
+    if lower == "half" {
+        // "half a cup" is one amount, not half of one
+        let article = tokens
+            .get(1)
+            .is_some_and(|t| matches!(t.to_lowercase().as_str(), "a" | "an"));
+        return Some((Amount::fraction(1, 2), if article { 2 } else { 1 }));
+    }
+    if let Some((_, value)) = NUMBER_WORDS.iter().find(|(word, _)| *word == lower) {
+        return Some((Amount::Decimal(*value as f64), 1));
+    }
+    let whole = Amount::parse(first)?;
+    if let Some(fraction) = tokens.get(1).filter(|t| is_fraction(t)) {
+        if let Some(mixed) = Amount::parse(&format!("{} {}", first, fraction)) {
+            return Some((mixed, 2));
+        }
+    }
+    Some((whole, 1))
+}
+
+fn is_fraction(token: &str) -> bool {
+    token.contains(['/', '⁄'])
+        || token.chars().count() == 1 && token.chars().all(quantity::is_unicode_fraction)
+}
+
+/// Inserts a space between a number and a unit written against it ("500g" → "500 g")
+fn space_attached_units(text: &str) -> String {
+    let mut spaced = String::with_capacity(text.len());
+    let mut previous: Option<char> = None;
+    for c in text.chars() {
+        if let Some(p) = previous {
+            let after_number = p.is_ascii_digit() || quantity::is_unicode_fraction(p);
+            if after_number && c.is_alphabetic() {
+                spaced.push(' ');
+            }
+        }
+        spaced.push(c);
+        previous = Some(c);
+    }
+    spaced
+}
+
+/// Removes "(...)" groups from `text`, collecting their contents as notes
+fn take_parenthesized(text: &str, notes: &mut Vec<String>) -> String {
+    let mut rest = String::new();
+    let mut remaining = text;
+    while let Some(open) = remaining.find('(') {
+        let Some(close) = remaining[open..].find(')').map(|c| open + c) else {
+            break;
+        };
+        rest.push_str(&remaining[..open]);
+        let note = remaining[open + 1..close].trim();
+        if !note.is_empty() {
+            notes.push(note.to_string());
+        }
+        remaining = &remaining[close + 1..];
+    }
+    rest.push_str(remaining);
+    rest
+}
+
+/// Splits "tomatoes, diced" at the first comma that isn't a decimal comma
+fn split_at_comma(text: &str) -> (&str, Option<&str>) {
+    let bytes = text.as_bytes();
+    let comma = text.char_indices().find(|(i, c)| {
+        let decimal = *i > 0
+            && bytes[i - 1].is_ascii_digit()
+            && bytes.get(i + 1).is_some_and(u8::is_ascii_digit);
+        *c == ',' && !decimal
+    });
+    match comma {
+        Some((i, _)) => (&text[..i], Some(&text[i + 1..])),
+        None => (text, None),
+    }
+}
+
+/// Splits a trailing "to taste" style phrase off `text`
+fn strip_qualifier(text: &str) -> (&str, Option<String>) {
+    for qualifier in QUALITATIVE_AMOUNTS.iter().chain(TRAILING_NOTES) {
+        let Some(split) = text.len().checked_sub(qualifier.len()) else {
+            continue;
+        };
+        let (Some(rest), Some(tail)) = (text.get(..split), text.get(split..)) else {
+            continue;
+        };
+        if tail.eq_ignore_ascii_case(qualifier)
+            && (rest.is_empty() || rest.ends_with(char::is_whitespace))
+        {
+            return (rest.trim_end(), Some(qualifier.to_string()));
+        }
+    }
+    (text, None)
+}
+
+/// Finds the longest known ingredient at the end of `words`, trying singular forms.
+/// Returns the ingredient name and how many leading words were dropped to find it.
+fn resolve_name(
+    words: &[&str],
+    resolve: &impl Fn(&str) -> Option<String>,
+) -> (Option<String>, usize) {
+    for dropped in 0..words.len() {
+        let candidate = words[dropped..].join(" ");
+        let found = std::iter::once(candidate.clone())
+            .chain(singular_forms(&candidate))
+            .find_map(|form| resolve(&form));
+        if found.is_some() {
+            return (found, dropped);
+        }
+    }
+    (None, 0)
+}
+
+/// English singular forms a plural might come from: "berries" → "berry", "tomatoes" → "tomato"
+pub(crate) fn singular_forms(name: &str) -> Vec<String> {
+    let mut forms = Vec::new();
+    if let Some(stem) = name.strip_suffix("ies") {
+        forms.push(format!("{}y", stem));
+    }
+    if let Some(stem) = name.strip_suffix("es") {
+        forms.push(stem.to_string());
+    }
+    if let Some(stem) = name.strip_suffix('s') {
+        forms.push(stem.to_string());
+    }
+    forms.retain(|f| !f.is_empty());
+    forms
+}
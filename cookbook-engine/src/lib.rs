@@ -1,6 +1,27 @@
+pub mod barcode;
+pub mod bundled_kb;
+pub mod cost;
 pub mod data_manager;
+pub mod duplicates;
 pub mod file_io;
+pub mod forecast;
+pub mod ingredient_parser;
+pub mod links;
+pub mod markdown;
+pub mod merge;
+pub mod migration;
+pub mod planner;
+pub mod quantity;
+pub mod schedule;
+pub mod shopping;
+pub mod slug;
+pub mod steps;
 pub mod types;
+pub mod units;
+pub mod yaml_merge;
 
 pub use data_manager::DataManager;
+pub use quantity::{Amount, Quantity};
+pub use steps::{Step, TemperatureUnit};
 pub use types::*;
+pub use units::UnitSystem;
@@ -0,0 +1,125 @@
+//! Wiki-style `[[links]]` between recipes, ingredients and knowledge base articles.
+//!
+//! Recipe instructions and KB articles can mention other items as `[[basil]]` or
+//! `[[Lasagna|the lasagna recipe]]`. This module finds those links in text and names their
+//! targets; `DataManager` resolves them against the loaded data, reports broken ones, and
+//! computes backlinks.
+
+/// Something a wiki link can point at
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LinkTarget {
+    /// A knowledge base article, by slug
+    Kb(String),
+    /// An ingredient, by name
+    Ingredient(String),
+    /// A recipe, by title
+    Recipe(String),
+}
+
+const URI_PREFIX: &str = "cookbook://";
+
+impl LinkTarget {
+    /// Returns a URI for the target, e.g. `cookbook://kb/basil`, for use in rendered links
+    pub fn to_uri(&self) -> String {
+        let (kind, id) = match self {
+            LinkTarget::Kb(slug) => ("kb", slug),
+            LinkTarget::Ingredient(name) => ("ingredient", name),
+            LinkTarget::Recipe(title) => ("recipe", title),
+        };
+        format!("{}{}/{}", URI_PREFIX, kind, id)
+    }
+
+    /// Parses a URI produced by `to_uri`
+    pub fn from_uri(uri: &str) -> Option<Self> {
+        let (kind, id) = uri.strip_prefix(URI_PREFIX)?.split_once('/')?;
+        if id.is_empty() {
+            return None;
+        }
+        match kind {
+            "kb" => Some(LinkTarget::Kb(id.to_string())),
+            "ingredient" => Some(LinkTarget::Ingredient(id.to_string())),
+            "recipe" => Some(LinkTarget::Recipe(id.to_string())),
+            _ => None,
+        }
+    }
+}
+
+/// Where a link was written
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LinkSource {
+    /// Recipe instructions, by recipe title
+    Recipe(String),
+    /// A KB article body, by slug
+    Kb(String),
+    /// The `kb` field of an ingredient, by ingredient name
+    Ingredient(String),
+}
+
+/// A `[[target]]` or `[[target|label]]` occurrence in a piece of text
+#[derive(Debug, Clone, PartialEq)]
+pub struct WikiLink {
+    /// The text naming the target, trimmed
+    pub target: String,
+    /// The text to display instead of the target, if given
+    pub label: Option<String>,
+    /// Byte range of the whole link, brackets included
+    pub start: usize,
+    pub end: usize,
+}
+
+impl WikiLink {
+    /// The text to display for the link
+    pub fn display_text(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.target)
+    }
+}
+
+/// A link whose target does not exist
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrokenLink {
+    pub source: LinkSource,
+    pub target: String,
+}
+
+/// Finds every wiki link in `text`, in order.
+///
+/// Links cannot span lines, and `[[]]` or `[[ | label]]` (no target) are not links.
+pub fn parse_wiki_links(text: &str) -> Vec<WikiLink> {
+    let mut links = Vec::new();
+    let mut pos = 0;
+    while let Some(open) = text[pos..].find("[[") {
+        let start = pos + open;
+        let inner_start = start + 2;
+        let Some(close) = text[inner_start..].find("]]") else {
+            break;
+        };
+        let inner = &text[inner_start..inner_start + close];
+        let end = inner_start + close + 2;
+
+        // An opening bracket inside means this one wasn't a link; retry from the inner one
+        if let Some(reopen) = inner.rfind("[[") {
+            pos = inner_start + reopen;
+            continue;
+        }
+
+        let (target, label) = match inner.split_once('|') {
+            Some((target, label)) => (target.trim(), Some(label.trim())),
+            None => (inner.trim(), None),
+        };
+        if !target.is_empty() && !inner.contains('\n') {
+            links.push(WikiLink {
+                target: target.to_string(),
+                label: label.filter(|l| !l.is_empty()).map(str::to_string),
+                start,
+                end,
+            });
+        }
+        pos = end;
+    }
+    links
+}
+
+/// Normalises a link target or slug for comparison: case-insensitive, spaces match underscores
+pub fn link_key(text: &str) -> String {
+    text.trim().to_lowercase().replace(' ', "_")
+}
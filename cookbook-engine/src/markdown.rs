@@ -0,0 +1,709 @@
+//! Markdown rendering for recipe instructions and KB articles.
+//!
+//! Covers the subset the data files actually use: headings, paragraphs, bullet and numbered
+//! lists (nested by indentation), block quotes, fenced code, horizontal rules, emphasis,
+//! inline code, links, images and `[[wiki links]]`. Output is either Pango markup, for GTK
+//! labels, or HTML.
+//!
+//! Pango markup cannot show pictures, so the Pango renderer returns the document as a list of
+//! segments and puts every image that stands on its own line in a segment of its own. The
+//! frontend draws those however it likes; inline images fall back to their alt text.
+//!
+//! Only web, mail and `cookbook://` links are kept; a link with any other scheme, such as
+//! `javascript:`, is shown as its text.
+use crate::links::{self, LinkTarget};
+
+/// Link schemes the renderers emit as links
+const ALLOWED_SCHEMES: &[&str] = &["http://", "https://", "mailto:", "cookbook://"];
+
+/// The markup language to produce
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkupFormat {
+    Pango,
+    Html,
+}
+
+/// A piece of a rendered document
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rendered {
+    /// Markup in the requested format
+    Markup(String),
+    /// An image on a line of its own, with its source as written in the Markdown
+    Image { src: String, alt: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Block {
+    Heading(usize, String),
+    Paragraph(String),
+    ListItem {
+        depth: usize,
+        number: Option<u32>,
+        text: String,
+    },
+    Quote(Vec<String>),
+    Table {
+        header: Option<Vec<String>>,
+        rows: Vec<Vec<String>>,
+    },
+    Code(String),
+    Rule,
+    Image {
+        src: String,
+        alt: String,
+    },
+}
+
+/// Renders Markdown to a list of segments.
+///
+/// `resolve` turns the target of a `[[wiki link]]` into a link target; links it can't
+/// resolve are rendered as broken. Consecutive markup is merged into a single segment, so
+/// a document without standalone images renders to exactly one `Rendered::Markup`.
+pub fn render(
+    markdown: &str,
+    format: MarkupFormat,
+    resolve: &dyn Fn(&str) -> Option<LinkTarget>,
+) -> Vec<Rendered> {
+    let renderer = Renderer { format, resolve };
+    let blocks = parse_blocks(markdown);
+
+    let mut segments = Vec::new();
+    let mut markup = String::new();
+    let mut open_lists: Vec<(usize, bool)> = Vec::new();
+    for block in &blocks {
+        if !matches!(block, Block::ListItem { .. }) {
+            markup.push_str(&renderer.close_lists(&mut open_lists, None));
+        }
+        match block {
+            Block::Image { src, alt } if format == MarkupFormat::Pango => {
+                if !markup.is_empty() {
+                    segments.push(finish_segment(format, std::mem::take(&mut markup)));
+                }
+                segments.push(Rendered::Image {
+                    src: src.clone(),
+                    alt: alt.clone(),
+                });
+            }
+            Block::ListItem {
+                depth,
+                number,
+                text,
+            } => {
+                // Blank line before a list in Pango, as between other blocks
+                if format == MarkupFormat::Pango && open_lists.is_empty() && !markup.is_empty() {
+                    markup.push('\n');
+                }
+                markup.push_str(&renderer.list_item(&mut open_lists, *depth, *number, text));
+            }
+            _ => {
+                if format == MarkupFormat::Pango && !markup.is_empty() {
+                    markup.push('\n');
+                }
+                markup.push_str(&renderer.block(block));
+            }
+        }
+    }
+    markup.push_str(&renderer.close_lists(&mut open_lists, None));
+    if !markup.is_empty() {
+        segments.push(finish_segment(format, markup));
+    }
+    segments
+}
+
+/// Pango labels show trailing newlines as blank space, so drop them
+fn finish_segment(format: MarkupFormat, mut markup: String) -> Rendered {
+    if format == MarkupFormat::Pango {
+        markup.truncate(markup.trim_end_matches('\n').len());
+    }
+    Rendered::Markup(markup)
+}
+
+/// Renders Markdown to a single markup string; standalone images are kept inline
+/// (`<img>` in HTML, alt text in Pango).
+pub fn render_to_string(
+    markdown: &str,
+    format: MarkupFormat,
+    resolve: &dyn Fn(&str) -> Option<LinkTarget>,
+) -> String {
+    let renderer = Renderer { format, resolve };
+    render(markdown, format, resolve)
+        .into_iter()
+        .map(|segment| match segment {
+            Rendered::Markup(markup) => markup,
+            Rendered::Image { src, alt } => renderer.image(&src, &alt),
+        })
+        .collect::<Vec<_>>()
+        .join(if format == MarkupFormat::Pango {
+            "\n\n"
+        } else {
+            "\n"
+        })
+        .trim_end()
+        .to_string()
+}
+
+/// Returns the sources of all images in the Markdown, in order
+pub fn image_sources(markdown: &str) -> Vec<String> {
+    let mut sources = Vec::new();
+    let mut rest = markdown;
+    while let Some(start) = rest.find("![") {
+        match parse_link_syntax(&rest[start + 1..]) {
+            Some((_, src, len)) => {
+                sources.push(src.to_string());
+                rest = &rest[start + 1 + len..];
+            }
+            None => rest = &rest[start + 2..],
+        }
+    }
+    sources
+}
+
+// ─── Block parsing ───────────────────────────────────────────────────────────
+
+fn parse_blocks(markdown: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut lines = markdown.lines().peekable();
+    let mut in_quote = false;
+
+    let flush = |paragraph: &mut Vec<&str>, blocks: &mut Vec<Block>| {
+        if !paragraph.is_empty() {
+            blocks.push(Block::Paragraph(paragraph.join(" ")));
+            paragraph.clear();
+        }
+    };
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim();
+        let was_in_quote = std::mem::replace(&mut in_quote, trimmed.starts_with('>'));
+
+        if trimmed.starts_with("```") {
+            flush(&mut paragraph, &mut blocks);
+            let mut code = Vec::new();
+            for code_line in lines.by_ref() {
+                if code_line.trim().starts_with("```") {
+                    break;
+                }
+                code.push(code_line);
+            }
+            blocks.push(Block::Code(code.join("\n")));
+        } else if trimmed.is_empty() {
+            flush(&mut paragraph, &mut blocks);
+        } else if let Some((level, text)) = parse_heading(trimmed) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Heading(level, text.to_string()));
+        } else if is_rule(trimmed) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Rule);
+        } else if let Some((depth, number, text)) = parse_list_item(line) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::ListItem {
+                depth,
+                number,
+                text: text.to_string(),
+            });
+        } else if trimmed.starts_with('|') {
+            flush(&mut paragraph, &mut blocks);
+            let mut table_lines = vec![trimmed];
+            while let Some(next) = lines.next_if(|l| l.trim().starts_with('|')) {
+                table_lines.push(next.trim());
+            }
+            blocks.push(parse_table(&table_lines));
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            flush(&mut paragraph, &mut blocks);
+            let quote = quote.trim().to_string();
+            // Consecutive quote lines form one quote, keeping their line breaks
+            match blocks.last_mut() {
+                Some(Block::Quote(quote_lines)) if was_in_quote => {
+                    if !quote.is_empty() {
+                        quote_lines.push(quote);
+                    }
+                }
+                _ => blocks.push(Block::Quote(vec![quote])),
+            }
+        } else if let Some((alt, src)) = parse_standalone_image(trimmed) {
+            flush(&mut paragraph, &mut blocks);
+            blocks.push(Block::Image {
+                src: src.to_string(),
+                alt: alt.to_string(),
+            });
+        } else {
+            paragraph.push(trimmed);
+        }
+    }
+    flush(&mut paragraph, &mut blocks);
+    blocks
+}
+
+/// Parses `| a | b |` rows; a `|---|---|` row marks the rows above it as the header
+fn parse_table(lines: &[&str]) -> Block {
+    let split = |line: &str| -> Vec<String> {
+        let inner = line.trim_start_matches('|');
+        let inner = inner.strip_suffix('|').unwrap_or(inner);
+        inner
+            .split('|')
+            .map(|cell| cell.trim().to_string())
+            .collect()
+    };
+    let is_separator =
+        |line: &str| line.chars().all(|c| matches!(c, '|' | '-' | ':' | ' ')) && line.contains('-');
+
+    match lines.iter().position(|line| is_separator(line)) {
+        Some(1) => Block::Table {
+            header: Some(split(lines[0])),
+            rows: lines[2..].iter().map(|line| split(line)).collect(),
+        },
+        _ => Block::Table {
+            header: None,
+            rows: lines
+                .iter()
+                .filter(|line| !is_separator(line))
+                .map(|line| split(line))
+                .collect(),
+        },
+    }
+}
+
+fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &line[level..];
+    if rest.is_empty() || rest.starts_with(' ') {
+        Some((level, rest.trim().trim_end_matches('#').trim_end()))
+    } else {
+        None
+    }
+}
+
+fn is_rule(line: &str) -> bool {
+    let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3
+        && ['-', '*', '_']
+            .iter()
+            .any(|&marker| compact.chars().all(|c| c == marker))
+}
+
+/// Returns (nesting depth, number for ordered items, text)
+fn parse_list_item(line: &str) -> Option<(usize, Option<u32>, &str)> {
+    let indent: usize = line
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum();
+    let trimmed = line.trim_start();
+
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(text) = trimmed.strip_prefix(bullet) {
+            return Some((indent / 2, None, text.trim()));
+        }
+    }
+
+    let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits == 0 || digits > 9 {
+        return None;
+    }
+    let rest = &trimmed[digits..];
+    let text = rest
+        .strip_prefix(". ")
+        .or_else(|| rest.strip_prefix(") "))?;
+    let number = trimmed[..digits].parse().ok()?;
+    Some((indent / 2, Some(number), text.trim()))
+}
+
+fn parse_standalone_image(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix('!')?;
+    let (alt, src, len) = parse_link_syntax(rest)?;
+    if len == rest.len() {
+        Some((alt, src))
+    } else {
+        None
+    }
+}
+
+/// Parses `[text](url)` at the start of `s`, returning (text, url, length consumed)
+fn parse_link_syntax(s: &str) -> Option<(&str, &str, usize)> {
+    let rest = s.strip_prefix('[')?;
+    let close = rest.find(']')?;
+    let text = &rest[..close];
+    let after = rest[close + 1..].strip_prefix('(')?;
+    let end = after.find(')')?;
+    let url = after[..end].trim();
+    // 1 for '[', close, 1 for ']', 1 for '(', end, 1 for ')'
+    Some((text, url, close + end + 4))
+}
+
+// ─── Rendering ───────────────────────────────────────────────────────────────
+
+struct Renderer<'a> {
+    format: MarkupFormat,
+    resolve: &'a dyn Fn(&str) -> Option<LinkTarget>,
+}
+
+impl Renderer<'_> {
+    fn block(&self, block: &Block) -> String {
+        let pango = self.format == MarkupFormat::Pango;
+        match block {
+            Block::Heading(level, text) => {
+                let text = self.inline(text);
+                if pango {
+                    let size = match level {
+                        1 => "xx-large",
+                        2 => "x-large",
+                        3 => "large",
+                        _ => "medium",
+                    };
+                    format!("<span size=\"{}\" weight=\"bold\">{}</span>\n", size, text)
+                } else {
+                    format!("<h{0}>{1}</h{0}>\n", level, text)
+                }
+            }
+            Block::Paragraph(text) => {
+                if pango {
+                    format!("{}\n", self.inline(text))
+                } else {
+                    format!("<p>{}</p>\n", self.inline(text))
+                }
+            }
+            Block::Quote(lines) => {
+                let lines: Vec<String> = lines
+                    .iter()
+                    .map(|line| match parse_list_item(line) {
+                        Some((_, None, text)) => format!("• {}", self.inline(text)),
+                        _ => self.inline(line),
+                    })
+                    .collect();
+                if pango {
+                    let quoted: Vec<String> = lines.iter().map(|l| format!("▎ {}", l)).collect();
+                    format!("<i>{}</i>\n", quoted.join("\n"))
+                } else {
+                    format!("<blockquote><p>{}</p></blockquote>\n", lines.join("<br>\n"))
+                }
+            }
+            Block::Table { header, rows } => self.table(header.as_ref(), rows),
+            Block::Code(code) => {
+                if pango {
+                    format!("<tt>{}</tt>\n", escape(code))
+                } else {
+                    format!("<pre><code>{}</code></pre>\n", escape(code))
+                }
+            }
+            Block::Rule => {
+                if pango {
+                    "<span alpha=\"50%\">────────────────────</span>\n".to_string()
+                } else {
+                    "<hr>\n".to_string()
+                }
+            }
+            Block::Image { src, alt } => format!("{}\n", self.image(src, alt)),
+            Block::ListItem { .. } => unreachable!("list items are rendered by list_item"),
+        }
+    }
+
+    fn table(&self, header: Option<&Vec<String>>, rows: &[Vec<String>]) -> String {
+        if self.format == MarkupFormat::Html {
+            let row_html = |cells: &Vec<String>, tag: &str| {
+                let cells: Vec<String> = cells
+                    .iter()
+                    .map(|cell| format!("<{0}>{1}</{0}>", tag, self.inline(cell)))
+                    .collect();
+                format!("<tr>{}</tr>\n", cells.concat())
+            };
+            let mut out = String::from("<table>\n");
+            if let Some(header) = header {
+                out.push_str(&format!("<thead>\n{}</thead>\n", row_html(header, "th")));
+            }
+            out.push_str("<tbody>\n");
+            for row in rows {
+                out.push_str(&row_html(row, "td"));
+            }
+            out.push_str("</tbody>\n</table>\n");
+            return out;
+        }
+
+        // Pango has no tables: pad the cells into monospace columns
+        let columns = header
+            .into_iter()
+            .chain(rows)
+            .map(Vec::len)
+            .max()
+            .unwrap_or(0);
+        let mut widths = vec![0; columns];
+        for row in header.into_iter().chain(rows) {
+            for (i, cell) in row.iter().enumerate() {
+                widths[i] = widths[i].max(visible_width(cell));
+            }
+        }
+        let format_row = |cells: &Vec<String>| -> String {
+            let padded: Vec<String> = widths
+                .iter()
+                .enumerate()
+                .map(|(i, &width)| {
+                    let cell = cells.get(i).map_or("", String::as_str);
+                    let padding = " ".repeat(width.saturating_sub(visible_width(cell)));
+                    format!("{}{}", self.inline(cell), padding)
+                })
+                .collect();
+            padded.join("  ").trim_end().to_string()
+        };
+
+        let mut lines = Vec::new();
+        if let Some(header) = header {
+            lines.push(format!("<b>{}</b>", format_row(header)));
+            let total = widths.iter().sum::<usize>() + 2 * columns.saturating_sub(1);
+            lines.push("─".repeat(total));
+        }
+        lines.extend(rows.iter().map(format_row));
+        format!("<tt>{}</tt>\n", lines.join("\n"))
+    }
+
+    fn list_item(
+        &self,
+        open: &mut Vec<(usize, bool)>,
+        depth: usize,
+        number: Option<u32>,
+        text: &str,
+    ) -> String {
+        let text = self.inline(text);
+        let ordered = number.is_some();
+        if self.format == MarkupFormat::Pango {
+            let indent = "    ".repeat(depth);
+            let marker = match number {
+                Some(n) => format!("{}.", n),
+                None if depth.is_multiple_of(2) => "•".to_string(),
+                None => "◦".to_string(),
+            };
+            // A top-level switch between bullets and numbers starts a separate list
+            let gap = match open.last() {
+                Some(&(_, was_ordered)) if depth == 0 && was_ordered != ordered => "\n",
+                _ => "",
+            };
+            open.clear();
+            open.push((depth, ordered));
+            return format!("{}{}{} {}\n", gap, indent, marker, text);
+        }
+
+        // Close deeper lists, then continue the list at this depth or start a new one
+        let mut out = self.close_lists(open, Some(depth));
+        match open.last() {
+            Some(&(d, o)) if d == depth && o == ordered => out.push_str("</li>\n"),
+            top => {
+                if top.is_some_and(|&(d, _)| d == depth) {
+                    // Switching between bullets and numbers at the same depth
+                    let (_, was_ordered) = open.pop().unwrap_or_default();
+                    out.push_str(close_list(was_ordered));
+                }
+                out.push_str(&open_list(ordered, number));
+                open.push((depth, ordered));
+            }
+        }
+        out.push_str(&format!("<li>{}", text));
+        out
+    }
+
+    /// Closes HTML lists deeper than `depth` (all of them if None)
+    fn close_lists(&self, open: &mut Vec<(usize, bool)>, depth: Option<usize>) -> String {
+        let mut out = String::new();
+        if self.format == MarkupFormat::Pango {
+            if depth.is_none() {
+                open.clear();
+            }
+            return out;
+        }
+        while let Some(&(d, ordered)) = open.last() {
+            if depth.is_some_and(|depth| d <= depth) {
+                break;
+            }
+            open.pop();
+            out.push_str(close_list(ordered));
+        }
+        out
+    }
+
+    fn image(&self, src: &str, alt: &str) -> String {
+        match self.format {
+            MarkupFormat::Pango => format!(
+                "<i>🖼 {}</i>",
+                escape(if alt.is_empty() { src } else { alt })
+            ),
+            MarkupFormat::Html => format!("<img src=\"{}\" alt=\"{}\">", escape(src), escape(alt)),
+        }
+    }
+
+    fn link(&self, href: &str, text: &str) -> String {
+        let href_lower = href.trim().to_lowercase();
+        if !ALLOWED_SCHEMES.iter().any(|s| href_lower.starts_with(s)) {
+            return text.to_string();
+        }
+        format!("<a href=\"{}\">{}</a>", escape(href.trim()), text)
+    }
+
+    fn broken_link(&self, text: &str) -> String {
+        match self.format {
+            MarkupFormat::Pango => format!("<span underline=\"error\">{}</span>", text),
+            MarkupFormat::Html => format!("<span class=\"broken-link\">{}</span>", text),
+        }
+    }
+
+    fn tag(&self, kind: Inline, text: &str) -> String {
+        let (pango, html) = match kind {
+            Inline::Strong => ("b", "strong"),
+            Inline::Emphasis => ("i", "em"),
+            Inline::Code => ("tt", "code"),
+        };
+        let tag = if self.format == MarkupFormat::Pango {
+            pango
+        } else {
+            html
+        };
+        format!("<{0}>{1}</{0}>", tag, text)
+    }
+
+    /// Renders inline Markdown: emphasis, code, links, images and wiki links
+    fn inline(&self, text: &str) -> String {
+        let mut out = String::new();
+        let mut plain_start = 0;
+        let mut i = 0;
+
+        while i < text.len() {
+            let rest = &text[i..];
+            let rendered: Option<(String, usize)> = if let Some(escaped) = rest.strip_prefix('\\') {
+                escaped
+                    .chars()
+                    .next()
+                    .filter(|c| c.is_ascii_punctuation())
+                    .map(|c| (escape(&c.to_string()), 1 + c.len_utf8()))
+            } else if let Some(code) = rest.strip_prefix('`') {
+                code.find('`')
+                    .map(|end| (self.tag(Inline::Code, &escape(&code[..end])), end + 2))
+            } else if rest.starts_with("[[") {
+                links::parse_wiki_links(rest)
+                    .into_iter()
+                    .next()
+                    .filter(|link| link.start == 0)
+                    .map(|link| {
+                        let label = escape(link.display_text());
+                        let html = match (self.resolve)(&link.target) {
+                            Some(target) => self.link(&target.to_uri(), &label),
+                            None => self.broken_link(&label),
+                        };
+                        (html, link.end)
+                    })
+            } else if rest.starts_with("![") {
+                parse_link_syntax(&rest[1..]).map(|(alt, src, len)| (self.image(src, alt), len + 1))
+            } else if rest.starts_with('[') {
+                parse_link_syntax(rest)
+                    .map(|(label, url, len)| (self.link(url, &self.inline(label)), len))
+            } else if rest.starts_with("**") || rest.starts_with("__") {
+                self.delimited(
+                    rest,
+                    &rest[..2],
+                    Inline::Strong,
+                    i == 0 || !is_word_char(text, i),
+                )
+            } else if rest.starts_with('*') || rest.starts_with('_') {
+                self.delimited(
+                    rest,
+                    &rest[..1],
+                    Inline::Emphasis,
+                    i == 0 || !is_word_char(text, i),
+                )
+            } else {
+                None
+            };
+
+            match rendered {
+                Some((html, len)) => {
+                    out.push_str(&escape(&text[plain_start..i]));
+                    out.push_str(&html);
+                    i += len;
+                    plain_start = i;
+                }
+                None => i += rest.chars().next().map_or(1, char::len_utf8),
+            }
+        }
+        out.push_str(&escape(&text[plain_start..]));
+        out
+    }
+
+    /// Renders `**strong**` / `*emphasis*` style spans starting at the front of `rest`.
+    /// Underscores only count at word boundaries, so `olive_oil` stays as it is.
+    fn delimited(
+        &self,
+        rest: &str,
+        delim: &str,
+        kind: Inline,
+        at_boundary: bool,
+    ) -> Option<(String, usize)> {
+        if delim.starts_with('_') && !at_boundary {
+            return None;
+        }
+        let inner_start = delim.len();
+        let body = &rest[inner_start..];
+        if body.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let close = body.find(delim)?;
+        if close == 0 || body[..close].ends_with(char::is_whitespace) {
+            return None;
+        }
+        let end = inner_start + close + delim.len();
+        if delim.starts_with('_') && rest[end..].starts_with(|c: char| c.is_alphanumeric()) {
+            return None;
+        }
+        Some((self.tag(kind, &self.inline(&body[..close])), end))
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Inline {
+    Strong,
+    Emphasis,
+    Code,
+}
+
+fn close_list(ordered: bool) -> &'static str {
+    if ordered {
+        "</li>\n</ol>\n"
+    } else {
+        "</li>\n</ul>\n"
+    }
+}
+
+fn open_list(ordered: bool, number: Option<u32>) -> String {
+    match (ordered, number) {
+        (true, Some(n)) if n != 1 => format!("<ol start=\"{}\">\n", n),
+        (true, _) => "<ol>\n".to_string(),
+        (false, _) => "<ul>\n".to_string(),
+    }
+}
+
+/// Approximate display width of a cell once its inline Markdown is rendered
+fn visible_width(cell: &str) -> usize {
+    cell.chars().filter(|&c| c != '*' && c != '`').count()
+}
+
+/// Returns true if the character before byte `i` is part of a word
+fn is_word_char(text: &str, i: usize) -> bool {
+    text[..i]
+        .chars()
+        .next_back()
+        .is_some_and(|c| c.is_alphanumeric())
+}
+
+/// Escapes text for Pango markup and HTML (the same five entities work for both)
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
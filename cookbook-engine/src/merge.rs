@@ -0,0 +1,192 @@
+//! Three-way merging of `pantry.yaml` for data directories edited on several devices.
+//!
+//! The desktop app and Pantryman both write `pantry.yaml`, and the sync layer in between
+//! (SAF mirror, pCloud, Syncthing, ...) only ever sees whole files. This module merges two
+//! diverged copies item by item against their common ancestor, using `last_updated` to
+//! break ties. Changes that cannot be reconciled are reported as conflicts instead of
+//! being dropped.
+use crate::types::*;
+use chrono::NaiveDate;
+use log::{debug, info};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How the two sides of a pantry conflict disagree
+#[derive(Debug, Clone, PartialEq)]
+pub enum PantryConflictKind {
+    /// Both sides changed the item differently on the same day
+    BothModified,
+    /// The item was removed locally but changed remotely
+    DeletedLocally,
+    /// The item was changed locally but removed remotely
+    DeletedRemotely,
+}
+
+/// A pantry item that could not be merged automatically.
+///
+/// The merged pantry keeps whichever version still holds data (the local one when both do),
+/// so resolving a conflict never requires digging the other version out of a backup.
+#[derive(Debug, Clone)]
+pub struct PantryConflict {
+    pub ingredient: String,
+    pub kind: PantryConflictKind,
+    pub base: Option<PantryItem>,
+    pub local: Option<PantryItem>,
+    pub remote: Option<PantryItem>,
+}
+
+/// Result of merging two pantries
+#[derive(Debug, Clone)]
+pub struct PantryMergeResult {
+    pub merged: Pantry,
+    pub conflicts: Vec<PantryConflict>,
+}
+
+impl PantryMergeResult {
+    /// Returns true if every item could be merged without user input
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Merges a local and a remote pantry against their common ancestor.
+///
+/// Items are matched by ingredient name. For each item:
+/// - if only one side changed it (or both made the same change), that change wins
+/// - if both sides changed it, the one with the later `last_updated` wins
+/// - if both changed it on the same day, or one side deleted what the other edited,
+///   the item is reported as a conflict
+///
+/// Pass an empty pantry as `base` when no common ancestor is known; every difference is
+/// then treated as a concurrent edit and nothing is considered deleted.
+pub fn merge_pantries(base: &Pantry, local: &Pantry, remote: &Pantry) -> PantryMergeResult {
+    let mut items = Vec::new();
+    let mut conflicts = Vec::new();
+
+    // Local order first, then anything that only exists remotely, then base-only items
+    // (which merge to a deletion). This keeps the file diff small on the local device.
+    let mut names: Vec<&str> = Vec::new();
+    let mut seen = HashSet::new();
+    for item in local.items.iter().chain(&remote.items).chain(&base.items) {
+        if seen.insert(item.ingredient.as_str()) {
+            names.push(item.ingredient.as_str());
+        }
+    }
+
+    for name in names {
+        let b = find_item(base, name);
+        let l = find_item(local, name);
+        let r = find_item(remote, name);
+
+        let (merged, kind) = merge_item(b, l, r);
+        if let Some(kind) = kind {
+            debug!("Pantry merge conflict on '{}': {:?}", name, kind);
+            conflicts.push(PantryConflict {
+                ingredient: name.to_string(),
+                kind,
+                base: b.cloned(),
+                local: l.cloned(),
+                remote: r.cloned(),
+            });
+        }
+        if let Some(item) = merged {
+            items.push(item.clone());
+        }
+    }
+
+    info!(
+        "Merged pantries: {} items, {} conflicts",
+        items.len(),
+        conflicts.len()
+    );
+
+    PantryMergeResult {
+        merged: Pantry {
+            version: local.version.max(remote.version),
+            items,
+        },
+        conflicts,
+    }
+}
+
+fn find_item<'a>(pantry: &'a Pantry, name: &str) -> Option<&'a PantryItem> {
+    pantry.items.iter().find(|item| item.ingredient == name)
+}
+
+/// Merges a single item. Returns the item to keep (if any) and the conflict kind (if any).
+fn merge_item<'a>(
+    base: Option<&'a PantryItem>,
+    local: Option<&'a PantryItem>,
+    remote: Option<&'a PantryItem>,
+) -> (Option<&'a PantryItem>, Option<PantryConflictKind>) {
+    // Same on both sides, or only one side moved away from the base
+    if local == remote || local == base {
+        return (remote, None);
+    }
+    if remote == base {
+        return (local, None);
+    }
+
+    match (local, remote) {
+        (Some(l), Some(r)) => match compare_dates(&l.last_updated, &r.last_updated) {
+            Ordering::Greater => (Some(l), None),
+            Ordering::Less => (Some(r), None),
+            Ordering::Equal => (Some(l), Some(PantryConflictKind::BothModified)),
+        },
+        (None, Some(r)) => (Some(r), Some(PantryConflictKind::DeletedLocally)),
+        (Some(l), None) => (Some(l), Some(PantryConflictKind::DeletedRemotely)),
+        // Both deleted is covered by `local == remote` above
+        (None, None) => (None, None),
+    }
+}
+
+/// Compares two `last_updated` values, falling back to string order for non-ISO dates
+fn compare_dates(a: &str, b: &str) -> Ordering {
+    match (
+        NaiveDate::parse_from_str(a, "%Y-%m-%d"),
+        NaiveDate::parse_from_str(b, "%Y-%m-%d"),
+    ) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        _ => a.cmp(b),
+    }
+}
+
+/// Returns true if `file_name` looks like a cloud-provider conflict copy of `pantry.yaml`.
+///
+/// Recognises the common naming schemes, e.g.:
+/// - `pantry (Laptop's conflicted copy 2026-01-02).yaml` (Dropbox)
+/// - `pantry (conflicted copy 2026-01-02 101500).yaml` (Nextcloud, ownCloud)
+/// - `pantry [conflicted].yaml` (pCloud)
+/// - `pantry.sync-conflict-20260102-101500-ABCDEFG.yaml` (Syncthing)
+pub fn is_pantry_conflict_copy(file_name: &str) -> bool {
+    let lower = file_name.to_lowercase();
+    lower != "pantry.yaml"
+        && lower.starts_with("pantry")
+        && lower.ends_with(".yaml")
+        && lower.contains("conflict")
+}
+
+/// Lists conflict copies of `pantry.yaml` in the top level of the data directory, sorted by name
+pub fn find_pantry_conflict_copies<P: AsRef<Path>>(
+    data_dir: P,
+) -> Result<Vec<PathBuf>, CookbookError> {
+    let entries =
+        fs::read_dir(data_dir.as_ref()).map_err(|e| CookbookError::ListDirError(e.to_string()))?;
+
+    let mut copies = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| CookbookError::ListDirError(e.to_string()))?;
+        let path = entry.path();
+        let is_copy = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(is_pantry_conflict_copy);
+        if path.is_file() && is_copy {
+            copies.push(path);
+        }
+    }
+    copies.sort();
+    Ok(copies)
+}
@@ -0,0 +1,323 @@
+//! Schema versioning and migrations for the data files.
+//!
+//! Every data file carries a `version` key (ingredient YAML, recipe and KB front matter,
+//! `pantry.yaml`, `mealplan.yaml`, `pantry_journal.yaml`). Files without one predate
+//! versioning and are treated as version 0. When a file is read, its YAML is upgraded in
+//! memory one migration at a time before it is deserialized, so older files keep loading
+//! after a schema change. `migrate_data_dir` rewrites the files on disk, keeping their
+//! comments and key order, and supports a dry run that only reports what would change.
+//!
+//! Migrations operate on the raw YAML mapping rather than the Rust structs, so keys the
+//! engine does not know about survive an upgrade.
+use crate::types::CookbookError;
+use crate::yaml_merge;
+use log::{info, warn};
+use serde_yaml::{Mapping, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The kinds of versioned data file in a data directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFileKind {
+    Ingredient,
+    Recipe,
+    Pantry,
+    KnowledgeBase,
+    MealPlan,
+    PantryJournal,
+}
+
+impl DataFileKind {
+    /// The schema version this build of the engine reads and writes
+    pub fn current_version(self) -> u32 {
+        migrations(self).last().map_or(0, |m| m.from + 1)
+    }
+}
+
+/// A single upgrade step from `from` to `from + 1`
+pub struct Migration {
+    pub from: u32,
+    pub description: &'static str,
+    apply: fn(&mut Mapping),
+}
+
+// Version 1 introduces the version key itself. Later schema changes append a migration
+// here; `current_version` follows automatically.
+const INGREDIENT_MIGRATIONS: &[Migration] = &[Migration {
+    from: 0,
+    description: "add schema version",
+    apply: no_op,
+}];
+
+const RECIPE_MIGRATIONS: &[Migration] = &[Migration {
+    from: 0,
+    description: "add schema version",
+    apply: no_op,
+}];
+
+// pantry.yaml has always carried `version: 1`; a pantry without it is treated as version 0
+const PANTRY_MIGRATIONS: &[Migration] = &[Migration {
+    from: 0,
+    description: "add schema version",
+    apply: no_op,
+}];
+
+const KB_MIGRATIONS: &[Migration] = &[Migration {
+    from: 0,
+    description: "add schema version",
+    apply: no_op,
+}];
+
+// mealplan.yaml was introduced with version 1
+const MEAL_PLAN_MIGRATIONS: &[Migration] = &[Migration {
+    from: 0,
+    description: "add schema version",
+    apply: no_op,
+}];
+
+// pantry_journal.yaml was introduced with version 1
+const PANTRY_JOURNAL_MIGRATIONS: &[Migration] = &[Migration {
+    from: 0,
+    description: "add schema version",
+    apply: no_op,
+}];
+
+fn no_op(_mapping: &mut Mapping) {}
+
+/// Returns the ordered list of migrations for a kind of file
+pub fn migrations(kind: DataFileKind) -> &'static [Migration] {
+    match kind {
+        DataFileKind::Ingredient => INGREDIENT_MIGRATIONS,
+        DataFileKind::Recipe => RECIPE_MIGRATIONS,
+        DataFileKind::Pantry => PANTRY_MIGRATIONS,
+        DataFileKind::KnowledgeBase => KB_MIGRATIONS,
+        DataFileKind::MealPlan => MEAL_PLAN_MIGRATIONS,
+        DataFileKind::PantryJournal => PANTRY_JOURNAL_MIGRATIONS,
+    }
+}
+
+/// Returns the schema version recorded in a mapping, or 0 if there is none
+pub fn schema_version(mapping: &Mapping) -> u32 {
+    mapping
+        .get("version")
+        .and_then(Value::as_u64)
+        .map_or(0, |v| v as u32)
+}
+
+/// Parses YAML text into a mapping, ready to be upgraded
+pub fn parse_mapping(yaml: &str) -> Result<Mapping, CookbookError> {
+    serde_yaml::from_str(yaml).map_err(|e| CookbookError::ParseError(e.to_string()))
+}
+
+/// Upgrades a mapping in place to the current schema version.
+///
+/// Returns the descriptions of the migrations that were applied (empty if the mapping
+/// was already current). Fails if the file was written by a newer version of the engine.
+pub fn upgrade(
+    kind: DataFileKind,
+    mapping: &mut Mapping,
+) -> Result<Vec<&'static str>, CookbookError> {
+    let from = schema_version(mapping);
+    let current = kind.current_version();
+    if from > current {
+        return Err(CookbookError::SchemaVersionError(format!(
+            "{:?} schema version {} is newer than the supported version {}",
+            kind, from, current
+        )));
+    }
+
+    let mut applied = Vec::new();
+    for migration in migrations(kind).iter().filter(|m| m.from >= from) {
+        (migration.apply)(mapping);
+        applied.push(migration.description);
+    }
+    stamp_version(kind, mapping);
+    Ok(applied)
+}
+
+/// Sets the `version` key to the current schema version, as the first key in the mapping
+pub fn stamp_version(kind: DataFileKind, mapping: &mut Mapping) {
+    let mut stamped = Mapping::new();
+    stamped.insert(
+        Value::from("version"),
+        Value::from(kind.current_version() as u64),
+    );
+    for (key, value) in std::mem::take(mapping) {
+        if key.as_str() != Some("version") {
+            stamped.insert(key, value);
+        }
+    }
+    *mapping = stamped;
+}
+
+/// Splits a Markdown file into its YAML front matter and body
+pub(crate) fn split_front_matter(content: &str) -> Result<(&str, &str), CookbookError> {
+    // Split frontmatter from content by finding the two "---" delimiters
+    let parts: Vec<&str> = content.splitn(3, "---").collect();
+    if parts.len() < 3 {
+        return Err(CookbookError::MarkdownError(
+            "Invalid markdown format: missing frontmatter delimiters".to_string(),
+        ));
+    }
+    Ok((parts[1], parts[2]))
+}
+
+/// A file that needs (or received) a schema upgrade
+#[derive(Debug, Clone)]
+pub struct FileMigration {
+    pub path: PathBuf,
+    pub kind: DataFileKind,
+    pub from_version: u32,
+    pub to_version: u32,
+    pub steps: Vec<&'static str>,
+}
+
+/// Outcome of migrating a data directory
+#[derive(Debug, Default)]
+pub struct MigrationReport {
+    pub dry_run: bool,
+    pub migrated: Vec<FileMigration>,
+    pub up_to_date: usize,
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+/// Upgrades every data file in `data_dir` to the current schema version.
+///
+/// With `dry_run` set nothing is written; the report lists what would change. Files that
+/// fail to parse are listed in `failed` and left untouched. This works on the raw files and
+/// does not need a `DataManager`, so it can repair a directory that no longer loads.
+pub fn migrate_data_dir<P: AsRef<Path>>(
+    data_dir: P,
+    dry_run: bool,
+) -> Result<MigrationReport, CookbookError> {
+    let data_dir = data_dir.as_ref();
+    if !data_dir.exists() {
+        return Err(CookbookError::DataDirError(format!(
+            "Directory not found: {:?}",
+            data_dir
+        )));
+    }
+
+    let mut report = MigrationReport {
+        dry_run,
+        ..Default::default()
+    };
+
+    let mut files = Vec::new();
+    let pantry_path = data_dir.join("pantry.yaml");
+    if pantry_path.exists() {
+        files.push((pantry_path, DataFileKind::Pantry));
+    }
+    let meal_plan_path = data_dir.join("mealplan.yaml");
+    if meal_plan_path.exists() {
+        files.push((meal_plan_path, DataFileKind::MealPlan));
+    }
+    let journal_path = data_dir.join("pantry_journal.yaml");
+    if journal_path.exists() {
+        files.push((journal_path, DataFileKind::PantryJournal));
+    }
+    for (subdir, ext, kind) in [
+        ("ingredients", "yaml", DataFileKind::Ingredient),
+        ("recipes", "md", DataFileKind::Recipe),
+        ("kb", "md", DataFileKind::KnowledgeBase),
+    ] {
+        for path in list_files(&data_dir.join(subdir), ext)? {
+            files.push((path, kind));
+        }
+    }
+
+    for (path, kind) in files {
+        match migrate_file(&path, kind, dry_run) {
+            Ok(Some(migration)) => report.migrated.push(migration),
+            Ok(None) => report.up_to_date += 1,
+            Err(e) => {
+                warn!("Failed to migrate {:?}: {}", path, e);
+                report.failed.push((path, e.to_string()));
+            }
+        }
+    }
+
+    info!(
+        "Migration {}: {} files upgraded, {} up to date, {} failed",
+        if dry_run { "dry run" } else { "complete" },
+        report.migrated.len(),
+        report.up_to_date,
+        report.failed.len()
+    );
+    Ok(report)
+}
+
+fn list_files(dir: &Path, ext: &str) -> Result<Vec<PathBuf>, CookbookError> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).map_err(|e| CookbookError::ListDirError(e.to_string()))? {
+        let path = entry
+            .map_err(|e| CookbookError::ListDirError(e.to_string()))?
+            .path();
+        if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some(ext) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Upgrades a single file, returning None if it was already current
+fn migrate_file(
+    path: &Path,
+    kind: DataFileKind,
+    dry_run: bool,
+) -> Result<Option<FileMigration>, CookbookError> {
+    let content = fs::read_to_string(path).map_err(|e| CookbookError::ReadError(e.to_string()))?;
+
+    let is_markdown = matches!(kind, DataFileKind::Recipe | DataFileKind::KnowledgeBase);
+    let (yaml, body) = if is_markdown {
+        let (front_matter, body) = split_front_matter(&content)?;
+        (front_matter, Some(body))
+    } else {
+        (content.as_str(), None)
+    };
+
+    let original = parse_mapping(yaml)?;
+    let from_version = schema_version(&original);
+    if from_version == kind.current_version() {
+        return Ok(None);
+    }
+    let mut mapping = original.clone();
+    let steps = upgrade(kind, &mut mapping)?;
+
+    if !dry_run {
+        let upgraded = serde_yaml::to_string(&mapping)
+            .map_err(|e| CookbookError::ParseError(format!("Failed to serialize: {}", e)))?;
+        // Lay the upgrade over the original text, as a normal save does, so comments and key
+        // order survive; keys a migration removed are dropped rather than kept as unknown
+        let removed: Vec<&str> = original
+            .keys()
+            .filter_map(Value::as_str)
+            .filter(|key| !mapping.contains_key(*key))
+            .collect();
+        let yaml = yaml_merge::merge_yaml_clearing(
+            yaml.trim_start_matches(['\r', '\n']),
+            &upgraded,
+            &removed,
+        );
+        let new_content = match body {
+            Some(body) => format!("---\n{}---{}", yaml, body),
+            None => yaml,
+        };
+        fs::write(path, new_content).map_err(|e| {
+            CookbookError::WriteError(format!("Failed to write migrated file: {}", e))
+        })?;
+        info!("Migrated {:?} from version {}", path, from_version);
+    }
+
+    Ok(Some(FileMigration {
+        path: path.to_path_buf(),
+        kind,
+        from_version,
+        to_version: kind.current_version(),
+        steps,
+    }))
+}
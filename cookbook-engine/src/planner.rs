@@ -0,0 +1,398 @@
+//! Meal plan suggestions: fills the open meals of a date range with recipes.
+//!
+//! Each meal is filled in turn, from the first day on, with the best scoring recipe that
+//! suits the dietary profile and fits the day's time budget. A recipe scores higher the more
+//! of its ingredients are in stock, and higher still for each ingredient that expires soon
+//! and no earlier meal has used up yet. It scores lower when it was planned within the
+//! repeat window, and when its tags are already common in the plan, so the week stays
+//! varied. A small seeded jitter decides between recipes that score about the same, so the
+//! same seed always gives the same plan and another seed can give a different one.
+//!
+//! Every suggested meal comes with the reasons it was picked; meals nothing could fill are
+//! listed with the reason instead.
+use crate::types::{Meal, MealPlanEntry, PantryItem, Recipe};
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Days before its use-by date that an ingredient counts as expiring
+pub const EXPIRY_HORIZON_DAYS: i64 = 3;
+
+/// Weight of the share of a recipe's ingredients that are in stock
+const IN_STOCK_WEIGHT: f64 = 2.0;
+/// Bonus per expiring ingredient a recipe uses up
+const EXPIRING_WEIGHT: f64 = 1.5;
+/// Penalty for a recipe planned the day before or after; it shrinks to 0 at the end of the window
+const REPEAT_WEIGHT: f64 = 3.0;
+/// Penalty per earlier meal in the plan sharing a tag, averaged over the recipe's tags
+const TAG_WEIGHT: f64 = 0.5;
+/// Largest random nudge, small enough to only decide between near-equal recipes
+const JITTER: f64 = 0.1;
+
+/// What the household eats
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DietaryProfile {
+    /// Tags every recipe must have, e.g. "vegetarian"
+    pub require_tags: Vec<String>,
+    /// Tags no recipe may have, e.g. "pork"
+    pub exclude_tags: Vec<String>,
+    /// Ingredients no recipe may need, e.g. for allergies; recipes where they are optional
+    /// are still suggested, to be made without them
+    pub exclude_ingredients: Vec<String>,
+}
+
+/// What to plan
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanRequest {
+    pub start: NaiveDate,
+    pub days: u32,
+    /// The meals to fill each day
+    pub meals: Vec<Meal>,
+    /// Minutes available for cooking on each weekday, Monday first; None for no limit.
+    /// A recipe's time is its prep time plus downtime
+    pub time_budget: [Option<u32>; 7],
+    pub profile: DietaryProfile,
+    /// Recipes planned within this many days before or after a meal are avoided
+    pub repeat_window_days: u32,
+    pub seed: u64,
+}
+
+impl PlanRequest {
+    /// Dinners for the week from `start`, with no limits and a 14-day repeat window
+    pub fn week(start: NaiveDate) -> Self {
+        PlanRequest {
+            start,
+            days: 7,
+            meals: vec![Meal::Dinner],
+            time_budget: [None; 7],
+            profile: DietaryProfile::default(),
+            repeat_window_days: 14,
+            seed: 0,
+        }
+    }
+}
+
+/// A recipe suggested for a meal, and why
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuggestedMeal {
+    pub entry: MealPlanEntry,
+    pub reasons: Vec<String>,
+}
+
+/// A meal that could not be filled
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnfilledMeal {
+    pub date: NaiveDate,
+    pub meal: Meal,
+    pub reason: String,
+}
+
+/// The outcome of `suggest_meal_plan`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MealPlanSuggestion {
+    /// Suggested meals by date and meal; meals already planned are left out
+    pub meals: Vec<SuggestedMeal>,
+    pub unfilled: Vec<UnfilledMeal>,
+}
+
+impl MealPlanSuggestion {
+    /// The suggested entries, ready for `DataManager::add_meal_plan_entry`
+    pub fn entries(&self) -> Vec<MealPlanEntry> {
+        self.meals.iter().map(|m| m.entry.clone()).collect()
+    }
+}
+
+/// Suggests recipes for the open meals of `request`.
+///
+/// `history` is the meal plan so far: it decides which meals are already taken, and which
+/// recipes were planned recently. `pantry` gives what is in stock and when it expires.
+pub fn suggest_meal_plan(
+    recipes: &[&Recipe],
+    pantry: &[PantryItem],
+    history: &[MealPlanEntry],
+    request: &PlanRequest,
+) -> MealPlanSuggestion {
+    let mut suggestion = MealPlanSuggestion::default();
+    let mut rng = SplitMix64(request.seed);
+    let stock: HashMap<&str, &PantryItem> =
+        pantry.iter().map(|i| (i.ingredient.as_str(), i)).collect();
+
+    // Sorted, so the seed alone decides the plan whatever order the recipes come in
+    let mut suitable: Vec<&Recipe> = recipes
+        .iter()
+        .copied()
+        .filter(|r| suits_profile(r, &request.profile))
+        .collect();
+    suitable.sort_by(|a, b| a.title.cmp(&b.title));
+
+    // Everything planned so far, with the meals suggested here added as they are picked
+    let mut planned: Vec<MealPlanEntry> = history.to_vec();
+    let end = request.start + Duration::days(request.days as i64);
+    let mut meals = request.meals.clone();
+    meals.sort();
+    meals.dedup();
+    // Expiring ingredients an earlier suggestion already uses up
+    let mut used_up: HashSet<&str> = HashSet::new();
+
+    for offset in 0..request.days as i64 {
+        let date = request.start + Duration::days(offset);
+        let budget = request.time_budget[date.weekday().num_days_from_monday() as usize];
+
+        for &meal in &meals {
+            if planned.iter().any(|e| e.date == date && e.meal == meal) {
+                continue;
+            }
+            let fitting: Vec<&Recipe> = suitable
+                .iter()
+                .copied()
+                .filter(|r| budget.is_none_or(|b| r.total_time() <= b))
+                .filter(|r| {
+                    !planned
+                        .iter()
+                        .any(|e| e.date == date && e.recipe == r.title)
+                })
+                .collect();
+            if fitting.is_empty() {
+                let reason = if suitable.is_empty() {
+                    "No recipe suits the dietary profile".to_string()
+                } else if let Some(budget) = budget {
+                    format!("No recipe fits the {} min budget", budget)
+                } else {
+                    "Every suitable recipe is already planned that day".to_string()
+                };
+                suggestion
+                    .unfilled
+                    .push(UnfilledMeal { date, meal, reason });
+                continue;
+            }
+
+            // Tag counts over the meals planned in the requested range
+            let mut tag_counts: HashMap<String, usize> = HashMap::new();
+            for entry in planned
+                .iter()
+                .filter(|e| e.date >= request.start && e.date < end)
+            {
+                if let Some(recipe) = recipes.iter().find(|r| r.title == entry.recipe) {
+                    for tag in recipe_tags(recipe) {
+                        *tag_counts.entry(tag).or_default() += 1;
+                    }
+                }
+            }
+
+            let context = Context {
+                date,
+                budget,
+                stock: &stock,
+                used_up: &used_up,
+                planned: &planned,
+                tag_counts: &tag_counts,
+                request,
+            };
+            let mut best: Option<(f64, &Recipe)> = None;
+            for recipe in fitting {
+                let score = context.score(recipe) + rng.next_f64() * JITTER;
+                if best.is_none_or(|(s, _)| score > s) {
+                    best = Some((score, recipe));
+                }
+            }
+            let (_, recipe) = best.expect("at least one recipe fits");
+            let reasons = context.reasons(recipe);
+            used_up.extend(context.expiring(recipe));
+            let entry = MealPlanEntry {
+                date,
+                meal,
+                recipe: recipe.title.clone(),
+                servings: None,
+            };
+            planned.push(entry.clone());
+            suggestion.meals.push(SuggestedMeal { entry, reasons });
+        }
+    }
+    suggestion
+}
+
+/// What a recipe is scored against for one meal; `'p` is the lifetime of the pantry
+struct Context<'c, 'p> {
+    date: NaiveDate,
+    budget: Option<u32>,
+    stock: &'c HashMap<&'p str, &'p PantryItem>,
+    used_up: &'c HashSet<&'p str>,
+    planned: &'c [MealPlanEntry],
+    tag_counts: &'c HashMap<String, usize>,
+    request: &'c PlanRequest,
+}
+
+impl<'c, 'p> Context<'c, 'p> {
+    fn score(&self, recipe: &Recipe) -> f64 {
+        let (in_stock, required) = self.stocked(recipe);
+        let mut score = 0.0;
+        if required > 0 {
+            score += IN_STOCK_WEIGHT * in_stock as f64 / required as f64;
+        }
+        score += EXPIRING_WEIGHT * self.expiring(recipe).len() as f64;
+        if let Some(days) = self.days_from_planned(recipe).map(i64::abs) {
+            let window = self.request.repeat_window_days as f64;
+            if (days as f64) < window {
+                score -= REPEAT_WEIGHT * (1.0 - (days - 1).max(0) as f64 / window);
+            }
+        }
+        let tags = recipe_tags(recipe);
+        if !tags.is_empty() {
+            let shared: usize = tags
+                .iter()
+                .map(|t| self.tag_counts.get(t).unwrap_or(&0))
+                .sum();
+            score -= TAG_WEIGHT * shared as f64 / tags.len() as f64;
+        }
+        score
+    }
+
+    fn reasons(&self, recipe: &Recipe) -> Vec<String> {
+        let mut reasons = Vec::new();
+        for name in self.expiring(recipe) {
+            let expires = self.stock[name]
+                .expires
+                .expect("expiring items have a date");
+            reasons.push(format!("Uses {} before it expires on {}", name, expires));
+        }
+
+        let (in_stock, required) = self.stocked(recipe);
+        if required > 0 && in_stock == required {
+            reasons.push("All ingredients in stock".to_string());
+        } else if required > 0 {
+            reasons.push(format!("{} of {} ingredients in stock", in_stock, required));
+        }
+
+        let time = recipe.total_time();
+        match self.budget {
+            _ if time == 0 => reasons.push("No cooking time given".to_string()),
+            Some(budget) => reasons.push(format!(
+                "Ready in {} min, within the {} min budget",
+                time, budget
+            )),
+            None => reasons.push(format!("Ready in {} min", time)),
+        }
+
+        let window = self.request.repeat_window_days;
+        match self.days_from_planned(recipe) {
+            Some(days) if days.abs() < window as i64 => reasons.push(if days > 0 {
+                format!("Last planned {} days ago", days)
+            } else {
+                format!("Planned again {} days later", -days)
+            }),
+            _ if window > 0 => reasons.push(format!("Not planned within {} days", window)),
+            _ => {}
+        }
+
+        let new_tags: Vec<String> = recipe_tags(recipe)
+            .into_iter()
+            .filter(|t| !self.tag_counts.contains_key(t))
+            .collect();
+        if !new_tags.is_empty() && !self.tag_counts.is_empty() {
+            reasons.push(format!("Adds variety: {}", new_tags.join(", ")));
+        }
+
+        let left_out: Vec<&str> = recipe
+            .ingredients
+            .iter()
+            .filter(|i| i.optional && excluded(&i.ingredient, &self.request.profile))
+            .map(|i| i.ingredient.as_str())
+            .collect();
+        if !left_out.is_empty() {
+            reasons.push(format!("Make it without {}", left_out.join(", ")));
+        }
+        reasons
+    }
+
+    /// (required ingredients in stock, required ingredients)
+    fn stocked(&self, recipe: &Recipe) -> (usize, usize) {
+        let required: Vec<_> = recipe.required_ingredients().collect();
+        let in_stock = required
+            .iter()
+            .filter(|i| self.stock.contains_key(i.ingredient.as_str()))
+            .count();
+        (in_stock, required.len())
+    }
+
+    /// The recipe's ingredients in stock that expire within the horizon of this meal and
+    /// that no earlier meal uses up
+    fn expiring(&self, recipe: &Recipe) -> Vec<&'p str> {
+        let mut names: Vec<&'p str> = recipe
+            .ingredients
+            .iter()
+            .filter_map(|i| self.stock.get_key_value(i.ingredient.as_str()))
+            .filter(|(name, item)| {
+                !self.used_up.contains(*name)
+                    && item.expires.is_some_and(|expires| {
+                        expires >= self.date
+                            && expires <= self.date + Duration::days(EXPIRY_HORIZON_DAYS)
+                    })
+            })
+            .map(|(name, _)| *name)
+            .collect();
+        // An ingredient may be listed in several groups of the recipe
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// Days from the nearest other date the recipe is planned to this meal: positive if it
+    /// was planned before, negative if it is planned again later in the plan
+    fn days_from_planned(&self, recipe: &Recipe) -> Option<i64> {
+        self.planned
+            .iter()
+            .filter(|e| e.recipe == recipe.title && e.date != self.date)
+            .map(|e| (self.date - e.date).num_days())
+            .min_by_key(|days| days.abs())
+    }
+}
+
+fn suits_profile(recipe: &Recipe, profile: &DietaryProfile) -> bool {
+    let tags = recipe_tags(recipe);
+    profile
+        .require_tags
+        .iter()
+        .all(|t| tags.contains(&t.trim().to_lowercase()))
+        && !profile
+            .exclude_tags
+            .iter()
+            .any(|t| tags.contains(&t.trim().to_lowercase()))
+        && !recipe
+            .required_ingredients()
+            .any(|i| excluded(&i.ingredient, profile))
+}
+
+fn excluded(ingredient: &str, profile: &DietaryProfile) -> bool {
+    profile
+        .exclude_ingredients
+        .iter()
+        .any(|e| e.trim().eq_ignore_ascii_case(ingredient))
+}
+
+fn recipe_tags(recipe: &Recipe) -> Vec<String> {
+    let mut tags: Vec<String> = recipe
+        .tags
+        .iter()
+        .flatten()
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+/// Small deterministic generator (SplitMix64), so plans repeat under the same seed
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_f64(&mut self) -> f64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
@@ -0,0 +1,363 @@
+//! Recipe quantities: plain numbers, exact fractions, ranges and "to taste".
+//!
+//! In YAML a plain amount stays a number (`quantity: 2.0`), so existing recipe files read and
+//! write unchanged. Everything else is a string: `"1/3"`, `"2 1/2"`, `"2-3"` or `"to taste"`.
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// Single-character fractions and their numerator and denominator, e.g. "½"
+const UNICODE_FRACTIONS: &[(char, u32, u32)] = &[
+    ('½', 1, 2),
+    ('⅓', 1, 3),
+    ('⅔', 2, 3),
+    ('¼', 1, 4),
+    ('¾', 3, 4),
+    ('⅕', 1, 5),
+    ('⅖', 2, 5),
+    ('⅗', 3, 5),
+    ('⅘', 4, 5),
+    ('⅙', 1, 6),
+    ('⅚', 5, 6),
+    ('⅐', 1, 7),
+    ('⅛', 1, 8),
+    ('⅜', 3, 8),
+    ('⅝', 5, 8),
+    ('⅞', 7, 8),
+    ('⅑', 1, 9),
+    ('⅒', 1, 10),
+];
+
+/// A single amount
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Amount {
+    /// A decimal number such as 2 or 0.75
+    Decimal(f64),
+    /// An exact fraction in lowest terms, possibly improper: 5/2 reads "2 1/2"
+    Fraction { numerator: u32, denominator: u32 },
+}
+
+impl Amount {
+    /// Builds a fraction in lowest terms; whole numbers become `Decimal`.
+    /// `denominator` must not be zero
+    pub fn fraction(numerator: u32, denominator: u32) -> Amount {
+        let divisor = gcd(numerator, denominator);
+        let (numerator, denominator) = (numerator / divisor, denominator / divisor);
+        if denominator == 1 {
+            Amount::Decimal(numerator as f64)
+        } else {
+            Amount::Fraction {
+                numerator,
+                denominator,
+            }
+        }
+    }
+
+    /// The amount as a number
+    pub fn value(&self) -> f64 {
+        match *self {
+            Amount::Decimal(value) => value,
+            Amount::Fraction {
+                numerator,
+                denominator,
+            } => numerator as f64 / denominator as f64,
+        }
+    }
+
+    /// Parses "2", "2.5", "2,5", "1/2", "2 1/2", "½" or "2½"
+    pub fn parse(text: &str) -> Option<Amount> {
+        let text = text.trim().replace('⁄', "/");
+        if let Some((whole, fraction)) = text.split_once(' ') {
+            let whole = parse_whole(whole.trim())?;
+            return match Amount::parse(fraction)? {
+                Amount::Fraction {
+                    numerator,
+                    denominator,
+                } if numerator < denominator => Some(Amount::fraction(
+                    whole.checked_mul(denominator)?.checked_add(numerator)?,
+                    denominator,
+                )),
+                _ => None,
+            };
+        }
+        if let Some(last) = text.chars().last() {
+            if let Some((_, numerator, denominator)) =
+                UNICODE_FRACTIONS.iter().find(|(c, _, _)| *c == last)
+            {
+                let whole = &text[..text.len() - last.len_utf8()];
+                let whole = if whole.is_empty() {
+                    0
+                } else {
+                    parse_whole(whole)?
+                };
+                return Some(Amount::fraction(
+                    whole.checked_mul(*denominator)?.checked_add(*numerator)?,
+                    *denominator,
+                ));
+            }
+        }
+        if let Some((numerator, denominator)) = text.split_once('/') {
+            let numerator = parse_whole(numerator.trim())?;
+            let denominator = parse_whole(denominator.trim()).filter(|d| *d > 0)?;
+            return Some(Amount::fraction(numerator, denominator));
+        }
+        let valid = text.chars().any(|c| c.is_ascii_digit())
+            && text
+                .chars()
+                .all(|c| c.is_ascii_digit() || c == '.' || c == ',');
+        if valid {
+            text.replace(',', ".").parse().ok().map(Amount::Decimal)
+        } else {
+            None
+        }
+    }
+
+    /// Multiplies the amount, keeping fractions exact when the factor is a whole number
+    pub fn scale(&self, factor: f64) -> Amount {
+        match *self {
+            Amount::Fraction {
+                numerator,
+                denominator,
+            } if factor >= 0.0 && factor.fract() == 0.0 && factor <= u32::MAX as f64 => {
+                match numerator.checked_mul(factor as u32) {
+                    Some(numerator) => Amount::fraction(numerator, denominator),
+                    None => Amount::Decimal(self.value() * factor),
+                }
+            }
+            _ => Amount::Decimal(self.value() * factor),
+        }
+    }
+}
+
+impl std::ops::Add for Amount {
+    type Output = Amount;
+
+    /// Adds exactly when both sides are fractions or whole numbers
+    fn add(self, other: Amount) -> Amount {
+        let exact = as_fraction(&self)
+            .zip(as_fraction(&other))
+            .and_then(|((a, b), (c, d))| {
+                let numerator = a.checked_mul(d)?.checked_add(c.checked_mul(b)?)?;
+                Some(Amount::fraction(numerator, b.checked_mul(d)?))
+            });
+        exact.unwrap_or_else(|| Amount::Decimal(self.value() + other.value()))
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Amount::Decimal(value) => write!(f, "{}", value),
+            Amount::Fraction {
+                numerator,
+                denominator,
+            } if numerator > denominator => write!(
+                f,
+                "{} {}/{}",
+                numerator / denominator,
+                numerator % denominator,
+                denominator
+            ),
+            Amount::Fraction {
+                numerator,
+                denominator,
+            } => write!(f, "{}/{}", numerator, denominator),
+        }
+    }
+}
+
+/// How much of an ingredient a recipe needs
+#[derive(Debug, Clone, PartialEq)]
+pub enum Quantity {
+    /// A single amount, e.g. "2" or "1/3"
+    Amount(Amount),
+    /// Anything from `min` to `max`, e.g. "2-3"
+    Range { min: Amount, max: Amount },
+    /// No amount, just a description such as "to taste"
+    Qualitative(String),
+}
+
+impl Quantity {
+    /// The amount to plan with: the value, or the upper end of a range.
+    /// None for qualitative quantities
+    pub fn value(&self) -> Option<f64> {
+        match self {
+            Quantity::Amount(amount) => Some(amount.value()),
+            Quantity::Range { max, .. } => Some(max.value()),
+            Quantity::Qualitative(_) => None,
+        }
+    }
+
+    /// True if a countable ingredient takes its plural form with this quantity:
+    /// "2 tomatoes", "1–2 tomatoes", "1.5 tomatoes", but "1 tomato", "1/2 tomato" and
+    /// "0.5 tomato". Amounts up to one are singular however they are written
+    pub fn is_plural(&self) -> bool {
+        match self {
+            Quantity::Amount(amount) => {
+                let value = amount.value();
+                value <= 0.0 || value > 1.0 + f64::EPSILON
+            }
+            Quantity::Range { .. } | Quantity::Qualitative(_) => true,
+        }
+    }
+
+    /// Adds two quantities of the same ingredient. Qualitative quantities only add to an
+    /// identical one ("to taste" + "to taste"); anything else can't be added
+    pub fn checked_add(&self, other: &Quantity) -> Option<Quantity> {
+        match (self, other) {
+            (Quantity::Amount(a), Quantity::Amount(b)) => Some(Quantity::Amount(*a + *b)),
+            (Quantity::Qualitative(a), Quantity::Qualitative(b)) => {
+                a.eq_ignore_ascii_case(b).then(|| self.clone())
+            }
+            (Quantity::Qualitative(_), _) | (_, Quantity::Qualitative(_)) => None,
+            _ => {
+                let (a_min, a_max) = self.bounds()?;
+                let (b_min, b_max) = other.bounds()?;
+                Some(Quantity::Range {
+                    min: a_min + b_min,
+                    max: a_max + b_max,
+                })
+            }
+        }
+    }
+
+    /// Multiplies the quantity, e.g. to scale a recipe to more servings.
+    /// Qualitative quantities don't change
+    pub fn scale(&self, factor: f64) -> Quantity {
+        match self {
+            Quantity::Amount(amount) => Quantity::Amount(amount.scale(factor)),
+            Quantity::Range { min, max } => Quantity::Range {
+                min: min.scale(factor),
+                max: max.scale(factor),
+            },
+            Quantity::Qualitative(_) => self.clone(),
+        }
+    }
+
+    fn bounds(&self) -> Option<(Amount, Amount)> {
+        match self {
+            Quantity::Amount(amount) => Some((*amount, *amount)),
+            Quantity::Range { min, max } => Some((*min, *max)),
+            Quantity::Qualitative(_) => None,
+        }
+    }
+}
+
+impl From<f64> for Quantity {
+    fn from(value: f64) -> Self {
+        Quantity::Amount(Amount::Decimal(value))
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Quantity::Amount(amount) => write!(f, "{}", amount),
+            Quantity::Range { min, max } => write!(f, "{}-{}", min, max),
+            Quantity::Qualitative(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+/// Why a quantity could not be read
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseQuantityError(pub String);
+
+impl fmt::Display for ParseQuantityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}' is not a quantity", self.0)
+    }
+}
+
+impl std::error::Error for ParseQuantityError {}
+
+impl FromStr for Quantity {
+    type Err = ParseQuantityError;
+
+    /// Parses "2", "1/3", "2 1/2", "½", "2-3", "2–3", "2 to 3" or a description without
+    /// digits such as "to taste". Text that has digits but isn't a quantity is an error
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        let error = || ParseQuantityError(text.to_string());
+        if text.is_empty() {
+            return Err(error());
+        }
+        if let Some(amount) = Amount::parse(text) {
+            return Ok(Quantity::Amount(amount));
+        }
+        if !text
+            .chars()
+            .any(|c| c.is_ascii_digit() || is_unicode_fraction(c))
+        {
+            return Ok(Quantity::Qualitative(text.to_string()));
+        }
+        let (min, max) = text
+            .split_once(['-', '–', '—'])
+            .or_else(|| text.split_once(" to "))
+            .ok_or_else(error)?;
+        match (Amount::parse(min), Amount::parse(max)) {
+            (Some(min), Some(max)) => Ok(Quantity::Range { min, max }),
+            _ => Err(error()),
+        }
+    }
+}
+
+impl Serialize for Quantity {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Quantity::Amount(Amount::Decimal(value)) => serializer.serialize_f64(*value),
+            _ => serializer.serialize_str(&self.to_string()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Quantity {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Number(f64),
+            Text(String),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Number(value) => Ok(Quantity::from(value)),
+            Raw::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+/// True for single-character fractions such as "½"
+pub fn is_unicode_fraction(c: char) -> bool {
+    UNICODE_FRACTIONS.iter().any(|(f, _, _)| *f == c)
+}
+
+fn parse_whole(text: &str) -> Option<u32> {
+    if text.is_empty() || !text.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+/// An amount as numerator and denominator, if it is a fraction or a whole number
+fn as_fraction(amount: &Amount) -> Option<(u32, u32)> {
+    match *amount {
+        Amount::Fraction {
+            numerator,
+            denominator,
+        } => Some((numerator, denominator)),
+        Amount::Decimal(value)
+            if value >= 0.0 && value.fract() == 0.0 && value <= u32::MAX as f64 =>
+        {
+            Some((value as u32, 1))
+        }
+        Amount::Decimal(_) => None,
+    }
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a.max(1)
+    } else {
+        gcd(b, a % b)
+    }
+}
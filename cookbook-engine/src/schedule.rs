@@ -0,0 +1,278 @@
+//! Backward scheduling: when to start each recipe and step so everything is ready to serve.
+//!
+//! Every recipe becomes a chain of blocks, one per step. A step that runs a timer takes the
+//! timer's length; the rest share the recipe's `prep_time`. Steps that mostly wait ("bake",
+//! "simmer", "let rest") are passive, everything else needs the cook's hands. Downtime the
+//! timers don't account for goes to untimed passive steps, or to a waiting block at the end.
+//!
+//! Scheduling works back from the serving time, latest blocks first. Passive blocks can run
+//! alongside anything, but the cook does one active block at a time, so an active block that
+//! clashes with one already placed moves earlier, into the latest gap that fits. A recipe
+//! whose last block had to move is ready before serving time.
+use crate::steps::Step;
+use crate::types::Recipe;
+use chrono::{Duration, NaiveDateTime};
+
+/// Minutes given to each untimed step of a recipe without a `prep_time`
+const DEFAULT_STEP_MINUTES: i64 = 5;
+
+/// Word beginnings that mark a step as mostly waiting: "Bake for 60 minutes", "simmering"
+const PASSIVE_STEMS: &[&str] = &[
+    "bake",
+    "baking",
+    "boil",
+    "braise",
+    "braising",
+    "chill",
+    "cool",
+    "freez",
+    "marinat",
+    "proof",
+    "refrigerat",
+    "rest",
+    "rise",
+    "rising",
+    "roast",
+    "simmer",
+    "soak",
+    "steep",
+];
+
+/// Whole words that do the same, but would match too much as beginnings ("bay leaves")
+const PASSIVE_WORDS: &[&str] = &["let", "leave", "wait"];
+
+/// One bar of the timeline
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineEntry {
+    /// Title of the recipe
+    pub recipe: String,
+    /// Number of the step, or None for time not tied to a step
+    pub step: Option<usize>,
+    /// What happens, e.g. the step's text
+    pub label: String,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    /// Needs the cook's hands; passive entries can overlap anything
+    pub active: bool,
+}
+
+/// When each recipe and step starts, working back from a serving time
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    pub serve_at: NaiveDateTime,
+    /// Every block of every recipe, sorted by start time
+    pub entries: Vec<TimelineEntry>,
+    /// Recipes without any steps, prep time or downtime to schedule
+    pub untimed: Vec<String>,
+}
+
+impl Schedule {
+    /// When the first recipe starts
+    pub fn start(&self) -> Option<NaiveDateTime> {
+        self.entries.iter().map(|e| e.start).min()
+    }
+
+    /// When `recipe` starts
+    pub fn recipe_start(&self, recipe: &str) -> Option<NaiveDateTime> {
+        self.recipe_entries(recipe).map(|e| e.start).min()
+    }
+
+    /// When `recipe` is done; earlier than `serve_at` if it had to make room for another
+    pub fn ready_at(&self, recipe: &str) -> Option<NaiveDateTime> {
+        self.recipe_entries(recipe).map(|e| e.end).max()
+    }
+
+    fn recipe_entries<'a>(&'a self, recipe: &'a str) -> impl Iterator<Item = &'a TimelineEntry> {
+        self.entries.iter().filter(move |e| e.recipe == recipe)
+    }
+}
+
+/// A stretch of a recipe before it is placed on the timeline
+#[derive(Debug, Clone)]
+struct Block {
+    step: Option<usize>,
+    label: String,
+    seconds: i64,
+    active: bool,
+}
+
+/// Schedules `recipes` so they are all ready at `serve_at`
+pub fn schedule(recipes: &[&Recipe], serve_at: NaiveDateTime) -> Schedule {
+    let mut chains: Vec<(&Recipe, Vec<Block>)> = Vec::new();
+    let mut untimed = Vec::new();
+    for recipe in recipes {
+        let blocks = plan_recipe(recipe);
+        if blocks.is_empty() {
+            untimed.push(recipe.title.clone());
+        } else {
+            chains.push((recipe, blocks));
+        }
+    }
+
+    // Seconds before serve_at: each chain's unplaced blocks end at `ends[i]`
+    let mut ends = vec![0i64; chains.len()];
+    let mut remaining: Vec<usize> = chains.iter().map(|(_, blocks)| blocks.len()).collect();
+    // (start, end) of placed active blocks, in seconds relative to serve_at
+    let mut busy: Vec<(i64, i64)> = Vec::new();
+    let mut entries = Vec::new();
+
+    loop {
+        // The chain whose next block ends latest; on a tie, the one with the most time left
+        let next = (0..chains.len())
+            .filter(|i| remaining[*i] > 0)
+            .max_by_key(|i| {
+                let left: i64 = chains[*i].1[..remaining[*i]]
+                    .iter()
+                    .map(|b| b.seconds)
+                    .sum();
+                (ends[*i], left)
+            });
+        let Some(i) = next else {
+            break;
+        };
+        remaining[i] -= 1;
+        let (recipe, blocks) = &chains[i];
+        let block = &blocks[remaining[i]];
+
+        let mut end = ends[i];
+        if block.active {
+            end = latest_free_end(&busy, end, block.seconds);
+            busy.push((end - block.seconds, end));
+        }
+        let start = end - block.seconds;
+        ends[i] = start;
+
+        entries.push(TimelineEntry {
+            recipe: recipe.title.clone(),
+            step: block.step,
+            label: block.label.clone(),
+            start: serve_at + Duration::seconds(start),
+            end: serve_at + Duration::seconds(end),
+            active: block.active,
+        });
+    }
+
+    entries.sort_by(|a, b| a.start.cmp(&b.start).then(a.end.cmp(&b.end)));
+    Schedule {
+        serve_at,
+        entries,
+        untimed,
+    }
+}
+
+/// The latest end, no later than `end`, at which `seconds` of work fit between busy blocks
+fn latest_free_end(busy: &[(i64, i64)], mut end: i64, seconds: i64) -> i64 {
+    while let Some((start, _)) = busy
+        .iter()
+        .find(|(start, stop)| *start < end && end - seconds < *stop)
+    {
+        end = *start;
+    }
+    end
+}
+
+/// Splits a recipe into blocks in cooking order
+fn plan_recipe(recipe: &Recipe) -> Vec<Block> {
+    let prep = recipe.prep_time.map(|m| m as i64 * 60);
+    let downtime = recipe.downtime.unwrap_or(0) as i64 * 60;
+    let steps = recipe.steps();
+
+    if steps.is_empty() {
+        let mut blocks = Vec::new();
+        if let Some(prep) = prep.filter(|p| *p > 0) {
+            blocks.push(Block {
+                step: None,
+                label: "Preparation".to_string(),
+                seconds: prep,
+                active: true,
+            });
+        }
+        if downtime > 0 {
+            blocks.push(Block {
+                step: None,
+                label: "Downtime".to_string(),
+                seconds: downtime,
+                active: false,
+            });
+        }
+        return blocks;
+    }
+
+    let passive: Vec<bool> = steps.iter().map(is_passive).collect();
+    let timed_seconds = |want_passive: bool| -> i64 {
+        steps
+            .iter()
+            .zip(&passive)
+            .filter(|(_, p)| **p == want_passive)
+            .map(|(s, _)| s.duration_seconds() as i64)
+            .sum()
+    };
+    let untimed = steps.iter().filter(|s| s.timers.is_empty()).count() as i64;
+    let untimed_passive = steps
+        .iter()
+        .zip(&passive)
+        .filter(|(s, p)| s.timers.is_empty() && **p)
+        .count() as i64;
+
+    // Hands-on time the timers don't cover, shared by the untimed steps
+    let active_share = match prep {
+        Some(prep) if untimed > 0 => (prep - timed_seconds(false)).max(0) / untimed,
+        Some(_) => 0,
+        None => DEFAULT_STEP_MINUTES * 60,
+    };
+    // Waiting time the timers don't cover, shared by the untimed passive steps
+    let leftover_downtime = (downtime - timed_seconds(true)).max(0);
+    let passive_share = if untimed_passive > 0 {
+        leftover_downtime / untimed_passive
+    } else {
+        0
+    };
+
+    let mut blocks = Vec::new();
+    for (step, passive) in steps.iter().zip(passive) {
+        let label = step.text.clone();
+        if !step.timers.is_empty() {
+            blocks.push(Block {
+                step: Some(step.number),
+                label,
+                seconds: step.duration_seconds() as i64,
+                active: !passive,
+            });
+            continue;
+        }
+        blocks.push(Block {
+            step: Some(step.number),
+            label: label.clone(),
+            seconds: active_share,
+            active: true,
+        });
+        if passive {
+            blocks.push(Block {
+                step: Some(step.number),
+                label,
+                seconds: passive_share,
+                active: false,
+            });
+        }
+    }
+    if untimed_passive == 0 && leftover_downtime > 0 {
+        blocks.push(Block {
+            step: None,
+            label: "Downtime".to_string(),
+            seconds: leftover_downtime,
+            active: false,
+        });
+    }
+    blocks.retain(|b| b.seconds > 0);
+    blocks
+}
+
+fn is_passive(step: &Step) -> bool {
+    step.text
+        .split(|c: char| !c.is_alphabetic())
+        .map(str::to_lowercase)
+        .any(|word| {
+            PASSIVE_STEMS.iter().any(|p| word.starts_with(p))
+                || PASSIVE_WORDS.contains(&word.as_str())
+        })
+}
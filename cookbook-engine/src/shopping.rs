@@ -0,0 +1,227 @@
+//! The shopping list: what to buy to get from today to the next shop.
+//!
+//! Two things put an ingredient on the list: a meal planned before the next shop that needs
+//! it while it isn't in the pantry, and a pantry forecast (see `forecast`) that has it
+//! running out before then. The amount is what is needed to last until the next shop, when
+//! it can be worked out.
+//!
+//! `check_stock` answers the smaller question of whether the pantry holds enough for one
+//! recipe. Both convert amounts with `units::convert_ingredient`, and report the density
+//! or unit weight they lacked when they can't.
+use crate::forecast;
+use crate::types::{Ingredient, MealPlanEntry, PantryChange, PantryItem, Recipe};
+use crate::units::{self, MissingConversion};
+use chrono::NaiveDate;
+
+/// An ingredient to buy
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShoppingItem {
+    pub ingredient: String,
+    /// How much is needed, or None if it isn't known (no amounts, or mixed units)
+    pub quantity: Option<f64>,
+    pub quantity_type: String,
+    /// Planned meals before the next shop that need it
+    pub recipes: Vec<String>,
+    /// When the pantry stock is expected to run out, for items still in stock
+    pub runs_out: Option<NaiveDate>,
+    /// What adding up the recipes' amounts lacked, when `quantity` is None because of it
+    pub missing: Option<MissingConversion>,
+}
+
+impl ShoppingItem {
+    /// Why the item is on the list, e.g. "Runs out around 2025-05-14; needed for Lasagna"
+    pub fn reason(&self) -> String {
+        let recipes = self.recipes.join(", ");
+        match (self.runs_out, self.recipes.is_empty()) {
+            (Some(date), true) => format!("Runs out around {}", date),
+            (Some(date), false) => format!("Runs out around {}; needed for {}", date, recipes),
+            (None, _) => format!("Needed for {}", recipes),
+        }
+    }
+}
+
+/// What to buy before `next_shop`, by ingredient name
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShoppingList {
+    pub today: NaiveDate,
+    pub next_shop: NaiveDate,
+    pub items: Vec<ShoppingItem>,
+}
+
+/// Builds the shopping list from the pantry, its journal and the planned meals with their
+/// recipes. Meals from `today` up to (not including) `next_shop` count. `ingredient` looks
+/// an ingredient up by name, for converting amounts
+pub fn shopping_list<'a>(
+    pantry: &[PantryItem],
+    journal: &[PantryChange],
+    plan: &[(&MealPlanEntry, &Recipe)],
+    ingredient: impl Fn(&str) -> Option<&'a Ingredient>,
+    today: NaiveDate,
+    next_shop: NaiveDate,
+) -> ShoppingList {
+    let plan: Vec<(&MealPlanEntry, &Recipe)> = plan
+        .iter()
+        .filter(|(e, _)| e.date >= today && e.date < next_shop)
+        .copied()
+        .collect();
+    let mut items: Vec<ShoppingItem> = Vec::new();
+
+    // Planned meals that need something the pantry doesn't have
+    for (entry, recipe) in &plan {
+        let factor = match (entry.servings, recipe.servings) {
+            (Some(wanted), Some(base)) if base > 0 => wanted as f64 / base as f64,
+            _ => 1.0,
+        };
+        for line in recipe.required_ingredients() {
+            if pantry.iter().any(|p| p.ingredient == line.ingredient) {
+                continue;
+            }
+            let amount = line
+                .quantity
+                .as_ref()
+                .and_then(|q| q.value())
+                .map(|a| a * factor);
+            let unit = line.quantity_type.clone().unwrap_or_default();
+            match items.iter_mut().find(|i| i.ingredient == line.ingredient) {
+                Some(item) => {
+                    item.quantity = match (item.quantity, amount) {
+                        (Some(a), Some(b)) => match units::convert_ingredient(
+                            b,
+                            &unit,
+                            &item.quantity_type,
+                            ingredient(&line.ingredient),
+                        ) {
+                            Ok(b) => Some(a + b),
+                            Err(missing) => {
+                                item.missing = Some(missing);
+                                None
+                            }
+                        },
+                        _ => None,
+                    };
+                    if !item.recipes.contains(&recipe.title) {
+                        item.recipes.push(recipe.title.clone());
+                    }
+                }
+                None => items.push(ShoppingItem {
+                    ingredient: line.ingredient.clone(),
+                    quantity: amount,
+                    quantity_type: unit,
+                    recipes: vec![recipe.title.clone()],
+                    runs_out: None,
+                    missing: None,
+                }),
+            }
+        }
+    }
+
+    // Pantry items that won't last until the next shop
+    for item in pantry {
+        let changes: Vec<&PantryChange> = journal
+            .iter()
+            .filter(|c| c.ingredient == item.ingredient)
+            .collect();
+        let Some(forecast) =
+            forecast::forecast(item, ingredient(&item.ingredient), &changes, &plan, today)
+        else {
+            continue;
+        };
+        let Some(runs_out) = forecast.runs_out.filter(|d| *d < next_shop) else {
+            continue;
+        };
+        let mut recipes: Vec<String> = Vec::new();
+        for name in forecast
+            .planned
+            .iter()
+            .map(|p| &p.recipe)
+            .chain(forecast.unmeasured.iter().map(|u| &u.recipe))
+        {
+            if !recipes.contains(name) {
+                recipes.push(name.clone());
+            }
+        }
+        let shortfall = forecast.shortfall(today, next_shop);
+        items.push(ShoppingItem {
+            ingredient: item.ingredient.clone(),
+            quantity: (shortfall > 0.0).then_some(shortfall),
+            quantity_type: item.quantity_type.clone(),
+            recipes,
+            runs_out: Some(runs_out),
+            missing: forecast.unmeasured.into_iter().find_map(|u| u.missing),
+        });
+    }
+
+    items.sort_by(|a, b| a.ingredient.cmp(&b.ingredient));
+    ShoppingList {
+        today,
+        next_shop,
+        items,
+    }
+}
+
+/// Whether the pantry holds enough of an ingredient for a recipe
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stock {
+    /// In stock, in at least the amount needed, or with no amounts to compare
+    Enough,
+    /// In stock, but short by this much, in the recipe's unit
+    Short(f64),
+    /// Not in the pantry
+    Missing,
+    /// In stock, but the amounts couldn't be compared without this
+    Unknown(MissingConversion),
+}
+
+/// The pantry's stock of one of a recipe's required ingredients
+#[derive(Debug, Clone, PartialEq)]
+pub struct StockCheck {
+    pub ingredient: String,
+    /// What the recipe needs, scaled, in `quantity_type`
+    pub needed: Option<f64>,
+    pub quantity_type: String,
+    pub stock: Stock,
+}
+
+/// Checks the pantry against a recipe's required ingredients, with their amounts
+/// multiplied by `factor` (e.g. 2.0 to cook it twice over)
+pub fn check_stock<'a>(
+    recipe: &Recipe,
+    factor: f64,
+    pantry: &[PantryItem],
+    ingredient: impl Fn(&str) -> Option<&'a Ingredient>,
+) -> Vec<StockCheck> {
+    recipe
+        .required_ingredients()
+        .map(|line| {
+            let needed = line
+                .quantity
+                .as_ref()
+                .and_then(|q| q.value())
+                .map(|a| a * factor);
+            let unit = line.quantity_type.clone().unwrap_or_default();
+            let item = pantry.iter().find(|p| p.ingredient == line.ingredient);
+            let stock = match item {
+                None => Stock::Missing,
+                Some(item) => match (item.quantity, needed) {
+                    (Some(held), Some(needed)) => match units::convert_ingredient(
+                        held,
+                        &item.quantity_type,
+                        &unit,
+                        ingredient(&line.ingredient),
+                    ) {
+                        Ok(held) if held >= needed => Stock::Enough,
+                        Ok(held) => Stock::Short(needed - held),
+                        Err(missing) => Stock::Unknown(missing),
+                    },
+                    _ => Stock::Enough,
+                },
+            };
+            StockCheck {
+                ingredient: line.ingredient.clone(),
+                needed,
+                quantity_type: unit,
+                stock,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,145 @@
+//! Slugs and file names for recipes and ingredients.
+//!
+//! Every recipe and ingredient file is named after a slug: a lowercase, filesystem-safe
+//! identifier derived once from the title or name and then kept, so renaming a recipe does not
+//! move its file. Slugs keep non-ASCII letters (`blåbær`) but fold decomposed accents into
+//! their composed form, so the same title typed on macOS and Linux gives the same file name.
+//! Case-only differences count as collisions, since several filesystems ignore case.
+use crate::types::CookbookError;
+use std::path::{Path, PathBuf};
+
+/// Longest slug accepted, in bytes, leaving room for an extension and a numeric suffix
+const MAX_SLUG_LEN: usize = 200;
+
+/// Device names Windows refuses as file names, whatever the extension
+const RESERVED_NAMES: &[&str] = &[
+    "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8",
+    "com9", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+/// Derives a slug from a title or name, e.g. `"Spaghetti Aglio e Olio"` → `spaghetti_aglio_e_olio`.
+///
+/// Letters and digits of any script are kept (lowercased); spaces, punctuation and path
+/// separators become single underscores. The result may be empty for titles without any
+/// letters or digits, which `validate_slug` rejects.
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    let mut pending_separator = false;
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if is_combining_mark(c) {
+            // Fold "e" + U+0301 into "é"; marks without a composed form are dropped
+            if let Some(base) = slug.pop() {
+                slug.push(compose(base, c).unwrap_or(base));
+            }
+        } else if c.is_alphanumeric() {
+            if pending_separator && !slug.is_empty() {
+                slug.push('_');
+            }
+            pending_separator = false;
+            slug.push(c);
+        } else {
+            pending_separator = true;
+        }
+    }
+    truncate(&mut slug, MAX_SLUG_LEN);
+    slug
+}
+
+/// Checks that a slug is safe to use as a file name inside a data directory.
+///
+/// Rejects empty slugs, `.`/`..` and hidden names, path separators, control characters,
+/// Windows device names and overlong slugs.
+pub fn validate_slug(slug: &str) -> Result<(), CookbookError> {
+    let problem = if slug.trim().is_empty() {
+        Some("it is empty")
+    } else if slug.starts_with('.') {
+        Some("it starts with a dot")
+    } else if slug.contains(['/', '\\']) {
+        Some("it contains a path separator")
+    } else if slug
+        .chars()
+        .any(|c| c.is_control() || matches!(c, ':' | '*' | '?' | '"' | '<' | '>' | '|'))
+    {
+        Some("it contains characters that are not allowed in file names")
+    } else if RESERVED_NAMES.contains(&slug.to_lowercase().as_str()) {
+        Some("it is a reserved device name")
+    } else if slug.len() > MAX_SLUG_LEN {
+        Some("it is too long")
+    } else {
+        None
+    };
+    match problem {
+        Some(problem) => Err(CookbookError::InvalidNameError(format!(
+            "'{}' cannot be used as a file name: {}",
+            slug, problem
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Returns true if two slugs would name the same file on a case-insensitive filesystem
+pub fn slugs_collide(a: &str, b: &str) -> bool {
+    a.to_lowercase() == b.to_lowercase()
+}
+
+/// Returns `slug`, or `slug_2`, `slug_3`, ... if it collides with any of `taken`
+pub fn unique_slug<'a>(slug: &str, taken: impl IntoIterator<Item = &'a str>) -> String {
+    let taken: Vec<&str> = taken.into_iter().collect();
+    let is_free = |candidate: &str| !taken.iter().any(|t| slugs_collide(t, candidate));
+    if is_free(slug) {
+        return slug.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}_{}", slug, n))
+        .find(|candidate| is_free(candidate))
+        .expect("an unused suffix always exists")
+}
+
+/// Builds the path of `{slug}.{extension}` inside `dir`, refusing slugs that would escape it
+pub fn slug_path(dir: &Path, slug: &str, extension: &str) -> Result<PathBuf, CookbookError> {
+    validate_slug(slug)?;
+    Ok(dir.join(format!("{}.{}", slug, extension)))
+}
+
+/// Returns the slug a file was saved under: its file name without the extension
+pub fn slug_from_path(path: &Path) -> Option<String> {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .map(str::to_string)
+}
+
+/// Combining diacritics, as produced by NFD normalization (e.g. macOS file names)
+fn is_combining_mark(c: char) -> bool {
+    ('\u{300}'..='\u{36F}').contains(&c)
+}
+
+/// Composes a lowercase Latin letter and a combining mark into a single character
+fn compose(base: char, mark: char) -> Option<char> {
+    let (bases, composed) = match mark {
+        '\u{300}' => ("aeiou", "àèìòù"),
+        '\u{301}' => ("aeiouy", "áéíóúý"),
+        '\u{302}' => ("aeiou", "âêîôû"),
+        '\u{303}' => ("ano", "ãñõ"),
+        '\u{308}' => ("aeiouy", "äëïöüÿ"),
+        '\u{30A}' => ("a", "å"),
+        '\u{327}' => ("c", "ç"),
+        _ => return None,
+    };
+    let index = bases.chars().position(|b| b == base)?;
+    composed.chars().nth(index)
+}
+
+/// Shortens a string to at most `max` bytes without splitting a character
+fn truncate(text: &mut String, max: usize) {
+    if text.len() <= max {
+        return;
+    }
+    let mut end = max;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    while text.ends_with('_') {
+        text.pop();
+    }
+}
@@ -0,0 +1,625 @@
+//! Recipe instructions as numbered steps, with the timers and temperatures they mention.
+//!
+//! Instructions are free Markdown. Numbered or bulleted list items become steps; without a
+//! list every paragraph is a step, and a recipe written as one paragraph is split into
+//! sentences. Durations ("Bake for 60 minutes", "10-15 min", "1 hour 30 minutes") and
+//! temperatures ("180C", "350 °F", "200 degrees Celsius") are picked up from the text.
+//!
+//! Anything the detection misses can be declared inline: `{timer: 10 min}`,
+//! `{timer: 1 hour, proofing}` (with a label) or `{temp: 180C}`. A marker reads as its value
+//! ("10 min", "180 °C") wherever instructions are shown.
+use crate::ingredient_parser::NUMBER_WORDS;
+use crate::quantity::Amount;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Range;
+
+/// Duration words and their length in seconds
+const DURATION_UNITS: &[(&str, u32)] = &[
+    ("sec", 1),
+    ("secs", 1),
+    ("second", 1),
+    ("seconds", 1),
+    ("min", 60),
+    ("mins", 60),
+    ("minute", 60),
+    ("minutes", 60),
+    ("h", 3600),
+    ("hr", 3600),
+    ("hrs", 3600),
+    ("hour", 3600),
+    ("hours", 3600),
+    ("day", 86400),
+    ("days", 86400),
+];
+
+/// Smallest value a bare "C" or "F" is read as a temperature for. Below it the letter is more
+/// likely a cup: "2 C milk"
+const BARE_UNIT_MIN_VALUE: f64 = 20.0;
+
+/// Characters trimmed from both ends of a word before reading it
+const WORD_PUNCTUATION: &[char] = &[
+    '.', ',', ';', ':', '!', '?', '(', ')', '[', ']', '"', '\'', '~', '*', '_',
+];
+
+/// Scale a temperature is given in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+impl TemperatureUnit {
+    /// "°C" or "°F"
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            TemperatureUnit::Celsius => "°C",
+            TemperatureUnit::Fahrenheit => "°F",
+        }
+    }
+}
+
+/// A temperature mentioned in a step
+#[derive(Debug, Clone, PartialEq)]
+pub struct Temperature {
+    pub value: f64,
+    pub unit: TemperatureUnit,
+    /// Declared with a `{temp: …}` marker rather than found in the text
+    pub explicit: bool,
+}
+
+impl Temperature {
+    /// The temperature in `unit`, unrounded
+    pub fn in_unit(&self, unit: TemperatureUnit) -> f64 {
+        match (self.unit, unit) {
+            (TemperatureUnit::Celsius, TemperatureUnit::Fahrenheit) => {
+                self.value * 9.0 / 5.0 + 32.0
+            }
+            (TemperatureUnit::Fahrenheit, TemperatureUnit::Celsius) => {
+                (self.value - 32.0) * 5.0 / 9.0
+            }
+            _ => self.value,
+        }
+    }
+
+    /// The temperature in `unit`, e.g. "180 °C". Converted values are rounded to whole
+    /// degrees, and to steps of 5 from 100 up like oven dials: 180 °C reads "355 °F"
+    pub fn display(&self, unit: TemperatureUnit) -> String {
+        let value = self.in_unit(unit);
+        let value = if unit == self.unit {
+            value
+        } else if value.abs() >= 100.0 {
+            (value / 5.0).round() * 5.0
+        } else {
+            value.round()
+        };
+        format!("{} {}", value, unit.symbol())
+    }
+}
+
+/// A duration mentioned in a step, to run as a countdown
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timer {
+    /// How long, in seconds; for a range such as "10-15 minutes" the shorter end, so the dish
+    /// gets checked in time
+    pub seconds: u32,
+    /// The longer end of a range
+    pub max_seconds: Option<u32>,
+    /// Name given in a `{timer: 1 hour, proofing}` marker
+    pub label: Option<String>,
+    /// Declared with a `{timer: …}` marker rather than found in the text
+    pub explicit: bool,
+}
+
+impl Timer {
+    /// The longest the timer can run: the upper end of a range, otherwise `seconds`
+    pub fn longest_seconds(&self) -> u32 {
+        self.max_seconds.unwrap_or(self.seconds)
+    }
+}
+
+impl fmt::Display for Timer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.max_seconds {
+            Some(max) => write!(
+                f,
+                "{} – {}",
+                format_duration(self.seconds),
+                format_duration(max)
+            ),
+            None => write!(f, "{}", format_duration(self.seconds)),
+        }
+    }
+}
+
+/// One numbered step of a recipe
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    /// Position in the recipe, starting at 1
+    pub number: usize,
+    /// The step's text with markers replaced by their values; temperatures are as written
+    pub text: String,
+    /// Durations in the order they appear
+    pub timers: Vec<Timer>,
+    /// Temperatures in the order they appear
+    pub temperatures: Vec<Temperature>,
+}
+
+impl Step {
+    /// The step's text with every temperature shown in `unit`
+    pub fn text_in(&self, unit: TemperatureUnit) -> String {
+        convert_temperatures(&self.text, unit)
+    }
+
+    /// Total time the step's timers run, taking the longer end of ranges
+    pub fn duration_seconds(&self) -> u32 {
+        self.timers.iter().map(Timer::longest_seconds).sum()
+    }
+}
+
+/// Splits instructions into numbered steps and reads their timers and temperatures
+pub fn parse_steps(instructions: &str) -> Vec<Step> {
+    split_steps(instructions)
+        .iter()
+        .enumerate()
+        .map(|(index, raw)| parse_step(index + 1, raw))
+        .collect()
+}
+
+/// Instructions ready to show: markers replaced by their values and every temperature in
+/// `unit`
+pub fn localize_instructions(instructions: &str, unit: TemperatureUnit) -> String {
+    convert_temperatures(&resolve_markers(instructions).0, unit)
+}
+
+/// Rewrites every temperature in `text` that isn't already in `unit`
+pub fn convert_temperatures(text: &str, unit: TemperatureUnit) -> String {
+    let mut converted = text.to_string();
+    for (range, temperature) in find_temperatures(text, false).into_iter().rev() {
+        if temperature.unit != unit {
+            converted.replace_range(range, &temperature.display(unit));
+        }
+    }
+    converted
+}
+
+/// A duration for people: "45 s", "1 min 30 s", "1 h 20 min", "2 d"
+pub fn format_duration(seconds: u32) -> String {
+    let parts = [
+        (seconds / 86400, "d"),
+        (seconds % 86400 / 3600, "h"),
+        (seconds % 3600 / 60, "min"),
+        (seconds % 60, "s"),
+    ];
+    let text: Vec<String> = parts
+        .iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{} {}", value, unit))
+        .collect();
+    if text.is_empty() {
+        "0 s".to_string()
+    } else {
+        text.join(" ")
+    }
+}
+
+fn parse_step(number: usize, raw: &str) -> Step {
+    let (text, markers) = resolve_markers(raw);
+    let overlaps_marker = |range: &Range<usize>| {
+        markers
+            .iter()
+            .any(|(marked, _)| range.start < marked.end && marked.start < range.end)
+    };
+
+    let mut timers: Vec<(usize, Timer)> = find_durations(&text)
+        .into_iter()
+        .filter(|(range, _)| !overlaps_marker(range))
+        .map(|(range, timer)| (range.start, timer))
+        .collect();
+    let mut temperatures: Vec<(usize, Temperature)> = find_temperatures(&text, false)
+        .into_iter()
+        .filter(|(range, _)| !overlaps_marker(range))
+        .map(|(range, temperature)| (range.start, temperature))
+        .collect();
+    for (range, marker) in markers {
+        match marker {
+            Marker::Timer(timer) => timers.push((range.start, timer)),
+            Marker::Temperature(temperature) => temperatures.push((range.start, temperature)),
+        }
+    }
+    timers.sort_by_key(|(start, _)| *start);
+    temperatures.sort_by_key(|(start, _)| *start);
+
+    Step {
+        number,
+        text,
+        timers: timers.into_iter().map(|(_, timer)| timer).collect(),
+        temperatures: temperatures.into_iter().map(|(_, t)| t).collect(),
+    }
+}
+
+// ── Splitting ────────────────────────────────────────────────────────────────
+
+/// List items, paragraphs, or the sentences of a lone paragraph. Headings are skipped
+fn split_steps(instructions: &str) -> Vec<String> {
+    let mut steps = Vec::new();
+    let mut current = String::new();
+    let mut has_list = false;
+    let flush = |current: &mut String, steps: &mut Vec<String>| {
+        if !current.is_empty() {
+            steps.push(std::mem::take(current));
+        }
+    };
+    for line in instructions.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            flush(&mut current, &mut steps);
+        } else if let Some(item) = strip_list_marker(line) {
+            flush(&mut current, &mut steps);
+            has_list = true;
+            current.push_str(item);
+        } else {
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(line);
+        }
+    }
+    flush(&mut current, &mut steps);
+
+    if !has_list && steps.len() == 1 {
+        return split_sentences(&steps[0]);
+    }
+    steps
+}
+
+/// The text of a "1. …", "1) …", "- …", "* …" or "+ …" line
+fn strip_list_marker(line: &str) -> Option<&str> {
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let rest = if digits > 0 {
+        line[digits..].strip_prefix(['.', ')'])?
+    } else {
+        line.strip_prefix(['-', '*', '+'])?
+    };
+    rest.starts_with(char::is_whitespace).then(|| rest.trim())
+}
+
+/// Splits after ".", "!" or "?" when the next word starts with a capital letter
+fn split_sentences(text: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        if !matches!(c, '.' | '!' | '?') {
+            continue;
+        }
+        let end = i + c.len_utf8();
+        let rest = &text[end..];
+        let next_word = rest.trim_start();
+        if next_word.len() < rest.len() && next_word.starts_with(char::is_uppercase) {
+            sentences.push(text[start..end].trim().to_string());
+            start = end;
+        }
+    }
+    let last = text[start..].trim();
+    if !last.is_empty() {
+        sentences.push(last.to_string());
+    }
+    sentences
+}
+
+// ── Explicit markers ─────────────────────────────────────────────────────────
+
+enum Marker {
+    Timer(Timer),
+    Temperature(Temperature),
+}
+
+/// Replaces `{timer: …}` and `{temp: …}` markers by their values, returning the new text and
+/// where each marker's value ended up. Markers that can't be read are left as they are
+fn resolve_markers(text: &str) -> (String, Vec<(Range<usize>, Marker)>) {
+    let mut resolved = String::new();
+    let mut markers = Vec::new();
+    let mut rest = text;
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}').map(|c| open + c) else {
+            break;
+        };
+        resolved.push_str(&rest[..open]);
+        let marker = &rest[open..=close];
+        match read_marker(&marker[1..marker.len() - 1]) {
+            Some((value, parsed)) => {
+                let start = resolved.len();
+                resolved.push_str(&value);
+                markers.push((start..resolved.len(), parsed));
+            }
+            None => resolved.push_str(marker),
+        }
+        rest = &rest[close + 1..];
+    }
+    resolved.push_str(rest);
+    (resolved, markers)
+}
+
+/// Reads the inside of a marker, returning the text it shows and what it declares
+fn read_marker(content: &str) -> Option<(String, Marker)> {
+    let (kind, value) = content.split_once(':')?;
+    match kind.trim().to_lowercase().as_str() {
+        "timer" => {
+            let (duration, label) = match value.split_once(',') {
+                Some((duration, label)) => (duration.trim(), Some(label.trim().to_string())),
+                None => (value.trim(), None),
+            };
+            let (_, mut timer) = find_durations(duration).into_iter().next()?;
+            timer.label = label.filter(|l| !l.is_empty());
+            timer.explicit = true;
+            Some((duration.to_string(), Marker::Timer(timer)))
+        }
+        "temp" | "temperature" => {
+            // A marker says it holds a temperature, so "{temp: 4C}" needs no oven value
+            let (_, mut temperature) = find_temperatures(value, true).into_iter().next()?;
+            temperature.explicit = true;
+            let shown = temperature.display(temperature.unit);
+            Some((shown, Marker::Temperature(temperature)))
+        }
+        _ => None,
+    }
+}
+
+// ── Detection ────────────────────────────────────────────────────────────────
+
+struct Token<'a> {
+    text: &'a str,
+    start: usize,
+    end: usize,
+}
+
+/// Splits text into words with their byte ranges, trimming punctuation. "180°C/350°F" gives
+/// two words, while "1/2" stays one
+fn tokenize(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut push = |start: usize, end: usize| {
+        let word = &text[start..end];
+        let trimmed_start = word.trim_start_matches(WORD_PUNCTUATION);
+        let trimmed = trimmed_start.trim_end_matches(WORD_PUNCTUATION);
+        if !trimmed.is_empty() {
+            let start = start + word.len() - trimmed_start.len();
+            tokens.push(Token {
+                text: trimmed,
+                start,
+                end: start + trimmed.len(),
+            });
+        }
+    };
+    let mut word_start = None;
+    let mut previous = ' ';
+    for (i, c) in text.char_indices() {
+        if c.is_whitespace() || (c == '/' && (previous.is_alphabetic() || previous == '°')) {
+            if let Some(start) = word_start.take() {
+                push(start, i);
+            }
+        } else if word_start.is_none() {
+            word_start = Some(i);
+        }
+        previous = c;
+    }
+    if let Some(start) = word_start {
+        push(start, text.len());
+    }
+    tokens
+}
+
+/// Every duration in `text` with its byte range
+fn find_durations(text: &str) -> Vec<(Range<usize>, Timer)> {
+    let tokens = tokenize(text);
+    let mut found = Vec::new();
+    let mut index = 0;
+    while index < tokens.len() {
+        let Some(mut duration) = duration_at(&tokens, index) else {
+            index += 1;
+            continue;
+        };
+        // "1 hour 30 minutes", "1 hour and 30 minutes"
+        loop {
+            let next = match tokens.get(duration.end) {
+                Some(t) if t.text.eq_ignore_ascii_case("and") => duration.end + 1,
+                _ => duration.end,
+            };
+            match duration_at(&tokens, next) {
+                Some(more)
+                    if more.unit < duration.unit
+                        && more.max.is_none()
+                        && duration.max.is_none() =>
+                {
+                    duration.min += more.min;
+                    duration.unit = more.unit;
+                    duration.end = more.end;
+                }
+                _ => break,
+            }
+        }
+        let range = tokens[index].start..tokens[duration.end - 1].end;
+        found.push((
+            range,
+            Timer {
+                seconds: duration.min.round() as u32,
+                max_seconds: duration.max.map(|max| max.round() as u32),
+                label: None,
+                explicit: false,
+            },
+        ));
+        index = duration.end;
+    }
+    found
+}
+
+struct Duration {
+    /// Seconds, or the lower end of a range
+    min: f64,
+    max: Option<f64>,
+    /// Seconds per unit of the last part, to tell "1 hour 30 minutes" from "30 minutes 1 hour"
+    unit: u32,
+    /// Index of the first token after the duration
+    end: usize,
+}
+
+/// A duration starting at token `index`: "10 minutes", "10min", "10-15 min", "10 to 15
+/// minutes", "2 1/2 hours", "an hour"
+fn duration_at(tokens: &[Token], index: usize) -> Option<Duration> {
+    let first = tokens.get(index)?.text;
+    let word = |offset: usize| tokens.get(index + offset).map(|t| t.text);
+
+    // Number and unit written together: "10min", "1h"
+    let split = first
+        .find(|c: char| c.is_alphabetic())
+        .unwrap_or(first.len());
+    if split > 0 && split < first.len() {
+        let value = Amount::parse(&first[..split])?.value();
+        let unit = duration_unit(&first[split..])?;
+        return Some(Duration {
+            min: value * unit as f64,
+            max: None,
+            unit,
+            end: index + 1,
+        });
+    }
+
+    let (mut min, mut max) = read_range(first)?;
+    let mut next = 1;
+    // "2 1/2 hours"
+    if max.is_none() && min.fract() == 0.0 {
+        if let Some(fraction) = word(1).and_then(Amount::parse) {
+            if matches!(fraction, Amount::Fraction { numerator, denominator } if numerator < denominator)
+            {
+                min += fraction.value();
+                next = 2;
+            }
+        }
+    }
+    // "10 to 15 minutes", "10 or 15 minutes"
+    if max.is_none() && matches!(word(next), Some("to" | "or")) {
+        if let Some((upper, None)) = word(next + 1).and_then(read_range) {
+            if word(next + 2).and_then(duration_unit).is_some() {
+                max = Some(upper);
+                next += 2;
+            }
+        }
+    }
+    let unit = duration_unit(word(next)?)?;
+    Some(Duration {
+        min: min * unit as f64,
+        max: max.map(|max| max * unit as f64),
+        unit,
+        end: index + next + 1,
+    })
+}
+
+/// A number, a range such as "10-15", or a number word such as "an"
+fn read_range(text: &str) -> Option<(f64, Option<f64>)> {
+    if let Some(amount) = Amount::parse(text) {
+        return Some((amount.value(), None));
+    }
+    if let Some((min, max)) = text.split_once(['-', '–']) {
+        return Some((
+            Amount::parse(min)?.value(),
+            Some(Amount::parse(max)?.value()),
+        ));
+    }
+    let lower = text.to_lowercase();
+    NUMBER_WORDS
+        .iter()
+        .find(|(word, _)| *word == lower)
+        .map(|(_, value)| (*value as f64, None))
+}
+
+fn duration_unit(word: &str) -> Option<u32> {
+    let lower = word.to_lowercase();
+    DURATION_UNITS
+        .iter()
+        .find(|(unit, _)| *unit == lower)
+        .map(|(_, seconds)| *seconds)
+}
+
+/// Every temperature in `text` with its byte range. A bare "180 degrees" is ambiguous and
+/// skipped. `any_value` reads a bare "C" or "F" as a temperature whatever the value
+fn find_temperatures(text: &str, any_value: bool) -> Vec<(Range<usize>, Temperature)> {
+    let tokens = tokenize(text);
+    let mut found = Vec::new();
+    let mut index = 0;
+    while index < tokens.len() {
+        let token = &tokens[index];
+        let digits = token.text.len()
+            - token
+                .text
+                .trim_start_matches(|c: char| c.is_ascii_digit() || c == '.' || c == ',')
+                .len();
+        let Some(value) = (digits > 0)
+            .then(|| Amount::parse(&token.text[..digits]))
+            .flatten()
+        else {
+            index += 1;
+            continue;
+        };
+        let suffix = &token.text[digits..];
+        let word = |offset: usize| tokens.get(index + offset).map(|t| t.text);
+        let oven_like = any_value || value.value() >= BARE_UNIT_MIN_VALUE;
+
+        // "180C", "180°C", then "180 C", "180 °C", "180° C", "180 degrees Celsius"
+        let (unit, end) = if !suffix.is_empty() && !is_degree_sign(suffix) {
+            (temperature_unit(suffix, oven_like), index + 1)
+        } else if let Some(unit) = word(1).and_then(|w| temperature_unit(w, oven_like)) {
+            (Some(unit), index + 2)
+        } else if is_degree_sign(suffix)
+            || word(1).is_some_and(|w| {
+                is_degree_sign(w) || matches!(w.to_lowercase().as_str(), "degree" | "degrees")
+            })
+        {
+            let unit_index = if is_degree_sign(suffix) { 1 } else { 2 };
+            (
+                word(unit_index).and_then(|w| temperature_unit(w, true)),
+                index + unit_index + 1,
+            )
+        } else {
+            (None, index + 1)
+        };
+
+        match unit {
+            Some(unit) => {
+                found.push((
+                    token.start..tokens[end - 1].end,
+                    Temperature {
+                        value: value.value(),
+                        unit,
+                        explicit: false,
+                    },
+                ));
+                index = end;
+            }
+            None => index += 1,
+        }
+    }
+    found
+}
+
+fn is_degree_sign(text: &str) -> bool {
+    matches!(text, "°" | "º")
+}
+
+/// "C", "°C", "°c", "Celsius", "F", "°F", "Fahrenheit". A lone lowercase "c" or "f" could be a
+/// cup or anything else, so it only counts after a degree sign; a lone "C" or "F" only when
+/// `bare_allowed`, after a degree or for a value an oven would have
+fn temperature_unit(word: &str, bare_allowed: bool) -> Option<TemperatureUnit> {
+    let unit = word.trim_start_matches(['°', 'º']);
+    let degree_sign = unit.len() < word.len();
+    match unit {
+        "C" if degree_sign || bare_allowed => Some(TemperatureUnit::Celsius),
+        "F" if degree_sign || bare_allowed => Some(TemperatureUnit::Fahrenheit),
+        "c" if degree_sign => Some(TemperatureUnit::Celsius),
+        "f" if degree_sign => Some(TemperatureUnit::Fahrenheit),
+        _ => match unit.to_lowercase().as_str() {
+            "celsius" | "centigrade" => Some(TemperatureUnit::Celsius),
+            "fahrenheit" => Some(TemperatureUnit::Fahrenheit),
+            _ => None,
+        },
+    }
+}
@@ -1,3 +1,5 @@
+use crate::quantity::Quantity; // Amounts in recipe ingredients
+use chrono::NaiveDate; // Dates in the meal plan
 use serde::{Deserialize, Serialize}; // For serialization and deserialization
 use std::collections::HashMap; // For storing ingredients and recipes
 use thiserror::Error; // For error handling
@@ -19,6 +21,46 @@ pub struct Ingredient {
     pub kb: Option<String>,
     pub tags: Option<Vec<String>>,
     pub translations: Option<HashMap<String, TranslationForms>>, // language code -> forms
+    /// Prices paid, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub prices: Vec<Price>,
+    /// Products sold as this ingredient, by barcode
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub packages: Vec<Package>,
+    /// Grams per millilitre, to convert between mass and volume
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub density: Option<f64>,
+    /// Grams in one piece, e.g. 55 for an egg
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit_weight: Option<f64>,
+}
+
+impl Ingredient {
+    /// The most recent price, whichever store it is from
+    pub fn latest_price(&self) -> Option<&Price> {
+        self.prices.iter().max_by_key(|p| p.date)
+    }
+}
+
+/// A price paid for an ingredient: `price` for one `unit`, e.g. 2.49 per kg
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Price {
+    pub date: NaiveDate,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub store: String,
+    pub price: f64,
+    #[serde(default)]
+    pub unit: String,
+}
+
+/// A product that is an ingredient: its EAN/UPC code and how much one package holds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Package {
+    pub barcode: String,
+    #[serde(default)]
+    pub quantity: Option<f64>,
+    #[serde(default)]
+    pub unit: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,11 +69,31 @@ pub struct TranslationForms {
     pub other: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct RecipeIngredient {
     pub ingredient: String,
-    pub quantity: Option<f64>,
+    pub quantity: Option<Quantity>,
     pub quantity_type: Option<String>,
+    /// Section of the ingredient list, e.g. "For the sauce"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Optional ingredients (garnishes and the like) don't count towards pantry coverage
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+    /// How to prepare the ingredient, e.g. "finely chopped"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preparation: Option<String>,
+}
+
+impl RecipeIngredient {
+    /// A copy with the quantity multiplied by `factor`, e.g. 1.5 to cook 3 servings of a
+    /// 2-serving recipe
+    pub fn scaled(&self, factor: f64) -> RecipeIngredient {
+        RecipeIngredient {
+            quantity: self.quantity.as_ref().map(|q| q.scale(factor)),
+            ..self.clone()
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -59,6 +121,11 @@ pub struct Recipe {
 
     #[serde(skip)]
     pub instructions: String,
+
+    /// File name of the recipe without the `.md` extension, taken from the file when loading.
+    /// Left empty for a new recipe, `DataManager` derives it from the title
+    #[serde(skip)]
+    pub slug: String,
 }
 
 impl Recipe {
@@ -66,24 +133,132 @@ impl Recipe {
     pub fn total_time(&self) -> u32 {
         self.prep_time.unwrap_or(0) + self.downtime.unwrap_or(0)
     }
+
+    /// Returns the ingredients grouped by section, in the order each section first appears.
+    /// Ingredients without a group form their own section with no name
+    pub fn ingredient_groups(&self) -> Vec<(Option<&str>, Vec<&RecipeIngredient>)> {
+        let mut groups: Vec<(Option<&str>, Vec<&RecipeIngredient>)> = Vec::new();
+        for ingredient in &self.ingredients {
+            let group = ingredient.group.as_deref();
+            match groups.iter_mut().find(|(name, _)| *name == group) {
+                Some((_, members)) => members.push(ingredient),
+                None => groups.push((group, vec![ingredient])),
+            }
+        }
+        groups
+    }
+
+    /// The ingredients the recipe can't do without, i.e. all but the optional ones
+    pub fn required_ingredients(&self) -> impl Iterator<Item = &RecipeIngredient> {
+        self.ingredients.iter().filter(|i| !i.optional)
+    }
+
+    /// The instructions as numbered steps, with their timers and temperatures
+    pub fn steps(&self) -> Vec<crate::steps::Step> {
+        crate::steps::parse_steps(&self.instructions)
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PantryItem {
     pub ingredient: String, //#TODO Should it perhaps be an Ingredient?
     pub quantity: Option<f64>,
     #[serde(default)]
     pub quantity_type: String,
     pub last_updated: String,
+    /// Use-by date, if known; meal plan suggestions prefer recipes that use it up in time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<NaiveDate>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Pantry {
     pub version: u8,
     pub items: Vec<PantryItem>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// A meal of the day in the meal plan, in the order they are eaten
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Meal {
+    Breakfast,
+    Lunch,
+    Dinner,
+    Snack,
+}
+
+impl Meal {
+    pub const ALL: [Meal; 4] = [Meal::Breakfast, Meal::Lunch, Meal::Dinner, Meal::Snack];
+
+    /// Name for display, e.g. "Dinner"
+    pub fn label(self) -> &'static str {
+        match self {
+            Meal::Breakfast => "Breakfast",
+            Meal::Lunch => "Lunch",
+            Meal::Dinner => "Dinner",
+            Meal::Snack => "Snack",
+        }
+    }
+}
+
+/// A recipe planned for a meal; the date, meal and recipe together identify the entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MealPlanEntry {
+    pub date: NaiveDate,
+    pub meal: Meal,
+    pub recipe: String,
+    /// Servings to cook, or None for the recipe's own servings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub servings: Option<u32>,
+}
+
+impl MealPlanEntry {
+    /// Returns true if `other` is in the same slot with the same recipe, whatever its servings
+    pub fn same_slot(&self, other: &MealPlanEntry) -> bool {
+        self.date == other.date && self.meal == other.meal && self.recipe == other.recipe
+    }
+}
+
+/// The contents of `mealplan.yaml`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MealPlan {
+    #[serde(default)]
+    pub entries: Vec<MealPlanEntry>,
+}
+
+/// A change to the quantity of a pantry item, as recorded in `pantry_journal.yaml`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PantryChange {
+    pub date: NaiveDate,
+    pub ingredient: String,
+    /// Quantity before the change, if it was known and in the same unit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous: Option<f64>,
+    /// Quantity after the change; 0 when the item was taken out of the pantry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<f64>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub quantity_type: String,
+}
+
+impl PantryChange {
+    /// The amount used up by this change, or 0 if the quantity went up or is unknown
+    pub fn used(&self) -> f64 {
+        match (self.previous, self.quantity) {
+            (Some(previous), Some(quantity)) if quantity < previous => previous - quantity,
+            _ => 0.0,
+        }
+    }
+}
+
+/// The contents of `pantry_journal.yaml`: every change to a pantry quantity, oldest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PantryJournal {
+    #[serde(default)]
+    pub changes: Vec<PantryChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KnowledgeBaseEntry {
     pub slug: String,
     pub title: String,
@@ -92,6 +267,33 @@ pub struct KnowledgeBaseEntry {
     pub content: String,
 }
 
+/// What `DataManager::delete_ingredient` should do about recipes that still use the ingredient
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeleteStrategy {
+    /// Refuse to delete while any recipe uses the ingredient
+    Block,
+    /// Delete anyway and strip the ingredient from every recipe that uses it
+    Cascade,
+    /// Delete and point every recipe that uses it at another existing ingredient instead
+    Replace(String),
+}
+
+/// Everything that refers to an ingredient, reported before and after deleting it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IngredientDependents {
+    /// Titles of recipes that list the ingredient, sorted
+    pub recipes: Vec<String>,
+    /// Slugs of KB articles that link to the ingredient, sorted
+    pub kb_links: Vec<String>,
+}
+
+impl IngredientDependents {
+    /// Returns true if nothing refers to the ingredient
+    pub fn is_empty(&self) -> bool {
+        self.recipes.is_empty() && self.kb_links.is_empty()
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum CookbookError {
     #[error("Failed to read file: {0}")]
@@ -114,4 +316,13 @@ pub enum CookbookError {
 
     #[error("Failed to update ingredient: {0}")]
     UpdateError(String),
+
+    #[error("Unsupported schema version: {0}")]
+    SchemaVersionError(String),
+
+    #[error("Ingredient is still in use: {0}")]
+    InUseError(String),
+
+    #[error("Invalid name: {0}")]
+    InvalidNameError(String),
 }
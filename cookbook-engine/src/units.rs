@@ -0,0 +1,288 @@
+//! Units of measure and conversion between them.
+//!
+//! Units are read with `ingredient_parser::canonical_unit`, so "Tablespoons" and "tbsp" are the
+//! same unit. Mass and volume units convert within their own kind; an empty unit and
+//! "piece" both count items. Other units ("clove", "can", "bunch") only match themselves,
+//! as do units the parser doesn't know.
+//!
+//! `convert_ingredient` also converts between kinds for one ingredient, using its density
+//! (cups of flour to grams) or the weight of one piece (eggs to grams), and says which of
+//! those it lacked when it can't.
+//!
+//! `format_amount` and `format_quantity` show amounts in the user's `UnitSystem`, rounded to
+//! what a cook would measure: 354.9 ml reads "1 ½ cups" in imperial.
+use crate::ingredient_parser::canonical_unit;
+use crate::quantity::{Amount, Quantity};
+use crate::types::Ingredient;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// What a unit measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dimension {
+    Mass,
+    Volume,
+    Count,
+}
+
+/// Each convertible unit with its size in grams, millilitres or items
+const SIZES: &[(&str, Dimension, f64)] = &[
+    ("mg", Dimension::Mass, 0.001),
+    ("g", Dimension::Mass, 1.0),
+    ("kg", Dimension::Mass, 1000.0),
+    ("oz", Dimension::Mass, 28.349_523_125),
+    ("lb", Dimension::Mass, 453.592_37),
+    ("ml", Dimension::Volume, 1.0),
+    ("cl", Dimension::Volume, 10.0),
+    ("dl", Dimension::Volume, 100.0),
+    ("l", Dimension::Volume, 1000.0),
+    ("tsp", Dimension::Volume, 4.928_921_593_75),
+    ("tbsp", Dimension::Volume, 14.786_764_781_25),
+    ("cup", Dimension::Volume, 236.588_236_5),
+    ("piece", Dimension::Count, 1.0),
+];
+
+/// The canonical spelling of a unit, with an empty unit read as "piece".
+/// Unknown units are lowercased and trimmed
+pub fn normalize(unit: &str) -> String {
+    let unit = unit.trim();
+    if unit.is_empty() {
+        return "piece".to_string();
+    }
+    canonical_unit(unit).map_or_else(|| unit.to_lowercase(), str::to_string)
+}
+
+fn size(unit: &str) -> Option<(Dimension, f64)> {
+    let unit = normalize(unit);
+    SIZES
+        .iter()
+        .find(|(name, _, _)| *name == unit)
+        .map(|(_, dimension, size)| (*dimension, *size))
+}
+
+/// What `unit` measures, or None if it doesn't convert to anything
+pub fn dimension(unit: &str) -> Option<Dimension> {
+    size(unit).map(|(dimension, _)| dimension)
+}
+
+/// Returns true if `a` and `b` are the same unit, e.g. "Tablespoons" and "tbsp"
+pub fn same_unit(a: &str, b: &str) -> bool {
+    normalize(a) == normalize(b)
+}
+
+/// Converts `amount` from one unit to another of the same kind, e.g. 500 g to 0.5 kg.
+/// None if the units measure different things or aren't known
+pub fn convert(amount: f64, from: &str, to: &str) -> Option<f64> {
+    if same_unit(from, to) {
+        return Some(amount);
+    }
+    let (from_dimension, from_size) = size(from)?;
+    let (to_dimension, to_size) = size(to)?;
+    (from_dimension == to_dimension).then(|| amount * from_size / to_size)
+}
+
+/// The data a conversion between kinds of unit was missing
+#[derive(Debug, Clone, PartialEq)]
+pub enum MissingConversion {
+    /// Mass and volume need the ingredient's density
+    Density,
+    /// Counted pieces need the weight of one
+    UnitWeight,
+    /// The unit only matches itself, e.g. "clove"
+    Unit(String),
+}
+
+impl fmt::Display for MissingConversion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MissingConversion::Density => write!(f, "a density"),
+            MissingConversion::UnitWeight => write!(f, "a weight per piece"),
+            MissingConversion::Unit(unit) => write!(f, "a conversion for \"{}\"", unit),
+        }
+    }
+}
+
+/// Converts an amount of an ingredient between any two units, using the ingredient's density
+/// or unit weight to go between mass, volume and pieces
+pub fn convert_ingredient(
+    amount: f64,
+    from: &str,
+    to: &str,
+    ingredient: Option<&Ingredient>,
+) -> Result<f64, MissingConversion> {
+    if let Some(converted) = convert(amount, from, to) {
+        return Ok(converted);
+    }
+    let (from_dimension, from_size) =
+        size(from).ok_or_else(|| MissingConversion::Unit(normalize(from)))?;
+    let (to_dimension, to_size) = size(to).ok_or_else(|| MissingConversion::Unit(normalize(to)))?;
+    let grams = amount * from_size * grams_per(from_dimension, ingredient)?;
+    Ok(grams / grams_per(to_dimension, ingredient)? / to_size)
+}
+
+/// Grams in one gram, millilitre or piece of the ingredient
+fn grams_per(
+    dimension: Dimension,
+    ingredient: Option<&Ingredient>,
+) -> Result<f64, MissingConversion> {
+    match dimension {
+        Dimension::Mass => Ok(1.0),
+        Dimension::Volume => ingredient
+            .and_then(|i| i.density)
+            .filter(|d| *d > 0.0)
+            .ok_or(MissingConversion::Density),
+        Dimension::Count => ingredient
+            .and_then(|i| i.unit_weight)
+            .filter(|w| *w > 0.0)
+            .ok_or(MissingConversion::UnitWeight),
+    }
+}
+
+/// Units quantities are shown in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum UnitSystem {
+    /// As the recipe or pantry item gives them
+    #[default]
+    Original,
+    /// Grams, kilograms, millilitres and litres. Teaspoons and tablespoons stay as they are,
+    /// metric kitchens measure with them too
+    Metric,
+    /// Ounces, pounds, US cups and spoons
+    Imperial,
+}
+
+/// Fractions cups, spoons and pounds are rounded to
+const FRACTIONS: &[(f64, &str)] = &[
+    (0.0, ""),
+    (0.125, "⅛"),
+    (0.25, "¼"),
+    (1.0 / 3.0, "⅓"),
+    (0.5, "½"),
+    (2.0 / 3.0, "⅔"),
+    (0.75, "¾"),
+    (1.0, ""),
+];
+/// Ounces are rounded to halves
+const HALVES: &[(f64, &str)] = &[(0.0, ""), (0.5, "½"), (1.0, "")];
+
+/// The unit `system` shows an amount in, or None if it stays in `unit`
+fn display_unit(amount: f64, unit: &str, system: UnitSystem) -> Option<&'static str> {
+    let (dimension, size) = size(unit)?;
+    let base = amount.abs() * size;
+    let spoon = matches!(normalize(unit).as_str(), "tsp" | "tbsp");
+    let target = match (system, dimension) {
+        (UnitSystem::Original, _) | (_, Dimension::Count) => return None,
+        (UnitSystem::Metric, Dimension::Volume) if spoon => return None,
+        (UnitSystem::Metric, Dimension::Mass) if base >= 1000.0 => "kg",
+        (UnitSystem::Metric, Dimension::Mass) => "g",
+        (UnitSystem::Metric, Dimension::Volume) if base >= 1000.0 => "l",
+        (UnitSystem::Metric, Dimension::Volume) => "ml",
+        (UnitSystem::Imperial, Dimension::Mass) if base >= unit_size("lb") => "lb",
+        (UnitSystem::Imperial, Dimension::Mass) => "oz",
+        (UnitSystem::Imperial, Dimension::Volume) if base >= unit_size("cup") / 4.0 => "cup",
+        (UnitSystem::Imperial, Dimension::Volume) if base >= unit_size("tbsp") => "tbsp",
+        (UnitSystem::Imperial, Dimension::Volume) => "tsp",
+    };
+    (normalize(unit) != target).then_some(target)
+}
+
+fn unit_size(unit: &str) -> f64 {
+    size(unit).map_or(1.0, |(_, size)| size)
+}
+
+/// An amount rounded for measuring in `unit`, as text: "1 ½" cups, "355" ml, "1.25" kg
+fn friendly(amount: f64, unit: &str) -> (f64, String) {
+    match unit {
+        "cup" | "tbsp" | "tsp" | "lb" => with_fraction(amount, FRACTIONS, 10.0),
+        "oz" => with_fraction(amount, HALVES, 4.0),
+        "kg" | "l" => {
+            let value = (amount * 20.0).round() / 20.0;
+            (value, value.to_string())
+        }
+        _ => {
+            let step = if amount < 10.0 {
+                0.5
+            } else if amount < 100.0 {
+                1.0
+            } else {
+                5.0
+            };
+            let value = ((amount / step).round() * step).max(step);
+            (value, value.to_string())
+        }
+    }
+}
+
+/// A whole number and the nearest of `fractions`, e.g. "1 ½"; whole numbers only from
+/// `whole_from` up. Never rounds a positive amount down to nothing
+fn with_fraction(amount: f64, fractions: &[(f64, &str)], whole_from: f64) -> (f64, String) {
+    if amount >= whole_from {
+        let value = amount.round();
+        return (value, value.to_string());
+    }
+    let whole = amount.trunc();
+    let rest = amount - whole;
+    let (fraction, glyph) = fractions
+        .iter()
+        .min_by(|a, b| (rest - a.0).abs().total_cmp(&(rest - b.0).abs()))
+        .copied()
+        .unwrap_or((0.0, ""));
+    let (fraction, glyph) = if whole + fraction == 0.0 && amount > 0.0 {
+        fractions[1]
+    } else {
+        (fraction, glyph)
+    };
+    let value = whole + fraction;
+    let text = match (value.trunc(), glyph) {
+        (whole, "") => whole.to_string(),
+        (0.0, glyph) => glyph.to_string(),
+        (whole, glyph) => format!("{} {}", whole, glyph),
+    };
+    (value, text)
+}
+
+/// "cup" or "cups"; the other units shown are abbreviations that don't change
+fn unit_label(unit: &str, value: f64) -> String {
+    if unit == "cup" && value > 1.0 {
+        "cups".to_string()
+    } else {
+        unit.to_string()
+    }
+}
+
+/// An amount with its unit in `system`, e.g. 354.9 ml reads "1 ½ cups" in imperial.
+/// Converted amounts are rounded to values a cook would measure; amounts that keep their
+/// unit, including every amount in `UnitSystem::Original`, are shown as given
+pub fn format_amount(amount: f64, unit: &str, system: UnitSystem) -> String {
+    match display_unit(amount, unit, system) {
+        Some(target) => {
+            let (value, text) = friendly(convert(amount, unit, target).unwrap_or(amount), target);
+            format!("{} {}", text, unit_label(target, value))
+        }
+        None => format!("{} {}", amount, unit).trim_end().to_string(),
+    }
+}
+
+/// A recipe quantity with its unit in `system`, as `format_amount` shows amounts. Both ends
+/// of a range are shown in the unit the upper end needs: "1–1 ½ cups"
+pub fn format_quantity(quantity: &Quantity, unit: &str, system: UnitSystem) -> String {
+    let target = quantity
+        .value()
+        .and_then(|max| display_unit(max, unit, system));
+    let (Some(target), Some(max)) = (target, quantity.value()) else {
+        return format!("{} {}", quantity, unit).trim_end().to_string();
+    };
+    let shown = |amount: &Amount| {
+        friendly(
+            convert(amount.value(), unit, target).unwrap_or(amount.value()),
+            target,
+        )
+    };
+    let (value, text) = shown(&Amount::Decimal(max));
+    match quantity {
+        Quantity::Range { min, .. } => {
+            format!("{}–{} {}", shown(min).1, text, unit_label(target, value))
+        }
+        _ => format!("{} {}", text, unit_label(target, value)),
+    }
+}
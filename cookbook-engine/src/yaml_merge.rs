@@ -0,0 +1,156 @@
+//! Writing YAML over a hand-edited file without losing what the engine doesn't know about.
+//!
+//! The structs in `types` only cover the keys the engine uses, so serializing one drops any
+//! extra keys (`source`, `author`, ...), comments and custom key order a person added in a
+//! text editor. `merge_yaml` lays freshly serialized YAML over the original text one
+//! top-level key at a time: keys whose value didn't change keep their original text, changed
+//! keys get the new text, unknown keys are left alone, and comments stay attached to the key
+//! below them.
+use serde_yaml::{Mapping, Value};
+
+/// A top-level key and the lines that belong to it
+struct Block<'a> {
+    key: String,
+    /// Blank and comment lines directly above the key
+    leading: Vec<&'a str>,
+    /// The key line and everything nested under it
+    body: Vec<&'a str>,
+}
+
+/// A YAML mapping split into top-level blocks
+struct Document<'a> {
+    blocks: Vec<Block<'a>>,
+    /// Blank and comment lines after the last key
+    trailing: Vec<&'a str>,
+}
+
+/// Merges `updated` (freshly serialized YAML) into `original` (the text it replaces).
+///
+/// Keys in `original` that `updated` lacks are kept, keys only in `updated` are inserted
+/// after the key that precedes them there. If either text isn't a plain block mapping, the
+/// result is simply `updated`.
+pub fn merge_yaml(original: &str, updated: &str) -> String {
+    merge_yaml_clearing(original, updated, &[])
+}
+
+/// Like `merge_yaml`, for types that leave `optional_keys` out when they are empty: one of
+/// those keys missing from `updated` was cleared, so it is removed rather than kept
+pub fn merge_yaml_clearing(original: &str, updated: &str, optional_keys: &[&str]) -> String {
+    let (Some(old), Some(new)) = (split_blocks(original), split_blocks(updated)) else {
+        return updated.to_string();
+    };
+
+    let mut merged: Vec<Block> = old
+        .blocks
+        .iter()
+        .filter(|block| {
+            !optional_keys.contains(&block.key.as_str())
+                || new.blocks.iter().any(|b| b.key == block.key)
+        })
+        .map(|block| {
+            let body = match new.blocks.iter().find(|b| b.key == block.key) {
+                Some(replacement) if block_value(block) != block_value(replacement) => {
+                    replacement.body.clone()
+                }
+                // Unchanged, or a key the engine doesn't know about
+                _ => block.body.clone(),
+            };
+            Block {
+                key: block.key.clone(),
+                leading: block.leading.clone(),
+                body,
+            }
+        })
+        .collect();
+
+    // Keys the original lacks go right after the key that precedes them in `updated`
+    for (index, block) in new.blocks.iter().enumerate() {
+        if merged.iter().any(|b| b.key == block.key) {
+            continue;
+        }
+        let position = new.blocks[..index]
+            .iter()
+            .rev()
+            .find_map(|previous| merged.iter().position(|b| b.key == previous.key))
+            .map_or(0, |p| p + 1);
+        // A key added at the very top goes below a file header (comments followed by a blank
+        // line); comments directly above the first key stay with it
+        let leading = match merged.get_mut(position) {
+            Some(next) if position == 0 => {
+                let header_end = next
+                    .leading
+                    .iter()
+                    .rposition(|line| line.trim().is_empty())
+                    .map_or(0, |blank| blank + 1);
+                next.leading.drain(..header_end).collect()
+            }
+            _ => Vec::new(),
+        };
+        merged.insert(
+            position,
+            Block {
+                key: block.key.clone(),
+                leading,
+                body: block.body.clone(),
+            },
+        );
+    }
+
+    let mut lines: Vec<&str> = Vec::new();
+    for block in &merged {
+        lines.extend(&block.leading);
+        lines.extend(&block.body);
+    }
+    lines.extend(&old.trailing);
+    let mut text = lines.join("\n");
+    text.push('\n');
+    text
+}
+
+/// Splits a block mapping into its top-level keys, or None if it isn't one
+fn split_blocks(text: &str) -> Option<Document<'_>> {
+    let mut blocks: Vec<Block> = Vec::new();
+    let mut pending: Vec<&str> = Vec::new();
+    for line in text.lines() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            // Belongs to whichever key comes next
+            pending.push(line);
+        } else if line.starts_with([' ', '\t', '-']) {
+            // Nested under the current key (including top-level sequence items)
+            let block = blocks.last_mut()?;
+            block.body.append(&mut pending);
+            block.body.push(line);
+        } else {
+            blocks.push(Block {
+                key: top_level_key(line)?,
+                leading: std::mem::take(&mut pending),
+                body: vec![line],
+            });
+        }
+    }
+    Some(Document {
+        blocks,
+        trailing: pending,
+    })
+}
+
+/// Returns the key of a `key: value` line, unquoting it if needed
+fn top_level_key(line: &str) -> Option<String> {
+    if let Some(quote) = line.chars().next().filter(|c| matches!(c, '"' | '\'')) {
+        let rest = &line[1..];
+        let end = rest.find(quote)?;
+        return rest[end + 1..]
+            .starts_with(':')
+            .then(|| rest[..end].to_string());
+    }
+    let end = line
+        .find(": ")
+        .or_else(|| line.strip_suffix(':').map(str::len))?;
+    Some(line[..end].trim_end().to_string())
+}
+
+/// Parses a block back into its value, for comparing old and new text
+fn block_value(block: &Block) -> Option<Value> {
+    let mapping: Mapping = serde_yaml::from_str(&block.body.join("\n")).ok()?;
+    mapping.into_iter().next().map(|(_, value)| value)
+}
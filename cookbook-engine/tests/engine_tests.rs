@@ -285,6 +285,10 @@ fn test_create_ingredient_persists() {
         kb: None,
         tags: Some(vec!["vegetable".to_string()]),
         translations: None,
+        prices: Vec::new(),
+        packages: Vec::new(),
+        density: None,
+        unit_weight: None,
     };
 
     {
@@ -312,6 +316,10 @@ fn test_update_ingredient_renames_file() {
         kb: None,
         tags: None,
         translations: None,
+        prices: Vec::new(),
+        packages: Vec::new(),
+        density: None,
+        unit_weight: None,
     };
 
     {
@@ -339,8 +347,12 @@ fn test_delete_ingredient_removes_from_pantry() {
 
     {
         let mut dm = DataManager::new(&temp_path).unwrap();
-        assert!(dm.is_in_pantry("potato"), "potato should be in pantry before deletion");
-        dm.delete_ingredient("potato").unwrap();
+        assert!(
+            dm.is_in_pantry("potato"),
+            "potato should be in pantry before deletion"
+        );
+        dm.delete_ingredient("potato", DeleteStrategy::Cascade)
+            .unwrap();
     }
 
     // Reload and verify
@@ -374,8 +386,13 @@ fn test_ingredient_display_name_singular() {
         kb: None,
         tags: None,
         translations: Some(translations),
+        prices: Vec::new(),
+        packages: Vec::new(),
+        density: None,
+        unit_weight: None,
     };
-    let display = DataManager::ingredient_display_name(&ingredient, "en", Some(1.0));
+    let display =
+        DataManager::ingredient_display_name(&ingredient, "en", Some(&Quantity::from(1.0)));
     assert_eq!(display, "potato");
 }
 
@@ -396,8 +413,13 @@ fn test_ingredient_display_name_plural() {
         kb: None,
         tags: None,
         translations: Some(translations),
+        prices: Vec::new(),
+        packages: Vec::new(),
+        density: None,
+        unit_weight: None,
     };
-    let display = DataManager::ingredient_display_name(&ingredient, "en", Some(2.0));
+    let display =
+        DataManager::ingredient_display_name(&ingredient, "en", Some(&Quantity::from(2.0)));
     assert_eq!(display, "potatoes");
 }
 
@@ -410,8 +432,13 @@ fn test_ingredient_display_name_fallback() {
         kb: None,
         tags: None,
         translations: None,
+        prices: Vec::new(),
+        packages: Vec::new(),
+        density: None,
+        unit_weight: None,
     };
-    let display = DataManager::ingredient_display_name(&ingredient, "en", Some(1.0));
+    let display =
+        DataManager::ingredient_display_name(&ingredient, "en", Some(&Quantity::from(1.0)));
     assert_eq!(display, "mystery_herb");
 }
 
@@ -454,6 +481,10 @@ fn test_ingredient_yaml_roundtrip() {
         kb: Some("carrot".to_string()),
         tags: Some(vec!["root".to_string(), "vegetable".to_string()]),
         translations: Some(translations),
+        prices: Vec::new(),
+        packages: Vec::new(),
+        density: None,
+        unit_weight: None,
     };
 
     original.to_file(&path).unwrap();
@@ -485,12 +516,14 @@ fn test_pantry_yaml_roundtrip() {
                 quantity: Some(3.0),
                 quantity_type: "kg".to_string(),
                 last_updated: "2026-01-01".to_string(),
+                expires: None,
             },
             PantryItem {
                 ingredient: "onion".to_string(),
                 quantity: None,
                 quantity_type: "".to_string(),
                 last_updated: "2026-01-02".to_string(),
+                expires: None,
             },
         ],
     };
@@ -521,13 +554,15 @@ fn test_recipe_roundtrip() {
         ingredients: vec![
             RecipeIngredient {
                 ingredient: "flour".to_string(),
-                quantity: Some(200.0),
+                quantity: Some(Quantity::from(200.0)),
                 quantity_type: Some("g".to_string()),
+                ..Default::default()
             },
             RecipeIngredient {
                 ingredient: "egg".to_string(),
-                quantity: Some(2.0),
+                quantity: Some(Quantity::from(2.0)),
                 quantity_type: None,
+                ..Default::default()
             },
         ],
         prep_time: Some(15),
@@ -536,6 +571,7 @@ fn test_recipe_roundtrip() {
         tags: Some(vec!["baking".to_string(), "dessert".to_string()]),
         image: None,
         instructions: "Mix flour and eggs. Bake for 45 minutes.".to_string(),
+        slug: String::new(),
     };
 
     original.to_file(&path).unwrap();
@@ -554,7 +590,7 @@ fn test_recipe_roundtrip() {
         .iter()
         .find(|i| i.ingredient == "flour")
         .expect("Expected flour ingredient");
-    assert_eq!(flour_ing.quantity, Some(200.0));
+    assert_eq!(flour_ing.quantity, Some(Quantity::from(200.0)));
     assert_eq!(flour_ing.quantity_type.as_deref(), Some("g"));
 
     let egg_ing = loaded
@@ -562,5 +598,3038 @@ fn test_recipe_roundtrip() {
         .iter()
         .find(|i| i.ingredient == "egg")
         .expect("Expected egg ingredient");
-    assert_eq!(egg_ing.quantity, Some(2.0));
+    assert_eq!(egg_ing.quantity, Some(Quantity::from(2.0)));
+}
+
+// ─── Group 9: Pantry merge ────────────────────────────────────────────────────
+
+fn pantry_item(ingredient: &str, quantity: f64, last_updated: &str) -> PantryItem {
+    PantryItem {
+        ingredient: ingredient.to_string(),
+        quantity: Some(quantity),
+        quantity_type: "".to_string(),
+        last_updated: last_updated.to_string(),
+        expires: None,
+    }
+}
+
+fn pantry(items: Vec<PantryItem>) -> Pantry {
+    Pantry { version: 1, items }
+}
+
+#[test]
+fn test_merge_pantries_takes_one_sided_changes() {
+    let base = pantry(vec![
+        pantry_item("potato", 2.0, "2026-01-01"),
+        pantry_item("tomato", 3.0, "2026-01-01"),
+    ]);
+    // Local edits potato; remote deletes tomato and adds milk
+    let local = pantry(vec![
+        pantry_item("potato", 1.0, "2026-01-05"),
+        pantry_item("tomato", 3.0, "2026-01-01"),
+    ]);
+    let remote = pantry(vec![
+        pantry_item("potato", 2.0, "2026-01-01"),
+        pantry_item("milk", 1.0, "2026-01-04"),
+    ]);
+
+    let result = merge::merge_pantries(&base, &local, &remote);
+    assert!(
+        result.is_clean(),
+        "Expected no conflicts, got: {:?}",
+        result.conflicts
+    );
+
+    let names: Vec<&str> = result
+        .merged
+        .items
+        .iter()
+        .map(|i| i.ingredient.as_str())
+        .collect();
+    assert_eq!(names, vec!["potato", "milk"]);
+    assert_eq!(result.merged.items[0].quantity, Some(1.0));
+}
+
+#[test]
+fn test_merge_pantries_newer_edit_wins() {
+    let base = pantry(vec![pantry_item("potato", 2.0, "2026-01-01")]);
+    let local = pantry(vec![pantry_item("potato", 1.0, "2026-01-03")]);
+    let remote = pantry(vec![pantry_item("potato", 5.0, "2026-01-10")]);
+
+    let result = merge::merge_pantries(&base, &local, &remote);
+    assert!(result.is_clean());
+    assert_eq!(result.merged.items[0].quantity, Some(5.0));
+}
+
+#[test]
+fn test_merge_pantries_reports_conflicts() {
+    let base = pantry(vec![
+        pantry_item("potato", 2.0, "2026-01-01"),
+        pantry_item("tomato", 3.0, "2026-01-01"),
+    ]);
+    // Same-day edits to potato; tomato deleted locally but edited remotely
+    let local = pantry(vec![pantry_item("potato", 1.0, "2026-01-05")]);
+    let remote = pantry(vec![
+        pantry_item("potato", 4.0, "2026-01-05"),
+        pantry_item("tomato", 6.0, "2026-01-05"),
+    ]);
+
+    let result = merge::merge_pantries(&base, &local, &remote);
+    assert_eq!(result.conflicts.len(), 2);
+
+    let potato = result
+        .conflicts
+        .iter()
+        .find(|c| c.ingredient == "potato")
+        .unwrap();
+    assert_eq!(potato.kind, merge::PantryConflictKind::BothModified);
+    let tomato = result
+        .conflicts
+        .iter()
+        .find(|c| c.ingredient == "tomato")
+        .unwrap();
+    assert_eq!(tomato.kind, merge::PantryConflictKind::DeletedLocally);
+
+    // Nothing is dropped: local wins the same-day edit, the remote edit survives the delete
+    let merged_potato = result
+        .merged
+        .items
+        .iter()
+        .find(|i| i.ingredient == "potato")
+        .unwrap();
+    assert_eq!(merged_potato.quantity, Some(1.0));
+    assert!(result.merged.items.iter().any(|i| i.ingredient == "tomato"));
+}
+
+#[test]
+fn test_merge_pantry_conflict_copy() {
+    let temp_dir = setup_temp_data_dir();
+    let temp_path = temp_dir.path().to_path_buf();
+
+    let copy_path = temp_path.join("pantry (Laptop's conflicted copy 2026-03-01).yaml");
+    pantry(vec![
+        pantry_item("flour", 1.0, "2026-03-01"),
+        pantry_item("potato", 9.0, "2026-03-01"),
+    ])
+    .to_file(&copy_path)
+    .unwrap();
+    std::fs::write(temp_path.join("notes (conflicted copy).txt"), "unrelated").unwrap();
+
+    let mut dm = DataManager::new(&temp_path).unwrap();
+    let copies = dm.find_pantry_conflict_copies().unwrap();
+    assert_eq!(copies, vec![copy_path.clone()]);
+
+    let result = dm.merge_pantry_conflict_copy(&copy_path).unwrap();
+    assert!(
+        result.is_clean(),
+        "Expected a clean merge, got: {:?}",
+        result.conflicts
+    );
+    assert!(
+        !copy_path.exists(),
+        "Expected the conflict copy to be removed"
+    );
+
+    // The copy's potato entry is newer than the fixture's, and flour is new
+    let dm2 = DataManager::new(&temp_path).unwrap();
+    assert!(dm2.is_in_pantry("flour"));
+    assert_eq!(dm2.get_pantry_item("potato").unwrap().quantity, Some(9.0));
+    assert!(
+        dm2.is_in_pantry("tomato"),
+        "Expected local-only items to survive"
+    );
+}
+
+// ─── Group 10: Schema migrations ─────────────────────────────────────────────
+
+#[test]
+fn test_legacy_files_load_without_version() {
+    // The fixture files predate schema versioning
+    let content = std::fs::read_to_string(fixture_data_dir().join("ingredients/egg.yaml")).unwrap();
+    assert!(!content.contains("version"));
+
+    let dm = DataManager::new(fixture_data_dir()).unwrap();
+    assert!(dm.get_ingredient("egg").is_some());
+}
+
+#[test]
+fn test_written_files_carry_schema_version() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("leek.yaml");
+    let ingredient = Ingredient {
+        name: "leek".to_string(),
+        slug: "leek".to_string(),
+        category: "vegetable".to_string(),
+        kb: None,
+        tags: None,
+        translations: None,
+        prices: Vec::new(),
+        packages: Vec::new(),
+        density: None,
+        unit_weight: None,
+    };
+    ingredient.to_file(&path).unwrap();
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    let expected = format!(
+        "version: {}\n",
+        migration::DataFileKind::Ingredient.current_version()
+    );
+    assert!(content.starts_with(&expected), "Got: {}", content);
+}
+
+#[test]
+fn test_newer_schema_version_is_rejected() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("future.yaml");
+    std::fs::write(
+        &path,
+        "version: 999\nname: future\nslug: future\ncategory: x\nkb: null\ntags: null\ntranslations: null\n",
+    )
+    .unwrap();
+
+    let result = Ingredient::from_file(&path);
+    assert!(matches!(result, Err(CookbookError::SchemaVersionError(_))));
+}
+
+#[test]
+fn test_migrate_data_dir_dry_run_then_apply() {
+    let temp_dir = setup_temp_data_dir();
+    let temp_path = temp_dir.path().to_path_buf();
+
+    // An unknown key and comments must survive the rewrite
+    let egg_path = temp_path.join("ingredients/egg.yaml");
+    let mut egg = std::fs::read_to_string(&egg_path).unwrap();
+    egg.push_str("# Free range only\nsupplier: farm shop\n");
+    std::fs::write(&egg_path, &egg).unwrap();
+    let lasagna_path = temp_path.join("recipes/Lasagna.md");
+    let lasagna = std::fs::read_to_string(&lasagna_path).unwrap().replacen(
+        "---\n",
+        "---\n# Family recipe\n",
+        1,
+    );
+    std::fs::write(&lasagna_path, &lasagna).unwrap();
+
+    let plan = migration::migrate_data_dir(&temp_path, true).unwrap();
+    assert!(plan.dry_run);
+    assert!(
+        plan.failed.is_empty(),
+        "Unexpected failures: {:?}",
+        plan.failed
+    );
+    // 10 ingredients and 2 recipes are unversioned; pantry.yaml is already version 1
+    assert_eq!(plan.migrated.len(), 12);
+    assert_eq!(plan.up_to_date, 1);
+    assert_eq!(
+        std::fs::read_to_string(&egg_path).unwrap(),
+        egg,
+        "Dry run must not write"
+    );
+
+    let report = migration::migrate_data_dir(&temp_path, false).unwrap();
+    assert_eq!(report.migrated.len(), 12);
+    // Only the version is added; the rest of the text is left as it was
+    assert_eq!(
+        std::fs::read_to_string(&egg_path).unwrap(),
+        format!("version: 1\n{}", egg)
+    );
+    assert_eq!(
+        std::fs::read_to_string(&lasagna_path).unwrap(),
+        lasagna.replacen("---\n", "---\nversion: 1\n", 1)
+    );
+
+    let again = migration::migrate_data_dir(&temp_path, true).unwrap();
+    assert!(again.migrated.is_empty());
+
+    // Everything still loads, instructions included
+    let dm = DataManager::new(&temp_path).unwrap();
+    assert_eq!(dm.get_all_ingredients().len(), 10);
+    let lasagna = dm.get_recipe("Lasagna").unwrap();
+    assert!(lasagna
+        .instructions
+        .starts_with("Start by boiling the potatoes."));
+}
+
+// ─── Group 11: Knowledge base CRUD ───────────────────────────────────────────
+
+fn sample_kb_entry(slug: &str, title: &str) -> KnowledgeBaseEntry {
+    KnowledgeBaseEntry {
+        slug: slug.to_string(),
+        title: title.to_string(),
+        image: None,
+        content: "\n# Notes\n\nSome text.\n".to_string(),
+    }
+}
+
+#[test]
+fn test_bundled_kb_entries_available_without_kb_dir() {
+    // The fixture has no kb directory, so every entry comes from the bundled set
+    let dm = DataManager::new(fixture_data_dir()).unwrap();
+    assert_eq!(dm.get_all_kb_entries().len(), 11);
+    let potato = dm.get_kb_entry("potato").unwrap();
+    assert_eq!(potato.title, "Potato");
+    assert!(dm.is_kb_entry_bundled("potato"));
+    assert!(dm.get_kb_image_path("potato.jpg").is_none());
+    assert!(dm.get_kb_image_data("potato.jpg").is_some());
+    assert!(dm.get_kb_image_data("../pantry.yaml").is_none());
+}
+
+#[test]
+fn test_create_update_delete_kb_entry() {
+    let temp_dir = setup_temp_data_dir();
+    let temp_path = temp_dir.path().to_path_buf();
+    let mut dm = DataManager::new(&temp_path).unwrap();
+
+    dm.create_kb_entry(sample_kb_entry("butter", "Butter"))
+        .unwrap();
+    assert!(temp_path.join("kb/butter.md").exists());
+    assert!(!dm.is_kb_entry_bundled("butter"));
+    assert!(dm
+        .create_kb_entry(sample_kb_entry("butter", "Again"))
+        .is_err());
+    assert!(dm
+        .create_kb_entry(sample_kb_entry("../escape", "Bad"))
+        .is_err());
+    // KB files follow the recipe and ingredient slug rules, case-only clashes included
+    assert!(dm.create_kb_entry(sample_kb_entry("con", "Bad")).is_err());
+    assert!(dm
+        .create_kb_entry(sample_kb_entry("Butter", "Again"))
+        .is_err());
+    dm.create_kb_entry(sample_kb_entry("ghee", "Ghee")).unwrap();
+    assert!(dm
+        .update_kb_entry("ghee", sample_kb_entry("BUTTER", "Ghee"))
+        .is_err());
+
+    let mut updated = sample_kb_entry("butter", "Butter & Ghee");
+    updated.content = "\nClarified.\n".to_string();
+    dm.update_kb_entry("butter", updated).unwrap();
+
+    let reloaded = DataManager::new(&temp_path).unwrap();
+    let butter = reloaded.get_kb_entry("butter").unwrap();
+    assert_eq!(butter.title, "Butter & Ghee");
+    assert!(butter.content.contains("Clarified."));
+
+    dm.set_kb_links("butter", &["butter".to_string()]).unwrap();
+    dm.delete_kb_entry("butter").unwrap();
+    assert!(dm.get_kb_entry("butter").is_none());
+    assert!(!temp_path.join("kb/butter.md").exists());
+
+    // Ingredients no longer link to the deleted entry
+    assert_eq!(dm.get_ingredient("butter").unwrap().kb, None);
+    let reloaded = DataManager::new(&temp_path).unwrap();
+    assert_eq!(reloaded.get_ingredient("butter").unwrap().kb, None);
+}
+
+#[test]
+fn test_data_dir_kb_entry_overrides_bundled() {
+    let temp_dir = setup_temp_data_dir();
+    let temp_path = temp_dir.path().to_path_buf();
+    let mut dm = DataManager::new(&temp_path).unwrap();
+
+    // Bundled entries cannot be deleted, but editing one saves an override
+    assert!(dm.delete_kb_entry("potato").is_err());
+    dm.update_kb_entry("potato", sample_kb_entry("potato", "My Potato"))
+        .unwrap();
+    assert!(temp_path.join("kb/potato.md").exists());
+
+    let mut reloaded = DataManager::new(&temp_path).unwrap();
+    assert_eq!(reloaded.get_kb_entry("potato").unwrap().title, "My Potato");
+    assert!(!reloaded.is_kb_entry_bundled("potato"));
+
+    // Deleting the override falls back to the bundled article
+    reloaded.delete_kb_entry("potato").unwrap();
+    assert_eq!(reloaded.get_kb_entry("potato").unwrap().title, "Potato");
+    assert!(reloaded.is_kb_entry_bundled("potato"));
+}
+
+#[test]
+fn test_kb_entry_files_named_differently_from_their_slug() {
+    let temp_dir = setup_temp_data_dir();
+    let temp_path = temp_dir.path().to_path_buf();
+    std::fs::create_dir_all(temp_path.join("kb")).unwrap();
+    sample_kb_entry("butter", "Butter")
+        .to_file(temp_path.join("kb/notes.md"))
+        .unwrap();
+    sample_kb_entry("ghee", "Ghee")
+        .to_file(temp_path.join("kb/clarified.md"))
+        .unwrap();
+    let mut dm = DataManager::new(&temp_path).unwrap();
+
+    // Deleting removes the file the entry was read from, so it stays deleted
+    dm.delete_kb_entry("butter").unwrap();
+    assert!(!temp_path.join("kb/notes.md").exists());
+
+    // Saving moves the entry to a file named after its slug
+    dm.update_kb_entry("ghee", sample_kb_entry("ghee", "Ghee & Butter Oil"))
+        .unwrap();
+    assert!(!temp_path.join("kb/clarified.md").exists());
+    assert!(temp_path.join("kb/ghee.md").exists());
+
+    let reloaded = DataManager::new(&temp_path).unwrap();
+    assert!(reloaded.get_kb_entry("butter").is_none());
+    assert_eq!(
+        reloaded.get_kb_entry("ghee").unwrap().title,
+        "Ghee & Butter Oil"
+    );
+}
+
+#[test]
+fn test_rename_kb_entry_updates_ingredient_refs() {
+    let temp_dir = setup_temp_data_dir();
+    let temp_path = temp_dir.path().to_path_buf();
+    let mut dm = DataManager::new(&temp_path).unwrap();
+
+    dm.create_kb_entry(sample_kb_entry("butter", "Butter"))
+        .unwrap();
+    dm.update_kb_entry("butter", sample_kb_entry("dairy-butter", "Butter"))
+        .unwrap();
+
+    assert!(dm.get_kb_entry("butter").is_none());
+    assert!(!temp_path.join("kb/butter.md").exists());
+    let reloaded = DataManager::new(&temp_path).unwrap();
+    let butter = reloaded.get_ingredient("butter").unwrap();
+    assert_eq!(butter.kb.as_deref(), Some("dairy-butter"));
+}
+
+#[test]
+fn test_set_kb_links() {
+    let temp_dir = setup_temp_data_dir();
+    let temp_path = temp_dir.path().to_path_buf();
+    let mut dm = DataManager::new(&temp_path).unwrap();
+
+    // potato already links to "potato"; link egg and flour instead
+    let changed = dm
+        .set_kb_links("potato", &["egg".to_string(), "flour".to_string()])
+        .unwrap();
+    assert_eq!(changed, vec!["egg", "flour", "potato"]);
+    assert!(dm
+        .set_kb_links("potato", &["unobtainium".to_string()])
+        .is_err());
+    assert!(dm.set_kb_links("no-such-article", &[]).is_err());
+
+    let reloaded = DataManager::new(&temp_path).unwrap();
+    let mut linked: Vec<&str> = reloaded
+        .get_ingredients_with_kb_reference("potato")
+        .iter()
+        .map(|i| i.name.as_str())
+        .collect();
+    linked.sort();
+    assert_eq!(linked, vec!["egg", "flour"]);
+    assert_eq!(reloaded.get_ingredient("potato").unwrap().kb, None);
+}
+
+// ─── Group 12: Wiki links and backlinks ──────────────────────────────────────
+
+#[test]
+fn test_parse_wiki_links() {
+    let text = "Boil [[potato]]es, then see [[ Lasagna | the lasagna ]]. Not [[]] or [[a\nb]]; [[x [[basil]].";
+    let links = links::parse_wiki_links(text);
+    let targets: Vec<&str> = links.iter().map(|l| l.target.as_str()).collect();
+    assert_eq!(targets, vec!["potato", "Lasagna", "basil"]);
+    assert_eq!(links[1].display_text(), "the lasagna");
+    assert_eq!(&text[links[0].start..links[0].end], "[[potato]]");
+
+    let uri = links::LinkTarget::Recipe("Lasagna".to_string()).to_uri();
+    assert_eq!(uri, "cookbook://recipe/Lasagna");
+    assert_eq!(
+        links::LinkTarget::from_uri(&uri),
+        Some(links::LinkTarget::Recipe("Lasagna".to_string()))
+    );
+    assert_eq!(links::LinkTarget::from_uri("https://example.com"), None);
+}
+
+#[test]
+fn test_resolve_wiki_link() {
+    let dm = DataManager::new(fixture_data_dir()).unwrap();
+    // KB articles win over the ingredient of the same name
+    assert_eq!(
+        dm.resolve_wiki_link("Potato"),
+        Some(links::LinkTarget::Kb("potato".to_string()))
+    );
+    assert_eq!(
+        dm.resolve_wiki_link("olive oil"),
+        Some(links::LinkTarget::Kb("olive_oil".to_string()))
+    );
+    assert_eq!(
+        dm.resolve_wiki_link("egg"),
+        Some(links::LinkTarget::Kb("egg".to_string()))
+    );
+    assert_eq!(
+        dm.resolve_wiki_link("yoghurt"),
+        Some(links::LinkTarget::Ingredient("yoghurt".to_string()))
+    );
+    assert_eq!(
+        dm.resolve_wiki_link("spaghetti aglio e olio"),
+        Some(links::LinkTarget::Recipe(
+            "Spaghetti Aglio e Olio".to_string()
+        ))
+    );
+    assert_eq!(dm.resolve_wiki_link("unobtainium"), None);
+}
+
+#[test]
+fn test_broken_links_and_backlinks() {
+    let temp_dir = setup_temp_data_dir();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+
+    let mut lasagna = dm.get_recipe("Lasagna").unwrap().clone();
+    lasagna.instructions =
+        "Boil the [[potato]]es. Serve with [[Spaghetti Aglio e Olio]] and [[unobtainium]]."
+            .to_string();
+    dm.update_recipe("Lasagna", lasagna).unwrap();
+
+    let broken = dm.find_broken_links();
+    assert!(broken.contains(&links::BrokenLink {
+        source: links::LinkSource::Recipe("Lasagna".to_string()),
+        target: "unobtainium".to_string(),
+    }));
+    // butter.yaml links to a KB article that doesn't exist
+    assert!(broken.contains(&links::BrokenLink {
+        source: links::LinkSource::Ingredient("butter".to_string()),
+        target: "butter".to_string(),
+    }));
+
+    assert_eq!(
+        dm.get_backlinks(&links::LinkTarget::Kb("potato".to_string())),
+        vec![links::LinkSource::Recipe("Lasagna".to_string())]
+    );
+    assert_eq!(
+        dm.get_backlinks(&links::LinkTarget::Recipe(
+            "Spaghetti Aglio e Olio".to_string()
+        )),
+        vec![links::LinkSource::Recipe("Lasagna".to_string())]
+    );
+    assert!(dm
+        .get_backlinks(&links::LinkTarget::Recipe("Lasagna".to_string()))
+        .is_empty());
+}
+
+// ─── Group 13: Markdown rendering ────────────────────────────────────────────
+
+fn no_links(_: &str) -> Option<links::LinkTarget> {
+    None
+}
+
+#[test]
+fn test_markdown_to_pango() {
+    let md = "# Title\n\nSome *soft* and **bold** text with `code`, a [link](https://example.com) and olive_oil.\n\n- one\n  - nested\n- two\n\n1. first\n2. second\n\n> quoted <b>";
+    let pango = markdown::render_to_string(md, markdown::MarkupFormat::Pango, &no_links);
+    assert_eq!(
+        pango,
+        "<span size=\"xx-large\" weight=\"bold\">Title</span>\n\n\
+         Some <i>soft</i> and <b>bold</b> text with <tt>code</tt>, a <a href=\"https://example.com\">link</a> and olive_oil.\n\n\
+         • one\n    ◦ nested\n• two\n\n\
+         1. first\n2. second\n\n\
+         <i>▎ quoted &lt;b&gt;</i>"
+    );
+
+    // Tables become padded monospace columns
+    let table = "| Dish | Note |\n|---|---|\n| Rösti | **Crispy** |\n| Mash | Soft |";
+    let pango = markdown::render_to_string(table, markdown::MarkupFormat::Pango, &no_links);
+    assert_eq!(
+        pango,
+        "<tt><b>Dish   Note</b>\n─────────────\nRösti  <b>Crispy</b>\nMash   Soft</tt>"
+    );
+}
+
+#[test]
+fn test_markdown_to_html() {
+    let md = "## Steps\n\n1. Boil\n   - salted water\n2. Drain\n\n```\nlet x = 1 < 2;\n```\n\n---\n\n![A potato](potato.jpg)";
+    let html = markdown::render_to_string(md, markdown::MarkupFormat::Html, &no_links);
+    assert_eq!(
+        html,
+        "<h2>Steps</h2>\n\
+         <ol>\n<li>Boil<ul>\n<li>salted water</li>\n</ul>\n</li>\n<li>Drain</li>\n</ol>\n\
+         <pre><code>let x = 1 &lt; 2;</code></pre>\n\
+         <hr>\n\
+         <img src=\"potato.jpg\" alt=\"A potato\">"
+    );
+}
+
+#[test]
+fn test_markdown_links_only_allowed_schemes() {
+    let html = |md: &str| markdown::render_to_string(md, markdown::MarkupFormat::Html, &no_links);
+    assert_eq!(
+        html("[site](https://example.com)"),
+        "<p><a href=\"https://example.com\">site</a></p>"
+    );
+    assert_eq!(
+        html("[mail](mailto:cook@example.com)"),
+        "<p><a href=\"mailto:cook@example.com\">mail</a></p>"
+    );
+    // The link ends at the first ")", as for any other link
+    assert_eq!(html("[x](javascript:alert(1))"), "<p>x)</p>");
+    assert_eq!(html("[x](JavaScript:void)"), "<p>x</p>");
+    assert_eq!(html("[x](data:text/html,hi)"), "<p>x</p>");
+    assert_eq!(
+        markdown::render_to_string(
+            "[x](javascript:void)",
+            markdown::MarkupFormat::Pango,
+            &no_links
+        ),
+        "x"
+    );
+}
+
+#[test]
+fn test_markdown_images_and_wiki_links() {
+    let dm = DataManager::new(fixture_data_dir()).unwrap();
+    let resolve = |target: &str| dm.resolve_wiki_link(target);
+    let md = "Intro about [[potato]] and [[unobtainium]].\n\n![Potato](potato.jpg)\n\nOutro.";
+    let segments = markdown::render(md, markdown::MarkupFormat::Pango, &resolve);
+    assert_eq!(
+        segments,
+        vec![
+            markdown::Rendered::Markup(
+                "Intro about <a href=\"cookbook://kb/potato\">potato</a> and <span underline=\"error\">unobtainium</span>."
+                    .to_string()
+            ),
+            markdown::Rendered::Image {
+                src: "potato.jpg".to_string(),
+                alt: "Potato".to_string()
+            },
+            markdown::Rendered::Markup("Outro.".to_string()),
+        ]
+    );
+    assert_eq!(markdown::image_sources(md), vec!["potato.jpg"]);
+
+    // Every bundled article renders to valid-looking markup
+    for entry in dm.get_all_kb_entries() {
+        let pango = dm.render_markdown(&entry.content, markdown::MarkupFormat::Pango);
+        assert!(
+            !pango.contains("**"),
+            "Unrendered emphasis in {}",
+            entry.slug
+        );
+        assert!(
+            !pango.contains("\n# "),
+            "Unrendered heading in {}",
+            entry.slug
+        );
+    }
+}
+
+// ─── Group 14: Ingredient rename propagation ─────────────────────────────────
+
+fn renamed_potato() -> Ingredient {
+    Ingredient {
+        name: "sweet_potato".to_string(),
+        slug: "sweet_potato".to_string(),
+        category: "vegetable".to_string(),
+        kb: Some("potato".to_string()),
+        tags: None,
+        translations: None,
+        prices: Vec::new(),
+        packages: Vec::new(),
+        density: None,
+        unit_weight: None,
+    }
+}
+
+#[test]
+fn test_preview_ingredient_rename_is_dry_run() {
+    let temp_dir = setup_temp_data_dir();
+    let dm = DataManager::new(temp_dir.path()).unwrap();
+    assert_eq!(dm.preview_ingredient_rename("potato"), vec!["Lasagna"]);
+    assert!(dm.preview_ingredient_rename("unobtainium").is_empty());
+
+    // Nothing was written
+    let lasagna = temp_dir.path().join("recipes/Lasagna.md");
+    assert!(std::fs::read_to_string(lasagna)
+        .unwrap()
+        .contains("ingredient: potato"));
+}
+
+#[test]
+fn test_rename_ingredient_rewrites_recipes() {
+    let temp_dir = setup_temp_data_dir();
+    {
+        let mut dm = DataManager::new(temp_dir.path()).unwrap();
+        let changed = dm.rename_ingredient("potato", renamed_potato()).unwrap();
+        assert_eq!(changed, vec!["Lasagna"]);
+        let lasagna = dm.get_recipe("Lasagna").unwrap();
+        assert!(lasagna
+            .ingredients
+            .iter()
+            .any(|i| i.ingredient == "sweet_potato"));
+    }
+
+    // Recipe files on disk carry the new name, with no staging files left behind
+    let dm = DataManager::new(temp_dir.path()).unwrap();
+    let lasagna = dm.get_recipe("Lasagna").unwrap();
+    assert!(lasagna
+        .ingredients
+        .iter()
+        .any(|i| i.ingredient == "sweet_potato"));
+    assert!(!lasagna.ingredients.iter().any(|i| i.ingredient == "potato"));
+    assert_eq!(dm.get_recipes_with_ingredient("sweet_potato").len(), 1);
+    let leftovers: Vec<_> = std::fs::read_dir(temp_dir.path().join("recipes"))
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().ends_with(".tmp"))
+        .collect();
+    assert!(
+        leftovers.is_empty(),
+        "Staging files left behind: {:?}",
+        leftovers
+    );
+}
+
+#[test]
+fn test_rename_ingredient_without_name_change_touches_no_recipes() {
+    let temp_dir = setup_temp_data_dir();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    let mut potato = dm.get_ingredient("potato").unwrap().clone();
+    potato.category = "root".to_string();
+    assert!(dm.rename_ingredient("potato", potato).unwrap().is_empty());
+    assert_eq!(dm.get_ingredient("potato").unwrap().category, "root");
+}
+
+// ─── Group 15: Ingredient deletion integrity ─────────────────────────────────
+
+#[test]
+fn test_ingredient_dependents() {
+    let temp_dir = setup_temp_data_dir();
+    std::fs::create_dir_all(temp_dir.path().join("kb")).unwrap();
+    std::fs::write(
+        temp_dir.path().join("kb/spuds.md"),
+        "---\nslug: spuds\ntitle: Spuds\n---\n\nSee [[sweet potato]] and [[Lasagna]].\n",
+    )
+    .unwrap();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    dm.create_ingredient(Ingredient {
+        name: "sweet potato".to_string(),
+        slug: "sweet_potato".to_string(),
+        category: "vegetable".to_string(),
+        kb: None,
+        tags: None,
+        translations: None,
+        prices: Vec::new(),
+        packages: Vec::new(),
+        density: None,
+        unit_weight: None,
+    })
+    .unwrap();
+
+    let potato = dm.ingredient_dependents("potato");
+    assert_eq!(potato.recipes, vec!["Lasagna"]);
+    let sweet = dm.ingredient_dependents("sweet potato");
+    assert!(sweet.recipes.is_empty());
+    assert_eq!(sweet.kb_links, vec!["spuds"]);
+    assert!(dm.ingredient_dependents("unobtainium").is_empty());
+}
+
+#[test]
+fn test_delete_ingredient_blocked_while_in_use() {
+    let temp_dir = setup_temp_data_dir();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    let err = dm
+        .delete_ingredient("potato", DeleteStrategy::Block)
+        .unwrap_err();
+    assert!(matches!(err, CookbookError::InUseError(_)));
+    assert!(err.to_string().contains("Lasagna"));
+    assert!(dm.get_ingredient("potato").is_some());
+    assert!(dm.is_in_pantry("potato"));
+    assert!(temp_dir.path().join("ingredients/potato.yaml").exists());
+}
+
+#[test]
+fn test_delete_ingredient_cascade_strips_recipes() {
+    let temp_dir = setup_temp_data_dir();
+    {
+        let mut dm = DataManager::new(temp_dir.path()).unwrap();
+        let count = dm.get_recipe("Lasagna").unwrap().ingredients.len();
+        let dependents = dm
+            .delete_ingredient("potato", DeleteStrategy::Cascade)
+            .unwrap();
+        assert_eq!(dependents.recipes, vec!["Lasagna"]);
+        assert_eq!(
+            dm.get_recipe("Lasagna").unwrap().ingredients.len(),
+            count - 1
+        );
+    }
+    let dm = DataManager::new(temp_dir.path()).unwrap();
+    let lasagna = dm.get_recipe("Lasagna").unwrap();
+    assert!(!lasagna.ingredients.iter().any(|i| i.ingredient == "potato"));
+    assert!(dm.get_ingredient("potato").is_none());
+}
+
+#[test]
+fn test_delete_ingredient_replace() {
+    let temp_dir = setup_temp_data_dir();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    assert!(dm
+        .delete_ingredient("potato", DeleteStrategy::Replace("potato".to_string()))
+        .is_err());
+    assert!(dm
+        .delete_ingredient("potato", DeleteStrategy::Replace("unobtainium".to_string()))
+        .is_err());
+    assert!(dm.get_ingredient("potato").is_some());
+
+    // A recipe already listing the replacement keeps a single entry for it
+    let count = dm.get_recipe("Lasagna").unwrap().ingredients.len();
+    dm.delete_ingredient("potato", DeleteStrategy::Replace("tomato".to_string()))
+        .unwrap();
+    let lasagna = DataManager::new(temp_dir.path())
+        .unwrap()
+        .get_recipe("Lasagna")
+        .unwrap()
+        .clone();
+    assert!(!lasagna.ingredients.iter().any(|i| i.ingredient == "potato"));
+    assert_eq!(
+        lasagna
+            .ingredients
+            .iter()
+            .filter(|i| i.ingredient == "tomato")
+            .count(),
+        1
+    );
+    assert_eq!(lasagna.ingredients.len(), count - 1);
+}
+
+// ─── Group 16: Duplicate ingredients ─────────────────────────────────────────
+
+fn ingredient_with(name: &str, tags: &[&str], translations: &[(&str, &str, &str)]) -> Ingredient {
+    Ingredient {
+        name: name.to_string(),
+        slug: name.replace(' ', "_"),
+        category: "vegetable".to_string(),
+        kb: None,
+        tags: Some(tags.iter().map(|t| t.to_string()).collect()),
+        translations: Some(
+            translations
+                .iter()
+                .map(|(lang, one, other)| {
+                    (
+                        lang.to_string(),
+                        TranslationForms {
+                            one: one.to_string(),
+                            other: other.to_string(),
+                        },
+                    )
+                })
+                .collect(),
+        ),
+        prices: Vec::new(),
+        packages: Vec::new(),
+        density: None,
+        unit_weight: None,
+    }
+}
+
+#[test]
+fn test_suggest_duplicate_ingredients() {
+    let temp_dir = setup_temp_data_dir();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    assert!(
+        dm.suggest_duplicate_ingredients().is_empty(),
+        "Fixture has no duplicates"
+    );
+
+    dm.create_ingredient(ingredient_with("tomatoes", &[], &[]))
+        .unwrap();
+    dm.create_ingredient(ingredient_with(
+        "kartoffel",
+        &[],
+        &[("en", "potato", "potatoes")],
+    ))
+    .unwrap();
+    let suggestions = dm.suggest_duplicate_ingredients();
+    let pairs: Vec<(&str, &str)> = suggestions
+        .iter()
+        .map(|s| (s.first.as_str(), s.second.as_str()))
+        .collect();
+    assert_eq!(pairs, vec![("tomato", "tomatoes"), ("kartoffel", "potato")]);
+    assert_eq!(suggestions[0].score, 1.0);
+    assert_eq!(
+        suggestions[1].reasons,
+        vec![
+            "Shared translation 'potato'",
+            "Shared translation 'potatoes'"
+        ]
+    );
+
+    assert!(duplicates::slug_similarity("tomato", "potato") < 0.85);
+    assert_eq!(duplicates::slug_similarity("Red-Onions", "red_onion"), 1.0);
+}
+
+#[test]
+fn test_merge_ingredients_combines_and_rewrites() {
+    let temp_dir = setup_temp_data_dir();
+    {
+        let mut dm = DataManager::new(temp_dir.path()).unwrap();
+        dm.create_ingredient(ingredient_with(
+            "tomatoes",
+            &["vegetable", "red"],
+            &[("en", "tomatoes", "tomatoes"), ("da", "tomat", "tomater")],
+        ))
+        .unwrap();
+        dm.update_pantry_item("tomatoes", Some(3.0), None).unwrap();
+        let mut lasagna = dm.get_recipe("Lasagna").unwrap().clone();
+        lasagna.ingredients.push(RecipeIngredient {
+            ingredient: "tomatoes".to_string(),
+            quantity: Some(Quantity::from(0.5)),
+            quantity_type: Some("kg".to_string()),
+            ..Default::default()
+        });
+        dm.update_recipe("Lasagna", lasagna).unwrap();
+
+        let result = dm.merge_ingredients("tomato", "tomatoes").unwrap();
+        assert_eq!(result.recipes, vec!["Lasagna"]);
+        assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+    }
+
+    let dm = DataManager::new(temp_dir.path()).unwrap();
+    assert!(dm.get_ingredient("tomatoes").is_none());
+    assert!(!temp_dir.path().join("ingredients/tomatoes.yaml").exists());
+
+    let tomato = dm.get_ingredient("tomato").unwrap();
+    let translations = tomato.translations.as_ref().unwrap();
+    assert_eq!(
+        translations["en"].other, "tomatoes",
+        "Kept ingredient's translation wins"
+    );
+    assert_eq!(translations["da"].one, "tomat");
+    assert_eq!(tomato.tags.as_ref().unwrap(), &vec!["vegetable", "red"]);
+
+    assert_eq!(dm.get_pantry_item("tomato").unwrap().quantity, Some(5.0));
+    assert!(dm.get_pantry_item("tomatoes").is_none());
+
+    // Both tomato lines in kg are folded into one
+    let lasagna = dm.get_recipe("Lasagna").unwrap();
+    let tomatoes: Vec<_> = lasagna
+        .ingredients
+        .iter()
+        .filter(|i| i.ingredient == "tomato")
+        .collect();
+    assert_eq!(tomatoes.len(), 1);
+    assert_eq!(tomatoes[0].quantity, Some(Quantity::from(1.5)));
+    assert!(!lasagna
+        .ingredients
+        .iter()
+        .any(|i| i.ingredient == "tomatoes"));
+}
+
+#[test]
+fn test_merge_ingredients_reports_unit_mismatch() {
+    let temp_dir = setup_temp_data_dir();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    assert!(dm.merge_ingredients("potato", "potato").is_err());
+    assert!(dm.merge_ingredients("potato", "unobtainium").is_err());
+
+    dm.create_ingredient(ingredient_with("potatoes", &[], &[]))
+        .unwrap();
+    dm.update_pantry_item("potatoes", Some(4.0), Some("pcs".to_string()))
+        .unwrap();
+    let result = dm.merge_ingredients("potato", "potatoes").unwrap();
+    assert_eq!(result.warnings.len(), 1);
+    let potato = dm.get_pantry_item("potato").unwrap();
+    assert_eq!(
+        (potato.quantity, potato.quantity_type.as_str()),
+        (Some(2.0), "kg")
+    );
+}
+
+#[test]
+fn test_sum_quantities_only_adds_matching_units() {
+    // A count without a unit is pieces, not grams
+    assert_eq!(
+        duplicates::sum_quantities(Some(3.0), "", Some(500.0), "g"),
+        None
+    );
+    assert_eq!(
+        duplicates::sum_quantities(Some(500.0), "g", Some(3.0), ""),
+        None
+    );
+    // Only a side with no amount takes the other's unit
+    assert_eq!(
+        duplicates::sum_quantities(None, "", Some(500.0), "g"),
+        Some((Some(500.0), "g".to_string()))
+    );
+    assert_eq!(
+        duplicates::sum_quantities(Some(3.0), "", Some(2.0), "pcs"),
+        Some((Some(5.0), "".to_string()))
+    );
+    // Units of the same kind convert to the first one's unit
+    assert_eq!(
+        duplicates::sum_quantities(Some(1.0), "kg", Some(500.0), "g"),
+        Some((Some(1.5), "kg".to_string()))
+    );
+    assert_eq!(
+        duplicates::sum_quantities(Some(1.0), "cup", Some(1.0), "kg"),
+        None
+    );
+
+    let q = |text: &str| text.parse::<Quantity>().unwrap();
+    assert_eq!(
+        duplicates::sum_recipe_quantities(Some(&q("3")), "", Some(&q("500")), "g"),
+        None
+    );
+    assert_eq!(
+        duplicates::sum_recipe_quantities(Some(&q("1/2")), "Cup", Some(&q("1/4")), "cups"),
+        Some((Some(q("3/4")), "Cup".to_string()))
+    );
+}
+
+// ─── Group 17: Slugs and file names ──────────────────────────────────────────
+
+fn recipe_titled(title: &str) -> Recipe {
+    Recipe {
+        title: title.to_string(),
+        ingredients: vec![],
+        prep_time: None,
+        downtime: None,
+        servings: None,
+        tags: None,
+        image: None,
+        instructions: String::new(),
+        slug: String::new(),
+    }
+}
+
+#[test]
+fn test_slugify_and_validate() {
+    assert_eq!(
+        slug::slugify("Spaghetti Aglio e Olio"),
+        "spaghetti_aglio_e_olio"
+    );
+    assert_eq!(slug::slugify("../../etc/passwd"), "etc_passwd");
+    assert_eq!(slug::slugify("  Mor's Blåbærgrød!  "), "mor_s_blåbærgrød");
+    // A decomposed accent (as typed on macOS) gives the same slug as a composed one
+    assert_eq!(
+        slug::slugify("Cafe\u{301} au lait"),
+        slug::slugify("Café au lait")
+    );
+    assert_eq!(slug::slugify("!!!"), "");
+
+    for bad in ["", "..", ".hidden", "a/b", "a\\b", "CON", "x:y"] {
+        assert!(
+            slug::validate_slug(bad).is_err(),
+            "{:?} should be rejected",
+            bad
+        );
+    }
+    assert!(slug::validate_slug("blåbærgrød").is_ok());
+    assert_eq!(slug::unique_slug("pasta", ["Pasta", "pasta_2"]), "pasta_3");
+}
+
+#[test]
+fn test_loaded_items_keep_their_file_names() {
+    let dm = DataManager::new(fixture_data_dir()).unwrap();
+    assert_eq!(dm.get_recipe("Lasagna").unwrap().slug, "Lasagna");
+    assert_eq!(
+        dm.get_recipe("Spaghetti Aglio e Olio").unwrap().slug,
+        "Spaghetti_Aglio_e_Olio"
+    );
+    assert_eq!(dm.get_ingredient("Nutella").unwrap().slug, "Nutella");
+}
+
+#[test]
+fn test_create_recipe_uses_safe_unique_slug() {
+    let temp_dir = setup_temp_data_dir();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+
+    dm.create_recipe(recipe_titled("../Escape / Artist"))
+        .unwrap();
+    assert_eq!(
+        dm.get_recipe("../Escape / Artist").unwrap().slug,
+        "escape_artist"
+    );
+    assert!(temp_dir.path().join("recipes/escape_artist.md").exists());
+
+    dm.create_recipe(recipe_titled("Pasta!")).unwrap();
+    dm.create_recipe(recipe_titled("Pasta?")).unwrap();
+    assert_eq!(dm.get_recipe("Pasta?").unwrap().slug, "pasta_2");
+
+    // Case-only differences are the same recipe, and slugs are checked for traversal
+    assert!(dm.create_recipe(recipe_titled("LASAGNA")).is_err());
+    let mut sneaky = recipe_titled("Sneaky");
+    sneaky.slug = "../pantry".to_string();
+    assert!(matches!(
+        dm.create_recipe(sneaky),
+        Err(CookbookError::InvalidNameError(_))
+    ));
+    assert!(dm.create_recipe(recipe_titled("???")).is_err());
+}
+
+#[test]
+fn test_renamed_recipe_keeps_its_file() {
+    let temp_dir = setup_temp_data_dir();
+    {
+        let mut dm = DataManager::new(temp_dir.path()).unwrap();
+        let mut lasagna = dm.get_recipe("Lasagna").unwrap().clone();
+        lasagna.title = "Potato Lasagna".to_string();
+        dm.update_recipe("Lasagna", lasagna).unwrap();
+        assert!(dm
+            .update_recipe("Potato Lasagna", recipe_titled("spaghetti aglio e olio"))
+            .is_err());
+    }
+    assert!(temp_dir.path().join("recipes/Lasagna.md").exists());
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    assert_eq!(dm.get_recipe("Potato Lasagna").unwrap().slug, "Lasagna");
+
+    dm.delete_recipe("Potato Lasagna").unwrap();
+    assert!(!temp_dir.path().join("recipes/Lasagna.md").exists());
+}
+
+// ─── Group 18: Preserving hand-edited files ──────────────────────────────────
+
+#[test]
+fn test_recipe_edit_keeps_unknown_keys_and_comments() {
+    let temp_dir = setup_temp_data_dir();
+    let path = temp_dir.path().join("recipes/soup.md");
+    std::fs::write(
+        &path,
+        "---\n\
+         # Where this came from\n\
+         source: Grandma's notebook\n\
+         Title: Soup\n\
+         Ingredients:\n\
+         - ingredient: potato\n  quantity: 1.0\n  quantity_type: kg\n\
+         PrepTime: 10 # minutes\n\
+         Servings: 4\n\
+         author:\n  name: Mor\n\
+         ---\n\
+         Boil.\n",
+    )
+    .unwrap();
+
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    let mut soup = dm.get_recipe("Soup").unwrap().clone();
+    soup.prep_time = Some(20);
+    dm.update_recipe("Soup", soup).unwrap();
+    // Renaming potato rewrites the recipe through a staging file, which must keep them too
+    let mut potato = dm.get_ingredient("potato").unwrap().clone();
+    potato.name = "spud".to_string();
+    dm.rename_ingredient("potato", potato).unwrap();
+
+    let written = std::fs::read_to_string(&path).unwrap();
+    let (front_matter, body) = written
+        .trim_start_matches("---\n")
+        .split_once("---\n")
+        .unwrap();
+    let keys: Vec<&str> = front_matter
+        .lines()
+        .filter(|l| !l.starts_with([' ', '-']))
+        .collect();
+    assert_eq!(
+        keys,
+        vec![
+            "version: 1",
+            "# Where this came from",
+            "source: Grandma's notebook",
+            "Title: Soup",
+            "Ingredients:",
+            "PrepTime: 20",
+            "Downtime: null",
+            "Servings: 4",
+            "Tags: null",
+            "image: null",
+            "author:",
+        ]
+    );
+    assert!(front_matter.contains("- ingredient: spud"));
+    assert!(front_matter.contains("  name: Mor"));
+    assert_eq!(body.trim(), "Boil.");
+}
+
+#[test]
+fn test_ingredient_edit_keeps_unknown_keys_and_comments() {
+    let temp_dir = setup_temp_data_dir();
+    let path = temp_dir.path().join("ingredients/egg.yaml");
+    let original = std::fs::read_to_string(&path).unwrap();
+    std::fs::write(
+        &path,
+        format!("# Free-range only\n\n{}supplier: Hansen's farm\n", original),
+    )
+    .unwrap();
+
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    let mut egg = dm.get_ingredient("egg").unwrap().clone();
+    egg.category = "dairy".to_string();
+    dm.update_ingredient("egg", egg).unwrap();
+
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert!(
+        written.starts_with("# Free-range only\n\nversion: 1\nname: egg\n"),
+        "{}",
+        written
+    );
+    assert!(written.contains("category: dairy\n"));
+    assert!(written.ends_with("supplier: Hansen's farm\n"));
+    assert_eq!(
+        DataManager::new(temp_dir.path())
+            .unwrap()
+            .get_ingredient("egg")
+            .unwrap()
+            .category,
+        "dairy"
+    );
+
+    // Saving without changes adds no keys for empty fields, only the schema version
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    for name in ["egg", "potato"] {
+        let path = temp_dir.path().join(format!("ingredients/{}.yaml", name));
+        let before = std::fs::read_to_string(&path).unwrap();
+        let ingredient = dm.get_ingredient(name).unwrap().clone();
+        dm.update_ingredient(name, ingredient).unwrap();
+        let after = std::fs::read_to_string(&path).unwrap();
+        let after = after.strip_prefix("version: 1\n").unwrap_or(&after);
+        assert_eq!(
+            after,
+            before.strip_prefix("version: 1\n").unwrap_or(&before),
+            "{}",
+            name
+        );
+    }
+
+    // Clearing an optional field takes its key out again
+    let mut egg = dm.get_ingredient("egg").unwrap().clone();
+    egg.unit_weight = Some(55.0);
+    dm.update_ingredient("egg", egg.clone()).unwrap();
+    assert!(std::fs::read_to_string(&path)
+        .unwrap()
+        .contains("unit_weight: 55.0\n"));
+    egg.unit_weight = None;
+    dm.update_ingredient("egg", egg).unwrap();
+    assert!(!std::fs::read_to_string(&path)
+        .unwrap()
+        .contains("unit_weight"));
+}
+
+#[test]
+fn test_merge_yaml() {
+    let original = "# Header\n\n# About b\nb: 2 # two\nextra: [1, 2]\nc: old\n";
+    let updated = "a: 1\nb: 2\nc: new\nd: 4\n";
+    assert_eq!(
+        yaml_merge::merge_yaml(original, updated),
+        "# Header\n\na: 1\n# About b\nb: 2 # two\nextra: [1, 2]\nc: new\nd: 4\n"
+    );
+    // Anything that isn't a plain block mapping is replaced wholesale
+    assert_eq!(
+        yaml_merge::merge_yaml("- just\n- a list\n", updated),
+        updated
+    );
+    // Optional keys missing from the update were cleared, unknown ones are kept
+    assert_eq!(
+        yaml_merge::merge_yaml_clearing("a: 1\nprices:\n- 2\nextra: 3\n", "a: 1\n", &["prices"]),
+        "a: 1\nextra: 3\n"
+    );
+}
+
+// ─── Group 19: Ingredient autocomplete ───────────────────────────────────────
+
+#[test]
+fn test_ingredient_completions_rank_exact_then_prefix_then_substring() {
+    let dm = DataManager::new(fixture_data_dir()).unwrap();
+    let names: Vec<String> = dm
+        .ingredient_completions("to", "en", 10)
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    assert_eq!(names, vec!["tomato", "potato"]);
+
+    let completions = dm.ingredient_completions("Egg", "en", 10);
+    assert_eq!(completions, vec![("egg".to_string(), "egg".to_string())]);
+}
+
+#[test]
+fn test_ingredient_completions_match_translations() {
+    let dm = DataManager::new(fixture_data_dir()).unwrap();
+    let completions = dm.ingredient_completions("tomatoes", "en", 10);
+    assert_eq!(
+        completions,
+        vec![("tomato".to_string(), "tomatoes".to_string())]
+    );
+    // Translations in other languages are not searched
+    assert!(dm.ingredient_completions("tomatoes", "da", 10).is_empty());
+}
+
+#[test]
+fn test_ingredient_completions_limit_and_empty_query() {
+    let dm = DataManager::new(fixture_data_dir()).unwrap();
+    assert!(dm.ingredient_completions("  ", "en", 10).is_empty());
+    assert_eq!(dm.ingredient_completions("e", "en", 2).len(), 2);
+}
+
+// ─── Group 20: Ingredient line parser ────────────────────────────────────────
+
+#[test]
+fn test_parse_ingredient_line_quantities_and_units() {
+    let dm = DataManager::new(fixture_data_dir()).unwrap();
+    let cases = [
+        ("2 1/2 cups diced tomatoes", "tomato", "2 1/2", Some("cup")),
+        ("1 kg potatoes", "potato", "1", Some("kg")),
+        ("a pinch of salt", "salt", "1", Some("pinch")),
+        ("½ tsp salt", "salt", "1/2", Some("tsp")),
+        ("1½ Tablespoons butter", "butter", "1 1/2", Some("tbsp")),
+        ("500g flour", "flour", "500", Some("g")),
+        ("0,5 l milk", "milk", "0.5", Some("l")),
+        ("two eggs", "egg", "2", None),
+    ];
+    for (line, name, quantity, unit) in cases {
+        let parsed = dm.parse_ingredient_line(line, "en");
+        assert_eq!(parsed.ingredient.ingredient, name, "{}", line);
+        assert_eq!(
+            parsed.ingredient.quantity,
+            Some(quantity.parse().unwrap()),
+            "{}",
+            line
+        );
+        assert_eq!(parsed.ingredient.quantity_type.as_deref(), unit, "{}", line);
+        assert!(
+            parsed.known && !parsed.is_uncertain(),
+            "{}: {:?}",
+            line,
+            parsed
+        );
+    }
+}
+
+#[test]
+fn test_parse_ingredient_line_ranges_and_notes() {
+    let dm = DataManager::new(fixture_data_dir()).unwrap();
+
+    let parsed = dm.parse_ingredient_line("2–3 large eggs (room temperature), beaten", "en");
+    assert_eq!(parsed.ingredient.ingredient, "egg");
+    assert_eq!(parsed.ingredient.quantity, Some("2-3".parse().unwrap()));
+    assert_eq!(
+        parsed.ingredient.preparation.as_deref(),
+        Some("large, room temperature, beaten")
+    );
+
+    let parsed = dm.parse_ingredient_line("1 to 2 tsp pepper", "en");
+    assert_eq!(parsed.ingredient.quantity, Some("1-2".parse().unwrap()));
+
+    let parsed = dm.parse_ingredient_line("salt to taste", "en");
+    assert_eq!(parsed.ingredient.ingredient, "salt");
+    assert_eq!(
+        parsed.ingredient.quantity,
+        Some(Quantity::Qualitative("to taste".to_string()))
+    );
+    assert_eq!(parsed.ingredient.preparation, None);
+    assert_eq!(parsed.confidence, 1.0);
+}
+
+#[test]
+fn test_parse_ingredient_line_flags_uncertain_lines() {
+    let dm = DataManager::new(fixture_data_dir()).unwrap();
+
+    let parsed = dm.parse_ingredient_line("3 cloves garlic, minced", "en");
+    assert_eq!(parsed.ingredient.ingredient, "garlic");
+    assert_eq!(parsed.ingredient.quantity_type.as_deref(), Some("clove"));
+    assert_eq!(parsed.ingredient.preparation.as_deref(), Some("minced"));
+    assert!(!parsed.known);
+    assert!(parsed.is_uncertain());
+    assert_eq!(parsed.warnings, vec!["'garlic' is not a known ingredient"]);
+
+    // A unit without an ingredient is read as an (unknown) ingredient
+    let parsed = dm.parse_ingredient_line("1 cup", "en");
+    assert_eq!(parsed.ingredient.ingredient, "cup");
+    assert!(parsed.is_uncertain());
+}
+
+// ─── Group 21: Quantities ────────────────────────────────────────────────────
+
+#[test]
+fn test_quantity_parse_and_display() {
+    let cases = [
+        ("2", "2"),
+        ("0,75", "0.75"),
+        ("1/3", "1/3"),
+        ("4/2", "2"),
+        ("2 1/2", "2 1/2"),
+        ("2½", "2 1/2"),
+        ("2–3", "2-3"),
+        ("1/2 to 1", "1/2-1"),
+        ("to taste", "to taste"),
+    ];
+    for (text, shown) in cases {
+        let quantity: Quantity = text.parse().unwrap();
+        assert_eq!(quantity.to_string(), shown, "{}", text);
+    }
+    assert_eq!("1/3".parse::<Quantity>().unwrap().value(), Some(1.0 / 3.0));
+    assert_eq!("2-3".parse::<Quantity>().unwrap().value(), Some(3.0));
+    assert!("2x".parse::<Quantity>().is_err());
+    assert!("".parse::<Quantity>().is_err());
+    // Whole numbers too large for a fraction are rejected rather than overflowing
+    assert!("1431655765⅓".parse::<Quantity>().is_err());
+    assert!("4294967295 1/2".parse::<Quantity>().is_err());
+}
+
+#[test]
+fn test_quantity_yaml_is_backward_compatible() {
+    let old: RecipeIngredient =
+        serde_yaml::from_str("ingredient: potato\nquantity: 2.0\nquantity_type: kg\n").unwrap();
+    assert_eq!(old.quantity, Some(Quantity::from(2.0)));
+    assert!(serde_yaml::to_string(&old)
+        .unwrap()
+        .contains("quantity: 2.0\n"));
+
+    let rich: RecipeIngredient =
+        serde_yaml::from_str("ingredient: garlic\nquantity: 2-3\nquantity_type: clove\n").unwrap();
+    assert_eq!(
+        rich.quantity,
+        Some(Quantity::Range {
+            min: Amount::Decimal(2.0),
+            max: Amount::Decimal(3.0),
+        })
+    );
+    let yaml = serde_yaml::to_string(&rich).unwrap();
+    assert_eq!(
+        serde_yaml::from_str::<RecipeIngredient>(&yaml).unwrap(),
+        rich
+    );
+
+    let fraction = RecipeIngredient {
+        ingredient: "flour".to_string(),
+        quantity: Some("1/3".parse().unwrap()),
+        quantity_type: Some("cup".to_string()),
+        ..Default::default()
+    };
+    let yaml = serde_yaml::to_string(&fraction).unwrap();
+    assert!(yaml.contains("quantity: 1/3\n"), "{}", yaml);
+    assert_eq!(
+        serde_yaml::from_str::<RecipeIngredient>(&yaml).unwrap(),
+        fraction
+    );
+}
+
+#[test]
+fn test_quantity_pluralizes_display_name() {
+    let dm = DataManager::new(fixture_data_dir()).unwrap();
+    let tomato = dm.get_ingredient("tomato").unwrap();
+    let name =
+        |q: &str| DataManager::ingredient_display_name(tomato, "en", Some(&q.parse().unwrap()));
+    assert_eq!(name("1"), "tomato");
+    assert_eq!(name("1/2"), "tomato");
+    assert_eq!(name("0.5"), "tomato");
+    assert_eq!(name("0"), "tomatoes");
+    assert_eq!(name("1 1/2"), "tomatoes");
+    assert_eq!(name("1-2"), "tomatoes");
+    assert_eq!(
+        DataManager::ingredient_display_name(tomato, "en", None),
+        "tomato"
+    );
+
+    let line = |q: &str, unit: Option<&str>| {
+        dm.format_recipe_ingredient(
+            &RecipeIngredient {
+                ingredient: "tomato".to_string(),
+                quantity: Some(q.parse().unwrap()),
+                quantity_type: unit.map(str::to_string),
+                ..Default::default()
+            },
+            "en",
+        )
+    };
+    assert_eq!(line("2-3", None), "2-3 tomatoes");
+    assert_eq!(line("1/2", Some("kg")), "1/2 kg tomato");
+    assert_eq!(line("to taste", None), "tomatoes to taste");
+}
+
+#[test]
+fn test_quantity_sums_when_merging_ingredients() {
+    let a: Quantity = "1/3".parse().unwrap();
+    let b: Quantity = "1/6".parse().unwrap();
+    assert_eq!(a.checked_add(&b).unwrap().to_string(), "1/2");
+    let range: Quantity = "1-2".parse().unwrap();
+    assert_eq!(
+        range.checked_add(&Quantity::from(1.0)).unwrap().to_string(),
+        "2-3"
+    );
+    assert!(Quantity::Qualitative("to taste".to_string())
+        .checked_add(&Quantity::from(1.0))
+        .is_none());
+}
+
+// ─── Group 22: Ingredient groups, optional items and preparation ─────────────
+
+#[test]
+fn test_recipe_ingredient_groups_and_notes_round_trip() {
+    let yaml = "ingredient: tomato\nquantity: 2.0\nquantity_type: null\n\
+                group: For the sauce\noptional: true\npreparation: finely chopped\n";
+    let ingredient: RecipeIngredient = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(ingredient.group.as_deref(), Some("For the sauce"));
+    assert!(ingredient.optional);
+    assert_eq!(ingredient.preparation.as_deref(), Some("finely chopped"));
+    assert_eq!(
+        serde_yaml::from_str::<RecipeIngredient>(&serde_yaml::to_string(&ingredient).unwrap())
+            .unwrap(),
+        ingredient
+    );
+
+    // Plain ingredients don't grow new keys when saved
+    let plain: RecipeIngredient =
+        serde_yaml::from_str("ingredient: potato\nquantity: 2.0\nquantity_type: kg\n").unwrap();
+    assert!(!plain.optional);
+    assert_eq!(
+        serde_yaml::to_string(&plain).unwrap(),
+        "ingredient: potato\nquantity: 2.0\nquantity_type: kg\n"
+    );
+}
+
+#[test]
+fn test_recipe_ingredient_groups_keep_first_appearance_order() {
+    let mut recipe = recipe_titled("Lasagna");
+    let item = |name: &str, group: Option<&str>| RecipeIngredient {
+        ingredient: name.to_string(),
+        group: group.map(str::to_string),
+        ..Default::default()
+    };
+    recipe.ingredients = vec![
+        item("tomato", Some("Sauce")),
+        item("milk", Some("Béchamel")),
+        item("potato", None),
+        item("butter", Some("Béchamel")),
+        item("salt", Some("Sauce")),
+    ];
+    let groups: Vec<(Option<&str>, Vec<&str>)> = recipe
+        .ingredient_groups()
+        .into_iter()
+        .map(|(group, items)| (group, items.iter().map(|i| i.ingredient.as_str()).collect()))
+        .collect();
+    assert_eq!(
+        groups,
+        vec![
+            (Some("Sauce"), vec!["tomato", "salt"]),
+            (Some("Béchamel"), vec!["milk", "butter"]),
+            (None, vec!["potato"]),
+        ]
+    );
+}
+
+#[test]
+fn test_pantry_coverage_ignores_optional_ingredients() {
+    let temp_dir = setup_temp_data_dir();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    assert!(dm.are_all_ingredients_in_pantry("Lasagna"));
+
+    let mut lasagna = dm.get_recipe("Lasagna").unwrap().clone();
+    lasagna.ingredients.push(RecipeIngredient {
+        ingredient: "flour".to_string(),
+        optional: true,
+        preparation: Some("sifted".to_string()),
+        ..Default::default()
+    });
+    dm.update_recipe("Lasagna", lasagna.clone()).unwrap();
+    assert!(dm.are_all_ingredients_in_pantry("Lasagna"));
+    assert!(dm
+        .get_recipe("Lasagna")
+        .unwrap()
+        .all_ingredients_in_stock(&dm));
+
+    let flour = lasagna.ingredients.last_mut().unwrap();
+    assert_eq!(
+        dm.format_recipe_ingredient(flour, "en"),
+        "flour, sifted (optional)"
+    );
+    flour.optional = false;
+    dm.update_recipe("Lasagna", lasagna).unwrap();
+    assert!(!dm.are_all_ingredients_in_pantry("Lasagna"));
+
+    let parsed = dm.parse_ingredient_line("1 tbsp butter (optional), softened", "en");
+    assert!(parsed.ingredient.optional);
+    assert_eq!(parsed.ingredient.preparation.as_deref(), Some("softened"));
+}
+
+// ─── Group 23: Instruction steps, timers and temperatures ────────────────────
+
+#[test]
+fn test_steps_from_numbered_list_and_single_paragraph() {
+    let dm = DataManager::new(fixture_data_dir()).unwrap();
+
+    let spaghetti = dm.get_recipe("Spaghetti Aglio e Olio").unwrap().steps();
+    assert_eq!(spaghetti.len(), 6);
+    assert_eq!(spaghetti[2].number, 3);
+    assert_eq!(
+        spaghetti[2].text,
+        "Add chili flakes and stir for 30 seconds."
+    );
+    assert_eq!(spaghetti[2].timers[0].seconds, 30);
+    // "1/2 cup of pasta water" is not a duration
+    assert!(spaghetti[0].timers.is_empty());
+
+    // Lasagna is one paragraph, so every sentence is a step
+    let lasagna = dm.get_recipe("Lasagna").unwrap().steps();
+    assert_eq!(lasagna.len(), 3);
+    assert_eq!(lasagna[2].text, "Bake for 60 minutes at 180C.");
+    assert_eq!(lasagna[2].timers[0].seconds, 3600);
+    assert_eq!(lasagna[2].temperatures[0].value, 180.0);
+    assert_eq!(lasagna[2].temperatures[0].unit, TemperatureUnit::Celsius);
+}
+
+#[test]
+fn test_step_durations_and_temperatures() {
+    let steps = steps::parse_steps(
+        "- Simmer for 10-15 min.\n\
+         - Roast 1 hour and 30 minutes at 350 °F, then rest 2 1/2 hours.\n\
+         - Heat the oven to 200 degrees Celsius/400°F. Chill 5 to 10 minutes.\n\
+         - Heat to 180 degrees and add 2 c flour.\n\
+         - Add 2 C milk, then bake at 180C or 350 F.",
+    );
+    assert_eq!(steps[0].timers[0].seconds, 600);
+    assert_eq!(steps[0].timers[0].max_seconds, Some(900));
+    assert_eq!(steps[0].duration_seconds(), 900);
+
+    let seconds: Vec<u32> = steps[1].timers.iter().map(|t| t.seconds).collect();
+    assert_eq!(seconds, vec![5400, 9000]);
+    assert_eq!(steps[1].temperatures[0].unit, TemperatureUnit::Fahrenheit);
+
+    let temperatures: Vec<f64> = steps[2].temperatures.iter().map(|t| t.value).collect();
+    assert_eq!(temperatures, vec![200.0, 400.0]);
+    assert_eq!(steps[2].timers[0].max_seconds, Some(600));
+
+    // A bare "180 degrees" could be either scale, and "2 c" is a cup
+    assert!(steps[3].temperatures.is_empty());
+    // A bare "C" or "F" is a temperature for oven values only; "2 C" is a cup too
+    let temperatures: Vec<f64> = steps[4].temperatures.iter().map(|t| t.value).collect();
+    assert_eq!(temperatures, vec![180.0, 350.0]);
+    assert_eq!(
+        steps::localize_instructions("Add 2 C milk.", TemperatureUnit::Fahrenheit),
+        "Add 2 C milk."
+    );
+    assert_eq!(
+        steps::localize_instructions("Chill at {temp: 4C}.", TemperatureUnit::Fahrenheit),
+        "Chill at 39 °F."
+    );
+    assert_eq!(steps::format_duration(5400), "1 h 30 min");
+}
+
+#[test]
+fn test_explicit_timer_and_temperature_markers() {
+    let steps = steps::parse_steps(
+        "1. Let the dough rise {timer: 1 hour, proofing} somewhere warm.\n\
+         2. Bake in a hot oven {temp: 220C} for 25 minutes.\n\
+         3. Leave {oddly} alone.",
+    );
+    assert_eq!(steps[0].text, "Let the dough rise 1 hour somewhere warm.");
+    assert_eq!(steps[0].timers.len(), 1);
+    assert!(steps[0].timers[0].explicit);
+    assert_eq!(steps[0].timers[0].label.as_deref(), Some("proofing"));
+
+    assert_eq!(steps[1].text, "Bake in a hot oven 220 °C for 25 minutes.");
+    assert_eq!(steps[1].temperatures.len(), 1);
+    assert!(steps[1].temperatures[0].explicit);
+    assert!(!steps[1].timers[0].explicit);
+
+    // Braces that aren't markers stay as written
+    assert_eq!(steps[2].text, "Leave {oddly} alone.");
+}
+
+#[test]
+fn test_temperature_conversion() {
+    let steps = steps::parse_steps("Bake at 180C, then at 350°F.");
+    assert_eq!(
+        steps[0].text_in(TemperatureUnit::Fahrenheit),
+        "Bake at 355 °F, then at 350°F."
+    );
+    assert_eq!(
+        steps[0].text_in(TemperatureUnit::Celsius),
+        "Bake at 180C, then at 175 °C."
+    );
+    assert_eq!(
+        steps::localize_instructions("Proof at {temp: 38 °C}.", TemperatureUnit::Fahrenheit),
+        "Proof at 100 °F."
+    );
+}
+
+// ─── Group 24: Cooking mode ──────────────────────────────────────────────────
+
+#[test]
+fn test_step_ingredients() {
+    let dm = DataManager::new(fixture_data_dir()).unwrap();
+    let recipe = dm.get_recipe("Spaghetti Aglio e Olio").unwrap();
+    let steps = recipe.steps();
+    let names = |step: &Step| -> Vec<String> {
+        dm.step_ingredients(recipe, step, "en")
+            .iter()
+            .map(|ri| ri.ingredient.clone())
+            .collect()
+    };
+    // "salted" water is not the salt itself
+    assert_eq!(names(&steps[0]), vec!["spaghetti"]);
+    assert_eq!(names(&steps[1]), vec!["garlic", "olive oil"]);
+    // Plural of an ingredient without translations
+    assert_eq!(names(&steps[2]), vec!["chili flake"]);
+    assert_eq!(names(&steps[4]), vec!["salt", "pepper"]);
+
+    // Translations count: Lasagna's ingredients are "potato" and "tomato"
+    let lasagna = dm.get_recipe("Lasagna").unwrap();
+    let first = &lasagna.steps()[0];
+    assert_eq!(dm.step_ingredients(lasagna, first, "en").len(), 1);
+}
+
+#[test]
+fn test_scaled_recipe_ingredient() {
+    let dm = DataManager::new(fixture_data_dir()).unwrap();
+    let half = RecipeIngredient {
+        ingredient: "tomato".to_string(),
+        quantity: Some("1/2".parse().unwrap()),
+        quantity_type: None,
+        ..Default::default()
+    };
+    // Two servings of a one-serving recipe
+    assert_eq!(
+        dm.format_recipe_ingredient(&half.scaled(2.0), "en"),
+        "1 tomato"
+    );
+    assert_eq!(
+        dm.format_recipe_ingredient(&half.scaled(6.0), "en"),
+        "3 tomatoes"
+    );
+    let to_taste = RecipeIngredient {
+        quantity: Some(Quantity::Qualitative("to taste".to_string())),
+        ..half.clone()
+    };
+    assert_eq!(to_taste.scaled(3.0), to_taste);
+}
+
+// ─── Group 25: Scheduling back from a serving time ───────────────────────────
+
+fn at(time: &str) -> chrono::NaiveDateTime {
+    chrono::NaiveDateTime::parse_from_str(&format!("2024-05-01 {}", time), "%Y-%m-%d %H:%M")
+        .unwrap()
+}
+
+#[test]
+fn test_schedule_single_recipe() {
+    let dm = DataManager::new(fixture_data_dir()).unwrap();
+    let lasagna = dm.get_recipe("Lasagna").unwrap();
+    let plan = schedule::schedule(&[lasagna], at("19:00"));
+
+    // 30 minutes of prep shared by the two untimed steps, then the 60-minute bake
+    assert_eq!(plan.start(), Some(at("17:30")));
+    assert_eq!(plan.ready_at("Lasagna"), Some(at("19:00")));
+    let bake = plan.entries.last().unwrap();
+    assert_eq!((bake.step, bake.active), (Some(3), false));
+    assert_eq!((bake.start, bake.end), (at("18:00"), at("19:00")));
+    assert!(plan.untimed.is_empty());
+}
+
+#[test]
+fn test_schedule_overlaps_downtime() {
+    let dm = DataManager::new(fixture_data_dir()).unwrap();
+    let lasagna = dm.get_recipe("Lasagna").unwrap();
+    let spaghetti = dm.get_recipe("Spaghetti Aglio e Olio").unwrap();
+    let plan = schedule::schedule(&[lasagna, spaghetti], at("19:00"));
+
+    // The pasta is made while the lasagna bakes, so the evening starts no earlier
+    assert_eq!(plan.start(), Some(at("17:30")));
+    assert_eq!(plan.ready_at("Spaghetti Aglio e Olio"), Some(at("19:00")));
+    assert!(plan.recipe_start("Spaghetti Aglio e Olio").unwrap() > at("18:00"));
+    // Boiling the pasta water is the spaghetti's downtime
+    assert!(plan
+        .entries
+        .iter()
+        .any(|e| e.recipe == "Spaghetti Aglio e Olio" && e.step == Some(1) && !e.active));
+
+    // The cook never does two things at once
+    let active: Vec<_> = plan.entries.iter().filter(|e| e.active).collect();
+    for (i, a) in active.iter().enumerate() {
+        for b in &active[i + 1..] {
+            assert!(
+                a.end <= b.start || b.end <= a.start,
+                "{:?} overlaps {:?}",
+                a,
+                b
+            );
+        }
+    }
+}
+
+#[test]
+fn test_schedule_moves_clashing_active_work_earlier() {
+    let mut salad = recipe_titled("Salad");
+    salad.prep_time = Some(30);
+    let mut dressing = recipe_titled("Dressing");
+    dressing.prep_time = Some(20);
+    let mut bread = recipe_titled("Bread");
+    bread.prep_time = None;
+
+    let plan = schedule::schedule(&[&salad, &dressing, &bread], at("19:00"));
+    assert_eq!(plan.ready_at("Salad"), Some(at("19:00")));
+    assert_eq!(plan.ready_at("Dressing"), Some(at("18:30")));
+    assert_eq!(plan.start(), Some(at("18:10")));
+    assert_eq!(plan.untimed, vec!["Bread"]);
+}
+
+// ─── Group 26: Meal plan ─────────────────────────────────────────────────────
+
+fn day(date: &str) -> chrono::NaiveDate {
+    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+}
+
+fn planned(date: &str, meal: Meal, recipe: &str) -> MealPlanEntry {
+    MealPlanEntry {
+        date: day(date),
+        meal,
+        recipe: recipe.to_string(),
+        servings: None,
+    }
+}
+
+#[test]
+fn test_meal_plan_range_and_persistence() {
+    let temp_dir = setup_temp_data_dir();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    assert!(dm
+        .get_meal_plan(day("2024-05-01"), day("2024-05-31"))
+        .is_empty());
+
+    dm.add_meal_plan_entry(planned("2024-05-08", Meal::Lunch, "Lasagna"))
+        .unwrap();
+    dm.add_meal_plan_entry(MealPlanEntry {
+        servings: Some(4),
+        ..planned("2024-05-06", Meal::Dinner, "Spaghetti Aglio e Olio")
+    })
+    .unwrap();
+    dm.add_meal_plan_entry(planned("2024-05-06", Meal::Lunch, "Lasagna"))
+        .unwrap();
+    dm.add_meal_plan_entry(planned("2024-05-13", Meal::Dinner, "Lasagna"))
+        .unwrap();
+
+    // Unknown recipes and repeats in the same slot are refused
+    assert!(dm
+        .add_meal_plan_entry(planned("2024-05-07", Meal::Dinner, "Nonexistent"))
+        .is_err());
+    assert!(dm
+        .add_meal_plan_entry(planned("2024-05-06", Meal::Lunch, "Lasagna"))
+        .is_err());
+
+    // The range includes both ends and comes back ordered by date and meal
+    let reloaded = DataManager::new(temp_dir.path()).unwrap();
+    let week: Vec<_> = reloaded
+        .get_meal_plan(day("2024-05-06"), day("2024-05-12"))
+        .into_iter()
+        .map(|e| (e.date.to_string(), e.meal, e.recipe.as_str(), e.servings))
+        .collect();
+    assert_eq!(
+        week,
+        vec![
+            ("2024-05-06".to_string(), Meal::Lunch, "Lasagna", None),
+            (
+                "2024-05-06".to_string(),
+                Meal::Dinner,
+                "Spaghetti Aglio e Olio",
+                Some(4)
+            ),
+            ("2024-05-08".to_string(), Meal::Lunch, "Lasagna", None),
+        ]
+    );
+
+    let yaml = std::fs::read_to_string(temp_dir.path().join("mealplan.yaml")).unwrap();
+    assert!(yaml.starts_with("version: 1\n"), "{}", yaml);
+    assert!(yaml.contains("meal: dinner"), "{}", yaml);
+}
+
+#[test]
+fn test_meal_plan_move_copy_and_remove() {
+    let temp_dir = setup_temp_data_dir();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    let lunch = MealPlanEntry {
+        servings: Some(3),
+        ..planned("2024-05-06", Meal::Lunch, "Lasagna")
+    };
+    dm.add_meal_plan_entry(lunch.clone()).unwrap();
+
+    let moved = dm
+        .move_meal_plan_entry(&lunch, day("2024-05-07"), Meal::Dinner)
+        .unwrap();
+    assert_eq!(moved.servings, Some(3));
+    let copy = dm
+        .copy_meal_plan_entry(&moved, day("2024-05-08"), Meal::Dinner)
+        .unwrap();
+    assert_eq!(copy.servings, Some(3));
+    // The old slot is empty; copying onto a slot that already has the recipe fails
+    assert!(dm
+        .move_meal_plan_entry(&lunch, day("2024-05-09"), Meal::Lunch)
+        .is_err());
+    assert!(dm
+        .copy_meal_plan_entry(&moved, day("2024-05-08"), Meal::Dinner)
+        .is_err());
+
+    dm.set_meal_plan_servings(&copy, None).unwrap();
+    let dates: Vec<_> = dm
+        .get_meal_plan(day("2024-05-01"), day("2024-05-31"))
+        .iter()
+        .map(|e| (e.date.to_string(), e.servings))
+        .collect();
+    assert_eq!(
+        dates,
+        vec![
+            ("2024-05-07".to_string(), Some(3)),
+            ("2024-05-08".to_string(), None)
+        ]
+    );
+
+    // Copying a week skips meals already planned in the target week
+    assert_eq!(
+        dm.copy_meal_plan_range(day("2024-05-06"), day("2024-05-12"), day("2024-05-13"))
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        dm.copy_meal_plan_range(day("2024-05-06"), day("2024-05-12"), day("2024-05-13"))
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        dm.get_meal_plan(day("2024-05-13"), day("2024-05-19")).len(),
+        2
+    );
+
+    assert!(dm.remove_meal_plan_entry(&moved).unwrap());
+    assert!(!dm.remove_meal_plan_entry(&moved).unwrap());
+    assert_eq!(
+        dm.get_meal_plan(day("2024-05-06"), day("2024-05-12")).len(),
+        1
+    );
+}
+
+#[test]
+fn test_corrupt_meal_plan_does_not_block_loading() {
+    let temp_dir = setup_temp_data_dir();
+    std::fs::write(
+        temp_dir.path().join("mealplan.yaml"),
+        "entries:\n- date: [not a date\n",
+    )
+    .unwrap();
+
+    let dm = DataManager::new(temp_dir.path()).unwrap();
+    assert!(dm
+        .get_meal_plan(day("2000-01-01"), day("2100-01-01"))
+        .is_empty());
+    assert_eq!(dm.get_all_ingredients().len(), 10);
+    assert!(dm.get_recipe("Lasagna").is_some());
+    assert!(dm.get_pantry_item("potato").is_some());
+}
+
+#[test]
+fn test_meal_plan_follows_recipe_rename() {
+    let temp_dir = setup_temp_data_dir();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    dm.add_meal_plan_entry(planned("2024-05-06", Meal::Dinner, "Lasagna"))
+        .unwrap();
+
+    let mut recipe = dm.get_recipe("Lasagna").unwrap().clone();
+    recipe.title = "Potato Lasagna".to_string();
+    dm.update_recipe("Lasagna", recipe).unwrap();
+
+    let reloaded = DataManager::new(temp_dir.path()).unwrap();
+    let plan = reloaded.get_meal_plan(day("2024-05-06"), day("2024-05-06"));
+    assert_eq!(plan[0].recipe, "Potato Lasagna");
+}
+
+// ─── Group 27: Meal plan suggestions ─────────────────────────────────────────
+
+fn recipe_for_plan(title: &str, minutes: u32, tags: &[&str], ingredients: &[&str]) -> Recipe {
+    Recipe {
+        prep_time: Some(minutes),
+        tags: Some(tags.iter().map(|t| t.to_string()).collect()),
+        ingredients: ingredients
+            .iter()
+            .map(|i| RecipeIngredient {
+                ingredient: i.to_string(),
+                ..Default::default()
+            })
+            .collect(),
+        ..recipe_titled(title)
+    }
+}
+
+fn stocked(ingredient: &str, expires: Option<&str>) -> PantryItem {
+    PantryItem {
+        expires: expires.map(day),
+        ..pantry_item(ingredient, 1.0, "2024-05-01")
+    }
+}
+
+fn suggested_titles(suggestion: &planner::MealPlanSuggestion) -> Vec<&str> {
+    suggestion
+        .meals
+        .iter()
+        .map(|m| m.entry.recipe.as_str())
+        .collect()
+}
+
+#[test]
+fn test_suggest_meal_plan_follows_profile_and_time_budget() {
+    let salad = recipe_for_plan("Quick Salad", 10, &["vegetarian", "salad"], &["lettuce"]);
+    let roast = recipe_for_plan("Pork Roast", 120, &["meat"], &["pork"]);
+    let mut curry = recipe_for_plan("Veg Curry", 45, &["Vegetarian", "curry"], &["rice"]);
+    curry.ingredients.push(RecipeIngredient {
+        ingredient: "peanut".to_string(),
+        optional: true,
+        ..Default::default()
+    });
+    let satay = recipe_for_plan("Satay", 30, &["vegetarian"], &["peanut"]);
+
+    let mut request = planner::PlanRequest::week(day("2024-05-06"));
+    request.profile.require_tags = vec!["vegetarian".to_string()];
+    request.profile.exclude_ingredients = vec!["Peanut".to_string()];
+    request.time_budget[0] = Some(15); // Monday
+    request.time_budget[1] = Some(5); // Tuesday
+
+    let suggestion =
+        planner::suggest_meal_plan(&[&salad, &roast, &curry, &satay], &[], &[], &request);
+    let titles = suggested_titles(&suggestion);
+    assert_eq!(titles.len(), 6);
+    assert_eq!(titles[0], "Quick Salad");
+    assert!(
+        titles
+            .iter()
+            .all(|t| *t == "Quick Salad" || *t == "Veg Curry"),
+        "{:?}",
+        titles
+    );
+
+    assert_eq!(suggestion.unfilled.len(), 1);
+    assert_eq!(suggestion.unfilled[0].date, day("2024-05-07"));
+    assert_eq!(
+        suggestion.unfilled[0].reason,
+        "No recipe fits the 5 min budget"
+    );
+
+    let curry_meal = suggestion
+        .meals
+        .iter()
+        .find(|m| m.entry.recipe == "Veg Curry")
+        .unwrap();
+    assert!(
+        curry_meal
+            .reasons
+            .contains(&"Make it without peanut".to_string()),
+        "{:?}",
+        curry_meal.reasons
+    );
+    assert!(suggestion.meals[0]
+        .reasons
+        .contains(&"Ready in 10 min, within the 15 min budget".to_string()));
+}
+
+#[test]
+fn test_suggest_meal_plan_uses_expiring_and_stocked_ingredients() {
+    let pie = recipe_for_plan("Spinach Pie", 40, &[], &["spinach", "flour"]);
+    let pasta = recipe_for_plan("Pasta", 20, &[], &["pasta", "flour"]);
+    let soup = recipe_for_plan("Soup", 30, &[], &["carrot"]);
+    let pantry = vec![
+        stocked("spinach", Some("2024-05-07")),
+        stocked("flour", None),
+        stocked("pasta", None),
+    ];
+
+    let mut request = planner::PlanRequest::week(day("2024-05-06"));
+    request.days = 3;
+    let suggestion = planner::suggest_meal_plan(&[&soup, &pasta, &pie], &pantry, &[], &request);
+    assert_eq!(
+        suggested_titles(&suggestion),
+        vec!["Spinach Pie", "Pasta", "Soup"]
+    );
+
+    let reasons = &suggestion.meals[0].reasons;
+    assert!(
+        reasons.contains(&"Uses spinach before it expires on 2024-05-07".to_string()),
+        "{:?}",
+        reasons
+    );
+    assert!(
+        reasons.contains(&"All ingredients in stock".to_string()),
+        "{:?}",
+        reasons
+    );
+    assert!(suggestion.meals[2]
+        .reasons
+        .contains(&"0 of 1 ingredients in stock".to_string()));
+
+    // Spinach in two groups of the pie still counts once
+    let mut pie = pie;
+    pie.ingredients.push(RecipeIngredient {
+        ingredient: "spinach".to_string(),
+        group: Some("Topping".to_string()),
+        ..Default::default()
+    });
+    let suggestion = planner::suggest_meal_plan(&[&soup, &pasta, &pie], &pantry, &[], &request);
+    let uses: Vec<_> = suggestion.meals[0]
+        .reasons
+        .iter()
+        .filter(|r| r.starts_with("Uses spinach"))
+        .collect();
+    assert_eq!(uses.len(), 1, "{:?}", suggestion.meals[0].reasons);
+}
+
+#[test]
+fn test_suggest_meal_plan_avoids_repeats_balances_tags_and_is_seeded() {
+    let curry = recipe_for_plan("Curry", 30, &["curry"], &[]);
+    let carbonara = recipe_for_plan("Carbonara", 30, &["pasta"], &[]);
+    let lasagne = recipe_for_plan("Lasagne", 30, &["pasta"], &[]);
+    let risotto = recipe_for_plan("Risotto", 30, &["rice"], &[]);
+    let recipes = [&curry, &carbonara, &lasagne, &risotto];
+    let history = vec![
+        planned("2024-05-05", Meal::Dinner, "Curry"),
+        planned("2024-05-07", Meal::Dinner, "Lasagne"),
+    ];
+
+    let mut plans = Vec::new();
+    for seed in 0..20 {
+        let mut request = planner::PlanRequest::week(day("2024-05-06"));
+        request.days = 3;
+        request.seed = seed;
+        let suggestion = planner::suggest_meal_plan(&recipes, &[], &history, &request);
+        assert_eq!(
+            suggestion,
+            planner::suggest_meal_plan(&recipes, &[], &history, &request)
+        );
+
+        // The planned Tuesday is kept; Curry was cooked the day before the week starts
+        let titles = suggested_titles(&suggestion);
+        assert_eq!(
+            suggestion
+                .meals
+                .iter()
+                .map(|m| m.entry.date.to_string())
+                .collect::<Vec<_>>(),
+            vec!["2024-05-06", "2024-05-08"]
+        );
+        assert!(!titles.contains(&"Curry"), "seed {}: {:?}", seed, titles);
+        // Tuesday already has pasta, so Monday gets rice; by Wednesday pasta is due again
+        assert_eq!(titles, vec!["Risotto", "Carbonara"], "seed {}", seed);
+    }
+
+    // Between equal recipes the seed decides
+    for seed in 0..20 {
+        let mut request = planner::PlanRequest::week(day("2024-05-06"));
+        request.days = 1;
+        request.seed = seed;
+        let suggestion = planner::suggest_meal_plan(&[&carbonara, &risotto], &[], &[], &request);
+        plans.push(suggestion.meals[0].entry.recipe.clone());
+    }
+    plans.sort();
+    plans.dedup();
+    assert_eq!(plans, vec!["Carbonara", "Risotto"]);
+}
+
+#[test]
+fn test_suggest_meal_plan_avoids_recipes_planned_later() {
+    let carbonara = recipe_for_plan("Carbonara", 30, &["pasta"], &[]);
+    let risotto = recipe_for_plan("Risotto", 30, &["pasta"], &[]);
+    let history = vec![planned("2024-05-08", Meal::Dinner, "Risotto")];
+
+    for seed in 0..20 {
+        let mut request = planner::PlanRequest::week(day("2024-05-07"));
+        request.days = 1;
+        request.seed = seed;
+        let suggestion =
+            planner::suggest_meal_plan(&[&carbonara, &risotto], &[], &history, &request);
+        assert_eq!(
+            suggested_titles(&suggestion),
+            vec!["Carbonara"],
+            "seed {}",
+            seed
+        );
+        assert!(suggestion.meals[0]
+            .reasons
+            .contains(&"Not planned within 14 days".to_string()));
+    }
+}
+
+// ─── Group 28: Pantry journal and consumption forecasts ──────────────────────
+
+fn change(
+    date: &str,
+    ingredient: &str,
+    previous: Option<f64>,
+    quantity: f64,
+    unit: &str,
+) -> PantryChange {
+    PantryChange {
+        date: day(date),
+        ingredient: ingredient.to_string(),
+        previous,
+        quantity: Some(quantity),
+        quantity_type: unit.to_string(),
+    }
+}
+
+#[test]
+fn test_pantry_changes_are_journaled() {
+    let temp_dir = setup_temp_data_dir();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    dm.update_pantry_item("egg", Some(3.0), Some(String::new()))
+        .unwrap();
+    dm.update_pantry_item("egg", Some(3.0), Some(String::new()))
+        .unwrap(); // unchanged, not recorded
+    dm.remove_from_pantry("egg").unwrap();
+    dm.update_pantry_item("milk", Some(1.0), Some("l".to_string()))
+        .unwrap();
+
+    let reloaded = DataManager::new(temp_dir.path()).unwrap();
+    let eggs = reloaded.get_pantry_changes("egg");
+    assert_eq!(eggs.len(), 2);
+    assert_eq!((eggs[0].previous, eggs[0].quantity), (Some(5.0), Some(3.0)));
+    assert_eq!((eggs[1].previous, eggs[1].quantity), (Some(3.0), Some(0.0)));
+    assert_eq!(eggs.iter().map(|c| c.used()).sum::<f64>(), 5.0);
+    // A new item has no previous quantity, so nothing counts as used
+    let milk = reloaded.get_pantry_changes("milk");
+    assert_eq!((milk[0].previous, milk[0].used()), (None, 0.0));
+}
+
+#[test]
+fn test_corrupt_pantry_journal_does_not_block_loading() {
+    let temp_dir = setup_temp_data_dir();
+    std::fs::write(
+        temp_dir.path().join("pantry_journal.yaml"),
+        "changes: {{{\n",
+    )
+    .unwrap();
+
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    assert!(dm.get_pantry_changes("egg").is_empty());
+    assert!(dm.get_recipe("Lasagna").is_some());
+    // Journaling carries on from an empty journal
+    dm.update_pantry_item("egg", Some(3.0), Some(String::new()))
+        .unwrap();
+    let reloaded = DataManager::new(temp_dir.path()).unwrap();
+    assert_eq!(reloaded.get_pantry_changes("egg").len(), 1);
+}
+
+#[test]
+fn test_merges_and_deletes_are_journaled() {
+    let temp_dir = setup_temp_data_dir();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+
+    // A pantry merge journals what it changed and removed
+    let base = dm.get_pantry().unwrap().clone();
+    let mut remote = base.clone();
+    remote.items.retain(|item| item.ingredient != "butter");
+    remote
+        .items
+        .iter_mut()
+        .find(|item| item.ingredient == "tomato")
+        .unwrap()
+        .quantity = Some(4.0);
+    assert!(dm.merge_pantry(Some(&base), &remote).unwrap().is_clean());
+    let tomato = dm.get_pantry_changes("tomato");
+    assert_eq!(
+        (tomato.len(), tomato[0].previous, tomato[0].quantity),
+        (1, Some(2.0), Some(4.0))
+    );
+    assert_eq!(dm.get_pantry_changes("butter")[0].quantity, Some(0.0));
+    assert!(dm.get_pantry_changes("egg").is_empty());
+
+    // Deleting an ingredient uses up its stock
+    dm.delete_ingredient("potato", DeleteStrategy::Cascade)
+        .unwrap();
+    let potato = dm.get_pantry_changes("potato");
+    assert_eq!(
+        (potato[0].previous, potato[0].quantity),
+        (Some(2.0), Some(0.0))
+    );
+
+    // Merging ingredients journals the larger total
+    dm.create_ingredient(ingredient_with("eggs", &[], &[]))
+        .unwrap();
+    dm.update_pantry_item("eggs", Some(2.0), Some(String::new()))
+        .unwrap();
+    dm.merge_ingredients("egg", "eggs").unwrap();
+    let egg = dm.get_pantry_changes("egg");
+    assert_eq!(
+        egg.last().map(|c| (c.previous, c.quantity)),
+        Some((Some(5.0), Some(7.0)))
+    );
+
+    // Every item of a removed ingredient goes, each journaled
+    let mut pantry = dm.get_pantry().unwrap().clone();
+    pantry.items.push(pantry_item("salt", 1.0, "2025-07-24"));
+    pantry.to_file(temp_dir.path().join("pantry.yaml")).unwrap();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    assert!(dm.remove_from_pantry("salt").unwrap());
+    assert!(!dm.is_in_pantry("salt"));
+    let salt: Vec<Option<f64>> = dm
+        .get_pantry_changes("salt")
+        .iter()
+        .map(|c| c.previous)
+        .collect();
+    assert_eq!(salt, vec![None, Some(1.0)]);
+}
+
+#[test]
+fn test_forecast_from_rate_and_planned_meals() {
+    let flour = PantryItem {
+        quantity_type: "g".to_string(),
+        ..pantry_item("flour", 1000.0, "2024-05-11")
+    };
+    let history = [
+        change("2024-05-01", "flour", None, 2000.0, "g"),
+        change("2024-05-05", "flour", Some(2000.0), 1700.0, "g"),
+        change("2024-05-08", "flour", Some(1700.0), 1800.0, "g"), // restocked, not use
+        change("2024-05-11", "flour", Some(1800.0), 1000.0, "g"),
+    ];
+    let changes: Vec<&PantryChange> = history.iter().collect();
+
+    // 1100 g used over 10 days
+    let alone = forecast::forecast(&flour, None, &changes, &[], day("2024-05-11")).unwrap();
+    assert!((alone.rate.as_ref().unwrap().per_day - 110.0).abs() < 1e-9);
+    assert_eq!(alone.runs_out, Some(day("2024-05-20")));
+    // Too little history for a rate
+    assert!(forecast::consumption_rate(&changes, "g", None, day("2024-05-06")).is_none());
+
+    let pancakes = Recipe {
+        ingredients: vec![RecipeIngredient {
+            ingredient: "flour".to_string(),
+            quantity: Some(Quantity::from(300.0)),
+            quantity_type: Some("g".to_string()),
+            ..Default::default()
+        }],
+        servings: Some(2),
+        ..recipe_titled("Pancakes")
+    };
+    let bread = Recipe {
+        ingredients: vec![RecipeIngredient {
+            ingredient: "flour".to_string(),
+            quantity: Some(Quantity::from(3.0)),
+            quantity_type: Some("cups".to_string()),
+            ..Default::default()
+        }],
+        ..recipe_titled("Bread")
+    };
+    let doubled = MealPlanEntry {
+        servings: Some(4),
+        ..planned("2024-05-13", Meal::Breakfast, "Pancakes")
+    };
+    let bake = planned("2024-05-14", Meal::Lunch, "Bread");
+    let with_plan = forecast::forecast(
+        &flour,
+        None,
+        &changes,
+        &[(&doubled, &pancakes), (&bake, &bread)],
+        day("2024-05-11"),
+    )
+    .unwrap();
+    assert_eq!(with_plan.planned.len(), 1);
+    assert_eq!(with_plan.planned[0].quantity, 600.0);
+    // Cups of flour can't count against grams without a density
+    assert_eq!(
+        with_plan.unmeasured,
+        [forecast::UnmeasuredUse {
+            recipe: "Bread".to_string(),
+            missing: Some(units::MissingConversion::Density)
+        }]
+    );
+    // 1000 - 2 days × 110 - 600 leaves 180 g on the 13th: one more day
+    assert_eq!(with_plan.runs_out, Some(day("2024-05-14")));
+}
+
+#[test]
+fn test_shopping_list_from_plan_and_forecast() {
+    let temp_dir = setup_temp_data_dir();
+    let journal = PantryJournal {
+        changes: vec![
+            change("2024-04-20", "egg", None, 12.0, ""),
+            change("2024-04-30", "egg", Some(12.0), 5.0, ""),
+        ],
+    };
+    journal
+        .to_file(temp_dir.path().join("pantry_journal.yaml"))
+        .unwrap();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    let measured = |ingredient: &str, quantity: f64, unit: &str| RecipeIngredient {
+        ingredient: ingredient.to_string(),
+        quantity: Some(Quantity::from(quantity)),
+        quantity_type: Some(unit.to_string()),
+        ..Default::default()
+    };
+    dm.create_recipe(Recipe {
+        ingredients: vec![measured("flour", 250.0, "g"), measured("egg", 2.0, "")],
+        servings: Some(2),
+        ..recipe_titled("Pancakes")
+    })
+    .unwrap();
+    dm.add_meal_plan_entry(planned("2024-05-08", Meal::Breakfast, "Pancakes"))
+        .unwrap();
+    dm.add_meal_plan_entry(planned("2024-05-10", Meal::Breakfast, "Pancakes"))
+        .unwrap();
+    dm.add_meal_plan_entry(MealPlanEntry {
+        servings: Some(4),
+        ..planned("2024-05-07", Meal::Dinner, "Lasagna")
+    })
+    .unwrap();
+    dm.add_meal_plan_entry(planned("2024-05-20", Meal::Dinner, "Lasagna"))
+        .unwrap(); // after the shop
+
+    let list = dm.shopping_list(day("2024-05-06"), day("2024-05-18"));
+    let names: Vec<&str> = list.items.iter().map(|i| i.ingredient.as_str()).collect();
+    // Tomato is in stock and Lasagna measures it in another unit, so it can't be forecast
+    assert_eq!(names, ["egg", "flour", "potato"]);
+
+    // Not in the pantry: what the planned meals need
+    let flour = &list.items[1];
+    assert_eq!(
+        (flour.quantity, flour.quantity_type.as_str(), flour.runs_out),
+        (Some(500.0), "g", None)
+    );
+    assert_eq!(flour.reason(), "Needed for Pancakes");
+
+    // 2 kg in stock, 4 kg for a double Lasagna
+    let potato = &list.items[2];
+    assert_eq!(
+        (potato.quantity, potato.runs_out),
+        (Some(2.0), Some(day("2024-05-07")))
+    );
+
+    // 7 eggs used in 16 days, and 2 per breakfast: the 5 left fall short on the 10th
+    let egg = &list.items[0];
+    assert_eq!(egg.runs_out, Some(day("2024-05-10")));
+    assert!((egg.quantity.unwrap() - 4.25).abs() < 1e-9);
+    assert_eq!(
+        egg.reason(),
+        "Runs out around 2024-05-10; needed for Pancakes"
+    );
+}
+
+// ─── Group 29: Units, prices and costs ───────────────────────────────────────
+
+fn price(date: &str, store: &str, amount: f64, unit: &str) -> Price {
+    Price {
+        date: day(date),
+        store: store.to_string(),
+        price: amount,
+        unit: unit.to_string(),
+    }
+}
+
+#[test]
+fn test_unit_conversion() {
+    assert_eq!(units::convert(500.0, "g", "kg"), Some(0.5));
+    assert!((units::convert(2.0, "Tablespoons", "tsp").unwrap() - 6.0).abs() < 1e-9);
+    assert!((units::convert(1.0, "lb", "g").unwrap() - 453.59237).abs() < 1e-9);
+    assert_eq!(units::convert(3.0, "", "pcs"), Some(3.0));
+    assert_eq!(units::convert(2.0, "cloves", "clove"), Some(2.0));
+    // Mass and volume don't convert without a density
+    assert_eq!(units::convert(1.0, "cup", "g"), None);
+    assert_eq!(units::convert(1.0, "can", "g"), None);
+    assert_eq!(units::dimension("dl"), Some(units::Dimension::Volume));
+}
+
+#[test]
+fn test_ingredient_prices_and_recipe_cost() {
+    let temp_dir = setup_temp_data_dir();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    dm.add_ingredient_price("potato", price("2024-05-01", "Netto", 1.5, "kg"))
+        .unwrap();
+    dm.add_ingredient_price("potato", price("2023-01-01", "Føtex", 2.0, "kg"))
+        .unwrap();
+    dm.add_ingredient_price("tomato", price("2023-06-01", "", 0.004, "g"))
+        .unwrap();
+    assert!(dm
+        .add_ingredient_price("potato", price("2024-05-01", "", -1.0, "kg"))
+        .is_err());
+    assert!(dm
+        .add_ingredient_price("unobtainium", price("2024-05-01", "", 1.0, "kg"))
+        .is_err());
+
+    let reloaded = DataManager::new(temp_dir.path()).unwrap();
+    let potato = reloaded.get_ingredient("potato").unwrap();
+    assert_eq!(
+        potato
+            .prices
+            .iter()
+            .map(|p| p.store.as_str())
+            .collect::<Vec<_>>(),
+        ["Føtex", "Netto"]
+    );
+    assert_eq!(potato.latest_price().unwrap().price, 1.5);
+
+    // Lasagna: 2 kg potatoes at 1.50 and 1 kg tomatoes at 0.004 per g, for 2 servings
+    let cost = reloaded.recipe_cost("Lasagna", day("2024-05-06")).unwrap();
+    assert!((cost.estimate.total - 7.0).abs() < 1e-9);
+    assert!((cost.per_serving().unwrap() - 3.5).abs() < 1e-9);
+    assert!(cost.estimate.is_complete());
+    // The tomato price is almost a year old
+    assert_eq!(cost.estimate.stale(), ["tomato"]);
+
+    assert!(dm
+        .remove_ingredient_price("potato", &price("2023-01-01", "Føtex", 2.0, "kg"))
+        .unwrap());
+    assert!(!dm
+        .remove_ingredient_price("potato", &price("2023-01-01", "Føtex", 2.0, "kg"))
+        .unwrap());
+    assert_eq!(dm.get_ingredient("potato").unwrap().prices.len(), 1);
+
+    // Removing the last price takes the key out of the file
+    assert!(dm
+        .remove_ingredient_price("potato", &price("2024-05-01", "Netto", 1.5, "kg"))
+        .unwrap());
+    assert!(
+        !std::fs::read_to_string(temp_dir.path().join("ingredients/potato.yaml"))
+            .unwrap()
+            .contains("prices")
+    );
+    assert!(DataManager::new(temp_dir.path())
+        .unwrap()
+        .get_ingredient("potato")
+        .unwrap()
+        .prices
+        .is_empty());
+}
+
+#[test]
+fn test_missing_prices_cost_filter_and_shopping_cost() {
+    let temp_dir = setup_temp_data_dir();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    dm.add_ingredient_price("potato", price("2024-05-01", "", 1.5, "kg"))
+        .unwrap();
+    dm.add_ingredient_price("tomato", price("2024-05-01", "", 4.0, "kg"))
+        .unwrap();
+    let today = day("2024-05-06");
+
+    // Spaghetti's ingredients have no prices: its cost is incomplete
+    let spaghetti = dm.recipe_cost("Spaghetti Aglio e Olio", today).unwrap();
+    assert!(!spaghetti.estimate.is_complete());
+    assert!(spaghetti.estimate.uncosted().contains(&"garlic"));
+    assert_eq!(
+        spaghetti.estimate.lines[0].issue,
+        Some(cost::PriceIssue::Missing)
+    );
+
+    // Only Lasagna (3.50 a serving) can be shown to be within a budget
+    let titles = |max: f64| {
+        dm.recipes_within_cost(max, today)
+            .iter()
+            .map(|r| r.title.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(titles(4.0), ["Lasagna"]);
+    assert!(titles(3.0).is_empty());
+
+    // A price per kg can't cost an amount in cups
+    let flour = Ingredient {
+        prices: vec![price("2024-05-01", "", 1.2, "kg")],
+        ..dm.get_ingredient("flour").unwrap().clone()
+    };
+    let line = cost::line_cost("flour", Some(&flour), Some(2.0), "cups", today);
+    assert_eq!(
+        (line.cost, line.issue),
+        (
+            None,
+            Some(cost::PriceIssue::Unconvertible {
+                price_unit: "kg".to_string()
+            })
+        )
+    );
+
+    // A double Lasagna needs 2 kg more potatoes than the pantry has
+    dm.add_meal_plan_entry(MealPlanEntry {
+        servings: Some(4),
+        ..planned("2024-05-07", Meal::Dinner, "Lasagna")
+    })
+    .unwrap();
+    let list = dm.shopping_list(today, day("2024-05-13"));
+    let estimate = dm.shopping_list_cost(&list);
+    assert!((estimate.total - 3.0).abs() < 1e-9);
+    assert!(estimate.is_complete());
+}
+
+#[test]
+fn test_merge_ingredients_keeps_both_price_histories() {
+    let temp_dir = setup_temp_data_dir();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    dm.create_ingredient(ingredient_with("tomatoes", &[], &[]))
+        .unwrap();
+    dm.add_ingredient_price("tomato", price("2024-05-01", "Netto", 4.0, "kg"))
+        .unwrap();
+    dm.add_ingredient_price("tomatoes", price("2024-03-01", "Føtex", 5.0, "kg"))
+        .unwrap();
+    // Recorded on both: kept once, with the kept ingredient's price
+    dm.add_ingredient_price("tomatoes", price("2024-05-01", "Netto", 3.0, "kg"))
+        .unwrap();
+    dm.add_ingredient_price("tomatoes", price("2024-05-01", "Netto", 0.5, "pcs"))
+        .unwrap();
+
+    dm.merge_ingredients("tomato", "tomatoes").unwrap();
+    let reloaded = DataManager::new(temp_dir.path()).unwrap();
+    assert_eq!(
+        reloaded.get_ingredient("tomato").unwrap().prices,
+        [
+            price("2024-03-01", "Føtex", 5.0, "kg"),
+            price("2024-05-01", "Netto", 4.0, "kg"),
+            price("2024-05-01", "Netto", 0.5, "pcs"),
+        ]
+    );
+}
+
+// ─── Group 30: Barcodes and the Open Food Facts importer ─────────────────────
+
+fn package(barcode: &str, quantity: Option<f64>, unit: &str) -> Package {
+    Package {
+        barcode: barcode.to_string(),
+        quantity,
+        unit: unit.to_string(),
+    }
+}
+
+#[test]
+fn test_barcode_normalization_and_package_sizes() {
+    assert_eq!(
+        barcode::normalize("4006381333931").as_deref(),
+        Some("4006381333931")
+    );
+    assert_eq!(
+        barcode::normalize("4006-3813 33931").as_deref(),
+        Some("4006381333931")
+    );
+    // UPC-A gains a leading zero, GTIN-14 loses one
+    assert_eq!(
+        barcode::normalize("036000291452").as_deref(),
+        Some("0036000291452")
+    );
+    assert_eq!(
+        barcode::normalize("04006381333931").as_deref(),
+        Some("4006381333931")
+    );
+    assert_eq!(barcode::normalize("96385074").as_deref(), Some("96385074"));
+    assert_eq!(barcode::normalize("4006381333932"), None); // bad check digit
+    assert_eq!(barcode::normalize("40063813339"), None);
+    assert_eq!(barcode::normalize("ABC"), None);
+
+    assert_eq!(
+        barcode::parse_package_size("500 g"),
+        Some((500.0, "g".to_string()))
+    );
+    assert_eq!(
+        barcode::parse_package_size("1,5 L"),
+        Some((1.5, "l".to_string()))
+    );
+    assert_eq!(
+        barcode::parse_package_size("6 x 125g"),
+        Some((750.0, "g".to_string()))
+    );
+    assert_eq!(barcode::parse_package_size("1 pack"), None);
+    assert_eq!(barcode::parse_package_size(""), None);
+}
+
+#[test]
+fn test_barcode_mapping_and_add_to_pantry() {
+    let temp_dir = setup_temp_data_dir();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    dm.add_ingredient_package("potato", package("4006381333931", Some(500.0), "g"))
+        .unwrap();
+    dm.add_ingredient_package("milk", package("96385074", Some(1.0), "l"))
+        .unwrap();
+    dm.add_ingredient_package("flour", package("036000291452", Some(1.0), "kg"))
+        .unwrap();
+    dm.add_ingredient_package("tomato", package("5701234567899", Some(400.0), "g"))
+        .unwrap();
+    // A code belongs to one ingredient, and must be valid
+    assert!(dm
+        .add_ingredient_package("butter", package("4006381333931", None, ""))
+        .is_err());
+    assert!(dm
+        .add_ingredient_package("butter", package("123", None, ""))
+        .is_err());
+    // Re-adding a code updates its package size
+    dm.add_ingredient_package("potato", package("4006 3813 33931", Some(1.0), "kg"))
+        .unwrap();
+
+    let reloaded = DataManager::new(temp_dir.path()).unwrap();
+    let (ingredient, found) = reloaded.lookup_barcode("0036000291452").unwrap();
+    assert_eq!(
+        (ingredient.name.as_str(), found.quantity),
+        ("flour", Some(1.0))
+    );
+    assert_eq!(
+        reloaded.get_ingredient("potato").unwrap().packages,
+        [package("4006381333931", Some(1.0), "kg")]
+    );
+    assert!(reloaded.lookup_barcode("7622210449283").is_none());
+
+    // 2 kg of potatoes plus a 1 kg bag
+    let potato = dm
+        .add_to_pantry_by_barcode("4006381333931")
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        (potato.quantity, potato.quantity_type.as_str()),
+        (Some(3.0), "kg")
+    );
+    // Milk was in stock without an amount, flour wasn't in the pantry: both get the package size
+    let milk = dm.add_to_pantry_by_barcode("96385074").unwrap().unwrap();
+    assert_eq!(
+        (milk.quantity, milk.quantity_type.as_str()),
+        (Some(1.0), "l")
+    );
+    let flour = dm
+        .add_to_pantry_by_barcode("036000291452")
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        (flour.quantity, flour.quantity_type.as_str()),
+        (Some(1.0), "kg")
+    );
+    // Tomatoes are counted, so grams can't be added
+    assert!(dm.add_to_pantry_by_barcode("5701234567899").is_err());
+    assert_eq!(dm.get_pantry_item("tomato").unwrap().quantity, Some(2.0));
+    assert!(dm
+        .add_to_pantry_by_barcode("7622210449283")
+        .unwrap()
+        .is_none());
+
+    assert!(dm.remove_ingredient_package("milk", "96385074").unwrap());
+    assert!(!dm.remove_ingredient_package("milk", "96385074").unwrap());
+    assert!(dm.lookup_barcode("96385074").is_none());
+    // Milk had no other package, so the key is gone from the file
+    assert!(
+        !std::fs::read_to_string(temp_dir.path().join("ingredients/milk.yaml"))
+            .unwrap()
+            .contains("packages")
+    );
+}
+
+#[test]
+fn test_open_food_facts_suggestions() {
+    let temp_dir = setup_temp_data_dir();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    dm.add_ingredient_package("potato", package("7622210449283", None, ""))
+        .unwrap();
+
+    let export = temp_dir.path().join("products.csv");
+    let rows = [
+        "code\tproduct_name\tgeneric_name\tquantity\tcategories_tags",
+        "036000291452\tPeeled Tomatoes\t\t400 g\ten:plant-based-foods,en:canned-tomatoes,en:tomatoes",
+        "4006381333931\tBio Weizenmehl Type 405\t\t1 kg\ten:cereals,en:flours",
+        "96385074\tFresh Whole Milk\t\t1 L\t",
+        "96385074\tFresh Whole Milk (duplicate)\t\t1 L\t",
+        "5701234567899\tChocolate Bar\t\t100 g\ten:snacks",
+        "7622210449283\tBaking Potatoes\t\t2 kg\ten:potatoes",
+        "123\tTomatoes\t\t\t",
+    ];
+    std::fs::write(&export, rows.join("\n")).unwrap();
+
+    let suggestions = dm.suggest_barcode_mappings(&export).unwrap();
+    let summary: Vec<_> = suggestions
+        .iter()
+        .map(|s| {
+            (
+                s.ingredient.as_str(),
+                s.package.barcode.as_str(),
+                s.confidence,
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("flour", "4006381333931", 0.9),
+            ("milk", "96385074", 0.6),
+            ("tomato", "0036000291452", 0.9)
+        ]
+    );
+    assert_eq!(suggestions[0].reason, "category \"flours\"");
+    assert_eq!(
+        suggestions[2].package,
+        package("0036000291452", Some(400.0), "g")
+    );
+    assert_eq!(suggestions[1].product, "Fresh Whole Milk");
+
+    // Comma-separated exports may quote fields
+    let csv = "code,product_name,quantity\n\"5701234567899\",\"Butter, salted\",250 g\n";
+    let suggestions = barcode::suggest_mappings(
+        csv.as_bytes(),
+        dm.get_all_ingredients(),
+        &Default::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        (
+            suggestions[0].ingredient.as_str(),
+            suggestions[0].package.quantity
+        ),
+        ("butter", Some(250.0))
+    );
+
+    assert!(barcode::suggest_mappings(
+        "name\nfoo\n".as_bytes(),
+        dm.get_all_ingredients(),
+        &Default::default()
+    )
+    .is_err());
+}
+
+#[test]
+fn test_merge_ingredients_keeps_barcodes() {
+    let temp_dir = setup_temp_data_dir();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    dm.create_ingredient(ingredient_with("tomatoes", &[], &[]))
+        .unwrap();
+    dm.add_ingredient_package("tomato", package("5701234567899", Some(400.0), "g"))
+        .unwrap();
+    dm.add_ingredient_package("tomatoes", package("4006381333931", Some(500.0), "g"))
+        .unwrap();
+    // A hand-edited file may list a code twice; the kept ingredient's package wins
+    let mut tomatoes = dm.get_ingredient("tomatoes").unwrap().clone();
+    tomatoes
+        .packages
+        .push(package("5701234567899", Some(1.0), "kg"));
+    dm.update_ingredient("tomatoes", tomatoes).unwrap();
+
+    dm.merge_ingredients("tomato", "tomatoes").unwrap();
+    let reloaded = DataManager::new(temp_dir.path()).unwrap();
+    assert_eq!(
+        reloaded.get_ingredient("tomato").unwrap().packages,
+        [
+            package("5701234567899", Some(400.0), "g"),
+            package("4006381333931", Some(500.0), "g"),
+        ]
+    );
+    let (ingredient, _) = reloaded.lookup_barcode("4006381333931").unwrap();
+    assert_eq!(ingredient.name, "tomato");
+}
+
+// ─── Group 31: Density and unit weight ───────────────────────────────────────
+
+fn with_conversions(
+    ingredient: &Ingredient,
+    density: Option<f64>,
+    unit_weight: Option<f64>,
+) -> Ingredient {
+    Ingredient {
+        density,
+        unit_weight,
+        ..ingredient.clone()
+    }
+}
+
+#[test]
+fn test_convert_with_density_and_unit_weight() {
+    let temp_dir = setup_temp_data_dir();
+    let dm = DataManager::new(temp_dir.path()).unwrap();
+    let flour = with_conversions(dm.get_ingredient("flour").unwrap(), Some(0.53), None);
+    let egg = with_conversions(dm.get_ingredient("egg").unwrap(), None, Some(55.0));
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-6;
+
+    assert!(close(
+        units::convert_ingredient(2.0, "cups", "g", Some(&flour)).unwrap(),
+        2.0 * 236.5882365 * 0.53
+    ));
+    assert!(close(
+        units::convert_ingredient(1.0, "kg", "cup", Some(&flour)).unwrap(),
+        1000.0 / 0.53 / 236.5882365
+    ));
+    assert!(close(
+        units::convert_ingredient(3.0, "", "g", Some(&egg)).unwrap(),
+        165.0
+    ));
+    assert!(close(
+        units::convert_ingredient(110.0, "g", "pcs", Some(&egg)).unwrap(),
+        2.0
+    ));
+    // Same-kind conversions need no ingredient data
+    assert!(close(
+        units::convert_ingredient(500.0, "g", "kg", None).unwrap(),
+        0.5
+    ));
+
+    // Missing data is named
+    assert_eq!(
+        units::convert_ingredient(1.0, "cup", "g", None),
+        Err(units::MissingConversion::Density)
+    );
+    assert_eq!(
+        units::convert_ingredient(1.0, "cup", "g", Some(&egg)),
+        Err(units::MissingConversion::Density)
+    );
+    assert_eq!(
+        units::convert_ingredient(2.0, "", "g", Some(&flour)),
+        Err(units::MissingConversion::UnitWeight)
+    );
+    assert_eq!(
+        units::convert_ingredient(2.0, "cloves", "g", Some(&flour)),
+        Err(units::MissingConversion::Unit("clove".to_string()))
+    );
+    assert_eq!(units::MissingConversion::Density.to_string(), "a density");
+}
+
+#[test]
+fn test_merge_ingredients_keeps_conversions() {
+    let temp_dir = setup_temp_data_dir();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    let tomato = with_conversions(dm.get_ingredient("tomato").unwrap(), None, Some(100.0));
+    dm.update_ingredient("tomato", tomato).unwrap();
+    let tomatoes = ingredient_with("tomatoes", &[], &[]);
+    dm.create_ingredient(with_conversions(&tomatoes, Some(1.02), Some(120.0)))
+        .unwrap();
+
+    // The kept ingredient's weight per piece wins; the density it lacked is taken over
+    dm.merge_ingredients("tomato", "tomatoes").unwrap();
+    let reloaded = DataManager::new(temp_dir.path()).unwrap();
+    let tomato = reloaded.get_ingredient("tomato").unwrap();
+    assert_eq!(
+        (tomato.density, tomato.unit_weight),
+        (Some(1.02), Some(100.0))
+    );
+}
+
+#[test]
+fn test_stock_checks_forecasts_and_shopping_across_units() {
+    let temp_dir = setup_temp_data_dir();
+    let mut dm = DataManager::new(temp_dir.path()).unwrap();
+    let flour = with_conversions(dm.get_ingredient("flour").unwrap(), Some(0.53), None);
+    dm.update_ingredient("flour", flour).unwrap();
+    dm.update_pantry_item("flour", Some(1.0), Some("kg".to_string()))
+        .unwrap();
+    assert_eq!(
+        DataManager::new(temp_dir.path())
+            .unwrap()
+            .get_ingredient("flour")
+            .unwrap()
+            .density,
+        Some(0.53)
+    );
+
+    let measured = |ingredient: &str, quantity: f64, unit: &str| RecipeIngredient {
+        ingredient: ingredient.to_string(),
+        quantity: Some(Quantity::from(quantity)),
+        quantity_type: Some(unit.to_string()),
+        ..Default::default()
+    };
+    dm.create_recipe(Recipe {
+        ingredients: vec![
+            measured("flour", 2.0, "cups"),
+            measured("egg", 2.0, ""),
+            measured("butter", 50.0, "g"),
+            measured("yoghurt", 200.0, "g"),
+        ],
+        servings: Some(2),
+        ..recipe_titled("Pancakes")
+    })
+    .unwrap();
+    dm.create_recipe(Recipe {
+        ingredients: vec![measured("yoghurt", 1.0, "cup")],
+        ..recipe_titled("Smoothie")
+    })
+    .unwrap();
+
+    // 2 cups of flour is about 251 g of the 1 kg in stock
+    let checks = dm.check_recipe_stock("Pancakes", None).unwrap();
+    let stock: Vec<_> = checks
+        .iter()
+        .map(|c| (c.ingredient.as_str(), c.stock.clone()))
+        .collect();
+    assert_eq!(
+        stock,
+        [
+            ("flour", shopping::Stock::Enough),
+            ("egg", shopping::Stock::Enough),
+            // Butter is counted in the pantry and weighed in the recipe
+            (
+                "butter",
+                shopping::Stock::Unknown(units::MissingConversion::UnitWeight)
+            ),
+            ("yoghurt", shopping::Stock::Missing),
+        ]
+    );
+    // Ten times over, the flour and eggs run short
+    let checks = dm.check_recipe_stock("Pancakes", Some(20)).unwrap();
+    let shopping::Stock::Short(flour_short) = checks[0].stock else {
+        panic!("{:?}", checks[0])
+    };
+    assert!((flour_short - (20.0 - 1000.0 / 0.53 / 236.5882365)).abs() < 1e-6);
+    assert_eq!(checks[1].stock, shopping::Stock::Short(15.0));
+    assert!(dm.check_recipe_stock("Waffles", None).is_none());
+
+    // Planned cups count against the stock in kg
+    dm.add_meal_plan_entry(planned("2024-05-07", Meal::Breakfast, "Pancakes"))
+        .unwrap();
+    dm.add_meal_plan_entry(planned("2024-05-08", Meal::Breakfast, "Smoothie"))
+        .unwrap();
+    let forecast = dm.forecast_pantry_item("flour", day("2024-05-06")).unwrap();
+    assert!((forecast.planned[0].quantity - 2.0 * 0.2365882365 * 0.53).abs() < 1e-6);
+    assert!(forecast.unmeasured.is_empty());
+
+    // Yoghurt in grams and in cups can't be added up without its density
+    let list = dm.shopping_list(day("2024-05-06"), day("2024-05-13"));
+    let yoghurt = list
+        .items
+        .iter()
+        .find(|i| i.ingredient == "yoghurt")
+        .unwrap();
+    assert_eq!(
+        (yoghurt.quantity, yoghurt.missing.clone()),
+        (None, Some(units::MissingConversion::Density))
+    );
+    let with_density = with_conversions(dm.get_ingredient("yoghurt").unwrap(), Some(1.03), None);
+    dm.update_ingredient("yoghurt", with_density).unwrap();
+    let list = dm.shopping_list(day("2024-05-06"), day("2024-05-13"));
+    let yoghurt = list
+        .items
+        .iter()
+        .find(|i| i.ingredient == "yoghurt")
+        .unwrap();
+    assert!((yoghurt.quantity.unwrap() - (200.0 + 236.5882365 * 1.03)).abs() < 1e-6);
+    assert_eq!(
+        (yoghurt.quantity_type.as_str(), yoghurt.missing.clone()),
+        ("g", None)
+    );
+}
+
+// ─── Group 32: Unit system display ───────────────────────────────────────────
+
+#[test]
+fn test_format_amounts_in_unit_system() {
+    use units::{format_amount, UnitSystem};
+
+    assert_eq!(format_amount(354.9, "ml", UnitSystem::Imperial), "1 ½ cups");
+    assert_eq!(format_amount(236.6, "ml", UnitSystem::Imperial), "1 cup");
+    assert_eq!(format_amount(120.0, "ml", UnitSystem::Imperial), "½ cup");
+    assert_eq!(format_amount(15.0, "ml", UnitSystem::Imperial), "1 tbsp");
+    assert_eq!(format_amount(2.5, "ml", UnitSystem::Imperial), "½ tsp");
+    assert_eq!(format_amount(1.0, "kg", UnitSystem::Imperial), "2 ¼ lb");
+    assert_eq!(format_amount(100.0, "g", UnitSystem::Imperial), "3 ½ oz");
+    assert_eq!(format_amount(2.0, "cups", UnitSystem::Metric), "475 ml");
+    assert_eq!(format_amount(1.5, "lb", UnitSystem::Metric), "680 g");
+    assert_eq!(format_amount(5.0, "cups", UnitSystem::Metric), "1.2 l");
+    // Spoons are metric too, and amounts that keep their unit aren't rounded
+    assert_eq!(format_amount(2.0, "tbsp", UnitSystem::Metric), "2 tbsp");
+    assert_eq!(format_amount(354.9, "ml", UnitSystem::Metric), "354.9 ml");
+    // Original units, pieces and units that don't convert stay as given
+    assert_eq!(format_amount(354.9, "ml", UnitSystem::Original), "354.9 ml");
+    assert_eq!(format_amount(3.0, "", UnitSystem::Imperial), "3");
+    assert_eq!(format_amount(2.0, "cloves", UnitSystem::Metric), "2 cloves");
+}
+
+#[test]
+fn test_format_recipe_ingredient_in_unit_system() {
+    let temp_dir = setup_temp_data_dir();
+    let dm = DataManager::new(temp_dir.path()).unwrap();
+    let line = |q: &str, unit: &str, system: UnitSystem| {
+        dm.format_recipe_ingredient_in(
+            &RecipeIngredient {
+                ingredient: "flour".to_string(),
+                quantity: Some(q.parse().unwrap()),
+                quantity_type: Some(unit.to_string()),
+                ..Default::default()
+            },
+            "en",
+            system,
+        )
+    };
+
+    assert_eq!(line("354.9", "ml", UnitSystem::Imperial), "1 ½ cups flour");
+    assert_eq!(line("200-250", "ml", UnitSystem::Imperial), "¾–1 cup flour");
+    assert_eq!(line("1 1/2", "cup", UnitSystem::Metric), "355 ml flour");
+    assert_eq!(line("to taste", "", UnitSystem::Metric), "flour to taste");
+    // The original units are still there
+    assert_eq!(line("354.9", "ml", UnitSystem::Original), "354.9 ml flour");
+    assert_eq!(
+        line("1 1/2", "cup", UnitSystem::Original),
+        dm.format_recipe_ingredient(
+            &RecipeIngredient {
+                ingredient: "flour".to_string(),
+                quantity: Some("1 1/2".parse().unwrap()),
+                quantity_type: Some("cup".to_string()),
+                ..Default::default()
+            },
+            "en"
+        )
+    );
 }
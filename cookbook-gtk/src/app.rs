@@ -1,7 +1,13 @@
 /// Main application component: AppModel (state), AppMsg (messages), AppWidgets (UI references).
 use crate::config::{Theme, UserSettings};
 use crate::ui_constants::*;
-use cookbook_engine::{DataManager, Ingredient, Recipe};
+use chrono::{Local, NaiveDate};
+use cookbook_engine::links::LinkTarget;
+use cookbook_engine::planner::DietaryProfile;
+use cookbook_engine::{
+    DataManager, DeleteStrategy, Ingredient, KnowledgeBaseEntry, Meal, MealPlanEntry, Recipe,
+    TemperatureUnit, UnitSystem,
+};
 use libadwaita as adw;
 use relm4::gtk;
 use relm4::{gtk::prelude::*, ComponentParts, ComponentSender, SimpleComponent};
@@ -15,6 +21,7 @@ use std::sync::mpsc;
 #[derive(Debug, Clone, PartialEq)]
 pub enum Tab {
     Recipes,
+    Plan,
     Pantry,
     Kb,
     Settings,
@@ -26,37 +33,82 @@ pub enum Tab {
 pub enum AppMsg {
     // Navigation
     SwitchTab(Tab),
+    OpenLink(LinkTarget),
 
     // Recipes
     SearchRecipes(String),
+    /// Only list recipes costing at most this per serving
+    SetRecipeCostLimit(Option<f64>),
     SelectRecipe(Option<String>),
     AddRecipe,
     EditRecipe(String),
+    CookRecipe(String),
+    ScheduleRecipes,
     DeleteRecipe(String),
     SaveRecipe { original: Option<String>, recipe: Recipe },
 
+    // Meal plan
+    ShowPlanWeek(NaiveDate),
+    AddPlanEntry(MealPlanEntry),
+    MovePlanEntry {
+        entry: MealPlanEntry,
+        date: NaiveDate,
+        meal: Meal,
+        copy: bool,
+    },
+    SetPlanServings {
+        entry: MealPlanEntry,
+        servings: Option<u32>,
+    },
+    RemovePlanEntry(MealPlanEntry),
+    CopyPreviousPlanWeek,
+    SuggestMeals,
+    ShowShoppingList,
+    AddPlanEntries(Vec<MealPlanEntry>),
+    SetMealSuggestionSettings {
+        time_budget: [u32; 7],
+        profile: DietaryProfile,
+    },
+
     // Pantry
     SearchIngredients(String),
     SelectIngredient(Option<String>),
     ToggleInStockOnly(bool),
     AddIngredient,
     EditIngredient(String),
-    DeleteIngredient(String),
+    DeleteIngredient {
+        name: String,
+        strategy: DeleteStrategy,
+    },
     SaveIngredient {
         original: Option<String>,
         ingredient: Ingredient,
         in_pantry: bool,
         qty: Option<f64>,
         qty_type: String,
+        expires: Option<NaiveDate>,
     },
 
     // Knowledge Base
     SelectKb(Option<String>),
+    AddKbEntry,
+    EditKbEntry(String),
+    DeleteKbEntry(String),
+    SaveKbEntry {
+        original: Option<String>,
+        entry: KnowledgeBaseEntry,
+        linked_ingredients: Vec<String>,
+    },
+
+    // Sync
+    MergePantryConflictCopies,
 
     // Settings
     SetDataDir(String),
     DataDirReady(String),
     SetTheme(String),
+    SetTemperatureUnit(TemperatureUnit),
+    SetUnitSystem(UnitSystem),
 
     // System
     ShowToast(String),
@@ -74,8 +126,13 @@ pub struct App {
 
     // Recipes state
     pub recipe_search: String,
+    pub recipe_max_cost: Option<f64>,
     pub selected_recipe: Option<String>,
 
+    // Meal plan state
+    /// Monday of the week shown
+    pub plan_week: NaiveDate,
+
     // Pantry state
     pub ingredient_search: String,
     pub selected_ingredient: Option<String>,
@@ -90,14 +147,22 @@ pub struct App {
     pub pantry_dirty: Cell<bool>,
     pub kb_dirty: Cell<bool>,
     pub recipe_detail_dirty: Cell<bool>,
+    pub plan_dirty: Cell<bool>,
     pub ingredient_detail_dirty: Cell<bool>,
     pub kb_detail_dirty: Cell<bool>,
 
     // Pending dialog requests (RefCell allows mutation from &self in update_view)
     pub pending_add_recipe: Cell<bool>,
     pub pending_edit_recipe: RefCell<Option<String>>,
+    pub pending_cook_recipe: RefCell<Option<String>>,
+    pub pending_schedule: Cell<bool>,
+    pub pending_suggest: Cell<bool>,
+    pub pending_shopping: Cell<bool>,
     pub pending_add_ingredient: Cell<bool>,
     pub pending_edit_ingredient: RefCell<Option<String>>,
+    pub pending_add_kb: Cell<bool>,
+    pub pending_edit_kb: RefCell<Option<String>>,
+    pub pending_conflict_copies: RefCell<Vec<PathBuf>>,
 
     // Channel for receiving a DataManager loaded on a background thread
     pub pending_dm: Option<mpsc::Receiver<Result<DataManager, String>>>,
@@ -115,6 +180,9 @@ pub struct AppWidgets {
     pub recipe_list: gtk::ListBox,
     pub recipe_detail: gtk::Box,
 
+    // Meal plan
+    pub planner: crate::planner::PlannerView,
+
     // Pantry
     pub pantry_list: gtk::ListBox,
     pub ingredient_detail: gtk::Box,
@@ -171,7 +239,9 @@ impl SimpleComponent for App {
             settings: Rc::new(RefCell::new(settings.clone())),
             tab: Tab::Recipes,
             recipe_search: String::new(),
+            recipe_max_cost: None,
             selected_recipe: None,
+            plan_week: crate::planner::week_start(Local::now().date_naive()),
             ingredient_search: String::new(),
             selected_ingredient: None,
             category_filter: Vec::new(),
@@ -181,12 +251,20 @@ impl SimpleComponent for App {
             pantry_dirty: Cell::new(true),
             kb_dirty: Cell::new(true),
             recipe_detail_dirty: Cell::new(false),
+            plan_dirty: Cell::new(true),
             ingredient_detail_dirty: Cell::new(false),
             kb_detail_dirty: Cell::new(false),
             pending_add_recipe: Cell::new(false),
             pending_edit_recipe: RefCell::new(None),
+            pending_cook_recipe: RefCell::new(None),
+            pending_schedule: Cell::new(false),
+            pending_suggest: Cell::new(false),
+            pending_shopping: Cell::new(false),
             pending_add_ingredient: Cell::new(false),
             pending_edit_ingredient: RefCell::new(None),
+            pending_add_kb: Cell::new(false),
+            pending_edit_kb: RefCell::new(None),
+            pending_conflict_copies: RefCell::new(Vec::new()),
             pending_dm: Some(rx),
         };
 
@@ -218,6 +296,7 @@ impl SimpleComponent for App {
 
         for (icon, label, tab_name) in &[
             ("emblem-documents-symbolic", "Recipes", "recipes"),
+            ("x-office-calendar-symbolic", "Meal Plan", "plan"),
             ("view-list-symbolic", "Pantry", "pantry"),
             ("system-help-symbolic", "Knowledge Base", "kb"),
             ("preferences-system-symbolic", "Settings", "settings"),
@@ -251,6 +330,7 @@ impl SimpleComponent for App {
                     let name = row.widget_name().to_string();
                     let tab = match name.as_str() {
                         "recipes" => Tab::Recipes,
+                        "plan" => Tab::Plan,
                         "pantry" => Tab::Pantry,
                         "kb" => Tab::Kb,
                         "settings" => Tab::Settings,
@@ -275,6 +355,10 @@ impl SimpleComponent for App {
             crate::recipes::build_recipes_tab(&None, sender.clone());
         main_stack.add_named(&recipes_widget, Some("recipes"));
 
+        // Meal plan tab
+        let planner = crate::planner::PlannerView::new(sender.clone());
+        main_stack.add_named(&planner.root, Some("plan"));
+
         // Pantry tab
         let (pantry_widget, pantry_list, ingredient_detail, in_stock_switch) =
             crate::pantry::build_pantry_tab(&None, false, sender.clone());
@@ -306,6 +390,7 @@ impl SimpleComponent for App {
             nav_list,
             recipe_list,
             recipe_detail,
+            planner,
             pantry_list,
             ingredient_detail,
             in_stock_switch,
@@ -324,14 +409,41 @@ impl SimpleComponent for App {
     fn update(&mut self, msg: AppMsg, sender: ComponentSender<Self>) {
         match msg {
             AppMsg::SwitchTab(tab) => {
+                if tab == Tab::Plan {
+                    self.plan_dirty.set(true);
+                }
                 self.tab = tab;
             }
+            AppMsg::OpenLink(target) => match target {
+                LinkTarget::Recipe(title) => {
+                    self.tab = Tab::Recipes;
+                    self.selected_recipe = Some(title);
+                    self.recipes_dirty.set(true);
+                    self.recipe_detail_dirty.set(true);
+                }
+                LinkTarget::Ingredient(name) => {
+                    self.tab = Tab::Pantry;
+                    self.selected_ingredient = Some(name);
+                    self.pantry_dirty.set(true);
+                    self.ingredient_detail_dirty.set(true);
+                }
+                LinkTarget::Kb(slug) => {
+                    self.tab = Tab::Kb;
+                    self.selected_kb = Some(slug);
+                    self.kb_dirty.set(true);
+                    self.kb_detail_dirty.set(true);
+                }
+            },
 
             // ── Recipes ───────────────────────────────────────────────────────
             AppMsg::SearchRecipes(q) => {
                 self.recipe_search = q;
                 self.recipes_dirty.set(true);
             }
+            AppMsg::SetRecipeCostLimit(max) => {
+                self.recipe_max_cost = max;
+                self.recipes_dirty.set(true);
+            }
             AppMsg::SelectRecipe(title) => {
                 self.selected_recipe = title;
                 self.recipe_detail_dirty.set(true);
@@ -342,6 +454,12 @@ impl SimpleComponent for App {
             AppMsg::EditRecipe(title) => {
                 *self.pending_edit_recipe.borrow_mut() = Some(title);
             }
+            AppMsg::CookRecipe(title) => {
+                *self.pending_cook_recipe.borrow_mut() = Some(title);
+            }
+            AppMsg::ScheduleRecipes => {
+                self.pending_schedule.set(true);
+            }
             AppMsg::DeleteRecipe(title) => {
                 if let Some(dm) = &self.dm {
                     match dm.borrow_mut().delete_recipe(&title) {
@@ -351,6 +469,7 @@ impl SimpleComponent for App {
                             }
                             self.recipes_dirty.set(true);
                             self.recipe_detail_dirty.set(true);
+                            self.plan_dirty.set(true);
                         }
                         Err(e) => {
                             sender.input(AppMsg::ShowToast(format!("Error: {}", e)));
@@ -372,6 +491,7 @@ impl SimpleComponent for App {
                             self.selected_recipe = Some(recipe.title.clone());
                             self.recipes_dirty.set(true);
                             self.recipe_detail_dirty.set(true);
+                            self.plan_dirty.set(true);
                         }
                         Err(e) => {
                             sender.input(AppMsg::ShowToast(format!("Error saving recipe: {}", e)));
@@ -380,6 +500,102 @@ impl SimpleComponent for App {
                 }
             }
 
+            // ── Meal plan ─────────────────────────────────────────────────────
+            AppMsg::ShowPlanWeek(week) => {
+                self.plan_week = week;
+                self.plan_dirty.set(true);
+            }
+            AppMsg::AddPlanEntry(entry) => {
+                if let Some(dm) = &self.dm {
+                    if let Err(e) = dm.borrow_mut().add_meal_plan_entry(entry) {
+                        sender.input(AppMsg::ShowToast(format!("Error: {}", e)));
+                    }
+                    self.plan_dirty.set(true);
+                }
+            }
+            AppMsg::MovePlanEntry {
+                entry,
+                date,
+                meal,
+                copy,
+            } => {
+                if let Some(dm) = &self.dm {
+                    let result = if copy {
+                        dm.borrow_mut().copy_meal_plan_entry(&entry, date, meal)
+                    } else {
+                        dm.borrow_mut().move_meal_plan_entry(&entry, date, meal)
+                    };
+                    if let Err(e) = result {
+                        sender.input(AppMsg::ShowToast(format!("Error: {}", e)));
+                    }
+                    self.plan_dirty.set(true);
+                }
+            }
+            AppMsg::SetPlanServings { entry, servings } => {
+                // The grid already shows the new value; rebuilding it would close the popover
+                if let Some(dm) = &self.dm {
+                    if let Err(e) = dm.borrow_mut().set_meal_plan_servings(&entry, servings) {
+                        sender.input(AppMsg::ShowToast(format!("Error: {}", e)));
+                    }
+                }
+            }
+            AppMsg::RemovePlanEntry(entry) => {
+                if let Some(dm) = &self.dm {
+                    if let Err(e) = dm.borrow_mut().remove_meal_plan_entry(&entry) {
+                        sender.input(AppMsg::ShowToast(format!("Error: {}", e)));
+                    }
+                    self.plan_dirty.set(true);
+                }
+            }
+            AppMsg::CopyPreviousPlanWeek => {
+                if let Some(dm) = &self.dm {
+                    let previous = self.plan_week - chrono::Duration::days(7);
+                    let last = self.plan_week - chrono::Duration::days(1);
+                    match dm
+                        .borrow_mut()
+                        .copy_meal_plan_range(previous, last, self.plan_week)
+                    {
+                        Ok(0) => sender.input(AppMsg::ShowToast(
+                            "Nothing to copy from the previous week".to_string(),
+                        )),
+                        Ok(count) => sender.input(AppMsg::ShowToast(format!(
+                            "Copied {} meals from the previous week",
+                            count
+                        ))),
+                        Err(e) => sender.input(AppMsg::ShowToast(format!("Error: {}", e))),
+                    }
+                    self.plan_dirty.set(true);
+                }
+            }
+            AppMsg::SuggestMeals => {
+                self.pending_suggest.set(true);
+            }
+            AppMsg::ShowShoppingList => {
+                self.pending_shopping.set(true);
+            }
+            AppMsg::AddPlanEntries(entries) => {
+                if let Some(dm) = &self.dm {
+                    let mut added = 0;
+                    for entry in entries {
+                        match dm.borrow_mut().add_meal_plan_entry(entry) {
+                            Ok(()) => added += 1,
+                            Err(e) => sender.input(AppMsg::ShowToast(format!("Error: {}", e))),
+                        }
+                    }
+                    sender.input(AppMsg::ShowToast(format!("Added {} meals", added)));
+                    self.plan_dirty.set(true);
+                }
+            }
+            AppMsg::SetMealSuggestionSettings {
+                time_budget,
+                profile,
+            } => {
+                let mut s = self.settings.borrow_mut();
+                s.cooking_time_budget = time_budget;
+                s.dietary_profile = profile;
+                s.save();
+            }
+
             // ── Pantry ────────────────────────────────────────────────────────
             AppMsg::SearchIngredients(q) => {
                 self.ingredient_search = q;
@@ -399,16 +615,24 @@ impl SimpleComponent for App {
             AppMsg::EditIngredient(name) => {
                 *self.pending_edit_ingredient.borrow_mut() = Some(name);
             }
-            AppMsg::DeleteIngredient(name) => {
+            AppMsg::DeleteIngredient { name, strategy } => {
                 if let Some(dm) = &self.dm {
-                    match dm.borrow_mut().delete_ingredient(&name) {
-                        Ok(_) => {
+                    let result = dm.borrow_mut().delete_ingredient(&name, strategy);
+                    match result {
+                        Ok(dependents) => {
                             if self.selected_ingredient.as_deref() == Some(&name) {
                                 self.selected_ingredient = None;
                             }
                             self.pantry_dirty.set(true);
                             self.ingredient_detail_dirty.set(true);
                             self.recipes_dirty.set(true); // availability may have changed
+                            if !dependents.recipes.is_empty() {
+                                self.recipe_detail_dirty.set(true);
+                                sender.input(AppMsg::ShowToast(format!(
+                                    "Updated recipes: {}",
+                                    dependents.recipes.join(", ")
+                                )));
+                            }
                         }
                         Err(e) => {
                             sender.input(AppMsg::ShowToast(format!("Error: {}", e)));
@@ -422,8 +646,16 @@ impl SimpleComponent for App {
                 in_pantry,
                 qty,
                 qty_type,
+                expires,
             } => {
                 if let Some(dm) = &self.dm {
+                    // Recipes that a rename rewrites, reported once the save succeeds
+                    let renamed_in = match original {
+                        Some(ref orig) if *orig != ingredient.name => {
+                            dm.borrow().preview_ingredient_rename(orig)
+                        }
+                        _ => Vec::new(),
+                    };
                     let result = if let Some(ref orig) = original {
                         dm.borrow_mut().update_ingredient_with_pantry(
                             orig,
@@ -450,12 +682,27 @@ impl SimpleComponent for App {
                             Ok(true)
                         }
                     };
+                    let result = result.and_then(|saved| {
+                        if in_pantry {
+                            dm.borrow_mut()
+                                .set_pantry_expiry(&ingredient.name, expires)?;
+                        }
+                        Ok(saved)
+                    });
                     match result {
                         Ok(_) => {
                             self.selected_ingredient = Some(ingredient.name.clone());
                             self.pantry_dirty.set(true);
                             self.ingredient_detail_dirty.set(true);
                             self.recipes_dirty.set(true);
+                            if !renamed_in.is_empty() {
+                                self.recipe_detail_dirty.set(true);
+                                sender.input(AppMsg::ShowToast(format!(
+                                    "Updated {} in: {}",
+                                    ingredient.name,
+                                    renamed_in.join(", ")
+                                )));
+                            }
                         }
                         Err(e) => {
                             sender.input(AppMsg::ShowToast(format!("Error: {}", e)));
@@ -469,6 +716,84 @@ impl SimpleComponent for App {
                 self.selected_kb = slug;
                 self.kb_detail_dirty.set(true);
             }
+            AppMsg::AddKbEntry => {
+                self.pending_add_kb.set(true);
+            }
+            AppMsg::EditKbEntry(slug) => {
+                *self.pending_edit_kb.borrow_mut() = Some(slug);
+            }
+            AppMsg::DeleteKbEntry(slug) => {
+                if let Some(dm) = &self.dm {
+                    match dm.borrow_mut().delete_kb_entry(&slug) {
+                        Ok(_) => {
+                            // A bundled article with the same slug may take its place
+                            if dm.borrow().get_kb_entry(&slug).is_none() {
+                                self.selected_kb = None;
+                            }
+                            self.kb_dirty.set(true);
+                            self.kb_detail_dirty.set(true);
+                        }
+                        Err(e) => {
+                            sender.input(AppMsg::ShowToast(format!("Error: {}", e)));
+                        }
+                    }
+                }
+            }
+            AppMsg::SaveKbEntry {
+                original,
+                entry,
+                linked_ingredients,
+            } => {
+                if let Some(dm) = &self.dm {
+                    let slug = entry.slug.clone();
+                    let result = match original {
+                        Some(ref orig) => dm.borrow_mut().update_kb_entry(orig, entry),
+                        None => dm.borrow_mut().create_kb_entry(entry).map(|_| true),
+                    };
+                    let result = result
+                        .and_then(|_| dm.borrow_mut().set_kb_links(&slug, &linked_ingredients));
+                    match result {
+                        Ok(_) => {
+                            self.selected_kb = Some(slug);
+                            self.kb_dirty.set(true);
+                            self.kb_detail_dirty.set(true);
+                            // Ingredient details show their KB link
+                            self.ingredient_detail_dirty.set(true);
+                        }
+                        Err(e) => {
+                            sender.input(AppMsg::ShowToast(format!("Error saving article: {}", e)));
+                        }
+                    }
+                }
+            }
+
+            // ── Sync ──────────────────────────────────────────────────────────
+            AppMsg::MergePantryConflictCopies => {
+                if let Some(dm) = &self.dm {
+                    let copies = dm
+                        .borrow()
+                        .find_pantry_conflict_copies()
+                        .unwrap_or_default();
+                    let mut unresolved = 0;
+                    for copy in &copies {
+                        match dm.borrow_mut().merge_pantry_conflict_copy(copy) {
+                            Ok(result) => unresolved += result.conflicts.len(),
+                            Err(e) => {
+                                sender.input(AppMsg::ShowToast(format!("Error: {}", e)));
+                            }
+                        }
+                    }
+                    if unresolved > 0 {
+                        sender.input(AppMsg::ShowToast(format!(
+                            "Merged pantry copies; {} items changed on both devices kept the local value",
+                            unresolved
+                        )));
+                    }
+                    self.pantry_dirty.set(true);
+                    self.ingredient_detail_dirty.set(true);
+                    self.recipes_dirty.set(true);
+                }
+            }
 
             // ── Settings ──────────────────────────────────────────────────────
             AppMsg::SetDataDir(dir) => {
@@ -493,6 +818,10 @@ impl SimpleComponent for App {
             AppMsg::DataDirReady(dir) => {
                 match self.pending_dm.take().and_then(|rx| rx.recv().ok()) {
                     Some(Ok(new_dm)) => {
+                        // Offer to fold in pantry copies left behind by the sync provider
+                        if let Ok(copies) = new_dm.find_pantry_conflict_copies() {
+                            *self.pending_conflict_copies.borrow_mut() = copies;
+                        }
                         self.dm = Some(Rc::new(RefCell::new(new_dm)));
                     }
                     Some(Err(e)) => {
@@ -503,6 +832,7 @@ impl SimpleComponent for App {
                     None => {}
                 }
                 self.recipes_dirty.set(true);
+                self.plan_dirty.set(true);
                 self.pantry_dirty.set(true);
                 self.kb_dirty.set(true);
                 self.selected_recipe = None;
@@ -520,6 +850,19 @@ impl SimpleComponent for App {
                 s.theme = theme;
                 s.save();
             }
+            AppMsg::SetTemperatureUnit(unit) => {
+                let mut s = self.settings.borrow_mut();
+                s.temperature_unit = unit;
+                s.save();
+                self.recipe_detail_dirty.set(true);
+            }
+            AppMsg::SetUnitSystem(system) => {
+                let mut s = self.settings.borrow_mut();
+                s.unit_system = system;
+                s.save();
+                self.recipe_detail_dirty.set(true);
+                self.ingredient_detail_dirty.set(true);
+            }
 
             // ── System ────────────────────────────────────────────────────────
             AppMsg::ShowToast(msg) => {
@@ -531,6 +874,7 @@ impl SimpleComponent for App {
                     self.dm = Some(Rc::new(RefCell::new(new_dm)));
                 }
                 self.recipes_dirty.set(true);
+                self.plan_dirty.set(true);
                 self.pantry_dirty.set(true);
                 self.kb_dirty.set(true);
             }
@@ -543,11 +887,17 @@ impl SimpleComponent for App {
         // Switch the visible tab
         let tab_name = match self.tab {
             Tab::Recipes => "recipes",
+            Tab::Plan => "plan",
             Tab::Pantry => "pantry",
             Tab::Kb => "kb",
             Tab::Settings => "settings",
         };
         widgets.main_stack.set_visible_child_name(tab_name);
+        // Keep the sidebar in step when the tab changed from elsewhere (e.g. a link)
+        let nav_selected = widgets.nav_list.selected_row().map(|r| r.widget_name());
+        if nav_selected.as_deref() != Some(tab_name) {
+            select_row_by_name(&widgets.nav_list, tab_name);
+        }
 
         // Rebuild recipe list if dirty
         if self.recipes_dirty.get() {
@@ -555,6 +905,7 @@ impl SimpleComponent for App {
                 &widgets.recipe_list,
                 &self.dm,
                 &self.recipe_search,
+                self.recipe_max_cost,
                 &sender,
             );
             self.recipes_dirty.set(false);
@@ -571,6 +922,8 @@ impl SimpleComponent for App {
                     &widgets.recipe_detail,
                     &self.dm,
                     title,
+                    self.settings.borrow().temperature_unit,
+                    self.settings.borrow().unit_system,
                     &sender,
                 );
             } else {
@@ -579,6 +932,15 @@ impl SimpleComponent for App {
             self.recipe_detail_dirty.set(false);
         }
 
+        // Meal plan: the recipe list follows the recipes, the grid its own flag
+        if self.recipes_dirty.get() {
+            widgets.planner.populate_recipes(&self.dm);
+        }
+        if self.plan_dirty.get() {
+            widgets.planner.show_week(&self.dm, self.plan_week);
+            self.plan_dirty.set(false);
+        }
+
         // Rebuild pantry list if dirty
         if self.pantry_dirty.get() {
             crate::pantry::populate_pantry_list(
@@ -606,6 +968,7 @@ impl SimpleComponent for App {
                     &widgets.ingredient_detail,
                     &self.dm,
                     name,
+                    self.settings.borrow().unit_system,
                     &sender,
                 );
             } else {
@@ -618,12 +981,15 @@ impl SimpleComponent for App {
         if self.kb_dirty.get() {
             crate::kb::populate_kb_list(&widgets.kb_list, &self.dm, &sender);
             self.kb_dirty.set(false);
+            if let Some(ref slug) = self.selected_kb {
+                select_row_by_name(&widgets.kb_list, slug);
+            }
         }
 
         // KB detail
         if self.kb_detail_dirty.get() || self.tab == Tab::Kb {
             if let Some(ref slug) = self.selected_kb {
-                crate::kb::update_kb_detail(&widgets.kb_detail, &self.dm, slug);
+                crate::kb::update_kb_detail(&widgets.kb_detail, &self.dm, slug, &sender);
             } else {
                 crate::kb::show_kb_placeholder(&widgets.kb_detail);
             }
@@ -633,10 +999,42 @@ impl SimpleComponent for App {
         // ── Open pending dialogs (need widget references for parent window) ───
         if self.pending_add_recipe.get() {
             self.pending_add_recipe.set(false);
-            open_add_recipe_dialog(&widgets.window, &self.dm, sender.clone());
+            let language = self.settings.borrow().language.clone();
+            open_add_recipe_dialog(&widgets.window, &self.dm, &language, sender.clone());
         }
         if let Some(title) = self.pending_edit_recipe.borrow_mut().take() {
-            open_edit_recipe_dialog(&widgets.window, &self.dm, &title, sender.clone());
+            let language = self.settings.borrow().language.clone();
+            open_edit_recipe_dialog(&widgets.window, &self.dm, &language, &title, sender.clone());
+        }
+        if let Some(title) = self.pending_cook_recipe.borrow_mut().take() {
+            let settings = self.settings.borrow();
+            open_cooking_mode(
+                &widgets.window,
+                &self.dm,
+                &settings.language,
+                settings.temperature_unit,
+                settings.unit_system,
+                &title,
+            );
+        }
+        if self.pending_schedule.get() {
+            self.pending_schedule.set(false);
+            open_schedule_dialog(&widgets.window, &self.dm, self.selected_recipe.as_deref());
+        }
+        if self.pending_suggest.get() {
+            self.pending_suggest.set(false);
+            open_suggest_dialog(
+                &widgets.window,
+                &self.dm,
+                &self.settings.borrow(),
+                self.plan_week,
+                sender.clone(),
+            );
+        }
+        if self.pending_shopping.get() {
+            self.pending_shopping.set(false);
+            let unit_system = self.settings.borrow().unit_system;
+            open_shopping_dialog(&widgets.window, &self.dm, unit_system);
         }
         if self.pending_add_ingredient.get() {
             self.pending_add_ingredient.set(false);
@@ -645,6 +1043,21 @@ impl SimpleComponent for App {
         if let Some(name) = self.pending_edit_ingredient.borrow_mut().take() {
             open_edit_ingredient_dialog(&widgets.window, &self.dm, &name, sender.clone());
         }
+        if self.pending_add_kb.get() {
+            self.pending_add_kb.set(false);
+            open_kb_dialog(&widgets.window, &self.dm, None, sender.clone());
+        }
+        if let Some(slug) = self.pending_edit_kb.borrow_mut().take() {
+            open_kb_dialog(&widgets.window, &self.dm, Some(&slug), sender.clone());
+        }
+        let conflict_copies = self.pending_conflict_copies.take();
+        if !conflict_copies.is_empty() {
+            crate::dialogs::show_pantry_conflict_dialog(
+                &widgets.window,
+                &conflict_copies,
+                sender.clone(),
+            );
+        }
     }
 }
 
@@ -677,48 +1090,93 @@ pub fn apply_theme(theme: &Theme) {
 // (These live here rather than in update() because they need widget references
 // to find the parent window.)
 
-pub fn open_add_recipe_dialog(window: &adw::ApplicationWindow, dm: &Option<Rc<RefCell<DataManager>>>, sender: ComponentSender<App>) {
-    let names = dm
-        .as_ref()
-        .map(|d| {
-            d.borrow()
-                .get_all_ingredients()
-                .into_iter()
-                .map(|i| i.name.clone())
-                .collect()
-        })
-        .unwrap_or_default();
-    crate::dialogs::show_recipe_dialog(window, names, None, sender);
+pub fn open_add_recipe_dialog(
+    window: &adw::ApplicationWindow,
+    dm: &Option<Rc<RefCell<DataManager>>>,
+    language: &str,
+    sender: ComponentSender<App>,
+) {
+    let Some(d) = dm else {
+        return;
+    };
+    crate::dialogs::show_recipe_dialog(window, d.clone(), language, None, sender);
 }
 
 pub fn open_edit_recipe_dialog(
     window: &adw::ApplicationWindow,
     dm: &Option<Rc<RefCell<DataManager>>>,
+    language: &str,
     title: &str,
     sender: ComponentSender<App>,
 ) {
-    let dm_borrow;
-    let recipe = if let Some(d) = dm {
-        dm_borrow = d.borrow();
-        dm_borrow.get_recipe(title).cloned()
-    } else {
-        None
+    let Some(d) = dm else {
+        return;
     };
+    let recipe = d.borrow().get_recipe(title).cloned();
     if let Some(ref recipe) = recipe {
-        let names = dm
-            .as_ref()
-            .map(|d| {
-                d.borrow()
-                    .get_all_ingredients()
-                    .into_iter()
-                    .map(|i| i.name.clone())
-                    .collect()
-            })
-            .unwrap_or_default();
-        crate::dialogs::show_recipe_dialog(window, names, Some(recipe), sender);
+        crate::dialogs::show_recipe_dialog(window, d.clone(), language, Some(recipe), sender);
+    }
+}
+
+pub fn open_cooking_mode(
+    window: &adw::ApplicationWindow,
+    dm: &Option<Rc<RefCell<DataManager>>>,
+    language: &str,
+    temperature_unit: TemperatureUnit,
+    unit_system: UnitSystem,
+    title: &str,
+) {
+    let Some(d) = dm else {
+        return;
+    };
+    let recipe = d.borrow().get_recipe(title).cloned();
+    if let Some(ref recipe) = recipe {
+        crate::recipes::cooking::show_cooking_mode(
+            window,
+            d.clone(),
+            recipe,
+            language,
+            temperature_unit,
+            unit_system,
+        );
     }
 }
 
+pub fn open_schedule_dialog(
+    window: &adw::ApplicationWindow,
+    dm: &Option<Rc<RefCell<DataManager>>>,
+    preselected: Option<&str>,
+) {
+    let Some(d) = dm else {
+        return;
+    };
+    crate::recipes::timeline::show_timeline_dialog(window, d.clone(), preselected);
+}
+
+pub fn open_suggest_dialog(
+    window: &adw::ApplicationWindow,
+    dm: &Option<Rc<RefCell<DataManager>>>,
+    settings: &UserSettings,
+    week: NaiveDate,
+    sender: ComponentSender<App>,
+) {
+    let Some(d) = dm else {
+        return;
+    };
+    crate::planner::suggest::show_suggest_dialog(window, d.clone(), settings, week, sender);
+}
+
+pub fn open_shopping_dialog(
+    window: &adw::ApplicationWindow,
+    dm: &Option<Rc<RefCell<DataManager>>>,
+    unit_system: UnitSystem,
+) {
+    let Some(d) = dm else {
+        return;
+    };
+    crate::shopping::show_shopping_dialog(window, d.clone(), unit_system);
+}
+
 pub fn open_add_ingredient_dialog(
     window: &adw::ApplicationWindow,
     dm: &Option<Rc<RefCell<DataManager>>>,
@@ -728,7 +1186,7 @@ pub fn open_add_ingredient_dialog(
         .as_ref()
         .map(|d| d.borrow().get_all_ingredient_categories())
         .unwrap_or_default();
-    crate::dialogs::show_ingredient_dialog(window, categories, None, None, sender);
+    crate::dialogs::show_ingredient_dialog(window, categories, None, None, Vec::new(), sender);
 }
 
 pub fn open_edit_ingredient_dialog(
@@ -738,13 +1196,13 @@ pub fn open_edit_ingredient_dialog(
     sender: ComponentSender<App>,
 ) {
     let dm_borrow;
-    let (ingredient, pantry_item) = if let Some(d) = dm {
+    let (ingredient, pantry_item, rename_affects) = if let Some(d) = dm {
         dm_borrow = d.borrow();
         let ing = dm_borrow.get_ingredient(name).cloned();
         let pantry = dm_borrow.get_pantry_item(name).cloned();
-        (ing, pantry)
+        (ing, pantry, dm_borrow.preview_ingredient_rename(name))
     } else {
-        (None, None)
+        (None, None, Vec::new())
     };
     if let Some(ref ingredient) = ingredient {
         let categories = dm
@@ -756,7 +1214,31 @@ pub fn open_edit_ingredient_dialog(
             categories,
             Some(ingredient),
             pantry_item.as_ref(),
+            rename_affects,
             sender,
         );
     }
 }
+
+pub fn open_kb_dialog(
+    window: &adw::ApplicationWindow,
+    dm: &Option<Rc<RefCell<DataManager>>>,
+    slug: Option<&str>,
+    sender: ComponentSender<App>,
+) {
+    let Some(d) = dm else {
+        return;
+    };
+    let dm_borrow = d.borrow();
+    let entry = slug.and_then(|s| dm_borrow.get_kb_entry(s)).cloned();
+    if slug.is_some() && entry.is_none() {
+        return;
+    }
+    let ingredients = dm_borrow
+        .get_all_ingredients()
+        .into_iter()
+        .map(|i| (i.name.clone(), i.kb.clone()))
+        .collect();
+    drop(dm_borrow);
+    crate::dialogs::show_kb_dialog(window, d.clone(), ingredients, entry.as_ref(), sender);
+}
@@ -1,3 +1,5 @@
+use cookbook_engine::planner::DietaryProfile;
+use cookbook_engine::{TemperatureUnit, UnitSystem};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -17,6 +19,19 @@ pub struct UserSettings {
     pub language: String,
     #[serde(default)]
     pub theme: Theme,
+    /// Scale recipe temperatures are shown in
+    #[serde(default)]
+    pub temperature_unit: TemperatureUnit,
+    /// Units recipe and pantry amounts are shown in
+    #[serde(default)]
+    pub unit_system: UnitSystem,
+    /// Minutes for cooking on each weekday, Monday first, used for meal suggestions;
+    /// 0 means no limit
+    #[serde(default)]
+    pub cooking_time_budget: [u32; 7],
+    /// Tags and ingredients meal suggestions keep to
+    #[serde(default)]
+    pub dietary_profile: DietaryProfile,
 }
 
 fn default_language() -> String {
@@ -29,6 +44,10 @@ impl Default for UserSettings {
             data_dir: None,
             language: "en".to_string(),
             theme: Theme::default(),
+            temperature_unit: TemperatureUnit::default(),
+            unit_system: UnitSystem::default(),
+            cooking_time_budget: [0; 7],
+            dietary_profile: DietaryProfile::default(),
         }
     }
 }
@@ -88,6 +107,10 @@ mod tests {
         assert_eq!(s.language, "en");
         assert_eq!(s.theme, Theme::System);
         assert!(s.data_dir.is_none());
+        assert_eq!(s.temperature_unit, TemperatureUnit::Celsius);
+        assert_eq!(s.unit_system, UnitSystem::Original);
+        assert_eq!(s.cooking_time_budget, [0; 7]);
+        assert_eq!(s.dietary_profile, DietaryProfile::default());
     }
 
     #[test]
@@ -96,11 +119,23 @@ mod tests {
             data_dir: Some("/tmp/test".to_string()),
             language: "de".to_string(),
             theme: Theme::Dark,
+            temperature_unit: TemperatureUnit::Fahrenheit,
+            unit_system: UnitSystem::Imperial,
+            cooking_time_budget: [30, 30, 30, 30, 45, 90, 0],
+            dietary_profile: DietaryProfile {
+                require_tags: vec!["vegetarian".to_string()],
+                exclude_tags: Vec::new(),
+                exclude_ingredients: vec!["peanut".to_string()],
+            },
         };
         let serialized = toml::to_string(&original).expect("serialize");
         let loaded: UserSettings = toml::from_str(&serialized).expect("deserialize");
         assert_eq!(loaded.data_dir, original.data_dir);
         assert_eq!(loaded.language, original.language);
         assert_eq!(loaded.theme, original.theme);
+        assert_eq!(loaded.temperature_unit, original.temperature_unit);
+        assert_eq!(loaded.unit_system, original.unit_system);
+        assert_eq!(loaded.cooking_time_budget, original.cooking_time_budget);
+        assert_eq!(loaded.dietary_profile, original.dietary_profile);
     }
 }
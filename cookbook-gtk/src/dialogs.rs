@@ -1,20 +1,29 @@
-/// Modal dialogs for creating and editing recipes and ingredients.
+/// Modal dialogs for creating and editing recipes, ingredients and KB articles.
 ///
 /// Each dialog is a `gtk::Window` shown as a transient child of the main window.
 /// When the user confirms, the dialog sends a message via the component sender.
-use cookbook_engine::{Ingredient, PantryItem, Recipe, RecipeIngredient};
+use chrono::NaiveDate;
+use cookbook_engine::{
+    slug, DataManager, Ingredient, KnowledgeBaseEntry, PantryItem, Price, Recipe,
+};
 use libadwaita as adw;
 use relm4::gtk;
 use relm4::ComponentSender;
+use std::cell::RefCell;
+use std::rc::Rc;
 
 use crate::app::{App, AppMsg};
+use crate::ingredient_editor::IngredientEditor;
 
 // ─── Recipe dialog ────────────────────────────────────────────────────────────
 
 /// Show a dialog for adding (original = None) or editing (original = Some) a recipe.
+///
+/// Ingredients are edited row by row; `dm` is only read, for autocomplete in `language`.
 pub fn show_recipe_dialog(
     parent: &impl gtk::prelude::IsA<gtk::Window>,
-    ingredient_names: Vec<String>,
+    dm: Rc<RefCell<DataManager>>,
+    language: &str,
     existing: Option<&Recipe>,
     sender: ComponentSender<App>,
 ) {
@@ -101,48 +110,24 @@ pub fn show_recipe_dialog(
     // Ingredients group
     let ing_group = adw::PreferencesGroup::new();
     ing_group.set_title("Ingredients");
-    ing_group.set_description(Some(
-        "One per line: \"ingredient_name quantity unit\" (e.g. \"potato 2 kg\")",
-    ));
-
-    let ing_text = gtk::TextView::new();
-    ing_text.set_monospace(true);
-    ing_text.set_wrap_mode(gtk::WrapMode::Word);
-    ing_text.set_top_margin(8);
-    ing_text.set_bottom_margin(8);
-    ing_text.set_left_margin(8);
-    ing_text.set_right_margin(8);
-    ing_text.add_css_class("card");
-    ing_text.set_size_request(-1, 100);
-
-    if let Some(r) = existing {
-        let lines: Vec<String> = r
-            .ingredients
-            .iter()
-            .map(|i| {
-                match (i.quantity, &i.quantity_type) {
-                    (Some(q), Some(u)) if !u.is_empty() => {
-                        format!("{} {} {}", i.ingredient, q, u)
-                    }
-                    (Some(q), _) => format!("{} {}", i.ingredient, q),
-                    _ => i.ingredient.clone(),
-                }
-            })
-            .collect();
-        ing_text.buffer().set_text(&lines.join("\n"));
-    }
-
-    let ing_scroll = gtk::ScrolledWindow::new();
-    ing_scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
-    ing_scroll.set_min_content_height(100);
-    ing_scroll.set_child(Some(&ing_text));
-
-    ing_group.add(&ing_scroll);
+    let ingredient_editor = IngredientEditor::new(
+        dm,
+        language,
+        existing
+            .map(|r| r.ingredients.as_slice())
+            .unwrap_or_default(),
+        sender.clone(),
+    );
+    ing_group.add(ingredient_editor.widget());
     page.add(&ing_group);
 
     // Instructions group
     let instr_group = adw::PreferencesGroup::new();
     instr_group.set_title("Instructions");
+    instr_group.set_description(Some(
+        "One step per numbered line. Times and temperatures are found automatically; \
+         add others as {timer: 10 min} or {temp: 180C}.",
+    ));
 
     let instr_text = gtk::TextView::new();
     instr_text.set_wrap_mode(gtk::WrapMode::Word);
@@ -198,6 +183,7 @@ pub fn show_recipe_dialog(
 
     let win_save = window.clone();
     let original_title = existing.map(|r| r.title.clone());
+    let original_slug = existing.map(|r| r.slug.clone()).unwrap_or_default();
     save_btn.connect_clicked(move |_| {
         let title = title_row.text().to_string();
         if title.trim().is_empty() {
@@ -220,38 +206,7 @@ pub fn show_recipe_dialog(
             }
         };
 
-        // Parse ingredients from text view
-        let (start, end) = ing_text.buffer().bounds();
-        let ing_raw = ing_text.buffer().text(&start, &end, false).to_string();
-        let ingredients: Vec<RecipeIngredient> = ing_raw
-            .lines()
-            .filter(|l| !l.trim().is_empty())
-            .map(|line| {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                match parts.as_slice() {
-                    [name] => RecipeIngredient {
-                        ingredient: name.to_string(),
-                        quantity: None,
-                        quantity_type: None,
-                    },
-                    [name, qty] => RecipeIngredient {
-                        ingredient: name.to_string(),
-                        quantity: qty.parse::<f64>().ok(),
-                        quantity_type: None,
-                    },
-                    [name, qty, unit, ..] => RecipeIngredient {
-                        ingredient: name.to_string(),
-                        quantity: qty.parse::<f64>().ok(),
-                        quantity_type: Some(unit.to_string()),
-                    },
-                    _ => RecipeIngredient {
-                        ingredient: line.trim().to_string(),
-                        quantity: None,
-                        quantity_type: None,
-                    },
-                }
-            })
-            .collect();
+        let ingredients = ingredient_editor.ingredients();
 
         // Parse instructions
         let (start, end) = instr_text.buffer().bounds();
@@ -269,6 +224,7 @@ pub fn show_recipe_dialog(
             tags,
             image: None,
             instructions,
+            slug: original_slug.clone(),
         };
 
         sender.input(AppMsg::SaveRecipe {
@@ -279,17 +235,20 @@ pub fn show_recipe_dialog(
     });
 
     window.present();
-    let _ = ingredient_names; // reserved for future autocomplete
 }
 
 // ─── Ingredient dialog ────────────────────────────────────────────────────────
 
 /// Show a dialog for adding or editing an ingredient, including pantry status.
+///
+/// `rename_affects` lists the recipes that renaming the ingredient would rewrite; the dialog
+/// shows them as soon as the name is changed.
 pub fn show_ingredient_dialog(
     parent: &impl gtk::prelude::IsA<gtk::Window>,
     categories: Vec<String>,
     existing: Option<&Ingredient>,
     pantry_item: Option<&PantryItem>,
+    rename_affects: Vec<String>,
     sender: ComponentSender<App>,
 ) {
     use adw::prelude::*;
@@ -329,6 +288,26 @@ pub fn show_ingredient_dialog(
     }
     details_group.add(&name_row);
 
+    // Dry-run preview of the recipes a rename would rewrite
+    if let Some(ing) = existing.filter(|_| !rename_affects.is_empty()) {
+        let original = ing.name.clone();
+        let group = details_group.clone();
+        let preview = format!(
+            "Renaming will update {} recipe{}: {}",
+            rename_affects.len(),
+            if rename_affects.len() == 1 { "" } else { "s" },
+            rename_affects.join(", ")
+        );
+        name_row.connect_changed(move |row| {
+            let renamed = row.text().trim() != original;
+            group.set_description(if renamed {
+                Some(preview.as_str())
+            } else {
+                None
+            });
+        });
+    }
+
     // Category (combo from existing categories + free entry)
     let cat_row = adw::EntryRow::new();
     cat_row.set_title("Category");
@@ -351,6 +330,22 @@ pub fn show_ingredient_dialog(
     }
     details_group.add(&tags_row);
 
+    // Unit conversions between mass, volume and pieces
+    let density_row = adw::EntryRow::new();
+    density_row.set_title("Density (g per ml)");
+    let unit_weight_row = adw::EntryRow::new();
+    unit_weight_row.set_title("Weight of one (g)");
+    if let Some(ing) = existing {
+        if let Some(density) = ing.density {
+            density_row.set_text(&density.to_string());
+        }
+        if let Some(weight) = ing.unit_weight {
+            unit_weight_row.set_text(&weight.to_string());
+        }
+    }
+    details_group.add(&density_row);
+    details_group.add(&unit_weight_row);
+
     page.add(&details_group);
 
     // Pantry group
@@ -381,17 +376,101 @@ pub fn show_ingredient_dialog(
     }
     pantry_group.add(&unit_row);
 
-    // Toggle qty/unit sensitivity based on in_pantry switch
+    let expires_row = adw::EntryRow::new();
+    expires_row.set_title("Use by (YYYY-MM-DD)");
+    expires_row.set_sensitive(in_pantry);
+    if let Some(date) = pantry_item.and_then(|item| item.expires) {
+        expires_row.set_text(&date.to_string());
+    }
+    pantry_group.add(&expires_row);
+
+    // Toggle qty/unit/use-by sensitivity based on in_pantry switch
     {
         let qty_clone = qty_row.clone();
         let unit_clone = unit_row.clone();
+        let expires_clone = expires_row.clone();
         in_pantry_row.connect_active_notify(move |row| {
             qty_clone.set_sensitive(row.is_active());
             unit_clone.set_sensitive(row.is_active());
+            expires_clone.set_sensitive(row.is_active());
         });
     }
 
     page.add(&pantry_group);
+
+    // Price history, oldest first; saved with the ingredient
+    let prices: Rc<RefCell<Vec<Price>>> = Rc::new(RefCell::new(
+        existing.map(|i| i.prices.clone()).unwrap_or_default(),
+    ));
+    let prices_group = adw::PreferencesGroup::new();
+    prices_group.set_title("Prices");
+    prices_group.set_description(Some("What you paid, used to estimate recipe costs"));
+
+    let price_list = gtk::ListBox::new();
+    price_list.add_css_class("boxed-list");
+    price_list.set_selection_mode(gtk::SelectionMode::None);
+    prices_group.add(&price_list);
+    show_prices(&price_list, &prices);
+
+    let price_row = adw::EntryRow::new();
+    price_row.set_title("Price paid");
+    prices_group.add(&price_row);
+    let price_unit_row = adw::EntryRow::new();
+    price_unit_row.set_title("Per unit (e.g. kg, l, pcs)");
+    if let Some(price) = existing.and_then(|i| i.latest_price()) {
+        price_unit_row.set_text(&price.unit);
+    }
+    prices_group.add(&price_unit_row);
+    let store_row = adw::EntryRow::new();
+    store_row.set_title("Store");
+    prices_group.add(&store_row);
+    let date_row = adw::EntryRow::new();
+    date_row.set_title("Date (YYYY-MM-DD)");
+    date_row.set_text(&chrono::Local::now().date_naive().to_string());
+    prices_group.add(&date_row);
+
+    let add_price_btn = gtk::Button::with_label("Add Price");
+    add_price_btn.set_halign(gtk::Align::End);
+    add_price_btn.set_margin_top(8);
+    {
+        let prices = prices.clone();
+        let price_list = price_list.clone();
+        let price_row = price_row.clone();
+        let price_unit_row = price_unit_row.clone();
+        let store_row = store_row.clone();
+        let date_row = date_row.clone();
+        add_price_btn.connect_clicked(move |_| {
+            let Some(amount) = price_row
+                .text()
+                .trim()
+                .replace(',', ".")
+                .parse::<f64>()
+                .ok()
+                .filter(|p| p.is_finite() && *p >= 0.0)
+            else {
+                return;
+            };
+            let Ok(date) = NaiveDate::parse_from_str(date_row.text().trim(), "%Y-%m-%d") else {
+                return;
+            };
+            let price = Price {
+                date,
+                store: store_row.text().trim().to_string(),
+                price: amount,
+                unit: price_unit_row.text().trim().to_string(),
+            };
+            {
+                let mut prices = prices.borrow_mut();
+                let at = prices.partition_point(|p| p.date <= price.date);
+                prices.insert(at, price);
+            }
+            price_row.set_text("");
+            show_prices(&price_list, &prices);
+        });
+    }
+    prices_group.add(&add_price_btn);
+
+    page.add(&prices_group);
     form.append(&page);
     scroll.set_child(Some(&form));
 
@@ -424,6 +503,12 @@ pub fn show_ingredient_dialog(
 
     let win_save = window.clone();
     let original_name = existing.map(|i| i.name.clone());
+    // Fields this dialog doesn't edit are carried over unchanged
+    let existing_kb = existing.and_then(|i| i.kb.clone());
+    // Keep the file name when editing; the engine derives one for new ingredients
+    let existing_slug = existing.map(|i| i.slug.clone()).unwrap_or_default();
+    let existing_translations = existing.and_then(|i| i.translations.clone());
+    let existing_packages = existing.map(|i| i.packages.clone()).unwrap_or_default();
     save_btn.connect_clicked(move |_| {
         let name = name_row.text().to_string().trim().to_string();
         if name.is_empty() {
@@ -445,16 +530,21 @@ pub fn show_ingredient_dialog(
 
         let ingredient = Ingredient {
             name: name.clone(),
-            slug: name.to_lowercase().replace(' ', "_"),
+            slug: existing_slug.clone(),
             category,
-            kb: None,
+            kb: existing_kb.clone(),
             tags,
-            translations: None,
+            translations: existing_translations.clone(),
+            prices: prices.borrow().clone(),
+            packages: existing_packages.clone(),
+            density: positive_number(&density_row.text()),
+            unit_weight: positive_number(&unit_weight_row.text()),
         };
 
         let in_pantry = in_pantry_row.is_active();
         let qty = qty_row.text().parse::<f64>().ok();
         let qty_type = unit_row.text().to_string();
+        let expires = NaiveDate::parse_from_str(expires_row.text().trim(), "%Y-%m-%d").ok();
 
         sender.input(AppMsg::SaveIngredient {
             original: original_name.clone(),
@@ -462,6 +552,322 @@ pub fn show_ingredient_dialog(
             in_pantry,
             qty,
             qty_type,
+            expires,
+        });
+        win_save.close();
+    });
+
+    window.present();
+}
+
+/// Reads a number greater than zero, accepting a decimal comma
+fn positive_number(text: &str) -> Option<f64> {
+    text.trim()
+        .replace(',', ".")
+        .parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite() && *n > 0.0)
+}
+
+/// Lists `prices`, newest first, each with a button that removes it
+fn show_prices(list: &gtk::ListBox, prices: &Rc<RefCell<Vec<Price>>>) {
+    use adw::prelude::*;
+
+    crate::utils::clear_list_box(list);
+    list.set_visible(!prices.borrow().is_empty());
+    for (index, price) in prices.borrow().iter().enumerate().rev() {
+        let row = adw::ActionRow::new();
+        let unit = if price.unit.is_empty() {
+            "piece"
+        } else {
+            price.unit.as_str()
+        };
+        row.set_title(&format!("{:.2} per {}", price.price, unit));
+        let mut subtitle = price.date.to_string();
+        if !price.store.is_empty() {
+            subtitle.push_str(&format!(" · {}", price.store));
+        }
+        row.set_subtitle(&subtitle);
+
+        let remove_btn = gtk::Button::from_icon_name("user-trash-symbolic");
+        remove_btn.add_css_class("flat");
+        remove_btn.set_valign(gtk::Align::Center);
+        remove_btn.set_tooltip_text(Some("Remove this price"));
+        let list_clone = list.clone();
+        let prices_clone = prices.clone();
+        remove_btn.connect_clicked(move |_| {
+            prices_clone.borrow_mut().remove(index);
+            show_prices(&list_clone, &prices_clone);
+        });
+        row.add_suffix(&remove_btn);
+        list.append(&row);
+    }
+}
+
+// ─── Knowledge base dialog ────────────────────────────────────────────────────
+
+/// Show a dialog for adding (existing = None) or editing a KB article.
+///
+/// `ingredients` lists every ingredient as `(name, current kb slug)`; the ones checked in the
+/// picker are linked to the article on save. `dm` is only read, to resolve `[[links]]` in
+/// the preview.
+pub fn show_kb_dialog(
+    parent: &impl gtk::prelude::IsA<gtk::Window>,
+    dm: Rc<RefCell<DataManager>>,
+    ingredients: Vec<(String, Option<String>)>,
+    existing: Option<&KnowledgeBaseEntry>,
+    sender: ComponentSender<App>,
+) {
+    use adw::prelude::*;
+
+    let is_edit = existing.is_some();
+    let window = adw::Window::builder()
+        .transient_for(parent)
+        .modal(true)
+        .title(if is_edit {
+            "Edit Article"
+        } else {
+            "Add Article"
+        })
+        .default_width(900)
+        .default_height(760)
+        .build();
+
+    let toolbar_view = adw::ToolbarView::new();
+    let header = adw::HeaderBar::new();
+    toolbar_view.add_top_bar(&header);
+
+    // ── Form ─────────────────────────────────────────────────────────────────
+    let scroll = gtk::ScrolledWindow::new();
+    scroll.set_vexpand(true);
+    scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+
+    let form = gtk::Box::new(gtk::Orientation::Vertical, 12);
+    form.set_margin_top(16);
+    form.set_margin_bottom(16);
+    form.set_margin_start(16);
+    form.set_margin_end(16);
+
+    let page = adw::PreferencesPage::new();
+    let group = adw::PreferencesGroup::new();
+    group.set_title("Details");
+
+    // Title
+    let title_row = adw::EntryRow::new();
+    title_row.set_title("Title");
+    if let Some(e) = existing {
+        title_row.set_text(&e.title);
+    }
+    group.add(&title_row);
+
+    // Slug (derived from the title when left empty)
+    let slug_row = adw::EntryRow::new();
+    slug_row.set_title("Slug (used in ingredient kb links)");
+    if let Some(e) = existing {
+        slug_row.set_text(&e.slug);
+    }
+    slug_row.connect_changed(|row| {
+        row.remove_css_class("error");
+        row.set_tooltip_text(None);
+    });
+    group.add(&slug_row);
+
+    // Image file name, relative to the kb folder
+    let image_row = adw::EntryRow::new();
+    image_row.set_title("Image (file in the kb folder)");
+    if let Some(image) = existing.and_then(|e| e.image.as_ref()) {
+        image_row.set_text(image);
+    }
+    group.add(&image_row);
+
+    page.add(&group);
+
+    // Body group: Markdown source on the left, live preview on the right
+    let body_group = adw::PreferencesGroup::new();
+    body_group.set_title("Article");
+    body_group.set_description(Some("Markdown. The preview updates as you type."));
+
+    let body_text = gtk::TextView::new();
+    body_text.set_monospace(true);
+    body_text.set_wrap_mode(gtk::WrapMode::Word);
+    body_text.set_top_margin(8);
+    body_text.set_bottom_margin(8);
+    body_text.set_left_margin(8);
+    body_text.set_right_margin(8);
+    if let Some(e) = existing {
+        body_text.buffer().set_text(&e.content);
+    }
+
+    let body_scroll = gtk::ScrolledWindow::new();
+    body_scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+    body_scroll.set_min_content_height(300);
+    body_scroll.set_hexpand(true);
+    body_scroll.add_css_class("card");
+    body_scroll.set_child(Some(&body_text));
+
+    let preview = gtk::Box::new(gtk::Orientation::Vertical, 12);
+    preview.set_margin_top(8);
+    preview.set_margin_bottom(8);
+    preview.set_margin_start(8);
+    preview.set_margin_end(8);
+
+    let preview_scroll = gtk::ScrolledWindow::new();
+    preview_scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+    preview_scroll.set_min_content_height(300);
+    preview_scroll.set_hexpand(true);
+    preview_scroll.set_child(Some(&preview));
+
+    let body_box = gtk::Box::new(gtk::Orientation::Horizontal, 12);
+    body_box.set_homogeneous(true);
+    body_box.append(&body_scroll);
+    body_box.append(&preview_scroll);
+    body_group.add(&body_box);
+    page.add(&body_group);
+
+    // Refresh the preview from the title and body
+    let refresh_preview = {
+        let preview = preview.clone();
+        let title_row = title_row.clone();
+        let buffer = body_text.buffer();
+        move || {
+            crate::utils::clear_box(&preview);
+            let title = gtk::Label::new(Some(&title_row.text()));
+            title.add_css_class("title-1");
+            title.set_halign(gtk::Align::Start);
+            title.set_wrap(true);
+            preview.append(&title);
+            preview.append(&gtk::Separator::new(gtk::Orientation::Horizontal));
+            let (start, end) = buffer.bounds();
+            let content = buffer.text(&start, &end, false);
+            // Links are shown but don't navigate away from the editor
+            crate::utils::append_markdown(&preview, &dm.borrow(), &content, None);
+        }
+    };
+    refresh_preview();
+    {
+        let refresh = refresh_preview.clone();
+        body_text.buffer().connect_changed(move |_| refresh());
+    }
+    title_row.connect_changed(move |_| refresh_preview());
+
+    // Ingredient picker
+    let link_group = adw::PreferencesGroup::new();
+    link_group.set_title("Linked ingredients");
+    link_group.set_description(Some(
+        "Checked ingredients link to this article. An ingredient links to one article at a time.",
+    ));
+
+    let link_search = gtk::SearchEntry::new();
+    link_search.set_placeholder_text(Some("Filter ingredients…"));
+    link_search.set_margin_bottom(8);
+    link_group.add(&link_search);
+
+    let link_list = gtk::ListBox::new();
+    link_list.set_selection_mode(gtk::SelectionMode::None);
+    link_list.add_css_class("boxed-list");
+
+    let original_slug = existing.map(|e| e.slug.clone());
+    let mut checks: Vec<(String, gtk::CheckButton)> = Vec::new();
+    let mut sorted = ingredients;
+    sorted.sort_by_key(|(name, _)| name.to_lowercase());
+    for (name, kb) in sorted {
+        let row = adw::ActionRow::new();
+        row.set_title(&name);
+        row.set_widget_name(&name);
+        let linked_here = original_slug.is_some() && kb == original_slug;
+        if let Some(other) = kb.as_ref().filter(|_| !linked_here) {
+            row.set_subtitle(&format!("Currently linked to \"{}\"", other));
+        }
+        let check = gtk::CheckButton::new();
+        check.set_active(linked_here);
+        row.add_prefix(&check);
+        row.set_activatable_widget(Some(&check));
+        link_list.append(&row);
+        checks.push((name, check));
+    }
+    link_group.add(&link_list);
+    page.add(&link_group);
+
+    {
+        let link_list = link_list.clone();
+        link_search.connect_search_changed(move |entry| {
+            let query = entry.text().to_lowercase();
+            let mut i = 0;
+            while let Some(row) = link_list.row_at_index(i) {
+                row.set_visible(row.widget_name().to_lowercase().contains(&query));
+                i += 1;
+            }
+        });
+    }
+
+    form.append(&page);
+    scroll.set_child(Some(&form));
+
+    // ── Buttons ───────────────────────────────────────────────────────────────
+    let btn_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+    btn_box.set_margin_top(8);
+    btn_box.set_margin_bottom(16);
+    btn_box.set_margin_start(16);
+    btn_box.set_margin_end(16);
+    btn_box.set_halign(gtk::Align::End);
+
+    let cancel_btn = gtk::Button::with_label("Cancel");
+    cancel_btn.add_css_class("pill");
+
+    let save_btn = gtk::Button::with_label(if is_edit { "Save" } else { "Add" });
+    save_btn.add_css_class("suggested-action");
+    save_btn.add_css_class("pill");
+
+    btn_box.append(&cancel_btn);
+    btn_box.append(&save_btn);
+
+    let outer = gtk::Box::new(gtk::Orientation::Vertical, 0);
+    outer.append(&scroll);
+    outer.append(&btn_box);
+    toolbar_view.set_content(Some(&outer));
+    window.set_content(Some(&toolbar_view));
+
+    // ── Event handlers ────────────────────────────────────────────────────────
+    let win_cancel = window.clone();
+    cancel_btn.connect_clicked(move |_| win_cancel.close());
+
+    let win_save = window.clone();
+    save_btn.connect_clicked(move |_| {
+        let title = title_row.text().trim().to_string();
+        if title.is_empty() {
+            return;
+        }
+        let slug = match slug_row.text().trim() {
+            "" => slug::slugify(&title),
+            s => s.to_string(),
+        };
+        // The slug names a file in the kb folder; a title without letters gives an empty one
+        if let Err(e) = slug::validate_slug(&slug) {
+            slug_row.add_css_class("error");
+            slug_row.set_tooltip_text(Some(&e.to_string()));
+            return;
+        }
+        let image = image_row.text().trim().to_string();
+
+        let (start, end) = body_text.buffer().bounds();
+        let body = body_text.buffer().text(&start, &end, false).to_string();
+
+        let entry = KnowledgeBaseEntry {
+            slug,
+            title,
+            image: if image.is_empty() { None } else { Some(image) },
+            content: body.trim().to_string(),
+        };
+        let linked_ingredients = checks
+            .iter()
+            .filter(|(_, check)| check.is_active())
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        sender.input(AppMsg::SaveKbEntry {
+            original: original_slug.clone(),
+            entry,
+            linked_ingredients,
         });
         win_save.close();
     });
@@ -469,6 +875,41 @@ pub fn show_ingredient_dialog(
     window.present();
 }
 
+// ─── Pantry conflict dialog ───────────────────────────────────────────────────
+
+/// Offer to merge conflict copies of pantry.yaml created by the sync provider.
+pub fn show_pantry_conflict_dialog(
+    parent: &impl gtk::prelude::IsA<gtk::Window>,
+    copies: &[std::path::PathBuf],
+    sender: ComponentSender<App>,
+) {
+    use adw::prelude::*;
+
+    let names: Vec<String> = copies
+        .iter()
+        .filter_map(|p| p.file_name())
+        .map(|n| format!("• {}", n.to_string_lossy()))
+        .collect();
+    let body = format!(
+        "The pantry was changed on two devices at once and your sync provider kept both versions:\n\n{}\n\nMerge them into pantry.yaml? Items changed on only one device are combined automatically.",
+        names.join("\n")
+    );
+
+    let dialog = adw::MessageDialog::new(Some(parent), Some("Merge pantry copies?"), Some(&body));
+    dialog.add_response("later", "Not Now");
+    dialog.add_response("merge", "Merge");
+    dialog.set_response_appearance("merge", adw::ResponseAppearance::Suggested);
+    dialog.set_default_response(Some("merge"));
+    dialog.set_close_response("later");
+
+    dialog.connect_response(None, move |_, response| {
+        if response == "merge" {
+            sender.input(AppMsg::MergePantryConflictCopies);
+        }
+    });
+    dialog.present();
+}
+
 /// Show a simple error in a message dialog.
 pub fn show_error_toast(parent: &impl gtk::prelude::IsA<gtk::Window>, message: &str) {
     use adw::prelude::*;
@@ -0,0 +1,576 @@
+/// Row-based ingredient editor used by the recipe dialog.
+///
+/// Every ingredient gets its own row with name, quantity, unit and preparation fields and an
+/// "optional" check. The name field completes from known ingredients (names and translations),
+/// and a name that doesn't match any ingredient is flagged with a button that creates it on
+/// the spot. Section rows such as "For the sauce" group the ingredients below them.
+use crate::app::{App, AppMsg};
+use cookbook_engine::{DataManager, Ingredient, Quantity, RecipeIngredient};
+use relm4::gtk;
+use relm4::gtk::{glib, prelude::*};
+use relm4::ComponentSender;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Most suggestions shown in the autocomplete popup
+const MAX_COMPLETIONS: usize = 8;
+
+/// Editor for a recipe's ingredient list. Cloning gives another handle to the same editor.
+#[derive(Clone)]
+pub struct IngredientEditor {
+    widget: gtk::Box,
+    list: gtk::ListBox,
+    /// One entry per list row, in display order
+    rows: Rc<RefCell<Vec<EditorRow>>>,
+    dm: Rc<RefCell<DataManager>>,
+    language: String,
+    sender: ComponentSender<App>,
+}
+
+/// One row of the list
+#[derive(Clone)]
+enum EditorRow {
+    /// A section header; the ingredients below it belong to the section until the next one.
+    /// An empty title ends the previous section
+    Section {
+        root: gtk::ListBoxRow,
+        title: gtk::Entry,
+    },
+    Ingredient(IngredientRow),
+}
+
+/// The widgets of one ingredient row
+#[derive(Clone)]
+struct IngredientRow {
+    root: gtk::ListBoxRow,
+    name: gtk::Entry,
+    quantity: gtk::Entry,
+    unit: gtk::Entry,
+    preparation: gtk::Entry,
+    optional: gtk::CheckButton,
+}
+
+impl EditorRow {
+    fn root(&self) -> &gtk::ListBoxRow {
+        match self {
+            EditorRow::Section { root, .. } => root,
+            EditorRow::Ingredient(row) => &row.root,
+        }
+    }
+
+    /// The field that takes focus when moving to this row
+    fn first_entry(&self) -> &gtk::Entry {
+        match self {
+            EditorRow::Section { title, .. } => title,
+            EditorRow::Ingredient(row) => &row.name,
+        }
+    }
+}
+
+impl IngredientEditor {
+    /// Builds the editor filled with `ingredients`, or with one empty row if there are none.
+    /// `language` selects which translations the autocomplete matches.
+    pub fn new(
+        dm: Rc<RefCell<DataManager>>,
+        language: &str,
+        ingredients: &[RecipeIngredient],
+        sender: ComponentSender<App>,
+    ) -> Self {
+        let widget = gtk::Box::new(gtk::Orientation::Vertical, 8);
+
+        let list = gtk::ListBox::new();
+        list.set_selection_mode(gtk::SelectionMode::None);
+        list.add_css_class("boxed-list");
+        widget.append(&list);
+
+        let btn_box = gtk::Box::new(gtk::Orientation::Horizontal, 8);
+        let add_btn = gtk::Button::with_label("Add Ingredient");
+        btn_box.append(&add_btn);
+        let section_btn = gtk::Button::with_label("Add Section");
+        section_btn.set_tooltip_text(Some("Start a group such as \"For the sauce\""));
+        btn_box.append(&section_btn);
+
+        // Pasting lines like "2 1/2 cups diced tomatoes" fills in rows
+        let paste_btn = gtk::MenuButton::new();
+        paste_btn.set_label("Paste Lines");
+        let popover = gtk::Popover::new();
+        let pop_box = gtk::Box::new(gtk::Orientation::Vertical, 8);
+        pop_box.set_margin_top(8);
+        pop_box.set_margin_bottom(8);
+        pop_box.set_margin_start(8);
+        pop_box.set_margin_end(8);
+        let hint = gtk::Label::new(Some(
+            "One ingredient per line, e.g. \"2 cups diced tomatoes\"",
+        ));
+        hint.add_css_class("dim-label");
+        hint.set_xalign(0.0);
+        let lines_text = gtk::TextView::new();
+        lines_text.set_wrap_mode(gtk::WrapMode::Word);
+        let lines_scroll = gtk::ScrolledWindow::new();
+        lines_scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+        lines_scroll.set_size_request(340, 160);
+        lines_scroll.set_child(Some(&lines_text));
+        let add_lines_btn = gtk::Button::with_label("Add");
+        add_lines_btn.add_css_class("suggested-action");
+        add_lines_btn.set_halign(gtk::Align::End);
+        pop_box.append(&hint);
+        pop_box.append(&lines_scroll);
+        pop_box.append(&add_lines_btn);
+        popover.set_child(Some(&pop_box));
+        paste_btn.set_popover(Some(&popover));
+        btn_box.append(&paste_btn);
+        widget.append(&btn_box);
+
+        let editor = Self {
+            widget,
+            list,
+            rows: Rc::new(RefCell::new(Vec::new())),
+            dm,
+            language: language.to_string(),
+            sender,
+        };
+        let mut group = None;
+        for ingredient in ingredients {
+            if ingredient.group != group {
+                group = ingredient.group.clone();
+                editor.add_section(group.as_deref().unwrap_or(""));
+            }
+            editor.add_row(Some(ingredient));
+        }
+        if ingredients.is_empty() {
+            editor.add_row(None);
+        }
+
+        let e = editor.clone();
+        add_btn.connect_clicked(move |_| {
+            e.add_row(None).grab_focus();
+        });
+
+        let e = editor.clone();
+        section_btn.connect_clicked(move |_| {
+            e.add_section("").grab_focus();
+        });
+
+        let e = editor.clone();
+        add_lines_btn.connect_clicked(move |_| {
+            let buffer = lines_text.buffer();
+            let (start, end) = buffer.bounds();
+            e.add_lines(&buffer.text(&start, &end, false));
+            buffer.set_text("");
+            popover.popdown();
+        });
+
+        editor
+    }
+
+    /// The editor's top-level widget
+    pub fn widget(&self) -> &gtk::Box {
+        &self.widget
+    }
+
+    /// Returns the ingredients in row order, skipping rows without a name. Each ingredient
+    /// takes the group of the nearest section row above it.
+    ///
+    /// Names are stored canonically, so a row reading "tomatoes" is saved as `tomato`; names
+    /// that match no ingredient are kept as typed.
+    pub fn ingredients(&self) -> Vec<RecipeIngredient> {
+        let dm = self.dm.borrow();
+        let mut group = None;
+        let mut ingredients = Vec::new();
+        for row in self.rows.borrow().iter() {
+            let row = match row {
+                EditorRow::Section { title, .. } => {
+                    group = non_empty(title);
+                    continue;
+                }
+                EditorRow::Ingredient(row) => row,
+            };
+            let Some(typed) = non_empty(&row.name) else {
+                continue;
+            };
+            let ingredient = dm
+                .find_ingredient_by_name_or_translation(&typed, &self.language)
+                .map_or(typed, |i| i.name.clone());
+            ingredients.push(RecipeIngredient {
+                ingredient,
+                quantity: row.quantity.text().parse::<Quantity>().ok(),
+                quantity_type: non_empty(&row.unit),
+                group: group.clone(),
+                optional: row.optional.is_active(),
+                preparation: non_empty(&row.preparation),
+            });
+        }
+        ingredients
+    }
+
+    /// Parses pasted ingredient lines into rows, flagging the ones the parser is unsure of
+    fn add_lines(&self, text: &str) {
+        // Pasted lines replace blank rows, like the one a new recipe starts with
+        let blank: Vec<EditorRow> = self
+            .rows
+            .borrow()
+            .iter()
+            .filter(|r| match r {
+                EditorRow::Ingredient(r) => [&r.name, &r.quantity, &r.unit, &r.preparation]
+                    .iter()
+                    .all(|e| e.text().trim().is_empty()),
+                EditorRow::Section { .. } => false,
+            })
+            .cloned()
+            .collect();
+        for row in &blank {
+            self.list.remove(row.root());
+        }
+        self.rows
+            .borrow_mut()
+            .retain(|r| !blank.iter().any(|b| b.root() == r.root()));
+
+        for line in text.lines().filter(|l| !l.trim().is_empty()) {
+            // A heading such as "For the sauce:" starts a section
+            if let Some(title) = line.trim().strip_suffix(':') {
+                self.add_section(title.trim());
+                continue;
+            }
+            let parsed = self.dm.borrow().parse_ingredient_line(line, &self.language);
+            let name = self.add_row(Some(&parsed.ingredient));
+            if parsed.is_uncertain() {
+                name.add_css_class("warning");
+                name.set_tooltip_text(Some(&format!(
+                    "Check this line: \"{}\"\n{}",
+                    line.trim(),
+                    parsed.warnings.join("\n")
+                )));
+            }
+        }
+        if self.rows.borrow().is_empty() {
+            self.add_row(None);
+        }
+    }
+
+    /// Appends a section row titled `title` and returns its title field
+    fn add_section(&self, title: &str) -> gtk::Entry {
+        let root = gtk::ListBoxRow::new();
+        root.set_activatable(false);
+
+        let hbox = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+        hbox.set_margin_top(6);
+        hbox.set_margin_bottom(6);
+        hbox.set_margin_start(6);
+        hbox.set_margin_end(6);
+
+        let title_entry = gtk::Entry::new();
+        title_entry.set_hexpand(true);
+        title_entry.set_text(title);
+        title_entry.set_placeholder_text(Some("Section, e.g. \"For the sauce\""));
+        title_entry.add_css_class("heading");
+
+        hbox.append(&title_entry);
+        for button in self.row_buttons() {
+            hbox.append(&button);
+        }
+        root.set_child(Some(&hbox));
+
+        // Enter moves on to the first ingredient of the section
+        let e = self.clone();
+        title_entry.connect_activate(move |entry| e.focus_after(entry));
+
+        self.list.append(&root);
+        self.rows.borrow_mut().push(EditorRow::Section {
+            root,
+            title: title_entry.clone(),
+        });
+        title_entry
+    }
+
+    /// Appends a row, filled from `ingredient` if given, and returns its name field
+    fn add_row(&self, ingredient: Option<&RecipeIngredient>) -> gtk::Entry {
+        let root = gtk::ListBoxRow::new();
+        root.set_activatable(false);
+
+        let vbox = gtk::Box::new(gtk::Orientation::Vertical, 6);
+        vbox.set_margin_top(6);
+        vbox.set_margin_bottom(6);
+        vbox.set_margin_start(6);
+        vbox.set_margin_end(6);
+        let hbox = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+        let details = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+
+        let name = gtk::Entry::new();
+        name.set_hexpand(true);
+        name.set_placeholder_text(Some("Ingredient"));
+
+        let quantity = gtk::Entry::new();
+        quantity.set_width_chars(7);
+        quantity.set_placeholder_text(Some("Qty"));
+        quantity.set_tooltip_text(Some("e.g. 2, 1/3, 2 1/2, 2-3 or \"to taste\""));
+
+        let unit = gtk::Entry::new();
+        unit.set_width_chars(6);
+        unit.set_placeholder_text(Some("Unit"));
+
+        let create_btn = gtk::Button::with_label("Create");
+        create_btn.set_visible(false);
+
+        let preparation = gtk::Entry::new();
+        preparation.set_hexpand(true);
+        preparation.set_placeholder_text(Some("Preparation, e.g. finely chopped"));
+
+        let optional = gtk::CheckButton::with_label("Optional");
+        optional.set_tooltip_text(Some("Not needed to cook the recipe, e.g. a garnish"));
+
+        hbox.append(&name);
+        hbox.append(&create_btn);
+        hbox.append(&quantity);
+        hbox.append(&unit);
+        for button in self.row_buttons() {
+            hbox.append(&button);
+        }
+        details.append(&preparation);
+        details.append(&optional);
+        vbox.append(&hbox);
+        vbox.append(&details);
+        root.set_child(Some(&vbox));
+
+        if let Some(i) = ingredient {
+            name.set_text(&i.ingredient);
+            if let Some(q) = &i.quantity {
+                quantity.set_text(&q.to_string());
+            }
+            if let Some(u) = &i.quantity_type {
+                unit.set_text(u);
+            }
+            if let Some(p) = &i.preparation {
+                preparation.set_text(p);
+            }
+            optional.set_active(i.optional);
+        }
+
+        // ── Name: autocomplete and unknown-ingredient warning ────────────────
+        self.attach_completion(&name);
+        self.flag_unknown(&name, &create_btn);
+        let e = self.clone();
+        let create = create_btn.clone();
+        name.connect_changed(move |entry| e.flag_unknown(entry, &create));
+
+        let e = self.clone();
+        let entry = name.clone();
+        create_btn.connect_clicked(move |btn| {
+            let name = entry.text().trim().to_string();
+            if name.is_empty() {
+                return;
+            }
+            e.sender.input(AppMsg::SaveIngredient {
+                original: None,
+                ingredient: Ingredient {
+                    name,
+                    slug: String::new(),
+                    category: String::new(),
+                    kb: None,
+                    tags: None,
+                    translations: None,
+                    prices: Vec::new(),
+                    packages: Vec::new(),
+                    density: None,
+                    unit_weight: None,
+                },
+                in_pantry: false,
+                qty: None,
+                qty_type: String::new(),
+                expires: None,
+            });
+            entry.remove_css_class("warning");
+            btn.set_visible(false);
+        });
+
+        // ── Quantity: flag values that can't be parsed ───────────────────────
+        quantity.connect_changed(|entry| {
+            let text = entry.text();
+            if text.trim().is_empty() || text.parse::<Quantity>().is_ok() {
+                entry.remove_css_class("error");
+            } else {
+                entry.add_css_class("error");
+            }
+        });
+
+        // ── Keyboard flow: Enter moves on, and adds a row after the last one ──
+        let next = quantity.clone();
+        name.connect_activate(move |_| {
+            next.grab_focus();
+        });
+        let next = unit.clone();
+        quantity.connect_activate(move |_| {
+            next.grab_focus();
+        });
+        let e = self.clone();
+        unit.connect_activate(move |entry| e.focus_after(entry));
+        let e = self.clone();
+        preparation.connect_activate(move |entry| e.focus_after(entry));
+
+        self.list.append(&root);
+        self.rows
+            .borrow_mut()
+            .push(EditorRow::Ingredient(IngredientRow {
+                root,
+                name: name.clone(),
+                quantity,
+                unit,
+                preparation,
+                optional,
+            }));
+        name
+    }
+
+    /// Move up, move down and remove buttons for a new row
+    fn row_buttons(&self) -> [gtk::Button; 3] {
+        let up_btn = gtk::Button::from_icon_name("go-up-symbolic");
+        up_btn.add_css_class("flat");
+        up_btn.set_tooltip_text(Some("Move up"));
+
+        let down_btn = gtk::Button::from_icon_name("go-down-symbolic");
+        down_btn.add_css_class("flat");
+        down_btn.set_tooltip_text(Some("Move down"));
+
+        let remove_btn = gtk::Button::from_icon_name("list-remove-symbolic");
+        remove_btn.add_css_class("flat");
+        remove_btn.set_tooltip_text(Some("Remove"));
+
+        let e = self.clone();
+        up_btn.connect_clicked(move |btn| e.move_row(btn, -1));
+        let e = self.clone();
+        down_btn.connect_clicked(move |btn| e.move_row(btn, 1));
+        let e = self.clone();
+        remove_btn.connect_clicked(move |btn| e.remove_row(btn));
+
+        [up_btn, down_btn, remove_btn]
+    }
+
+    /// Focuses the row after the one containing `widget`, adding an ingredient row at the end
+    fn focus_after(&self, widget: &impl IsA<gtk::Widget>) {
+        let Some(index) = self.row_index(widget) else {
+            return;
+        };
+        let next_row = self
+            .rows
+            .borrow()
+            .get(index + 1)
+            .map(|r| r.first_entry().clone());
+        match next_row {
+            Some(next) => next.grab_focus(),
+            None => self.add_row(None).grab_focus(),
+        };
+    }
+
+    /// Index of the row containing `widget`
+    fn row_index(&self, widget: &impl IsA<gtk::Widget>) -> Option<usize> {
+        let root = widget
+            .ancestor(gtk::ListBoxRow::static_type())
+            .and_downcast::<gtk::ListBoxRow>()?;
+        self.rows.borrow().iter().position(|r| *r.root() == root)
+    }
+
+    /// Moves the row containing `btn` up (offset -1) or down (offset 1)
+    fn move_row(&self, btn: &gtk::Button, offset: isize) {
+        let Some(index) = self.row_index(btn) else {
+            return;
+        };
+        let mut rows = self.rows.borrow_mut();
+        let Some(target) = index.checked_add_signed(offset).filter(|t| *t < rows.len()) else {
+            return;
+        };
+        let row = rows.remove(index);
+        self.list.remove(row.root());
+        self.list.insert(row.root(), target as i32);
+        rows.insert(target, row);
+        drop(rows);
+        // Keep focus on the button so the row can be moved again from the keyboard
+        btn.grab_focus();
+    }
+
+    /// Removes the row containing `btn`, leaving an empty row if it was the last one
+    fn remove_row(&self, btn: &gtk::Button) {
+        let Some(index) = self.row_index(btn) else {
+            return;
+        };
+        let row = self.rows.borrow_mut().remove(index);
+        self.list.remove(row.root());
+        if self.rows.borrow().is_empty() {
+            self.add_row(None);
+        }
+    }
+
+    /// Shows the "Create" button and a warning style while the name matches no ingredient
+    fn flag_unknown(&self, entry: &gtk::Entry, create_btn: &gtk::Button) {
+        let text = entry.text().trim().to_string();
+        let unknown = !text.is_empty()
+            && self
+                .dm
+                .borrow()
+                .find_ingredient_by_name_or_translation(&text, &self.language)
+                .is_none();
+        create_btn.set_visible(unknown);
+        if unknown {
+            entry.add_css_class("warning");
+            create_btn.set_tooltip_text(Some(&format!("Add '{}' as a new ingredient", text)));
+        } else {
+            entry.remove_css_class("warning");
+            entry.set_tooltip_text(None);
+        }
+    }
+
+    /// Attaches a completion popup listing matching ingredients.
+    ///
+    /// Suggestions come ranked from `DataManager::ingredient_completions`; a suggestion that
+    /// matched a translation reads "tomatoes → tomato" and inserts the ingredient name.
+    fn attach_completion(&self, entry: &gtk::Entry) {
+        // Columns: ingredient name, label shown in the popup
+        let model = gtk::ListStore::new(&[glib::Type::STRING, glib::Type::STRING]);
+
+        let completion = gtk::EntryCompletion::new();
+        completion.set_model(Some(&model));
+        completion.set_text_column(1);
+        completion.set_minimum_key_length(1);
+        completion.set_popup_completion(true);
+        completion.set_inline_completion(false);
+        // The model is already filtered by the engine
+        completion.set_match_func(|_, _, _| true);
+
+        let target = entry.clone();
+        completion.connect_match_selected(move |_, model, iter| {
+            let name: String = model.get(iter, 0);
+            target.set_text(&name);
+            target.set_position(-1);
+            glib::Propagation::Stop
+        });
+
+        // Connected before set_completion, so the model is refilled before the popup reacts
+        // to the same keystroke
+        let dm = self.dm.clone();
+        let language = self.language.clone();
+        entry.connect_changed(move |entry| {
+            model.clear();
+            let text = entry.text();
+            let completions = dm
+                .borrow()
+                .ingredient_completions(&text, &language, MAX_COMPLETIONS);
+            // Nothing to suggest once the field holds an ingredient name exactly
+            if completions.iter().any(|(name, _)| *name == text.as_str()) {
+                return;
+            }
+            for (name, matched) in completions {
+                let label = if matched.eq_ignore_ascii_case(&name) {
+                    name.clone()
+                } else {
+                    format!("{} → {}", matched, name)
+                };
+                model.insert_with_values(None, &[(0, &name), (1, &label)]);
+            }
+        });
+        entry.set_completion(Some(&completion));
+    }
+}
+
+/// The entry's trimmed text, or None if it is empty
+fn non_empty(entry: &gtk::Entry) -> Option<String> {
+    let text = entry.text().trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
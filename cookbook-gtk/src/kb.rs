@@ -1,6 +1,7 @@
-/// Knowledge Base tab: lists KB articles and shows their content.
+/// Knowledge Base tab: lists KB articles, shows their content, and opens the editor.
 use crate::app::{App, AppMsg};
 use crate::ui_constants::*;
+use cookbook_engine::links::LinkTarget;
 use cookbook_engine::DataManager;
 use libadwaita as adw;
 use relm4::gtk;
@@ -42,6 +43,16 @@ pub fn build_kb_tab(
     list_scroll.set_child(Some(&kb_list));
     left.append(&list_scroll);
 
+    // Add article button
+    let add_btn = gtk::Button::with_label("Add Article");
+    add_btn.add_css_class("flat");
+    add_btn.set_margin_all(DEFAULT_MARGIN);
+    {
+        let sender_add = sender.clone();
+        add_btn.connect_clicked(move |_| sender_add.input(AppMsg::AddKbEntry));
+    }
+    left.append(&add_btn);
+
     // ── Right: article detail ─────────────────────────────────────────────────
     let detail_scroll = gtk::ScrolledWindow::new();
     detail_scroll.set_hexpand(true);
@@ -149,6 +160,7 @@ pub fn update_kb_detail(
     detail: &gtk::Box,
     dm: &Option<Rc<RefCell<DataManager>>>,
     slug: &str,
+    sender: &ComponentSender<App>,
 ) {
     crate::utils::clear_box(detail);
 
@@ -163,24 +175,63 @@ pub fn update_kb_detail(
         return;
     };
 
-    // Title
+    // ── Header ────────────────────────────────────────────────────────────────
+    let header_box = gtk::Box::new(gtk::Orientation::Horizontal, ROW_SPACING);
+    header_box.set_halign(gtk::Align::Fill);
+
     let title = gtk::Label::new(Some(&entry.title));
     title.add_css_class("title-1");
     title.set_halign(gtk::Align::Start);
+    title.set_hexpand(true);
     title.set_wrap(true);
-    detail.append(&title);
+    header_box.append(&title);
+
+    // Edit/Delete buttons
+    let btn_box = gtk::Box::new(gtk::Orientation::Horizontal, ROW_SPACING);
+    btn_box.set_valign(gtk::Align::Start);
+
+    let edit_btn = gtk::Button::with_label("Edit");
+    edit_btn.add_css_class("flat");
+    {
+        let sender_edit = sender.clone();
+        let slug_clone = slug.to_string();
+        edit_btn.connect_clicked(move |_| {
+            sender_edit.input(AppMsg::EditKbEntry(slug_clone.clone()));
+        });
+    }
+    btn_box.append(&edit_btn);
+
+    // Bundled articles can be edited (which saves a copy) but not deleted
+    if !dm.is_kb_entry_bundled(slug) {
+        let delete_btn = gtk::Button::with_label("Delete");
+        delete_btn.add_css_class("flat");
+        delete_btn.add_css_class("destructive-action");
+        {
+            let sender_delete = sender.clone();
+            let slug_clone = slug.to_string();
+            let title_clone = entry.title.clone();
+            delete_btn.connect_clicked(move |btn| {
+                let window = btn.root().and_then(|r| r.downcast::<gtk::Window>().ok());
+                show_delete_kb_confirm(window.as_ref(), &slug_clone, &title_clone, &sender_delete);
+            });
+        }
+        btn_box.append(&delete_btn);
+    }
+
+    header_box.append(&btn_box);
+    detail.append(&header_box);
 
     detail.append(&gtk::Separator::new(gtk::Orientation::Horizontal));
 
-    // Content as plain text
-    let content_label = gtk::Label::new(Some(&entry.content));
-    content_label.set_halign(gtk::Align::Start);
-    content_label.set_valign(gtk::Align::Start);
-    content_label.set_wrap(true);
-    content_label.set_wrap_mode(gtk::pango::WrapMode::WordChar);
-    content_label.set_selectable(true);
-    content_label.set_xalign(0.0);
-    detail.append(&content_label);
+    if let Some(picture) = entry
+        .image
+        .as_deref()
+        .and_then(|image| crate::utils::image_widget(&dm, image))
+    {
+        detail.append(&picture);
+    }
+
+    crate::utils::append_markdown(detail, &dm, &entry.content, Some(sender));
 
     // Linked ingredients
     let linked = dm.get_ingredients_with_kb_reference(slug);
@@ -198,6 +249,37 @@ pub fn update_kb_detail(
             detail.append(&label);
         }
     }
+
+    crate::utils::append_backlinks(detail, &dm, &LinkTarget::Kb(slug.to_string()), sender);
+}
+
+fn show_delete_kb_confirm(
+    parent: Option<&gtk::Window>,
+    slug: &str,
+    title: &str,
+    sender: &ComponentSender<App>,
+) {
+    use adw::prelude::*;
+
+    let dialog = adw::MessageDialog::new(
+        parent,
+        Some(&format!("Delete \"{}\"?", title)),
+        Some("This article will be permanently removed. If it replaced a bundled article, the bundled version is shown again."),
+    );
+    dialog.add_response("cancel", "Cancel");
+    dialog.add_response("delete", "Delete");
+    dialog.set_response_appearance("delete", adw::ResponseAppearance::Destructive);
+    dialog.set_default_response(Some("cancel"));
+    dialog.set_close_response("cancel");
+
+    let sender_clone = sender.clone();
+    let slug_owned = slug.to_string();
+    dialog.connect_response(None, move |_, response| {
+        if response == "delete" {
+            sender_clone.input(AppMsg::DeleteKbEntry(slug_owned.clone()));
+        }
+    });
+    dialog.present();
 }
 
 pub fn show_kb_placeholder(detail: &gtk::Box) {
@@ -205,7 +287,7 @@ pub fn show_kb_placeholder(detail: &gtk::Box) {
     let status = adw::StatusPage::new();
     status.set_icon_name(Some("system-help-symbolic"));
     status.set_title("Knowledge Base");
-    status.set_description(Some("Select an article from the list to read it."));
+    status.set_description(Some("Select an article to read it, or add a new one."));
     status.set_vexpand(true);
     detail.append(&status);
 }
@@ -1,9 +1,12 @@
 pub mod app;
 pub mod config;
 pub mod dialogs;
+pub mod ingredient_editor;
 pub mod kb;
 pub mod pantry;
+pub mod planner;
 pub mod recipes;
 pub mod settings;
+pub mod shopping;
 pub mod ui_constants;
 pub mod utils;
@@ -1,7 +1,8 @@
 /// Pantry tab: ingredient list with in-stock toggle and detail view.
 use crate::app::{App, AppMsg};
 use crate::ui_constants::*;
-use cookbook_engine::DataManager;
+use cookbook_engine::links::LinkTarget;
+use cookbook_engine::{units, DataManager, DeleteStrategy, IngredientDependents, UnitSystem};
 use libadwaita as adw;
 use relm4::gtk;
 use relm4::{gtk::prelude::*, ComponentSender, RelmWidgetExt};
@@ -199,6 +200,7 @@ pub fn update_ingredient_detail(
     detail: &gtk::Box,
     dm: &Option<Rc<RefCell<DataManager>>>,
     name: &str,
+    unit_system: UnitSystem,
     sender: &ComponentSender<App>,
 ) {
     crate::utils::clear_box(detail);
@@ -238,6 +240,21 @@ pub fn update_ingredient_detail(
         }
     }
 
+    // Unit conversions
+    let mut conversions = Vec::new();
+    if let Some(density) = ing.density {
+        conversions.push(format!("{} g per ml", density));
+    }
+    if let Some(weight) = ing.unit_weight {
+        conversions.push(format!("{} g each", weight));
+    }
+    if !conversions.is_empty() {
+        let conversions_label = gtk::Label::new(Some(&conversions.join(" · ")));
+        conversions_label.add_css_class("caption");
+        conversions_label.set_halign(gtk::Align::Start);
+        detail.append(&conversions_label);
+    }
+
     detail.append(&gtk::Separator::new(gtk::Orientation::Horizontal));
 
     // ── Pantry status ─────────────────────────────────────────────────────────
@@ -253,16 +270,53 @@ pub fn update_ingredient_detail(
         detail.append(&status);
 
         if let Some(qty) = item.quantity {
-            let qty_text = if item.quantity_type.is_empty() {
-                format!("Quantity: {}", qty)
-            } else {
-                format!("Quantity: {} {}", qty, item.quantity_type)
-            };
-            let qty_label = gtk::Label::new(Some(&qty_text));
+            let qty_text = units::format_amount(qty, &item.quantity_type, unit_system);
+            let qty_label = gtk::Label::new(Some(&format!("Quantity: {}", qty_text)));
             qty_label.set_halign(gtk::Align::Start);
+            let original = units::format_amount(qty, &item.quantity_type, UnitSystem::Original);
+            if original != qty_text {
+                qty_label.set_tooltip_text(Some(&original));
+            }
             detail.append(&qty_label);
         }
 
+        if let Some(expires) = item.expires {
+            let expires_label = gtk::Label::new(Some(&format!("Use by: {}", expires)));
+            expires_label.set_halign(gtk::Align::Start);
+            detail.append(&expires_label);
+        }
+
+        let today = chrono::Local::now().date_naive();
+        if let Some(forecast) = dm.forecast_pantry_item(name, today) {
+            let mut lines = Vec::new();
+            if let Some(rate) = &forecast.rate {
+                lines.push(format!(
+                    "Uses about {:.2} {} a day (over {} days)",
+                    rate.per_day, rate.quantity_type, rate.days
+                ));
+            }
+            if let Some(date) = forecast.runs_out {
+                lines.push(format!("Runs out around {}", date.format("%A %-d %B")));
+            }
+            if !forecast.unmeasured.is_empty() {
+                let uses: Vec<String> = forecast
+                    .unmeasured
+                    .iter()
+                    .map(|u| match &u.missing {
+                        Some(missing) => format!("{} (needs {})", u.recipe, missing),
+                        None => format!("{} (no amount)", u.recipe),
+                    })
+                    .collect();
+                lines.push(format!("Not counted: planned {}", uses.join(", ")));
+            }
+            if !lines.is_empty() {
+                let forecast_label = gtk::Label::new(Some(&lines.join("\n")));
+                forecast_label.set_halign(gtk::Align::Start);
+                forecast_label.set_wrap(true);
+                detail.append(&forecast_label);
+            }
+        }
+
         let updated = gtk::Label::new(Some(&format!("Last updated: {}", item.last_updated)));
         updated.add_css_class("caption");
         updated.add_css_class("dim-label");
@@ -305,13 +359,26 @@ pub fn update_ingredient_detail(
     if let Some(kb_slug) = &ing.kb {
         if let Some(kb_entry) = dm.get_kb_entry(kb_slug) {
             detail.append(&gtk::Separator::new(gtk::Orientation::Horizontal));
-            let kb_label = gtk::Label::new(Some(&format!("📖 {}", kb_entry.title)));
+            let kb_label = gtk::Label::new(None);
+            kb_label.set_markup(&format!(
+                "<a href=\"{}\">📖 {}</a>",
+                gtk::glib::markup_escape_text(&LinkTarget::Kb(kb_slug.clone()).to_uri()),
+                gtk::glib::markup_escape_text(&kb_entry.title)
+            ));
             kb_label.set_halign(gtk::Align::Start);
             kb_label.add_css_class("caption");
+            crate::utils::connect_wiki_links(&kb_label, sender);
             detail.append(&kb_label);
         }
     }
 
+    crate::utils::append_backlinks(
+        detail,
+        &dm,
+        &LinkTarget::Ingredient(ing.name.clone()),
+        sender,
+    );
+
     detail.append(&gtk::Separator::new(gtk::Orientation::Horizontal));
 
     // ── Action buttons ────────────────────────────────────────────────────────
@@ -334,9 +401,23 @@ pub fn update_ingredient_detail(
     {
         let sender_delete = sender.clone();
         let name_clone = name.to_string();
+        let dependents = dm.ingredient_dependents(name);
+        let mut replacements: Vec<String> = dm
+            .get_all_ingredients()
+            .into_iter()
+            .map(|i| i.name.clone())
+            .filter(|n| n != name)
+            .collect();
+        replacements.sort();
         delete_btn.connect_clicked(move |btn| {
             let window = btn.root().and_then(|r| r.downcast::<gtk::Window>().ok());
-            show_delete_ingredient_confirm(window.as_ref(), &name_clone, &sender_delete);
+            show_delete_ingredient_confirm(
+                window.as_ref(),
+                &name_clone,
+                &dependents,
+                &replacements,
+                &sender_delete,
+            );
         });
     }
 
@@ -345,30 +426,91 @@ pub fn update_ingredient_detail(
     detail.append(&btn_box);
 }
 
+/// Confirm deleting an ingredient.
+///
+/// If recipes still use it, the dialog lists them and offers to remove the ingredient from
+/// those recipes or replace it with another one; cancelling leaves everything as it is.
 fn show_delete_ingredient_confirm(
     parent: Option<&gtk::Window>,
     name: &str,
+    dependents: &IngredientDependents,
+    replacements: &[String],
     sender: &ComponentSender<App>,
 ) {
     use adw::prelude::*;
 
-    let dialog = adw::MessageDialog::new(
-        parent,
-        Some(&format!("Delete \"{}\"?", name)),
-        Some("This will remove the ingredient and its pantry entry. This cannot be undone."),
-    );
+    let mut body =
+        if dependents.recipes.is_empty() {
+            "This will remove the ingredient and its pantry entry. This cannot be undone."
+                .to_string()
+        } else {
+            format!(
+            "It is used by {} recipe{}: {}.\n\nRemove it from those recipes, or replace it with \
+             another ingredient. This cannot be undone.",
+            dependents.recipes.len(),
+            if dependents.recipes.len() == 1 { "" } else { "s" },
+            dependents.recipes.join(", ")
+        )
+        };
+    if !dependents.kb_links.is_empty() {
+        body.push_str(&format!(
+            "\n\nLinks to it in these articles will be broken: {}.",
+            dependents.kb_links.join(", ")
+        ));
+    }
+
+    let dialog =
+        adw::MessageDialog::new(parent, Some(&format!("Delete \"{}\"?", name)), Some(&body));
     dialog.add_response("cancel", "Cancel");
-    dialog.add_response("delete", "Delete");
-    dialog.set_response_appearance("delete", adw::ResponseAppearance::Destructive);
+
+    // Replacement picker, only offered when recipes depend on the ingredient
+    let replacement_dropdown = if dependents.recipes.is_empty() || replacements.is_empty() {
+        None
+    } else {
+        let items: Vec<&str> = replacements.iter().map(String::as_str).collect();
+        let dropdown = gtk::DropDown::from_strings(&items);
+        let row = gtk::Box::new(gtk::Orientation::Horizontal, ROW_SPACING);
+        row.append(&gtk::Label::new(Some("Replace with")));
+        dropdown.set_hexpand(true);
+        row.append(&dropdown);
+        dialog.set_extra_child(Some(&row));
+        dialog.add_response("replace", "Replace");
+        Some(dropdown)
+    };
+
+    let delete_response = if dependents.recipes.is_empty() {
+        dialog.add_response("delete", "Delete");
+        "delete"
+    } else {
+        dialog.add_response("cascade", "Remove from Recipes");
+        "cascade"
+    };
+    dialog.set_response_appearance(delete_response, adw::ResponseAppearance::Destructive);
     dialog.set_default_response(Some("cancel"));
     dialog.set_close_response("cancel");
 
     let sender_clone = sender.clone();
     let name_owned = name.to_string();
+    let replacements = replacements.to_vec();
     dialog.connect_response(None, move |_, response| {
-        if response == "delete" {
-            sender_clone.input(AppMsg::DeleteIngredient(name_owned.clone()));
-        }
+        let strategy = match response {
+            "delete" => DeleteStrategy::Block,
+            "cascade" => DeleteStrategy::Cascade,
+            "replace" => {
+                let Some(dropdown) = &replacement_dropdown else {
+                    return;
+                };
+                let Some(replacement) = replacements.get(dropdown.selected() as usize) else {
+                    return;
+                };
+                DeleteStrategy::Replace(replacement.clone())
+            }
+            _ => return,
+        };
+        sender_clone.input(AppMsg::DeleteIngredient {
+            name: name_owned.clone(),
+            strategy,
+        });
     });
     dialog.present();
 }
@@ -0,0 +1,408 @@
+/// Meal Plan tab: a week grid of meals, filled by dragging recipes from the list beside it.
+///
+/// Dropping a recipe on a cell plans it for that day and meal. Planned meals can be dragged
+/// to another cell to move them, or with Ctrl held to copy them. Clicking one sets its
+/// servings or removes it.
+use crate::app::{App, AppMsg};
+use crate::ui_constants::*;
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use cookbook_engine::links::LinkTarget;
+use cookbook_engine::{DataManager, Meal, MealPlanEntry};
+use relm4::gtk;
+use relm4::gtk::{gdk, glib};
+use relm4::{gtk::prelude::*, ComponentSender, RelmWidgetExt};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+pub mod suggest;
+
+/// Narrowest a day column gets before the grid scrolls sideways
+const DAY_COLUMN_WIDTH: i32 = 130;
+
+/// The widgets of the Meal Plan tab
+#[derive(Clone)]
+pub struct PlannerView {
+    pub root: gtk::Widget,
+    recipe_list: gtk::ListBox,
+    week_label: gtk::Label,
+    grid: gtk::Grid,
+    /// The Monday of the week the grid shows
+    week: Rc<Cell<Option<NaiveDate>>>,
+    /// The planned meal being dragged, if the drag started in the grid rather than the list
+    dragged: Rc<RefCell<Option<MealPlanEntry>>>,
+    sender: ComponentSender<App>,
+}
+
+/// The Monday of the week `date` falls in
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+impl PlannerView {
+    /// Build the full Meal Plan tab widget
+    pub fn new(sender: ComponentSender<App>) -> Self {
+        let paned = gtk::Paned::new(gtk::Orientation::Horizontal);
+        paned.set_hexpand(true);
+        paned.set_vexpand(true);
+        paned.set_position(LIST_PANE_WIDTH);
+
+        // ── Left: recipes to drag ────────────────────────────────────────────
+        let left = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        left.set_width_request(200);
+
+        let search = gtk::SearchEntry::new();
+        search.set_placeholder_text(Some("Search recipes…"));
+        search.set_margin_all(DEFAULT_MARGIN);
+        left.append(&search);
+
+        let recipe_list = gtk::ListBox::new();
+        recipe_list.set_selection_mode(gtk::SelectionMode::None);
+        recipe_list.add_css_class("navigation-sidebar");
+        {
+            let search = search.clone();
+            recipe_list.set_filter_func(move |row| {
+                let query = search.text().to_lowercase();
+                query.is_empty() || row.widget_name().to_lowercase().contains(&query)
+            });
+        }
+        {
+            let recipe_list = recipe_list.clone();
+            search.connect_search_changed(move |_| recipe_list.invalidate_filter());
+        }
+        let list_scroll = gtk::ScrolledWindow::new();
+        list_scroll.set_vexpand(true);
+        list_scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+        list_scroll.set_child(Some(&recipe_list));
+        left.append(&list_scroll);
+
+        let hint = gtk::Label::new(Some("Drag a recipe onto a meal"));
+        hint.add_css_class("dim-label");
+        hint.add_css_class("caption");
+        hint.set_margin_all(DEFAULT_MARGIN);
+        left.append(&hint);
+
+        // ── Right: week navigation and grid ──────────────────────────────────
+        let right = gtk::Box::new(gtk::Orientation::Vertical, SECTION_SPACING);
+        right.set_margin_all(DEFAULT_MARGIN);
+
+        let nav = gtk::Box::new(gtk::Orientation::Horizontal, ROW_SPACING);
+        let prev_btn = gtk::Button::from_icon_name("go-previous-symbolic");
+        prev_btn.set_tooltip_text(Some("Previous week"));
+        let today_btn = gtk::Button::with_label("This Week");
+        let next_btn = gtk::Button::from_icon_name("go-next-symbolic");
+        next_btn.set_tooltip_text(Some("Next week"));
+        let week_label = gtk::Label::new(None);
+        week_label.add_css_class("title-2");
+        week_label.set_hexpand(true);
+        week_label.set_halign(gtk::Align::Start);
+        week_label.set_margin_start(DEFAULT_MARGIN);
+        let suggest_btn = gtk::Button::with_label("Suggest Meals…");
+        suggest_btn.add_css_class("flat");
+        suggest_btn.set_tooltip_text(Some(
+            "Fill the open meals of this week from the pantry and your diet",
+        ));
+        let shopping_btn = gtk::Button::with_label("Shopping List…");
+        shopping_btn.add_css_class("flat");
+        shopping_btn.set_tooltip_text(Some(
+            "What the planned meals need and what runs out before the next shop",
+        ));
+        let copy_btn = gtk::Button::with_label("Copy Previous Week");
+        copy_btn.add_css_class("flat");
+        copy_btn.set_tooltip_text(Some("Plan the meals of the week before again this week"));
+        nav.append(&prev_btn);
+        nav.append(&today_btn);
+        nav.append(&next_btn);
+        nav.append(&week_label);
+        nav.append(&suggest_btn);
+        nav.append(&copy_btn);
+        nav.append(&shopping_btn);
+        right.append(&nav);
+
+        let grid = gtk::Grid::new();
+        grid.set_row_spacing(ROW_SPACING as u32);
+        grid.set_column_spacing(ROW_SPACING as u32);
+        grid.set_column_homogeneous(false);
+        let grid_scroll = gtk::ScrolledWindow::new();
+        grid_scroll.set_vexpand(true);
+        grid_scroll.set_hexpand(true);
+        grid_scroll.set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Automatic);
+        grid_scroll.set_child(Some(&grid));
+        right.append(&grid_scroll);
+
+        paned.set_start_child(Some(&left));
+        paned.set_end_child(Some(&right));
+
+        let view = PlannerView {
+            root: paned.upcast(),
+            recipe_list,
+            week_label,
+            grid,
+            week: Rc::new(Cell::new(None)),
+            dragged: Rc::new(RefCell::new(None)),
+            sender: sender.clone(),
+        };
+        for (button, days) in [(&prev_btn, -7), (&next_btn, 7)] {
+            let v = view.clone();
+            button.connect_clicked(move |_| {
+                if let Some(start) = v.week.get() {
+                    v.sender
+                        .input(AppMsg::ShowPlanWeek(start + Duration::days(days)));
+                }
+            });
+        }
+        {
+            let sender = sender.clone();
+            today_btn.connect_clicked(move |_| {
+                sender.input(AppMsg::ShowPlanWeek(week_start(Local::now().date_naive())));
+            });
+        }
+        {
+            let sender = sender.clone();
+            suggest_btn.connect_clicked(move |_| sender.input(AppMsg::SuggestMeals));
+        }
+        {
+            let sender = sender.clone();
+            copy_btn.connect_clicked(move |_| sender.input(AppMsg::CopyPreviousPlanWeek));
+        }
+        shopping_btn.connect_clicked(move |_| sender.input(AppMsg::ShowShoppingList));
+
+        view
+    }
+
+    /// Rebuild the list of recipes to drag from
+    pub fn populate_recipes(&self, dm: &Option<Rc<RefCell<DataManager>>>) {
+        crate::utils::clear_list_box(&self.recipe_list);
+        let Some(dm) = dm else {
+            return;
+        };
+        let dm = dm.borrow();
+        let mut recipes: Vec<_> = dm.get_all_recipes().iter().collect();
+        recipes.sort_by(|a, b| a.title.cmp(&b.title));
+
+        for recipe in recipes {
+            let row = gtk::ListBoxRow::new();
+            row.set_widget_name(&recipe.title);
+            let label = gtk::Label::new(Some(&recipe.title));
+            label.set_halign(gtk::Align::Start);
+            label.set_ellipsize(gtk::pango::EllipsizeMode::End);
+            label.set_margin_top(ROW_SPACING);
+            label.set_margin_bottom(ROW_SPACING);
+            label.set_margin_start(DEFAULT_MARGIN);
+            label.set_margin_end(DEFAULT_MARGIN);
+            row.set_child(Some(&label));
+
+            let source = gtk::DragSource::new();
+            source.set_actions(gdk::DragAction::COPY);
+            source.set_content(Some(&gdk::ContentProvider::for_value(
+                &recipe.title.to_value(),
+            )));
+            row.add_controller(source);
+            self.recipe_list.append(&row);
+        }
+    }
+
+    /// Rebuild the grid for the week starting on `week`
+    pub fn show_week(&self, dm: &Option<Rc<RefCell<DataManager>>>, week: NaiveDate) {
+        while let Some(child) = self.grid.first_child() {
+            self.grid.remove(&child);
+        }
+        self.week.set(Some(week));
+        let last = week + Duration::days(6);
+        self.week_label.set_text(&format!(
+            "{} – {}",
+            week.format("%-d %b"),
+            last.format("%-d %b %Y")
+        ));
+
+        let Some(dm) = dm else {
+            let label = gtk::Label::new(Some("No data directory set"));
+            label.add_css_class("dim-label");
+            self.grid.attach(&label, 0, 0, 1, 1);
+            return;
+        };
+        let dm = dm.borrow();
+        let entries = dm.get_meal_plan(week, last);
+        let today = Local::now().date_naive();
+
+        for (row, meal) in Meal::ALL.iter().enumerate() {
+            let label = gtk::Label::new(Some(meal.label()));
+            label.add_css_class("heading");
+            label.set_halign(gtk::Align::End);
+            label.set_valign(gtk::Align::Start);
+            label.set_margin_top(ROW_SPACING);
+            self.grid.attach(&label, 0, row as i32 + 1, 1, 1);
+        }
+
+        for column in 0..7 {
+            let date = week + Duration::days(column);
+            let header = gtk::Label::new(Some(&date.format("%a %-d").to_string()));
+            header.add_css_class("heading");
+            if date == today {
+                header.add_css_class("accent");
+            }
+            self.grid.attach(&header, column as i32 + 1, 0, 1, 1);
+
+            for (row, meal) in Meal::ALL.iter().enumerate() {
+                let cell = self.build_cell(date, *meal);
+                for entry in entries.iter().filter(|e| e.date == date && e.meal == *meal) {
+                    let exists = dm.get_recipe(&entry.recipe).is_some();
+                    cell.append(&self.build_entry(entry, exists));
+                }
+                self.grid
+                    .attach(&cell, column as i32 + 1, row as i32 + 1, 1, 1);
+            }
+        }
+    }
+
+    /// A drop target for one day and meal
+    fn build_cell(&self, date: NaiveDate, meal: Meal) -> gtk::Box {
+        let cell = gtk::Box::new(gtk::Orientation::Vertical, ROW_SPACING);
+        cell.add_css_class("card");
+        cell.set_width_request(DAY_COLUMN_WIDTH);
+        cell.set_height_request(64);
+        cell.set_hexpand(true);
+
+        let target = gtk::DropTarget::new(
+            glib::Type::STRING,
+            gdk::DragAction::COPY | gdk::DragAction::MOVE,
+        );
+        let v = self.clone();
+        target.connect_drop(move |target, value, _, _| {
+            let Ok(recipe) = value.get::<String>() else {
+                return false;
+            };
+            match v.dragged.borrow_mut().take() {
+                Some(entry) => {
+                    let copy = target
+                        .current_event_state()
+                        .contains(gdk::ModifierType::CONTROL_MASK);
+                    v.sender.input(AppMsg::MovePlanEntry {
+                        entry,
+                        date,
+                        meal,
+                        copy,
+                    });
+                }
+                None => v.sender.input(AppMsg::AddPlanEntry(MealPlanEntry {
+                    date,
+                    meal,
+                    recipe,
+                    servings: None,
+                })),
+            }
+            true
+        });
+        cell.add_controller(target);
+        cell
+    }
+
+    /// A planned meal: drag it to move or copy, click it for servings and removal
+    fn build_entry(&self, entry: &MealPlanEntry, exists: bool) -> gtk::MenuButton {
+        let title = gtk::Label::new(Some(&entry.recipe));
+        title.set_wrap(true);
+        title.set_xalign(0.0);
+        let servings = gtk::Label::new(Some(&servings_text(entry.servings)));
+        servings.add_css_class("caption");
+        servings.add_css_class("dim-label");
+        servings.set_xalign(0.0);
+        let content = gtk::Box::new(gtk::Orientation::Vertical, 0);
+        content.append(&title);
+        content.append(&servings);
+
+        let button = gtk::MenuButton::new();
+        button.set_child(Some(&content));
+        button.set_always_show_arrow(false);
+        button.add_css_class("flat");
+        button.set_margin_top(ROW_SPACING);
+        button.set_margin_start(ROW_SPACING);
+        button.set_margin_end(ROW_SPACING);
+        if !exists {
+            title.add_css_class("dim-label");
+            button.set_tooltip_text(Some("This recipe no longer exists"));
+        }
+
+        // ── Popover: servings, open, remove ───────────────────────────────────
+        let popover_box = gtk::Box::new(gtk::Orientation::Vertical, ROW_SPACING);
+        popover_box.set_margin_all(ROW_SPACING);
+        let servings_row = gtk::Box::new(gtk::Orientation::Horizontal, ROW_SPACING);
+        servings_row.append(&gtk::Label::new(Some("Servings")));
+        let spin = gtk::SpinButton::with_range(0.0, 99.0, 1.0);
+        spin.set_value(entry.servings.unwrap_or(0) as f64);
+        spin.set_tooltip_text(Some("0 cooks the recipe's own number of servings"));
+        servings_row.append(&spin);
+        popover_box.append(&servings_row);
+
+        let open_btn = gtk::Button::with_label("Open Recipe");
+        open_btn.add_css_class("flat");
+        open_btn.set_sensitive(exists);
+        popover_box.append(&open_btn);
+        let remove_btn = gtk::Button::with_label("Remove");
+        remove_btn.add_css_class("flat");
+        remove_btn.add_css_class("destructive-action");
+        popover_box.append(&remove_btn);
+
+        let popover = gtk::Popover::new();
+        popover.set_child(Some(&popover_box));
+        button.set_popover(Some(&popover));
+
+        {
+            let sender = self.sender.clone();
+            let entry = entry.clone();
+            spin.connect_value_changed(move |spin| {
+                let value = Some(spin.value() as u32).filter(|v| *v > 0);
+                servings.set_text(&servings_text(value));
+                sender.input(AppMsg::SetPlanServings {
+                    entry: entry.clone(),
+                    servings: value,
+                });
+            });
+        }
+        {
+            let sender = self.sender.clone();
+            let recipe = entry.recipe.clone();
+            let popover = popover.clone();
+            open_btn.connect_clicked(move |_| {
+                popover.popdown();
+                sender.input(AppMsg::OpenLink(LinkTarget::Recipe(recipe.clone())));
+            });
+        }
+        {
+            let sender = self.sender.clone();
+            let entry = entry.clone();
+            remove_btn.connect_clicked(move |_| {
+                popover.popdown();
+                sender.input(AppMsg::RemovePlanEntry(entry.clone()));
+            });
+        }
+
+        // ── Dragging to another cell ──────────────────────────────────────────
+        let source = gtk::DragSource::new();
+        source.set_actions(gdk::DragAction::MOVE | gdk::DragAction::COPY);
+        source.set_content(Some(&gdk::ContentProvider::for_value(
+            &entry.recipe.to_value(),
+        )));
+        {
+            let dragged = self.dragged.clone();
+            let entry = entry.clone();
+            source.connect_drag_begin(move |_, _| {
+                *dragged.borrow_mut() = Some(entry.clone());
+            });
+        }
+        {
+            let dragged = self.dragged.clone();
+            source.connect_drag_end(move |_, _, _| {
+                dragged.borrow_mut().take();
+            });
+        }
+        button.add_controller(source);
+        button
+    }
+}
+
+fn servings_text(servings: Option<u32>) -> String {
+    match servings {
+        Some(1) => "1 serving".to_string(),
+        Some(n) => format!("{} servings", n),
+        None => "Recipe servings".to_string(),
+    }
+}
@@ -0,0 +1,285 @@
+/// Meal suggestion dialog: fills the open meals of a week from the pantry and the diet.
+///
+/// The suggestions update as the options change, each with the reasons it was picked.
+/// "Shuffle" tries another seed; "Add to Plan" plans them and remembers the time budget and
+/// diet for next time.
+use crate::app::{App, AppMsg};
+use crate::config::UserSettings;
+use crate::ui_constants::*;
+use chrono::NaiveDate;
+use cookbook_engine::planner::{DietaryProfile, MealPlanSuggestion, PlanRequest};
+use cookbook_engine::{DataManager, Meal};
+use libadwaita as adw;
+use libadwaita::prelude::*;
+use relm4::gtk;
+use relm4::{ComponentSender, RelmWidgetExt};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+const WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+/// Opens the suggestion dialog for the week starting on `week`
+pub fn show_suggest_dialog(
+    parent: &impl IsA<gtk::Window>,
+    dm: Rc<RefCell<DataManager>>,
+    settings: &UserSettings,
+    week: NaiveDate,
+    sender: ComponentSender<App>,
+) {
+    let window = adw::Window::builder()
+        .transient_for(parent)
+        .modal(true)
+        .title("Suggest Meals")
+        .default_width(960)
+        .default_height(680)
+        .build();
+
+    let toolbar_view = adw::ToolbarView::new();
+    let header = adw::HeaderBar::new();
+    let add_btn = gtk::Button::with_label("Add to Plan");
+    add_btn.add_css_class("suggested-action");
+    header.pack_end(&add_btn);
+    let shuffle_btn = gtk::Button::from_icon_name("media-playlist-shuffle-symbolic");
+    shuffle_btn.set_tooltip_text(Some("Suggest other recipes"));
+    header.pack_end(&shuffle_btn);
+    toolbar_view.add_top_bar(&header);
+
+    // ── Left: options ────────────────────────────────────────────────────────
+    let page = adw::PreferencesPage::new();
+
+    let meals_group = adw::PreferencesGroup::new();
+    meals_group.set_title("Meals");
+    let meal_rows: Vec<(Meal, adw::SwitchRow)> = Meal::ALL
+        .iter()
+        .map(|meal| {
+            let row = adw::SwitchRow::new();
+            row.set_title(meal.label());
+            row.set_active(*meal == Meal::Dinner);
+            meals_group.add(&row);
+            (*meal, row)
+        })
+        .collect();
+    page.add(&meals_group);
+
+    let time_group = adw::PreferencesGroup::new();
+    time_group.set_title("Time to Cook");
+    time_group.set_description(Some(
+        "Minutes per day, including waiting time; 0 for no limit",
+    ));
+    let time_rows: Vec<adw::SpinRow> = WEEKDAYS
+        .iter()
+        .zip(settings.cooking_time_budget)
+        .map(|(day, minutes)| {
+            let row = adw::SpinRow::with_range(0.0, 600.0, 5.0);
+            row.set_title(day);
+            row.set_value(minutes as f64);
+            time_group.add(&row);
+            row
+        })
+        .collect();
+    page.add(&time_group);
+
+    let diet_group = adw::PreferencesGroup::new();
+    diet_group.set_title("Diet");
+    diet_group.set_description(Some("Comma-separated"));
+    let profile = &settings.dietary_profile;
+    let require_row = list_row("Only recipes tagged", &profile.require_tags);
+    let exclude_tags_row = list_row("No recipes tagged", &profile.exclude_tags);
+    let exclude_ingredients_row = list_row("Leave out ingredients", &profile.exclude_ingredients);
+    diet_group.add(&require_row);
+    diet_group.add(&exclude_tags_row);
+    diet_group.add(&exclude_ingredients_row);
+    page.add(&diet_group);
+
+    let variety_group = adw::PreferencesGroup::new();
+    variety_group.set_title("Variety");
+    let repeat_row = adw::SpinRow::with_range(0.0, 60.0, 1.0);
+    repeat_row.set_title("Avoid repeats within");
+    repeat_row.set_subtitle("Days");
+    repeat_row.set_value(PlanRequest::week(week).repeat_window_days as f64);
+    variety_group.add(&repeat_row);
+    page.add(&variety_group);
+
+    // ── Right: suggestions ───────────────────────────────────────────────────
+    let results = gtk::Box::new(gtk::Orientation::Vertical, SECTION_SPACING);
+    results.set_margin_all(DEFAULT_MARGIN);
+    let results_scroll = gtk::ScrolledWindow::new();
+    results_scroll.set_hexpand(true);
+    results_scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+    results_scroll.set_child(Some(&results));
+
+    let paned = gtk::Paned::new(gtk::Orientation::Horizontal);
+    paned.set_position(380);
+    paned.set_start_child(Some(&page));
+    paned.set_end_child(Some(&results_scroll));
+    toolbar_view.set_content(Some(&paned));
+    window.set_content(Some(&toolbar_view));
+
+    // ── Recompute on every change ────────────────────────────────────────────
+    let seed = Rc::new(Cell::new(0u64));
+    let suggestion: Rc<RefCell<MealPlanSuggestion>> = Rc::default();
+    let build_request = {
+        let seed = seed.clone();
+        let meal_rows = meal_rows.clone();
+        let time_rows = time_rows.clone();
+        let require_row = require_row.clone();
+        let exclude_tags_row = exclude_tags_row.clone();
+        let exclude_ingredients_row = exclude_ingredients_row.clone();
+        let repeat_row = repeat_row.clone();
+        move || {
+            let mut request = PlanRequest::week(week);
+            request.meals = meal_rows
+                .iter()
+                .filter(|(_, row)| row.is_active())
+                .map(|(meal, _)| *meal)
+                .collect();
+            for (budget, row) in request.time_budget.iter_mut().zip(&time_rows) {
+                *budget = Some(row.value() as u32).filter(|m| *m > 0);
+            }
+            request.profile = DietaryProfile {
+                require_tags: split_list(&require_row.text()),
+                exclude_tags: split_list(&exclude_tags_row.text()),
+                exclude_ingredients: split_list(&exclude_ingredients_row.text()),
+            };
+            request.repeat_window_days = repeat_row.value() as u32;
+            request.seed = seed.get();
+            request
+        }
+    };
+    let build_request = Rc::new(build_request);
+    let refresh = {
+        let build_request = build_request.clone();
+        let suggestion = suggestion.clone();
+        let add_btn = add_btn.clone();
+        move || {
+            let new = dm.borrow().suggest_meal_plan(&build_request());
+            show_suggestion(&results, &new);
+            add_btn.set_sensitive(!new.meals.is_empty());
+            *suggestion.borrow_mut() = new;
+        }
+    };
+    refresh();
+    let refresh = Rc::new(refresh);
+
+    for (_, row) in &meal_rows {
+        let refresh = refresh.clone();
+        row.connect_active_notify(move |_| refresh());
+    }
+    for row in time_rows.iter().chain([&repeat_row]) {
+        let refresh = refresh.clone();
+        row.connect_value_notify(move |_| refresh());
+    }
+    for row in [&require_row, &exclude_tags_row, &exclude_ingredients_row] {
+        let refresh = refresh.clone();
+        row.connect_changed(move |_| refresh());
+    }
+    shuffle_btn.connect_clicked(move |_| {
+        seed.set(seed.get().wrapping_add(1));
+        refresh();
+    });
+    {
+        let window = window.clone();
+        add_btn.connect_clicked(move |_| {
+            let request = build_request();
+            let mut time_budget = [0; 7];
+            for (minutes, budget) in time_budget.iter_mut().zip(request.time_budget) {
+                *minutes = budget.unwrap_or(0);
+            }
+            sender.input(AppMsg::SetMealSuggestionSettings {
+                time_budget,
+                profile: request.profile,
+            });
+            sender.input(AppMsg::AddPlanEntries(suggestion.borrow().entries()));
+            window.close();
+        });
+    }
+
+    window.present();
+}
+
+fn list_row(title: &str, values: &[String]) -> adw::EntryRow {
+    let row = adw::EntryRow::new();
+    row.set_title(title);
+    row.set_text(&values.join(", "));
+    row
+}
+
+fn split_list(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Lists the suggestions by day, each with its reasons, and the meals left open
+fn show_suggestion(results: &gtk::Box, suggestion: &MealPlanSuggestion) {
+    crate::utils::clear_box(results);
+
+    if suggestion.meals.is_empty() && suggestion.unfilled.is_empty() {
+        let label = gtk::Label::new(Some("Every chosen meal of this week is already planned."));
+        label.add_css_class("dim-label");
+        results.append(&label);
+        return;
+    }
+
+    let mut dates: Vec<NaiveDate> = suggestion
+        .meals
+        .iter()
+        .map(|m| m.entry.date)
+        .chain(suggestion.unfilled.iter().map(|u| u.date))
+        .collect();
+    dates.sort();
+    dates.dedup();
+
+    for date in dates {
+        let day_label = gtk::Label::new(Some(&date.format("%A %-d %B").to_string()));
+        day_label.add_css_class("heading");
+        day_label.set_halign(gtk::Align::Start);
+        results.append(&day_label);
+
+        for meal in Meal::ALL {
+            if let Some(suggested) = suggestion
+                .meals
+                .iter()
+                .find(|m| m.entry.date == date && m.entry.meal == meal)
+            {
+                let title = gtk::Label::new(Some(&format!(
+                    "{} · {}",
+                    meal.label(),
+                    suggested.entry.recipe
+                )));
+                title.set_halign(gtk::Align::Start);
+                title.set_margin_start(DEFAULT_MARGIN);
+                results.append(&title);
+
+                let reasons = gtk::Label::new(Some(&suggested.reasons.join("\n")));
+                reasons.add_css_class("dim-label");
+                reasons.add_css_class("caption");
+                reasons.set_halign(gtk::Align::Start);
+                reasons.set_wrap(true);
+                reasons.set_margin_start(DEFAULT_MARGIN * 2);
+                results.append(&reasons);
+            }
+            if let Some(unfilled) = suggestion
+                .unfilled
+                .iter()
+                .find(|u| u.date == date && u.meal == meal)
+            {
+                let label =
+                    gtk::Label::new(Some(&format!("{} · {}", meal.label(), unfilled.reason)));
+                label.add_css_class("warning");
+                label.set_halign(gtk::Align::Start);
+                label.set_margin_start(DEFAULT_MARGIN);
+                results.append(&label);
+            }
+        }
+    }
+}
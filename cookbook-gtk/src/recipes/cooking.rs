@@ -0,0 +1,403 @@
+/// Full-screen cooking mode: one step at a time in large type.
+///
+/// Space, Enter, → and Page Down go to the next step; Backspace, ← and Page Up go back; Home
+/// and End jump to the start and the last step, and Escape leaves. The first page lists every
+/// ingredient; each step after it lists the ingredients it mentions, scaled to the servings
+/// chosen in the header, and has a button per timer. Running timers stay in a bar at the
+/// bottom across steps and send a desktop notification when they finish.
+use crate::ui_constants::*;
+use cookbook_engine::markdown::MarkupFormat;
+use cookbook_engine::steps::{Step, Timer};
+use cookbook_engine::{DataManager, Recipe, RecipeIngredient, TemperatureUnit, UnitSystem};
+use libadwaita as adw;
+use relm4::gtk;
+use relm4::gtk::{gdk, gio, glib, pango, prelude::*};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// How much larger than normal text the step is shown
+const STEP_TEXT_SCALE: f64 = 1.8;
+
+/// The cooking window and what it shows. Cloning gives another handle to the same view.
+#[derive(Clone)]
+struct CookingView {
+    window: adw::Window,
+    recipe: Rc<Recipe>,
+    steps: Rc<Vec<Step>>,
+    dm: Rc<RefCell<DataManager>>,
+    language: String,
+    temperature_unit: TemperatureUnit,
+    unit_system: UnitSystem,
+    /// Page shown: 0 is the ingredient overview, then one page per step
+    page: Rc<Cell<usize>>,
+    /// Cleared when the window closes, which stops running timers
+    open: Rc<Cell<bool>>,
+    servings: Option<gtk::SpinButton>,
+    progress: gtk::Label,
+    text: gtk::Label,
+    ingredients: gtk::Box,
+    timer_buttons: gtk::Box,
+    running_timers: gtk::Box,
+    prev_btn: gtk::Button,
+    next_btn: gtk::Button,
+}
+
+/// Opens cooking mode for `recipe` over `parent`
+pub fn show_cooking_mode(
+    parent: &impl IsA<gtk::Window>,
+    dm: Rc<RefCell<DataManager>>,
+    recipe: &Recipe,
+    language: &str,
+    temperature_unit: TemperatureUnit,
+    unit_system: UnitSystem,
+) {
+    use adw::prelude::*;
+
+    let window = adw::Window::builder()
+        .transient_for(parent)
+        .title(format!("Cooking {}", recipe.title))
+        .default_width(900)
+        .default_height(700)
+        .build();
+    // Needed for notifications and to keep the screen on
+    let app = parent.application();
+    window.set_application(app.as_ref());
+
+    let toolbar_view = adw::ToolbarView::new();
+    let header = adw::HeaderBar::new();
+    header.set_title_widget(Some(&adw::WindowTitle::new(&recipe.title, "Cooking mode")));
+
+    // Scaling only makes sense if the recipe says how many it serves
+    let servings = recipe.servings.filter(|s| *s > 0).map(|base| {
+        let spin = gtk::SpinButton::with_range(1.0, 99.0, 1.0);
+        spin.set_value(base as f64);
+        spin.set_tooltip_text(Some("Scale the ingredients to this many servings"));
+        let servings_box = gtk::Box::new(gtk::Orientation::Horizontal, ROW_SPACING);
+        servings_box.append(&gtk::Label::new(Some("Servings")));
+        servings_box.append(&spin);
+        header.pack_end(&servings_box);
+        spin
+    });
+    toolbar_view.add_top_bar(&header);
+
+    // ── Step page ────────────────────────────────────────────────────────────
+    let content = gtk::Box::new(gtk::Orientation::Vertical, SECTION_SPACING);
+    content.set_margin_top(32);
+    content.set_margin_bottom(32);
+    content.set_margin_start(48);
+    content.set_margin_end(48);
+
+    let progress = gtk::Label::new(None);
+    progress.add_css_class("dim-label");
+    progress.add_css_class("title-4");
+    progress.set_halign(gtk::Align::Start);
+    content.append(&progress);
+
+    let text = gtk::Label::new(None);
+    text.set_wrap(true);
+    text.set_wrap_mode(pango::WrapMode::WordChar);
+    text.set_xalign(0.0);
+    text.set_halign(gtk::Align::Start);
+    let attrs = pango::AttrList::new();
+    attrs.insert(pango::AttrFloat::new_scale(STEP_TEXT_SCALE));
+    text.set_attributes(Some(&attrs));
+    // Links would lead out of cooking mode
+    text.connect_activate_link(|_, _| glib::Propagation::Stop);
+    content.append(&text);
+
+    let ingredients = gtk::Box::new(gtk::Orientation::Vertical, ROW_SPACING);
+    content.append(&ingredients);
+
+    let timer_buttons = gtk::Box::new(gtk::Orientation::Horizontal, ROW_SPACING);
+    timer_buttons.set_halign(gtk::Align::Start);
+    content.append(&timer_buttons);
+
+    let scroll = gtk::ScrolledWindow::new();
+    scroll.set_vexpand(true);
+    scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+    scroll.set_child(Some(&content));
+    toolbar_view.set_content(Some(&scroll));
+
+    // ── Bottom bar: running timers and navigation ────────────────────────────
+    let bottom = gtk::Box::new(gtk::Orientation::Horizontal, SECTION_SPACING);
+    bottom.set_margin_top(ROW_SPACING);
+    bottom.set_margin_bottom(DEFAULT_MARGIN);
+    bottom.set_margin_start(DEFAULT_MARGIN);
+    bottom.set_margin_end(DEFAULT_MARGIN);
+
+    let running_timers = gtk::Box::new(gtk::Orientation::Horizontal, ROW_SPACING);
+    running_timers.set_hexpand(true);
+    bottom.append(&running_timers);
+
+    let prev_btn = gtk::Button::with_label("Back");
+    prev_btn.add_css_class("pill");
+    let next_btn = gtk::Button::with_label("Next");
+    next_btn.add_css_class("pill");
+    next_btn.add_css_class("suggested-action");
+    bottom.append(&prev_btn);
+    bottom.append(&next_btn);
+    toolbar_view.add_bottom_bar(&bottom);
+
+    window.set_content(Some(&toolbar_view));
+
+    let view = CookingView {
+        window: window.clone(),
+        recipe: Rc::new(recipe.clone()),
+        steps: Rc::new(recipe.steps()),
+        dm,
+        language: language.to_string(),
+        temperature_unit,
+        unit_system,
+        page: Rc::new(Cell::new(0)),
+        open: Rc::new(Cell::new(true)),
+        servings,
+        progress,
+        text,
+        ingredients,
+        timer_buttons,
+        running_timers,
+        prev_btn: prev_btn.clone(),
+        next_btn: next_btn.clone(),
+    };
+    view.show_page(0);
+
+    // ── Signals ──────────────────────────────────────────────────────────────
+    let v = view.clone();
+    prev_btn.connect_clicked(move |_| v.go(-1));
+    let v = view.clone();
+    next_btn.connect_clicked(move |_| {
+        if v.page.get() == v.last_page() {
+            v.window.close();
+        } else {
+            v.go(1);
+        }
+    });
+
+    if let Some(spin) = &view.servings {
+        let v = view.clone();
+        spin.connect_value_changed(move |_| v.show_page(v.page.get()));
+    }
+
+    // Captured before the focused widget sees them, so space turns the page even when a
+    // button has focus
+    let keys = gtk::EventControllerKey::new();
+    keys.set_propagation_phase(gtk::PropagationPhase::Capture);
+    let v = view.clone();
+    keys.connect_key_pressed(move |_, key, _, _| {
+        // Leave typing in the servings field alone
+        let typing = GtkWindowExt::focus(&v.window).is_some_and(|w| w.is::<gtk::Text>());
+        match key {
+            gdk::Key::Escape => v.window.close(),
+            _ if typing => return glib::Propagation::Proceed,
+            gdk::Key::space
+            | gdk::Key::Return
+            | gdk::Key::KP_Enter
+            | gdk::Key::Right
+            | gdk::Key::Page_Down => v.go(1),
+            gdk::Key::BackSpace | gdk::Key::Left | gdk::Key::Page_Up => v.go(-1),
+            gdk::Key::Home => v.show_page(0),
+            gdk::Key::End => v.show_page(v.last_page()),
+            _ => return glib::Propagation::Proceed,
+        }
+        glib::Propagation::Stop
+    });
+    window.add_controller(keys);
+
+    // Keep the screen on while cooking
+    let inhibit_cookie = app.as_ref().map(|app| {
+        app.inhibit(
+            Some(&window),
+            gtk::ApplicationInhibitFlags::IDLE,
+            Some("Cooking mode is open"),
+        )
+    });
+    let v = view.clone();
+    window.connect_close_request(move |_| {
+        v.open.set(false);
+        if let (Some(app), Some(cookie)) = (app.as_ref(), inhibit_cookie) {
+            app.uninhibit(cookie);
+        }
+        glib::Propagation::Proceed
+    });
+
+    window.fullscreen();
+    window.present();
+}
+
+impl CookingView {
+    fn last_page(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Moves `offset` pages, staying within the recipe
+    fn go(&self, offset: isize) {
+        if let Some(page) = self
+            .page
+            .get()
+            .checked_add_signed(offset)
+            .filter(|p| *p <= self.last_page())
+        {
+            self.show_page(page);
+        }
+    }
+
+    /// Ingredient quantities are multiplied by this for the chosen servings
+    fn scale_factor(&self) -> f64 {
+        match (&self.servings, self.recipe.servings) {
+            (Some(spin), Some(base)) if base > 0 => spin.value() / base as f64,
+            _ => 1.0,
+        }
+    }
+
+    fn show_page(&self, page: usize) {
+        self.page.set(page);
+        crate::utils::clear_box(&self.ingredients);
+        crate::utils::clear_box(&self.timer_buttons);
+
+        let dm = self.dm.borrow();
+        if page == 0 {
+            self.progress.set_text("Ingredients");
+            let text = if self.steps.is_empty() {
+                "This recipe has no instructions yet."
+            } else {
+                "Get everything ready."
+            };
+            self.text.set_text(text);
+            for (group, ingredients) in self.recipe.ingredient_groups() {
+                if let Some(group) = group {
+                    let label = gtk::Label::new(Some(group));
+                    label.add_css_class("heading");
+                    label.set_halign(gtk::Align::Start);
+                    label.set_margin_top(ROW_SPACING);
+                    self.ingredients.append(&label);
+                }
+                self.append_ingredients(&dm, &ingredients);
+            }
+        } else {
+            let step = &self.steps[page - 1];
+            self.progress
+                .set_text(&format!("Step {} of {}", step.number, self.steps.len()));
+            let markup =
+                dm.render_markdown(&step.text_in(self.temperature_unit), MarkupFormat::Pango);
+            self.text.set_markup(markup.trim());
+
+            let mentioned = dm.step_ingredients(&self.recipe, step, &self.language);
+            if !mentioned.is_empty() {
+                let label = gtk::Label::new(Some("You need"));
+                label.add_css_class("heading");
+                label.set_halign(gtk::Align::Start);
+                self.ingredients.append(&label);
+                self.append_ingredients(&dm, &mentioned);
+            }
+
+            for timer in &step.timers {
+                let btn = gtk::Button::with_label(&format!("⏱ Start {}", timer));
+                btn.add_css_class("pill");
+                let v = self.clone();
+                let timer = timer.clone();
+                let step_number = step.number;
+                btn.connect_clicked(move |_| v.start_timer(step_number, &timer));
+                self.timer_buttons.append(&btn);
+            }
+        }
+
+        self.prev_btn.set_sensitive(page > 0);
+        self.next_btn.set_label(if page == self.last_page() {
+            "Finish"
+        } else {
+            "Next"
+        });
+    }
+
+    fn append_ingredients(&self, dm: &DataManager, ingredients: &[&RecipeIngredient]) {
+        let factor = self.scale_factor();
+        for ingredient in ingredients {
+            let line = dm.format_recipe_ingredient_in(
+                &ingredient.scaled(factor),
+                &self.language,
+                self.unit_system,
+            );
+            let label = gtk::Label::new(Some(&format!("• {}", line)));
+            label.add_css_class("title-4");
+            label.set_halign(gtk::Align::Start);
+            label.set_wrap(true);
+            self.ingredients.append(&label);
+        }
+    }
+
+    /// Starts a countdown in the bottom bar. Ranges count down the shorter end
+    fn start_timer(&self, step_number: usize, timer: &Timer) {
+        let name = timer
+            .label
+            .clone()
+            .unwrap_or_else(|| format!("Step {}", step_number));
+        let end = Instant::now() + Duration::from_secs(timer.seconds as u64);
+
+        let chip = gtk::Box::new(gtk::Orientation::Horizontal, ROW_SPACING);
+        chip.add_css_class("card");
+        let label = gtk::Label::new(Some(&format!(
+            "{} · {}",
+            name,
+            countdown(timer.seconds as u64)
+        )));
+        label.set_margin_start(DEFAULT_MARGIN);
+        label.add_css_class("title-4");
+        label.add_css_class("numeric");
+        let cancel_btn = gtk::Button::from_icon_name("window-close-symbolic");
+        cancel_btn.add_css_class("flat");
+        cancel_btn.set_tooltip_text(Some("Cancel timer"));
+        chip.append(&label);
+        chip.append(&cancel_btn);
+        self.running_timers.append(&chip);
+
+        let running = Rc::new(Cell::new(true));
+        let r = running.clone();
+        let bar = self.running_timers.clone();
+        let c = chip.clone();
+        cancel_btn.connect_clicked(move |_| {
+            r.set(false);
+            bar.remove(&c);
+        });
+
+        let view = self.clone();
+        glib::timeout_add_seconds_local(1, move || {
+            if !running.get() || !view.open.get() {
+                return glib::ControlFlow::Break;
+            }
+            let left = end.saturating_duration_since(Instant::now());
+            if !left.is_zero() {
+                // Round up, so the timer reads 0:00 only once it is done
+                let seconds = left.as_secs() + u64::from(left.subsec_nanos() > 0);
+                label.set_text(&format!("{} · {}", name, countdown(seconds)));
+                return glib::ControlFlow::Continue;
+            }
+            label.set_text(&format!("{} · done", name));
+            label.add_css_class("success");
+            cancel_btn.set_tooltip_text(Some("Dismiss"));
+            view.window.error_bell();
+            view.notify(&name);
+            glib::ControlFlow::Break
+        });
+    }
+
+    fn notify(&self, timer_name: &str) {
+        let Some(app) = self.window.application() else {
+            return;
+        };
+        let notification = gio::Notification::new(&format!("{} is done", timer_name));
+        notification.set_body(Some(&self.recipe.title));
+        notification.set_priority(gio::NotificationPriority::Urgent);
+        app.send_notification(None, &notification);
+    }
+}
+
+/// "4:05" or "1:02:03"
+fn countdown(seconds: u64) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
@@ -1,13 +1,19 @@
 /// Recipes tab: recipe list with availability indicators and detail view.
 use crate::app::{App, AppMsg};
 use crate::ui_constants::*;
-use cookbook_engine::DataManager;
+use chrono::Local;
+use cookbook_engine::links::LinkTarget;
+use cookbook_engine::shopping::Stock;
+use cookbook_engine::{steps, DataManager, TemperatureUnit, UnitSystem};
 use libadwaita as adw;
 use relm4::gtk;
 use relm4::{gtk::prelude::*, ComponentSender, RelmWidgetExt};
 use std::cell::RefCell;
 use std::rc::Rc;
 
+pub mod cooking;
+pub mod timeline;
+
 /// Build the full Recipes tab widget.
 ///
 /// Returns `(tab_widget, recipe_list_box, recipe_detail_box)`.
@@ -32,6 +38,31 @@ pub fn build_recipes_tab(
     search.set_margin_end(DEFAULT_MARGIN);
     left.append(&search);
 
+    // Cost filter: 0 shows every recipe
+    let cost_box = gtk::Box::new(gtk::Orientation::Horizontal, ROW_SPACING);
+    cost_box.set_margin_start(DEFAULT_MARGIN);
+    cost_box.set_margin_end(DEFAULT_MARGIN);
+    cost_box.set_margin_bottom(DEFAULT_MARGIN);
+    let cost_label = gtk::Label::new(Some("Max cost per serving"));
+    cost_label.add_css_class("caption");
+    cost_label.set_hexpand(true);
+    cost_label.set_halign(gtk::Align::Start);
+    cost_box.append(&cost_label);
+    let cost_spin = gtk::SpinButton::with_range(0.0, 1000.0, 0.5);
+    cost_spin.set_digits(2);
+    cost_spin.set_tooltip_text(Some(
+        "Only show recipes whose priced cost per serving is at most this. 0 shows all recipes",
+    ));
+    {
+        let sender_cost = sender.clone();
+        cost_spin.connect_value_changed(move |spin| {
+            let max = Some(spin.value()).filter(|v| *v > 0.0);
+            sender_cost.input(AppMsg::SetRecipeCostLimit(max));
+        });
+    }
+    cost_box.append(&cost_spin);
+    left.append(&cost_box);
+
     left.append(&gtk::Separator::new(gtk::Orientation::Horizontal));
 
     let list_scroll = gtk::ScrolledWindow::new();
@@ -44,15 +75,28 @@ pub fn build_recipes_tab(
     list_scroll.set_child(Some(&recipe_list));
     left.append(&list_scroll);
 
-    // Add recipe button
+    // Add recipe and schedule buttons
+    let button_box = gtk::Box::new(gtk::Orientation::Horizontal, ROW_SPACING);
+    button_box.set_homogeneous(true);
+    button_box.set_margin_all(DEFAULT_MARGIN);
     let add_btn = gtk::Button::with_label("Add Recipe");
     add_btn.add_css_class("flat");
-    add_btn.set_margin_all(DEFAULT_MARGIN);
     {
         let sender_add = sender.clone();
         add_btn.connect_clicked(move |_| sender_add.input(AppMsg::AddRecipe));
     }
-    left.append(&add_btn);
+    button_box.append(&add_btn);
+    let schedule_btn = gtk::Button::with_label("Schedule…");
+    schedule_btn.add_css_class("flat");
+    schedule_btn.set_tooltip_text(Some(
+        "Work out when to start cooking to serve dishes together",
+    ));
+    {
+        let sender_schedule = sender.clone();
+        schedule_btn.connect_clicked(move |_| sender_schedule.input(AppMsg::ScheduleRecipes));
+    }
+    button_box.append(&schedule_btn);
+    left.append(&button_box);
 
     // ── Right: recipe detail ──────────────────────────────────────────────────
     let detail_scroll = gtk::ScrolledWindow::new();
@@ -69,7 +113,7 @@ pub fn build_recipes_tab(
     paned.set_end_child(Some(&detail_scroll));
 
     // Populate initial list
-    populate_recipe_list(&recipe_list, dm, "", &sender);
+    populate_recipe_list(&recipe_list, dm, "", None, &sender);
 
     // Search handler
     {
@@ -95,11 +139,12 @@ pub fn build_recipes_tab(
     (paned.upcast(), recipe_list, recipe_detail)
 }
 
-/// Rebuild the recipe list with an optional search query.
+/// Rebuild the recipe list with an optional search query and maximum cost per serving.
 pub fn populate_recipe_list(
     list: &gtk::ListBox,
     dm: &Option<Rc<RefCell<DataManager>>>,
     search: &str,
+    max_cost: Option<f64>,
     _sender: &ComponentSender<App>,
 ) {
     crate::utils::clear_list_box(list);
@@ -110,13 +155,21 @@ pub fn populate_recipe_list(
     };
 
     let dm = dm.borrow();
-    let recipes = if search.is_empty() {
+    let mut recipes = if search.is_empty() {
         let mut all: Vec<_> = dm.get_all_recipes().iter().collect();
         all.sort_by(|a, b| a.title.cmp(&b.title));
         all
     } else {
         dm.search_recipes(search)
     };
+    if let Some(max) = max_cost {
+        let within: Vec<&str> = dm
+            .recipes_within_cost(max, Local::now().date_naive())
+            .iter()
+            .map(|r| r.title.as_str())
+            .collect();
+        recipes.retain(|r| within.contains(&r.title.as_str()));
+    }
 
     if recipes.is_empty() {
         list.append(&empty_state_row("No recipes found"));
@@ -169,6 +222,8 @@ pub fn update_recipe_detail(
     detail: &gtk::Box,
     dm: &Option<Rc<RefCell<DataManager>>>,
     title: &str,
+    temperature_unit: TemperatureUnit,
+    unit_system: UnitSystem,
     sender: &ComponentSender<App>,
 ) {
     crate::utils::clear_box(detail);
@@ -199,6 +254,17 @@ pub fn update_recipe_detail(
     let btn_box = gtk::Box::new(gtk::Orientation::Horizontal, ROW_SPACING);
     btn_box.set_valign(gtk::Align::Start);
 
+    let cook_btn = gtk::Button::with_label("Cook");
+    cook_btn.add_css_class("flat");
+    cook_btn.set_tooltip_text(Some("Full-screen cooking mode, one step at a time"));
+    {
+        let sender_cook = sender.clone();
+        let title_clone = title.to_string();
+        cook_btn.connect_clicked(move |_| {
+            sender_cook.input(AppMsg::CookRecipe(title_clone.clone()));
+        });
+    }
+
     let edit_btn = gtk::Button::with_label("Edit");
     edit_btn.add_css_class("flat");
     {
@@ -221,6 +287,7 @@ pub fn update_recipe_detail(
         });
     }
 
+    btn_box.append(&cook_btn);
     btn_box.append(&edit_btn);
     btn_box.append(&delete_btn);
     header_box.append(&btn_box);
@@ -280,33 +347,93 @@ pub fn update_recipe_detail(
         detail.append(&ready_label);
     }
 
-    for ing in &recipe.ingredients {
-        let in_pantry = dm.is_in_pantry(&ing.ingredient);
-        let row = gtk::Box::new(gtk::Orientation::Horizontal, ROW_SPACING);
-        row.set_margin_start(DEFAULT_MARGIN);
+    // Amounts compared with the pantry, converting units where the ingredient allows
+    let stock = dm.check_recipe_stock(title, None).unwrap_or_default();
 
-        let dot = gtk::Label::new(Some(if in_pantry { "●" } else { "○" }));
-        if in_pantry {
-            dot.add_css_class("success");
-        } else {
-            dot.add_css_class("error");
+    for (group, ingredients) in recipe.ingredient_groups() {
+        if let Some(group) = group {
+            let group_label = gtk::Label::new(Some(group));
+            group_label.add_css_class("caption-heading");
+            group_label.set_halign(gtk::Align::Start);
+            group_label.set_margin_top(ROW_SPACING);
+            detail.append(&group_label);
         }
-        row.append(&dot);
-
-        let display_name = dm.recipe_ingredient_display_name(ing, "en");
-        let qty_str = match (ing.quantity, &ing.quantity_type) {
-            (Some(q), Some(u)) if !u.is_empty() => format!("{} {} {}", q, u, display_name),
-            (Some(q), _) => format!("{} {}", q, display_name),
-            _ => display_name,
-        };
-        let label = gtk::Label::new(Some(&qty_str));
-        label.set_halign(gtk::Align::Start);
-        if !in_pantry {
-            label.add_css_class("dim-label");
+
+        for ing in ingredients {
+            let in_pantry = dm.is_in_pantry(&ing.ingredient);
+            let row = gtk::Box::new(gtk::Orientation::Horizontal, ROW_SPACING);
+            row.set_margin_start(DEFAULT_MARGIN);
+
+            let dot = gtk::Label::new(Some(if in_pantry { "●" } else { "○" }));
+            if in_pantry {
+                dot.add_css_class("success");
+            } else if ing.optional {
+                // Missing optional ingredients don't stop you from cooking
+                dot.add_css_class("dim-label");
+            } else {
+                dot.add_css_class("error");
+            }
+            row.append(&dot);
+
+            let qty_str = dm.format_recipe_ingredient_in(ing, "en", unit_system);
+            let label = gtk::Label::new(Some(&qty_str));
+            label.set_halign(gtk::Align::Start);
+            // The amount as the recipe gives it, when the preferred units changed it
+            let original = dm.format_recipe_ingredient(ing, "en");
+            if original != qty_str {
+                label.set_tooltip_text(Some(&original));
+            }
+            if !in_pantry {
+                label.add_css_class("dim-label");
+            }
+            row.append(&label);
+
+            let check = stock.iter().find(|c| c.ingredient == ing.ingredient);
+            let note = check.and_then(|c| match &c.stock {
+                Stock::Short(short) => {
+                    let amount = format!("{:.2} {}", short, c.quantity_type);
+                    Some((format!("{} short", amount.trim_end()), "warning"))
+                }
+                Stock::Unknown(missing) => Some((
+                    format!("can't compare with the pantry without {}", missing),
+                    "dim-label",
+                )),
+                _ => None,
+            });
+            if let Some((note, class)) = note {
+                let note_label = gtk::Label::new(Some(&note));
+                note_label.add_css_class("caption");
+                note_label.add_css_class(class);
+                row.append(&note_label);
+            }
+
+            detail.append(&row);
         }
-        row.append(&label);
+    }
 
-        detail.append(&row);
+    // ── Cost ──────────────────────────────────────────────────────────────────
+    // Only shown once some ingredient has a price, so unpriced collections stay quiet
+    if let Some(cost) = dm
+        .recipe_cost(title, Local::now().date_naive())
+        .filter(|c| c.estimate.lines.iter().any(|l| l.price.is_some()))
+    {
+        let mut text = format!("Cost ≈ {:.2}", cost.estimate.total);
+        if let Some(per_serving) = cost.per_serving() {
+            text.push_str(&format!(" ({:.2} per serving)", per_serving));
+        }
+        let cost_label = gtk::Label::new(Some(&text));
+        cost_label.set_halign(gtk::Align::Start);
+        cost_label.set_margin_top(ROW_SPACING);
+        detail.append(&cost_label);
+        let notes = crate::utils::cost_notes(&cost.estimate);
+        if !notes.is_empty() {
+            let notes_label = gtk::Label::new(Some(&notes));
+            notes_label.add_css_class("caption");
+            notes_label.add_css_class("warning");
+            notes_label.set_halign(gtk::Align::Start);
+            notes_label.set_wrap(true);
+            detail.append(&notes_label);
+        }
     }
 
     // ── Instructions ──────────────────────────────────────────────────────────
@@ -318,14 +445,17 @@ pub fn update_recipe_detail(
         instr_header.set_halign(gtk::Align::Start);
         detail.append(&instr_header);
 
-        let instr_label = gtk::Label::new(Some(&recipe.instructions));
-        instr_label.set_halign(gtk::Align::Start);
-        instr_label.set_wrap(true);
-        instr_label.set_wrap_mode(gtk::pango::WrapMode::WordChar);
-        instr_label.set_selectable(true);
-        instr_label.set_xalign(0.0);
-        detail.append(&instr_label);
+        // Timer and temperature markers read as their values, in the preferred scale
+        let instructions = steps::localize_instructions(&recipe.instructions, temperature_unit);
+        crate::utils::append_markdown(detail, &dm, &instructions, Some(sender));
     }
+
+    crate::utils::append_backlinks(
+        detail,
+        &dm,
+        &LinkTarget::Recipe(recipe.title.clone()),
+        sender,
+    );
 }
 
 fn show_delete_recipe_confirm(
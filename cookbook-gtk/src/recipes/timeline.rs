@@ -0,0 +1,350 @@
+/// Timeline dialog: pick recipes and a serving time, and see when to start each step.
+///
+/// The chart has one lane per recipe. Solid bars need the cook's hands, pale bars are waiting
+/// time (baking, simmering) that other work can overlap. Below the chart the same plan is
+/// listed step by step.
+use crate::ui_constants::*;
+use chrono::{Duration, Local, NaiveDateTime, NaiveTime, Timelike};
+use cookbook_engine::schedule::{self, Schedule};
+use cookbook_engine::DataManager;
+use libadwaita as adw;
+use relm4::gtk;
+use relm4::gtk::{cairo, prelude::*};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Width of the recipe names left of the chart
+const LANE_LABEL_WIDTH: f64 = 170.0;
+const LANE_HEIGHT: f64 = 34.0;
+/// Room for the time axis under the lanes
+const AXIS_HEIGHT: f64 = 26.0;
+/// Bar colour (the Adwaita accent blue); waiting time is drawn paler
+const BAR_COLOR: (f64, f64, f64) = (0.21, 0.52, 0.89);
+
+/// Opens the timeline dialog, with `preselected` ticked
+pub fn show_timeline_dialog(
+    parent: &impl IsA<gtk::Window>,
+    dm: Rc<RefCell<DataManager>>,
+    preselected: Option<&str>,
+) {
+    use adw::prelude::*;
+
+    let window = adw::Window::builder()
+        .transient_for(parent)
+        .modal(true)
+        .title("Plan a Meal")
+        .default_width(960)
+        .default_height(640)
+        .build();
+
+    let toolbar_view = adw::ToolbarView::new();
+    let header = adw::HeaderBar::new();
+    toolbar_view.add_top_bar(&header);
+
+    let paned = gtk::Paned::new(gtk::Orientation::Horizontal);
+    paned.set_position(LIST_PANE_WIDTH);
+
+    // ── Left: serving time and recipes ───────────────────────────────────────
+    let left = gtk::Box::new(gtk::Orientation::Vertical, ROW_SPACING);
+    left.set_margin_top(DEFAULT_MARGIN);
+    left.set_margin_bottom(DEFAULT_MARGIN);
+    left.set_margin_start(DEFAULT_MARGIN);
+    left.set_margin_end(DEFAULT_MARGIN);
+
+    let time_box = gtk::Box::new(gtk::Orientation::Horizontal, ROW_SPACING);
+    time_box.append(&gtk::Label::new(Some("Serve at")));
+    let time_entry = gtk::Entry::new();
+    time_entry.set_text("19:00");
+    time_entry.set_width_chars(6);
+    time_entry.set_tooltip_text(Some(
+        "Time as HH:MM; a time already past today means tomorrow",
+    ));
+    time_box.append(&time_entry);
+    left.append(&time_box);
+
+    let recipe_list = gtk::ListBox::new();
+    recipe_list.set_selection_mode(gtk::SelectionMode::None);
+    recipe_list.add_css_class("boxed-list");
+    let mut checks = Vec::new();
+    {
+        let dm = dm.borrow();
+        let mut recipes: Vec<_> = dm.get_all_recipes().iter().collect();
+        recipes.sort_by_key(|r| r.title.to_lowercase());
+        for recipe in recipes {
+            let check = gtk::CheckButton::with_label(&recipe.title);
+            check.set_active(preselected == Some(recipe.title.as_str()));
+            check.set_margin_top(ROW_SPACING);
+            check.set_margin_bottom(ROW_SPACING);
+            check.set_margin_start(ROW_SPACING);
+            recipe_list.append(&check);
+            checks.push((recipe.title.clone(), check));
+        }
+    }
+    let list_scroll = gtk::ScrolledWindow::new();
+    list_scroll.set_vexpand(true);
+    list_scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+    list_scroll.set_child(Some(&recipe_list));
+    left.append(&list_scroll);
+
+    // ── Right: chart and step list ───────────────────────────────────────────
+    let right = gtk::Box::new(gtk::Orientation::Vertical, SECTION_SPACING);
+    right.set_margin_top(DEFAULT_MARGIN);
+    right.set_margin_bottom(DEFAULT_MARGIN);
+    right.set_margin_start(DEFAULT_MARGIN);
+    right.set_margin_end(DEFAULT_MARGIN);
+
+    let summary = gtk::Label::new(None);
+    summary.add_css_class("title-3");
+    summary.set_halign(gtk::Align::Start);
+    summary.set_wrap(true);
+    right.append(&summary);
+
+    let notes = gtk::Label::new(None);
+    notes.add_css_class("dim-label");
+    notes.set_halign(gtk::Align::Start);
+    notes.set_wrap(true);
+    right.append(&notes);
+
+    let plan: Rc<RefCell<Option<Schedule>>> = Rc::new(RefCell::new(None));
+    let chart = gtk::DrawingArea::new();
+    chart.set_hexpand(true);
+    {
+        let plan = plan.clone();
+        chart.set_draw_func(move |area, cr, width, _| {
+            if let Some(plan) = plan.borrow().as_ref() {
+                draw_chart(area, cr, width as f64, plan);
+            }
+        });
+    }
+    right.append(&chart);
+
+    let steps_list = gtk::Box::new(gtk::Orientation::Vertical, ROW_SPACING);
+    right.append(&steps_list);
+
+    let right_scroll = gtk::ScrolledWindow::new();
+    right_scroll.set_hexpand(true);
+    right_scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+    right_scroll.set_child(Some(&right));
+
+    paned.set_start_child(Some(&left));
+    paned.set_end_child(Some(&right_scroll));
+    toolbar_view.set_content(Some(&paned));
+    window.set_content(Some(&toolbar_view));
+
+    // ── Recompute whenever the time or the selection changes ─────────────────
+    let checks = Rc::new(checks);
+    let refresh = {
+        let time_entry = time_entry.clone();
+        let checks = checks.clone();
+        move || {
+            let Some(serve_at) = parse_serving_time(&time_entry.text()) else {
+                time_entry.add_css_class("error");
+                return;
+            };
+            time_entry.remove_css_class("error");
+
+            let dm = dm.borrow();
+            let recipes: Vec<_> = checks
+                .iter()
+                .filter(|(_, check)| check.is_active())
+                .filter_map(|(title, _)| dm.get_recipe(title))
+                .collect();
+            let new_plan = schedule::schedule(&recipes, serve_at);
+            show_plan(&new_plan, &summary, &notes, &steps_list);
+            let lanes = recipes.len() - new_plan.untimed.len();
+            chart.set_content_height((lanes as f64 * LANE_HEIGHT + AXIS_HEIGHT) as i32);
+            chart.set_visible(lanes > 0);
+            *plan.borrow_mut() = Some(new_plan);
+            chart.queue_draw();
+        }
+    };
+    refresh();
+    let refresh = Rc::new(refresh);
+    {
+        let refresh = refresh.clone();
+        time_entry.connect_changed(move |_| refresh());
+    }
+    for (_, check) in checks.iter() {
+        let refresh = refresh.clone();
+        check.connect_toggled(move |_| refresh());
+    }
+
+    window.present();
+}
+
+/// "19:00" today, or tomorrow if that time has passed
+fn parse_serving_time(text: &str) -> Option<NaiveDateTime> {
+    let time = NaiveTime::parse_from_str(text.trim(), "%H:%M").ok()?;
+    let now = Local::now().naive_local();
+    let today = now.date().and_time(time);
+    Some(if today > now {
+        today
+    } else {
+        today + Duration::days(1)
+    })
+}
+
+fn show_plan(plan: &Schedule, summary: &gtk::Label, notes: &gtk::Label, steps_list: &gtk::Box) {
+    crate::utils::clear_box(steps_list);
+
+    match plan.start() {
+        Some(start) => summary.set_text(&format!(
+            "Start at {} to serve at {}",
+            start.format("%H:%M"),
+            plan.serve_at.format("%H:%M")
+        )),
+        None => summary.set_text("Tick the recipes you want to serve together."),
+    }
+
+    let mut lines = Vec::new();
+    let mut recipes: Vec<&str> = plan.entries.iter().map(|e| e.recipe.as_str()).collect();
+    recipes.sort();
+    recipes.dedup();
+    for recipe in recipes {
+        if let Some(ready) = plan.ready_at(recipe).filter(|r| *r < plan.serve_at) {
+            lines.push(format!(
+                "{} is ready at {}, {} min early, so the cook can finish the others",
+                recipe,
+                ready.format("%H:%M"),
+                (plan.serve_at - ready).num_minutes()
+            ));
+        }
+    }
+    for recipe in &plan.untimed {
+        lines.push(format!(
+            "{} has no times or steps and isn't scheduled",
+            recipe
+        ));
+    }
+    notes.set_text(&lines.join("\n"));
+    notes.set_visible(!lines.is_empty());
+
+    for entry in &plan.entries {
+        let row = gtk::Box::new(gtk::Orientation::Horizontal, SECTION_SPACING);
+        let time = gtk::Label::new(Some(&format!(
+            "{} – {}",
+            entry.start.format("%H:%M"),
+            entry.end.format("%H:%M")
+        )));
+        time.add_css_class("numeric");
+        time.set_valign(gtk::Align::Start);
+        row.append(&time);
+
+        let step = match entry.step {
+            Some(number) => format!("Step {}", number),
+            None => entry.label.clone(),
+        };
+        let kind = if entry.active { "hands-on" } else { "waiting" };
+        let what = gtk::Label::new(Some(&format!("{} · {} ({})", entry.recipe, step, kind)));
+        what.add_css_class("heading");
+        what.set_valign(gtk::Align::Start);
+        row.append(&what);
+
+        if entry.step.is_some() {
+            let text = gtk::Label::new(Some(&entry.label));
+            text.set_wrap(true);
+            text.set_xalign(0.0);
+            text.set_hexpand(true);
+            if !entry.active {
+                text.add_css_class("dim-label");
+            }
+            row.append(&text);
+        }
+        steps_list.append(&row);
+    }
+}
+
+/// Draws one lane per recipe with a bar per block, a time axis and a line at serving time
+fn draw_chart(area: &gtk::DrawingArea, cr: &cairo::Context, width: f64, plan: &Schedule) {
+    let Some(first) = plan.start() else {
+        return;
+    };
+    // Start the axis on a quarter hour
+    let axis_start = first
+        - Duration::minutes(first.minute() as i64 % 15)
+        - Duration::seconds(first.second() as i64);
+    let span = (plan.serve_at - axis_start).num_seconds().max(1) as f64;
+    let chart_width = (width - LANE_LABEL_WIDTH - DEFAULT_MARGIN as f64).max(1.0);
+    let x = |time: NaiveDateTime| {
+        LANE_LABEL_WIDTH + (time - axis_start).num_seconds() as f64 / span * chart_width
+    };
+
+    let mut lanes: Vec<&str> = Vec::new();
+    for entry in &plan.entries {
+        if !lanes.contains(&entry.recipe.as_str()) {
+            lanes.push(&entry.recipe);
+        }
+    }
+    let lanes_height = lanes.len() as f64 * LANE_HEIGHT;
+
+    #[allow(deprecated)]
+    let fg = area.style_context().color();
+    let (r, g, b) = BAR_COLOR;
+    cr.set_font_size(12.0);
+
+    for (index, recipe) in lanes.iter().enumerate() {
+        let top = index as f64 * LANE_HEIGHT;
+        cr.set_source_rgba(
+            fg.red() as f64,
+            fg.green() as f64,
+            fg.blue() as f64,
+            fg.alpha() as f64,
+        );
+        cr.move_to(0.0, top + LANE_HEIGHT / 2.0 + 4.0);
+        let _ = cr.show_text(&ellipsize(cr, recipe, LANE_LABEL_WIDTH - 8.0));
+
+        for entry in plan.entries.iter().filter(|e| e.recipe == *recipe) {
+            let (x0, x1) = (x(entry.start), x(entry.end));
+            cr.rectangle(x0, top + 6.0, (x1 - x0).max(2.0), LANE_HEIGHT - 12.0);
+            cr.set_source_rgba(r, g, b, if entry.active { 1.0 } else { 0.35 });
+            let _ = cr.fill();
+        }
+    }
+
+    // Time axis: a tick every 15, 30 or 60 minutes depending on the span
+    let tick_minutes = match span as i64 / 60 {
+        0..=120 => 15,
+        121..=240 => 30,
+        _ => 60,
+    };
+    cr.set_source_rgba(
+        fg.red() as f64,
+        fg.green() as f64,
+        fg.blue() as f64,
+        0.5 * fg.alpha() as f64,
+    );
+    let mut tick = axis_start;
+    while tick <= plan.serve_at {
+        let tx = x(tick);
+        cr.rectangle(tx, 0.0, 1.0, lanes_height + 4.0);
+        let _ = cr.fill();
+        cr.move_to(tx + 2.0, lanes_height + AXIS_HEIGHT - 8.0);
+        let _ = cr.show_text(&tick.format("%H:%M").to_string());
+        tick += Duration::minutes(tick_minutes);
+    }
+
+    // Serving time
+    cr.set_source_rgb(0.88, 0.11, 0.14);
+    cr.rectangle(x(plan.serve_at) - 1.0, 0.0, 2.0, lanes_height + 4.0);
+    let _ = cr.fill();
+}
+
+/// Shortens `text` with "…" until it fits in `max_width`
+fn ellipsize(cr: &cairo::Context, text: &str, max_width: f64) -> String {
+    let fits = |t: &str| {
+        cr.text_extents(t)
+            .map_or(true, |e| e.x_advance() <= max_width)
+    };
+    if fits(text) {
+        return text.to_string();
+    }
+    let mut chars: Vec<char> = text.chars().collect();
+    while !chars.is_empty() {
+        chars.pop();
+        let shortened = format!("{}…", chars.iter().collect::<String>());
+        if fits(&shortened) {
+            return shortened;
+        }
+    }
+    String::new()
+}
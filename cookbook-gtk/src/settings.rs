@@ -1,5 +1,6 @@
 use crate::app::AppMsg;
 use crate::config::{Theme, UserSettings};
+use cookbook_engine::{TemperatureUnit, UnitSystem};
 use libadwaita as adw;
 use relm4::gtk;
 use relm4::ComponentSender;
@@ -47,7 +48,7 @@ pub fn build_settings_page(sender: &ComponentSender<crate::app::App>) -> gtk::Wi
             let sender_inner = sender_clone.clone();
             let dir_row_inner = dir_row_clone.clone();
             dialog.connect_response(move |d, response| {
-                if gtk::ResponseType::from(response) == gtk::ResponseType::Accept {
+                if response == gtk::ResponseType::Accept {
                     if let Some(file) = d.file() {
                         if let Some(path) = file.path() {
                             let path_str = path.display().to_string();
@@ -99,6 +100,60 @@ pub fn build_settings_page(sender: &ComponentSender<crate::app::App>) -> gtk::Wi
     appearance_group.add(&theme_row);
     page.add(&appearance_group);
 
+    // ── Cooking group ────────────────────────────────────────────────────────
+    let cooking_group = adw::PreferencesGroup::new();
+    cooking_group.set_title("Cooking");
+
+    let temperature_row = adw::ComboRow::new();
+    temperature_row.set_title("Temperatures");
+    temperature_row.set_subtitle("Oven temperatures in recipes are converted to this scale");
+    let temperature_model = gtk::StringList::new(&["Celsius (°C)", "Fahrenheit (°F)"]);
+    temperature_row.set_model(Some(&temperature_model));
+    temperature_row.set_selected(match settings.temperature_unit {
+        TemperatureUnit::Celsius => 0,
+        TemperatureUnit::Fahrenheit => 1,
+    });
+
+    {
+        let sender_unit = sender.clone();
+        temperature_row.connect_selected_notify(move |row| {
+            let unit = match row.selected() {
+                1 => TemperatureUnit::Fahrenheit,
+                _ => TemperatureUnit::Celsius,
+            };
+            sender_unit.input(AppMsg::SetTemperatureUnit(unit));
+        });
+    }
+
+    cooking_group.add(&temperature_row);
+
+    let units_row = adw::ComboRow::new();
+    units_row.set_title("Units");
+    units_row.set_subtitle("Recipe and pantry amounts are converted and rounded to these units");
+    let units_model =
+        gtk::StringList::new(&["As written", "Metric (g, ml)", "Imperial (oz, cups)"]);
+    units_row.set_model(Some(&units_model));
+    units_row.set_selected(match settings.unit_system {
+        UnitSystem::Original => 0,
+        UnitSystem::Metric => 1,
+        UnitSystem::Imperial => 2,
+    });
+
+    {
+        let sender_units = sender.clone();
+        units_row.connect_selected_notify(move |row| {
+            let system = match row.selected() {
+                1 => UnitSystem::Metric,
+                2 => UnitSystem::Imperial,
+                _ => UnitSystem::Original,
+            };
+            sender_units.input(AppMsg::SetUnitSystem(system));
+        });
+    }
+
+    cooking_group.add(&units_row);
+    page.add(&cooking_group);
+
     scroll.set_child(Some(&page));
     scroll.upcast()
 }
@@ -0,0 +1,163 @@
+/// Shopping list dialog: what the planned meals need and what runs out before the next shop.
+///
+/// Pantry items make the list once the pantry journal has a week or more of quantity changes
+/// to estimate their use from.
+use crate::ui_constants::*;
+use chrono::{Duration, Local};
+use cookbook_engine::cost::CostEstimate;
+use cookbook_engine::shopping::{ShoppingItem, ShoppingList};
+use cookbook_engine::{units, DataManager, UnitSystem};
+use libadwaita as adw;
+use libadwaita::prelude::*;
+use relm4::gtk;
+use relm4::RelmWidgetExt;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Days to the next shop the dialog starts with
+const DEFAULT_DAYS_TO_NEXT_SHOP: u32 = 7;
+
+/// Opens the shopping list dialog
+pub fn show_shopping_dialog(
+    parent: &impl IsA<gtk::Window>,
+    dm: Rc<RefCell<DataManager>>,
+    unit_system: UnitSystem,
+) {
+    let window = adw::Window::builder()
+        .transient_for(parent)
+        .modal(true)
+        .title("Shopping List")
+        .default_width(560)
+        .default_height(640)
+        .build();
+
+    let toolbar_view = adw::ToolbarView::new();
+    let header = adw::HeaderBar::new();
+    let copy_btn = gtk::Button::from_icon_name("edit-copy-symbolic");
+    copy_btn.set_tooltip_text(Some("Copy the list as text"));
+    header.pack_end(&copy_btn);
+    toolbar_view.add_top_bar(&header);
+
+    let content = gtk::Box::new(gtk::Orientation::Vertical, SECTION_SPACING);
+    content.set_margin_all(DEFAULT_MARGIN);
+
+    let options = gtk::ListBox::new();
+    options.add_css_class("boxed-list");
+    options.set_selection_mode(gtk::SelectionMode::None);
+    let days_row = adw::SpinRow::with_range(1.0, 60.0, 1.0);
+    days_row.set_title("Days until the next shop");
+    days_row.set_value(DEFAULT_DAYS_TO_NEXT_SHOP as f64);
+    options.append(&days_row);
+    content.append(&options);
+
+    let summary = gtk::Label::new(None);
+    summary.add_css_class("dim-label");
+    summary.set_halign(gtk::Align::Start);
+    summary.set_wrap(true);
+    content.append(&summary);
+
+    let items = gtk::ListBox::new();
+    items.add_css_class("boxed-list");
+    items.set_selection_mode(gtk::SelectionMode::None);
+    content.append(&items);
+
+    let scroll = gtk::ScrolledWindow::new();
+    scroll.set_vexpand(true);
+    scroll.set_policy(gtk::PolicyType::Never, gtk::PolicyType::Automatic);
+    scroll.set_child(Some(&content));
+    toolbar_view.set_content(Some(&scroll));
+    window.set_content(Some(&toolbar_view));
+
+    let list: Rc<RefCell<Option<ShoppingList>>> = Rc::default();
+    let refresh = {
+        let list = list.clone();
+        let days_row = days_row.clone();
+        move || {
+            let today = Local::now().date_naive();
+            let next_shop = today + Duration::days(days_row.value() as i64);
+            let new = dm.borrow().shopping_list(today, next_shop);
+            let cost = dm.borrow().shopping_list_cost(&new);
+            show_list(&items, &summary, &new, &cost, unit_system);
+            *list.borrow_mut() = Some(new);
+        }
+    };
+    refresh();
+    days_row.connect_value_notify(move |_| refresh());
+
+    copy_btn.connect_clicked(move |btn| {
+        if let Some(list) = list.borrow().as_ref() {
+            let text: Vec<String> = list
+                .items
+                .iter()
+                .map(|item| format!("- {}", item_title(item, unit_system)))
+                .collect();
+            btn.clipboard().set_text(&text.join("\n"));
+        }
+    });
+
+    window.present();
+}
+
+fn show_list(
+    items: &gtk::ListBox,
+    summary: &gtk::Label,
+    list: &ShoppingList,
+    cost: &CostEstimate,
+    unit_system: UnitSystem,
+) {
+    crate::utils::clear_list_box(items);
+    items.set_visible(!list.items.is_empty());
+    if list.items.is_empty() {
+        summary.set_text(&format!(
+            "Nothing to buy before {}.",
+            list.next_shop.format("%A %-d %B")
+        ));
+        return;
+    }
+    summary.set_text(&format!(
+        "{} items to last until {}.",
+        list.items.len(),
+        list.next_shop.format("%A %-d %B")
+    ));
+    // Only priced lists get a total
+    if cost.lines.iter().any(|l| l.price.is_some()) {
+        let text = format!(
+            "{} Estimated cost ≈ {:.2}. {}",
+            summary.text(),
+            cost.total,
+            crate::utils::cost_notes(cost)
+        );
+        summary.set_text(text.trim_end());
+    }
+    for item in &list.items {
+        let row = adw::ActionRow::new();
+        row.set_title(&item_title(item, unit_system));
+        match &item.missing {
+            Some(missing) => row.set_subtitle(&format!(
+                "{}. Amounts not added up without {} for {}",
+                item.reason(),
+                missing,
+                item.ingredient
+            )),
+            None => row.set_subtitle(&item.reason()),
+        }
+        if item.runs_out.is_some() {
+            row.add_prefix(&gtk::Image::from_icon_name("view-refresh-symbolic"));
+        } else {
+            row.add_prefix(&gtk::Image::from_icon_name("x-office-calendar-symbolic"));
+        }
+        items.append(&row);
+    }
+}
+
+/// "flour — 500 g", or just the name when the amount isn't known
+fn item_title(item: &ShoppingItem, unit_system: UnitSystem) -> String {
+    match item.quantity {
+        Some(quantity) => {
+            let rounded = (quantity * 100.0).ceil() / 100.0;
+            let amount = units::format_amount(rounded, &item.quantity_type, unit_system);
+            format!("{} — {}", item.ingredient, amount)
+        }
+        None => item.ingredient.clone(),
+    }
+}
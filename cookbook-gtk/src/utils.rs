@@ -1,5 +1,12 @@
-use relm4::gtk;
+use crate::app::{App, AppMsg};
+use cookbook_engine::cost::CostEstimate;
+use cookbook_engine::links::{LinkSource, LinkTarget};
+use cookbook_engine::markdown::{self, MarkupFormat, Rendered};
+use cookbook_engine::DataManager;
+use gtk::glib;
 use gtk::prelude::*;
+use relm4::gtk;
+use relm4::ComponentSender;
 use std::fs;
 use std::path::Path;
 
@@ -21,7 +28,7 @@ pub fn clear_list_box(container: &gtk::ListBox) {
 /// and a minimal pantry.yaml if they are missing.
 pub fn validate_and_create_data_dir<P: AsRef<Path>>(data_dir: P) {
     let data_dir = data_dir.as_ref();
-    for subdir in &["ingredients", "recipes", "recipes/img"] {
+    for subdir in &["ingredients", "recipes", "recipes/img", "kb"] {
         let path = data_dir.join(subdir);
         if !path.exists() {
             let _ = fs::create_dir_all(&path);
@@ -32,3 +39,145 @@ pub fn validate_and_create_data_dir<P: AsRef<Path>>(data_dir: P) {
         let _ = fs::write(&pantry, "version: 1\nitems: []\n");
     }
 }
+
+/// Render Markdown (recipe instructions, KB articles) into `container`.
+///
+/// Text goes into selectable labels and standalone images into pictures. With a sender,
+/// `[[wiki links]]` navigate within the app; without one (e.g. an editor preview) they are
+/// shown but inert.
+pub fn append_markdown(
+    container: &gtk::Box,
+    dm: &DataManager,
+    text: &str,
+    sender: Option<&ComponentSender<App>>,
+) {
+    let resolve = |target: &str| dm.resolve_wiki_link(target);
+    for segment in markdown::render(text, MarkupFormat::Pango, &resolve) {
+        match segment {
+            Rendered::Markup(markup) => {
+                let label = gtk::Label::new(None);
+                label.set_markup(&markup);
+                label.set_halign(gtk::Align::Start);
+                label.set_valign(gtk::Align::Start);
+                label.set_wrap(true);
+                label.set_wrap_mode(gtk::pango::WrapMode::WordChar);
+                label.set_selectable(true);
+                label.set_xalign(0.0);
+                match sender {
+                    Some(sender) => connect_wiki_links(&label, sender),
+                    None => {
+                        label.connect_activate_link(|_, _| glib::Propagation::Stop);
+                    }
+                }
+                container.append(&label);
+            }
+            Rendered::Image { src, alt } => match image_widget(dm, &src) {
+                Some(picture) => {
+                    picture.set_alternative_text(Some(&alt));
+                    container.append(&picture);
+                }
+                None => {
+                    let label = gtk::Label::new(Some(&format!("🖼 {}", alt)));
+                    label.add_css_class("dim-label");
+                    label.set_halign(gtk::Align::Start);
+                    container.append(&label);
+                }
+            },
+        }
+    }
+}
+
+/// Load an image referenced from Markdown or front matter.
+///
+/// Looks in the knowledge base (data dir or bundled) first, then `recipes/img`.
+pub fn image_widget(dm: &DataManager, src: &str) -> Option<gtk::Picture> {
+    let data = dm.get_kb_image_data(src).or_else(|| {
+        let is_plain = !src.contains(['/', '\\']) && src != "..";
+        is_plain
+            .then(|| dm.get_data_dir().join("recipes").join("img").join(src))
+            .and_then(|path| fs::read(path).ok())
+    })?;
+
+    let loader = gtk::gdk_pixbuf::PixbufLoader::new();
+    loader.write(&data).ok()?;
+    loader.close().ok()?;
+    let texture = gtk::gdk::Texture::for_pixbuf(&loader.pixbuf()?);
+
+    let picture = gtk::Picture::for_paintable(&texture);
+    picture.set_can_shrink(true);
+    picture.set_keep_aspect_ratio(true);
+    picture.set_halign(gtk::Align::Start);
+    picture.set_size_request(-1, 240);
+    Some(picture)
+}
+
+/// Route clicks on `cookbook://` links in a label to the app, so they switch tabs.
+pub fn connect_wiki_links(label: &gtk::Label, sender: &ComponentSender<App>) {
+    let sender = sender.clone();
+    label.connect_activate_link(move |_, uri| match LinkTarget::from_uri(uri) {
+        Some(target) => {
+            sender.input(AppMsg::OpenLink(target));
+            glib::Propagation::Stop
+        }
+        None => glib::Propagation::Proceed,
+    });
+}
+
+/// Append a "Mentioned in" section listing the recipes and articles that link to `target`.
+pub fn append_backlinks(
+    container: &gtk::Box,
+    dm: &DataManager,
+    target: &LinkTarget,
+    sender: &ComponentSender<App>,
+) {
+    let sources = dm.get_backlinks(target);
+    if sources.is_empty() {
+        return;
+    }
+
+    container.append(&gtk::Separator::new(gtk::Orientation::Horizontal));
+    let header = gtk::Label::new(Some("Mentioned in"));
+    header.add_css_class("heading");
+    header.set_halign(gtk::Align::Start);
+    container.append(&header);
+
+    for source in sources {
+        let (text, source_target) = match source {
+            LinkSource::Recipe(title) => (format!("🍽 {}", title), LinkTarget::Recipe(title)),
+            LinkSource::Kb(slug) => {
+                let title = dm
+                    .get_kb_entry(&slug)
+                    .map_or_else(|| slug.clone(), |e| e.title.clone());
+                (format!("📖 {}", title), LinkTarget::Kb(slug))
+            }
+            LinkSource::Ingredient(name) => (name.clone(), LinkTarget::Ingredient(name)),
+        };
+        let label = gtk::Label::new(None);
+        label.set_markup(&format!(
+            "<a href=\"{}\">{}</a>",
+            glib::markup_escape_text(&source_target.to_uri()),
+            glib::markup_escape_text(&text)
+        ));
+        label.set_halign(gtk::Align::Start);
+        label.add_css_class("caption");
+        connect_wiki_links(&label, sender);
+        container.append(&label);
+    }
+}
+
+/// What a cost estimate leaves out or may get wrong, e.g. "Not counted: garlic.", or empty
+pub fn cost_notes(cost: &CostEstimate) -> String {
+    let mut notes = Vec::new();
+    let uncosted = cost.uncosted();
+    if !uncosted.is_empty() {
+        notes.push(format!("Not counted: {}.", uncosted.join(", ")));
+    }
+    let stale = cost.stale();
+    if !stale.is_empty() {
+        notes.push(format!(
+            "Prices may be out of date for {}.",
+            stale.join(", ")
+        ));
+    }
+    notes.join(" ")
+}
@@ -13,7 +13,7 @@ fn fixture_data_dir() -> PathBuf {
 fn data_manager_loads_recipes() {
     let dm = DataManager::new(fixture_data_dir()).unwrap();
     assert!(
-        dm.get_all_recipes().len() >= 1,
+        !dm.get_all_recipes().is_empty(),
         "expected at least one recipe in example/data"
     );
 }
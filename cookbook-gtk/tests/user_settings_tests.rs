@@ -9,9 +9,11 @@ fn default_settings_are_system_theme() {
 
 #[test]
 fn settings_round_trip_toml() {
-    let mut s = UserSettings::default();
-    s.theme = Theme::Dark;
-    s.data_dir = Some("/tmp/test_data".to_string());
+    let s = UserSettings {
+        theme: Theme::Dark,
+        data_dir: Some("/tmp/test_data".to_string()),
+        ..Default::default()
+    };
 
     let encoded = toml::to_string(&s).expect("serialize to toml");
     let decoded: UserSettings = toml::from_str(&encoded).expect("deserialize from toml");
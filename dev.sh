@@ -36,6 +36,8 @@ show_help() {
     echo "  test                - Run all tests"
     echo "  engine-test         - Run cookbook-engine tests (fast, no display needed)"
     echo "  gtk-test-headless   - Run GTK tests headlessly (requires xvfb-run)"
+    echo "  migrate <dir> [--apply] - Upgrade data files to the current schema (dry run unless --apply)"
+    echo "  barcodes <dir> <products.csv> [--apply] - Suggest barcode mappings from an Open Food Facts export (dry run unless --apply)"
     echo "  help                - Show this help message"
     echo ""
 }
@@ -170,6 +172,18 @@ run_clean() {
     echo -e "${GREEN}✅ Clean complete${NC}"
 }
 
+run_migrate() {
+    echo -e "${CYAN}🔄 Migrating data files...${NC}"
+    cd "$PROJECT_ROOT"
+    cargo run -q -p cookbook-engine --bin cookbook-migrate -- "$@"
+}
+
+run_barcodes() {
+    echo -e "${CYAN}🏷  Suggesting barcode mappings...${NC}"
+    cd "$PROJECT_ROOT"
+    cargo run -q -p cookbook-engine --bin cookbook-barcodes -- "$@"
+}
+
 run_test() {
     echo -e "${CYAN}🧪 Running tests...${NC}"
     cd "$PROJECT_ROOT"
@@ -209,6 +223,14 @@ case "${1:-help}" in
     "test")
         run_test
         ;;
+    "migrate")
+        shift
+        run_migrate "$@"
+        ;;
+    "barcodes")
+        shift
+        run_barcodes "$@"
+        ;;
     "help"|"--help"|"-h")
         show_help
         ;;
@@ -19,6 +19,17 @@ data class Ingredient(
     val lastUpdated: String?
 )
 
+/**
+ * Result of adding a scanned product to the pantry
+ */
+data class BarcodeResult(
+    val found: Boolean = false,
+    val ingredient: String?,
+    val quantity: Double?,
+    val quantityType: String?,
+    val error: String?
+)
+
 /**
  * Kotlin wrapper for the Rust cookbook-engine
  */
@@ -127,6 +138,32 @@ class CookbookEngine(dataDir: String) {
         return deleteIngredient(nativePtr, ingredientName)
     }
     
+    /**
+     * Add one package of a scanned product to the pantry
+     * @return the ingredient's pantry entry after adding, or null if no ingredient has the
+     * barcode yet (map it with [mapBarcode])
+     * @throws IllegalStateException if the package couldn't be added, e.g. its unit doesn't
+     * convert to the pantry's
+     */
+    fun addByBarcode(barcode: String): BarcodeResult? {
+        val json = addByBarcode(nativePtr, barcode)
+        val result = gson.fromJson(json, BarcodeResult::class.java)
+        result.error?.let { throw IllegalStateException(it) }
+        return if (result.found) result else null
+    }
+    
+    /**
+     * Map a barcode to an ingredient, with the size of one package if known
+     */
+    fun mapBarcode(
+        ingredientName: String,
+        barcode: String,
+        quantity: Double? = null,
+        quantityType: String? = null
+    ): Boolean {
+        return mapBarcode(nativePtr, ingredientName, barcode, quantity ?: 0.0, quantityType ?: "")
+    }
+    
     /**
      * Get all available categories
      */
@@ -160,4 +197,6 @@ class CookbookEngine(dataDir: String) {
     private external fun updateIngredient(nativePtr: Long, originalName: String, newName: String, category: String, kbSlug: String, tagsJson: String): Boolean
     private external fun deleteIngredient(nativePtr: Long, ingredientName: String): Boolean
     private external fun getAllCategories(nativePtr: Long): String
+    private external fun addByBarcode(nativePtr: Long, barcode: String): String
+    private external fun mapBarcode(nativePtr: Long, ingredientName: String, barcode: String, quantity: Double, quantityType: String): Boolean
 }
@@ -1,7 +1,7 @@
 use jni::objects::{JClass, JString};
-use jni::sys::{jstring, jboolean, jlong};
+use jni::sys::{jstring, jboolean, jdouble, jlong};
 use jni::JNIEnv;
-use cookbook_engine::{DataManager, Ingredient};
+use cookbook_engine::{slug, DataManager, DeleteStrategy, Ingredient, Package};
 use std::path::Path;
 
 #[cfg(target_os = "android")]
@@ -343,11 +343,15 @@ pub extern "system" fn Java_com_example_pantryman_CookbookEngine_createIngredien
     
     let ingredient = Ingredient {
         name: name_str.clone(),
-        slug: name_str.replace(" ", "_").to_lowercase(),
+        slug: slug::slugify(&name_str),
         category: category_str.clone(),
         kb: kb_str.clone(),
         tags,
         translations: None,
+        prices: Vec::new(),
+        packages: Vec::new(),
+        density: None,
+        unit_weight: None,
     };
     
     let manager = unsafe { &mut *(manager_ptr as *mut DataManager) };
@@ -410,17 +414,23 @@ pub extern "system" fn Java_com_example_pantryman_CookbookEngine_updateIngredien
     
     let tags: Option<Vec<String>> = serde_json::from_str(&tags_str).ok();
     
+    let manager = unsafe { &mut *(manager_ptr as *mut DataManager) };
+    
+    // The app doesn't edit prices, barcodes or conversions, so keep the recorded ones
+    let existing = manager.get_ingredient(&original_name_str);
     let ingredient = Ingredient {
         name: new_name_str.clone(),
-        slug: new_name_str.replace(" ", "_").to_lowercase(),
+        slug: String::new(), // keep the existing file name
         category: category_str.clone(),
         kb: kb_str.clone(),
         tags,
         translations: None,
+        prices: existing.map(|i| i.prices.clone()).unwrap_or_default(),
+        packages: existing.map(|i| i.packages.clone()).unwrap_or_default(),
+        density: existing.and_then(|i| i.density),
+        unit_weight: existing.and_then(|i| i.unit_weight),
     };
     
-    let manager = unsafe { &mut *(manager_ptr as *mut DataManager) };
-    
     log_info!("updateIngredient called: '{}' -> '{}', category='{}', kb_slug='{:?}', tags='{}'", 
              original_name_str, new_name_str, category_str, kb_str, tags_str);
     
@@ -457,7 +467,8 @@ pub extern "system" fn Java_com_example_pantryman_CookbookEngine_deleteIngredien
     
     log_info!("deleteIngredient called for: '{}'", ingredient_name_str);
     
-    match manager.delete_ingredient(&ingredient_name_str) {
+    // Refuse while recipes still use the ingredient; the app has no way to pick a strategy yet
+    match manager.delete_ingredient(&ingredient_name_str, DeleteStrategy::Block) {
         Ok(_) => {
             log_info!("Successfully deleted ingredient '{}'", ingredient_name_str);
             1 // true
@@ -488,3 +499,88 @@ pub extern "system" fn Java_com_example_pantryman_CookbookEngine_getAllCategorie
         Err(_) => string_to_jstring(&mut env, "[]".to_string()).unwrap_or(std::ptr::null_mut()),
     }
 }
+
+// Add one package of a scanned product to the pantry. Returns the updated pantry item as
+// JSON, {"found": false} if no ingredient has the barcode, or {"error": ...} if it failed
+#[no_mangle]
+pub extern "system" fn Java_com_example_pantryman_CookbookEngine_addByBarcode(
+    mut env: JNIEnv,
+    _class: JClass,
+    manager_ptr: jlong,
+    barcode: JString,
+) -> jstring {
+    let result = if manager_ptr == 0 {
+        serde_json::json!({ "error": "No data manager" })
+    } else {
+        let barcode_str = jstring_to_string(&mut env, &barcode).unwrap_or_default();
+        let manager = unsafe { &mut *(manager_ptr as *mut DataManager) };
+
+        log_info!("addByBarcode called for barcode: {}", barcode_str);
+
+        match manager.add_to_pantry_by_barcode(&barcode_str) {
+            Ok(Some(item)) => {
+                log_info!("Added a package of '{}' to the pantry", item.ingredient);
+                serde_json::json!({
+                    "found": true,
+                    "ingredient": item.ingredient,
+                    "quantity": item.quantity,
+                    "quantityType": item.quantity_type
+                })
+            },
+            Ok(None) => {
+                log_info!("No ingredient has barcode {}", barcode_str);
+                serde_json::json!({ "found": false })
+            },
+            Err(e) => {
+                log_error!("Failed to add barcode {} to the pantry: {:?}", barcode_str, e);
+                serde_json::json!({ "error": e.to_string() })
+            }
+        }
+    };
+
+    string_to_jstring(&mut env, result.to_string()).unwrap_or(std::ptr::null_mut())
+}
+
+// Map a barcode to an ingredient, with the size of one package (quantity <= 0 if unknown)
+#[no_mangle]
+pub extern "system" fn Java_com_example_pantryman_CookbookEngine_mapBarcode(
+    mut env: JNIEnv,
+    _class: JClass,
+    manager_ptr: jlong,
+    ingredient_name: JString,
+    barcode: JString,
+    quantity: jdouble,
+    quantity_type: JString,
+) -> jboolean {
+    if manager_ptr == 0 {
+        return 0; // false
+    }
+
+    let ingredient_name_str = match jstring_to_string(&mut env, &ingredient_name) {
+        Ok(name) => name,
+        Err(_) => return 0,
+    };
+    let barcode_str = match jstring_to_string(&mut env, &barcode) {
+        Ok(code) => code,
+        Err(_) => return 0,
+    };
+    let quantity_type_str = jstring_to_string(&mut env, &quantity_type).unwrap_or_default();
+
+    let manager = unsafe { &mut *(manager_ptr as *mut DataManager) };
+    let package = Package {
+        barcode: barcode_str.clone(),
+        quantity: if quantity > 0.0 { Some(quantity) } else { None },
+        unit: quantity_type_str,
+    };
+
+    match manager.add_ingredient_package(&ingredient_name_str, package) {
+        Ok(_) => {
+            log_info!("Mapped barcode {} to '{}'", barcode_str, ingredient_name_str);
+            1 // true
+        },
+        Err(e) => {
+            log_error!("Failed to map barcode {} to '{}': {:?}", barcode_str, ingredient_name_str, e);
+            0 // false
+        }
+    }
+}